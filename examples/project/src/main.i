@@ -0,0 +1,6 @@
+namespace App
+module Main
+
+let main () =
+    let x = (Utils.answer())
+    printf "Answer: %d\n" x
@@ -0,0 +1,310 @@
+//! # Debug info
+//!
+//! DWARF debug info for generated module: compile unit, functions as
+//! `DISubprogram`, let-values and parameters as `DILocalVariable`
+//! and source locations of instructions as `DILocation`. Let-values
+//! are SSA values, so they are described with `llvm.dbg.value`
+//! intrinsic.
+//!
+//! https://llvm.org/docs/SourceLevelDebugging.html
+
+use crate::llvm::builder::{
+    BasicBlockBuilder,
+    BuildResult,
+    FunctionBuilder,
+    ModuleBuilder,
+    Value,
+};
+use crate::llvm::debug_info::{
+    DIBasicType,
+    DICompileUnit,
+    DIDerivedType,
+    DIExpression,
+    DIFile,
+    DILocalVariable,
+    DILocation,
+    DISubprogram,
+    DISubroutineType,
+    DebugInfo,
+    DwarfEncoding,
+    EmissionKind,
+};
+use crate::llvm::intrinsics::Intrinsic;
+use crate::llvm::metadata::{
+    Metadata,
+    MetadataKind,
+    MetadataNode,
+    MetadataRef,
+    ModuleFlagBehavior,
+};
+use crate::llvm::type_system::single_value::FloatingPointType;
+use crate::llvm::types::Type;
+use std::path::Path;
+
+/// Version of debug info metadata, supported by LLVM
+const DEBUG_INFO_VERSION: u32 = 3;
+const DWARF_VERSION: u32 = 4;
+
+/// Debug info builder. Keeps compile unit, created types and
+/// subprogram of function, which is currently generated.
+pub struct DebugInfoBuilder {
+    file: MetadataRef,
+    unit: MetadataRef,
+    types: Vec<(Type, MetadataRef)>,
+    /// Subprogram of current function and its parameter types
+    subprogram: Option<(MetadataRef, DISubprogram, Vec<Option<Type>>)>,
+}
+
+fn node(module: &mut ModuleBuilder, distinct: bool, v: DebugInfo) -> MetadataRef {
+    module.metadata(MetadataNode {
+        distinct,
+        value: Metadata::DebugInfo(Box::new(v)),
+    })
+}
+
+impl DebugInfoBuilder {
+    /// Create compile unit for source file. Relative file name is
+    /// resolved from current directory.
+    pub fn new(module: &mut ModuleBuilder, source_file: &str) -> Self {
+        let path = Path::new(source_file);
+        let directory = match path.parent() {
+            Some(dir) if path.is_absolute() => dir.to_path_buf(),
+            _ => std::env::current_dir().unwrap_or_default(),
+        };
+        let file = node(
+            module,
+            false,
+            DebugInfo::File(DIFile {
+                filename: source_file.to_string(),
+                directory: directory.to_string_lossy().to_string(),
+            }),
+        );
+        let unit = node(
+            module,
+            true,
+            DebugInfo::CompileUnit(DICompileUnit {
+                // There is no DWARF language for iLang, C is closest
+                // for debuggers
+                language: "DW_LANG_C".to_string(),
+                file,
+                producer: "iLang".to_string(),
+                is_optimized: false,
+                runtime_version: 0,
+                emission_kind: EmissionKind::FullDebug,
+            }),
+        );
+        module.named_metadata("llvm.dbg.cu", unit);
+        let version = |v: u32| Metadata::Value(Type::Integer32, v.to_string());
+        module.module_flag(
+            ModuleFlagBehavior::Max,
+            "Dwarf Version",
+            version(DWARF_VERSION),
+        );
+        module.module_flag(
+            ModuleFlagBehavior::Warning,
+            "Debug Info Version",
+            version(DEBUG_INFO_VERSION),
+        );
+        Self {
+            file,
+            unit,
+            types: vec![],
+            subprogram: None,
+        }
+    }
+
+    /// Type of parameter by its name in source code
+    pub fn source_type(name: &str) -> Option<Type> {
+        match name {
+            "int" => Some(Type::Integer32),
+            "float" => Some(Type::FloatingPoint(FloatingPointType::Double)),
+            "byte" => Some(Type::Integer8),
+            "bool" => Some(Type::Integer1),
+            "string" => Some(Type::pointer1(Type::Integer8)),
+            _ => None,
+        }
+    }
+
+    /// Debug info type for value type. Types, which have no
+    /// representation in source code, are not described.
+    fn ty(&mut self, module: &mut ModuleBuilder, ty: &Type) -> Option<MetadataRef> {
+        if let Some((_, v)) = self.types.iter().find(|(t, _)| t == ty) {
+            return Some(*v);
+        }
+        let basic = |name: &str, size, encoding| {
+            DebugInfo::BasicType(DIBasicType {
+                name: name.to_string(),
+                size,
+                encoding,
+            })
+        };
+        let v = match ty {
+            Type::Integer1 => basic("bool", 8, DwarfEncoding::Boolean),
+            Type::Integer8 => basic("byte", 8, DwarfEncoding::UnsignedChar),
+            Type::Integer32 => basic("int", 32, DwarfEncoding::Signed),
+            Type::Integer64 => basic("int64", 64, DwarfEncoding::Signed),
+            Type::FloatingPoint(FloatingPointType::Double) => {
+                basic("float", 64, DwarfEncoding::Float)
+            }
+            Type::Pointer(p) => DebugInfo::DerivedType(DIDerivedType {
+                tag: "DW_TAG_pointer_type".to_string(),
+                base_type: self.ty(module, p.pointee.as_ref()?)?,
+                size: 64,
+            }),
+            _ => return None,
+        };
+        let v = node(module, false, v);
+        self.types.push((ty.clone(), v));
+        Some(v)
+    }
+
+    /// Start function: create subprogram and attach it to function.
+    /// Arguments of function are described by variables in retained
+    /// nodes of subprogram.
+    pub fn begin_function(
+        &mut self,
+        module: &mut ModuleBuilder,
+        func: &mut FunctionBuilder,
+        name: &str,
+        line: u32,
+        params: &[(String, Option<Type>)],
+        artificial: bool,
+    ) {
+        let mut subprogram = DISubprogram {
+            name: name.to_string(),
+            scope: self.file,
+            file: self.file,
+            line,
+            ty: None,
+            scope_line: line,
+            artificial,
+            local_to_unit: true,
+            definition: true,
+            unit: Some(self.unit),
+            retained_nodes: None,
+        };
+        let scope = node(module, true, DebugInfo::Subprogram(subprogram.clone()));
+        // Parameters, which aren't arguments of emitted function, are
+        // not described
+        let params = &params[..params.len().min(func.params().len())];
+        if !params.is_empty() {
+            let mut nodes = vec![];
+            for (i, (param, ty)) in params.iter().enumerate() {
+                let ty = ty.as_ref().and_then(|ty| self.ty(module, ty));
+                let v = DILocalVariable {
+                    name: param.clone(),
+                    arg: Some(i as u32 + 1),
+                    scope,
+                    file: self.file,
+                    line,
+                    ty,
+                };
+                nodes.push(Metadata::Node(node(
+                    module,
+                    false,
+                    DebugInfo::LocalVariable(v),
+                )));
+            }
+            subprogram.retained_nodes = Some(module.metadata(MetadataNode {
+                distinct: false,
+                value: Metadata::Tuple(nodes),
+            }));
+        }
+        let param_types = params.iter().map(|(_, ty)| ty.clone()).collect();
+        self.subprogram = Some((scope, subprogram, param_types));
+        func.attach(MetadataKind::Dbg, scope);
+    }
+
+    /// Finish function, when its result type is known
+    pub fn end_function(&mut self, module: &mut ModuleBuilder, result_type: &Type) {
+        let (scope, mut subprogram, param_types) = match self.subprogram.take() {
+            Some(v) => v,
+            None => return,
+        };
+        let mut types = vec![self.ty_or_null(module, Some(result_type))];
+        for ty in param_types.iter() {
+            types.push(self.ty_or_null(module, ty.as_ref()));
+        }
+        let types = module.metadata(MetadataNode {
+            distinct: false,
+            value: Metadata::Tuple(types),
+        });
+        subprogram.ty = Some(node(
+            module,
+            false,
+            DebugInfo::SubroutineType(DISubroutineType { types }),
+        ));
+        module.replace_metadata(
+            scope,
+            MetadataNode {
+                distinct: true,
+                value: Metadata::DebugInfo(Box::new(DebugInfo::Subprogram(subprogram))),
+            },
+        );
+    }
+
+    /// Type reference, `null` for `void` and unknown types
+    fn ty_or_null(&mut self, module: &mut ModuleBuilder, ty: Option<&Type>) -> Metadata {
+        match ty.and_then(|ty| self.ty(module, ty)) {
+            Some(v) => Metadata::Node(v),
+            None => Metadata::Null,
+        }
+    }
+
+    /// Source location in current function. Lines and columns
+    /// start from 1.
+    pub fn location(
+        &self,
+        module: &mut ModuleBuilder,
+        line: u32,
+        column: u32,
+    ) -> Option<MetadataRef> {
+        let (scope, _, _) = self.subprogram.as_ref()?;
+        Some(node(
+            module,
+            false,
+            DebugInfo::Location(DILocation {
+                line,
+                column,
+                scope: *scope,
+            }),
+        ))
+    }
+
+    /// Describe let-value of current function:
+    /// `call void @llvm.dbg.value(metadata i32 %1, metadata !12, metadata !DIExpression())`
+    pub fn local_variable(
+        &mut self,
+        module: &mut ModuleBuilder,
+        bb: &mut BasicBlockBuilder,
+        name: &str,
+        line: u32,
+        value: &Value,
+    ) -> BuildResult<()> {
+        let scope = match &self.subprogram {
+            Some((scope, _, _)) => *scope,
+            None => return Ok(()),
+        };
+        let v = DILocalVariable {
+            name: name.to_string(),
+            arg: None,
+            scope,
+            file: self.file,
+            line,
+            ty: self.ty(module, &value.ty),
+        };
+        let variable = node(module, false, DebugInfo::LocalVariable(v));
+        let dbg_value = module.intrinsic(&Intrinsic::DbgValue)?;
+        let args = [
+            value.to_string(),
+            variable.to_string(),
+            DIExpression(vec![]).to_string(),
+        ];
+        let args = args
+            .iter()
+            .map(|v| Value::new(Type::Metadata, v))
+            .collect::<Vec<_>>();
+        bb.call(&dbg_value, &args)?;
+        Ok(())
+    }
+}
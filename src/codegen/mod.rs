@@ -2,17 +2,40 @@
 //!
 //! COdegen based on syntax analyzer and LLVM code generation
 
+mod debug_info;
+
+use self::debug_info::DebugInfoBuilder;
+use crate::llvm::atomic_ordering::AtomicOrdering;
 use crate::llvm::attribute_groups::Attributes;
-use crate::llvm::context::Context;
-use crate::llvm::global_variables::UnnamedAddr::UnnamedAddr;
-use crate::llvm::instructions::memory_access_addressing_operations::GetElementPtr;
-use crate::llvm::instructions::other_operations::Call;
+use crate::llvm::builder::{
+    BasicBlockBuilder,
+    BuildError,
+    FunctionBuilder,
+    FunctionRef,
+    ModuleBuilder,
+    Value,
+};
+use crate::llvm::data_layout::DataLayout;
+use crate::llvm::functions::{
+    ArgumentList,
+    Function,
+};
+use crate::llvm::instructions::memory_access_addressing_operations::AtomicRMWOperation;
+use crate::llvm::intrinsics::{
+    Intrinsic,
+    MathFunction,
+    OverflowOperation,
+};
+use crate::llvm::ir::Module;
 use crate::llvm::linkage_types::LinkageTypes::{
+    External,
     Internal,
-    Private,
 };
-use crate::llvm::type_system::aggregate::ArrayType;
-use crate::llvm::type_system::single_value::PointerType;
+use crate::llvm::target_triple::TargetTriple;
+use crate::llvm::type_system::single_value::{
+    FloatingPointType,
+    VectorType,
+};
 use crate::llvm::types::Type;
 use crate::llvm::types::Type::{
     Integer1,
@@ -21,76 +44,167 @@ use crate::llvm::types::Type::{
     Integer8,
     Void,
 };
-use crate::llvm::InstructionSet;
-use crate::parser::ast::*;
-use std::collections::{
-    HashMap,
-    HashSet,
+use crate::llvm::verifier::{
+    self,
+    VerifyError,
 };
+use crate::parser::ast::*;
+use std::collections::HashMap;
 
 pub type Result = std::result::Result<String, CodegenError>;
 
+/// Entry point of executable, called by C runtime
+pub const ENTRY_POINT: &str = "main";
+
+/// Function initializing global let-values, it's called by static
+/// constructor
+pub const GLOBAL_INIT_FUNCTION: &str = "_GLOBAL_let_main";
+
+/// Result of statement codegen: value produced by statement (if it
+/// produce any value)
+pub type StatementResult = std::result::Result<Option<Value>, CodegenError>;
+
+/// Result of expression codegen: expression value
+pub type ExpressionResult = std::result::Result<Value, CodegenError>;
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum CodegenError {
     ModuleNotFound,
     ParseSourceCode,
+    ValueNotFound(String),
+    ExpressionTypeMismatch(Type, Type),
+    UnsupportedOperand(String),
+    UnsupportedOperation(ExpressionOperation, Type),
+    UnsupportedConversion(Type, Type),
+    ArgumentsCountMismatch(String, usize),
+    /// Literal, which doesn't fit 64-bit integer
+    IntegerLiteralOutOfRange(String),
+    /// Language construction, which isn't supported by codegen yet
+    Unsupported(String),
+    Build(BuildError),
+    /// Generated IR is malformed
+    Verify(Box<VerifyError>),
 }
 
-/// Codegen structure
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct Codegen<'a> {
-    ctx: Context,
-    global_ctx: Context,
-    let_values: HashSet<String>,
-    global_let_values: HashMap<LetValueName, ValueType>,
-    global_let_expressions: Vec<String>,
-    function_declarations: Vec<FunctionDeclaration>,
-    ast: &'a Main<'a>,
+impl std::fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CodegenError::ModuleNotFound => write!(f, "module declaration not found"),
+            CodegenError::ParseSourceCode => write!(f, "failed to parse source code"),
+            CodegenError::ValueNotFound(v) => write!(f, "value `{}` not found", v),
+            CodegenError::ExpressionTypeMismatch(x, y) => {
+                write!(f, "type mismatch in expression: `{}` and `{}`", x, y)
+            }
+            CodegenError::UnsupportedOperand(v) => write!(f, "unsupported operand `{}`", v),
+            CodegenError::UnsupportedOperation(op, ty) => {
+                write!(f, "operation {:?} isn't supported for `{}`", op, ty)
+            }
+            CodegenError::UnsupportedConversion(x, y) => {
+                write!(f, "can't convert `{}` to `{}`", x, y)
+            }
+            CodegenError::ArgumentsCountMismatch(name, count) => {
+                write!(
+                    f,
+                    "function `{}` can't be called with {} arguments",
+                    name, count
+                )
+            }
+            CodegenError::IntegerLiteralOutOfRange(v) => {
+                write!(f, "integer literal `{}` is out of range", v)
+            }
+            CodegenError::Unsupported(v) => write!(f, "{} isn't supported", v),
+            CodegenError::Build(err) => write!(f, "failed to build IR: {}", err),
+            CodegenError::Verify(err) => write!(f, "malformed IR: {}", err),
+        }
+    }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct FunctionDeclaration {
-    name: String,
-    declaration: String,
+impl From<BuildError> for CodegenError {
+    fn from(err: BuildError) -> Self {
+        CodegenError::Build(err)
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct FunctionParameter {
-    name: String,
-    global: bool,
+impl From<Box<VerifyError>> for CodegenError {
+    fn from(err: Box<VerifyError>) -> Self {
+        CodegenError::Verify(err)
+    }
 }
 
-impl InstructionSet for FunctionParameter {
-    fn set_context(&mut self, _ctx: u64) {}
-    fn get_value(&self) -> Option<String> {
-        if self.global {
-            Some(format!("@{}", self.name))
-        } else {
-            Some(format!("%{}", self.name))
-        }
-    }
+/// Codegen options
+#[derive(Debug, Clone, Default)]
+pub struct CodegenOptions {
+    /// Emit DWARF debug info
+    pub debug_info: bool,
+    /// Path of source file, used by debug info. By default source
+    /// file name is derived from module name.
+    pub source_file: Option<String>,
+    /// Integer `+`, `-` and `*` trap on signed overflow
+    pub overflow_checks: bool,
+    /// Emit typed pointers `i8*` instead of opaque `ptr`, for LLVM
+    /// versions prior to 15
+    pub typed_pointers: bool,
+    /// Target triple, default is `x86_64-unknown-linux-gnu`
+    pub target_triple: Option<String>,
+    /// Data layout of target triple, it's provided by target machine
+    pub data_layout: Option<String>,
+    /// Functions and global let-values are external, with symbols
+    /// qualified by module id, like `App.Utils.add`, so modules of
+    /// project are linked together
+    pub qualified_symbols: bool,
+    /// Entry point isn't qualified in entry module of project
+    pub entry_module: bool,
+    /// Functions of other modules, called by qualified name, or by
+    /// name if it isn't defined by module
+    pub external_functions: Vec<ExternalFunction>,
+    /// Global let-values of other modules, used by name
+    pub external_globals: Vec<ExternalGlobal>,
 }
 
-impl std::fmt::Display for FunctionParameter {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let s = if self.global {
-            format!("@{}", self.name)
-        } else {
-            format!("%{}", self.name)
-        };
-        write!(f, "{}", s)
-    }
+/// Function of other module
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalFunction {
+    /// Qualified name used by caller, like `Utils.add`
+    pub name: String,
+    /// Symbol of function in object file
+    pub symbol: String,
+    pub parameter_types: Vec<Type>,
+    pub result_type: Type,
+}
+
+/// Global let-value of other module
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalGlobal {
+    /// Name used in module
+    pub name: String,
+    /// Symbol of global variable in object file
+    pub symbol: String,
+    pub value_type: Type,
+}
+
+/// Codegen structure
+#[allow(dead_code)]
+pub struct Codegen<'a> {
+    module: ModuleBuilder,
+    options: CodegenOptions,
+    /// Debug info builder, if debug info enabled
+    debug_info: Option<DebugInfoBuilder>,
+    let_values: HashMap<LetValueName, Value>,
+    global_let_values: HashMap<LetValueName, ValueType>,
+    /// Pointers to global let-values
+    global_values: HashMap<LetValueName, Value>,
+    global_let_expressions: Vec<String>,
+    /// Already generated functions of current module
+    functions: HashMap<String, FunctionRef>,
+    /// Module id, namespace included
+    module_id: String,
+    ast: &'a Main<'a>,
 }
 
 pub struct TypeExpressionResult {
     pub value: String,
 }
 
-/// Instructions set stack for block
-pub type VecInstructionSet = Vec<Box<dyn InstructionSet>>;
-
 /// Build in types.
 #[derive(Debug, Clone)]
 pub enum BuildInTypes {
@@ -112,272 +226,889 @@ pub struct ValueType {
 
 impl<'a> Codegen<'a> {
     #[allow(clippy::ptr_arg)]
-    fn new(ast: &'a Main) -> Self {
+    fn new(ast: &'a Main, options: CodegenOptions) -> Self {
         Self {
-            ctx: Context::new(),
-            global_ctx: Context::new(),
-            let_values: HashSet::new(),
+            module: ModuleBuilder::new(""),
+            options,
+            debug_info: None,
+            let_values: HashMap::new(),
             global_let_values: HashMap::new(),
+            global_values: HashMap::new(),
             global_let_expressions: vec![],
-            function_declarations: vec![],
+            functions: HashMap::new(),
+            module_id: String::new(),
             ast,
         }
     }
 
-    pub fn expression(&self) -> Result {
-        println!("\t#[call] expression");
-        let src = "".to_string();
-        Ok(src)
+    /// Module id of AST: module name, qualified by namespace if
+    /// module is preceded by namespace
+    pub fn module_id(ast: &Main) -> Option<String> {
+        let mut namespace = vec![];
+        for v in ast.iter() {
+            match v {
+                MainStatement::Namespace(ns) => {
+                    namespace = ns.iter().map(|v| v.fragment().to_string()).collect()
+                }
+                MainStatement::Module(m) => {
+                    namespace.extend(m.module_name.iter().map(|v| v.fragment().to_string()));
+                    return Some(namespace.join("."));
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Symbol of function of current module
+    fn function_symbol(&self, fn_name: &str) -> String {
+        if !self.options.qualified_symbols || (self.options.entry_module && fn_name == ENTRY_POINT)
+        {
+            fn_name.to_string()
+        } else {
+            format!("{}.{}", self.module_id, fn_name)
+        }
+    }
+
+    /// Symbol of global let-value of current module
+    fn global_symbol(&self, name: &str) -> String {
+        if self.options.qualified_symbols {
+            format!("{}.{}", self.module_id, name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Operations precedence for expressions. Expressions parsed
+    /// as right-recursive sequence, so precedence applied at codegen
+    /// stage.
+    fn operation_precedence(op: &ExpressionOperation) -> u8 {
+        match op {
+            ExpressionOperation::Multiply | ExpressionOperation::Divide => 3,
+            ExpressionOperation::Plus | ExpressionOperation::Minus => 2,
+            ExpressionOperation::ShiftLeft | ExpressionOperation::ShiftRight => 1,
+        }
+    }
+
+    /// Line and column of source position
+    fn span_position(span: &Span) -> (u32, u32) {
+        (span.location_line(), span.get_column() as u32)
     }
 
-    pub fn type_expression(&mut self, te: &TypeExpression) -> VecInstructionSet {
-        #[cfg(feature = "type_expression")]
-        println!("\t#[call] type_expression: TypeExpression = {:#?}", te.expr);
-        match te.expr {
-            BasicTypeExpression::Number(n) => {
-                let mut v: Vec<Box<dyn InstructionSet>> = vec![];
-                v.push(Box::new(alloca!(Integer32 0)));
-                v.push(Box::new(store!(Integer32 n, 0)));
-                v
+    /// Line and column of value expression
+    fn value_position(vle: &ValueExpression) -> (u32, u32) {
+        match vle {
+            ValueExpression::ParameterValue(pv) => Self::span_position(pv),
+            ValueExpression::TypeExpression(te) => (te.position.line, te.position.column as u32),
+        }
+    }
+
+    /// Position of first operand of expression
+    fn expression_position(e: &Expression) -> Option<(u32, u32)> {
+        match &e.function_statement {
+            ExpressionFunctionValueCall::FunctionValue(FunctionValue::ValueList(vl)) => {
+                vl.first().map(Self::value_position)
+            }
+            ExpressionFunctionValueCall::FunctionValue(FunctionValue::Expression(e)) => {
+                Self::expression_position(e)
             }
-            BasicTypeExpression::String(ref s) => {
-                let gty = Type::Array(ArrayType((s.len() + 1) as i32, Box::new(Type::Integer8)));
-                let mut g = global!(Constant gty 0);
-                global!(g.linkage @Private);
-                global!(g.unnamed_addr @UnnamedAddr);
-                global!(g.initializer_constant @Type::raw_string(s));
-                vec![Box::new(g)]
+            ExpressionFunctionValueCall::FunctionCall(fc) => {
+                fc.function_call_name.first().map(Self::span_position)
+            }
+        }
+    }
+
+    /// Set source position of instructions, which are appended
+    /// after this call. Used only if debug info enabled.
+    fn set_position(&mut self, bb: &mut BasicBlockBuilder, position: Option<(u32, u32)>) {
+        if let (Some(di), Some((line, column))) = (&self.debug_info, position) {
+            bb.set_debug_location(di.location(&mut self.module, line, column));
+        }
+    }
+
+    /// Integer operation, which traps on signed overflow. Result
+    /// of `llvm.*.with.overflow` intrinsic is checked, and
+    /// `llvm.trap` is called in separate block on overflow.
+    fn checked_operation(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        op: OverflowOperation,
+        lhs: Value,
+        rhs: Value,
+    ) -> ExpressionResult {
+        let f = self
+            .module
+            .intrinsic(&Intrinsic::WithOverflow(op, lhs.ty.clone()))?;
+        let res = bb.call(&f, &[lhs, rhs])?.unwrap();
+        let value = bb.extractvalue(&res, &[0])?;
+        let overflow = bb.extractvalue(&res, &[1])?;
+        let trap_label = bb.create_block("overflow");
+        let next_label = bb.create_block("no_overflow");
+        bb.cond_br(&overflow, &trap_label, &next_label)?;
+        bb.position_at_end(&trap_label)?;
+        let trap = self.module.intrinsic(&Intrinsic::Trap)?;
+        bb.call(&trap, &[])?;
+        bb.unreachable()?;
+        bb.position_at_end(&next_label)?;
+        Ok(value)
+    }
+
+    /// Shift amount modulo bit width, as shift by bit width or more
+    /// is poison value. Constant amount is calculated at compile
+    /// time.
+    fn shift_amount(bb: &mut BasicBlockBuilder, value: Value) -> ExpressionResult {
+        let mask = value.ty.scalar_type().integer_width().unwrap() - 1;
+        if let Ok(n) = value.value.parse::<i64>() {
+            return Ok(Value::new(value.ty, &(n & i64::from(mask)).to_string()));
+        }
+        let mask = match &value.ty {
+            Type::Vector(v) => {
+                let lane = format!("{} {}", v.element_type, mask);
+                format!("<{}>", vec![lane; v.elemetns as usize].join(", "))
             }
-            BasicTypeExpression::Bool(n) => {
-                let mut v: Vec<Box<dyn InstructionSet>> = vec![];
-                v.push(Box::new(alloca!(Integer1 0)));
-                v.push(Box::new(store!(Integer1 n, 0)));
-                v
+            _ => mask.to_string(),
+        };
+        let mask = Value::new(value.ty.clone(), &mask);
+        Ok(bb.and(&value, &mask)?)
+    }
+
+    /// Binary operation for two values with same types.
+    /// Integer and floating point values, and vectors of them,
+    /// supported. Scalar integer operations are checked for
+    /// overflow, if overflow checks enabled.
+    pub fn binary_operation(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        op: &ExpressionOperation,
+        lhs: Value,
+        rhs: Value,
+    ) -> ExpressionResult {
+        if !lhs.ty.is_compatible(&rhs.ty) {
+            return Err(CodegenError::ExpressionTypeMismatch(lhs.ty, rhs.ty));
+        }
+        let ty = lhs.ty.clone();
+        if self.options.overflow_checks && ty.is_integer() {
+            let op = match op {
+                ExpressionOperation::Plus => Some(OverflowOperation::SAdd),
+                ExpressionOperation::Minus => Some(OverflowOperation::SSub),
+                ExpressionOperation::Multiply => Some(OverflowOperation::SMul),
+                _ => None,
+            };
+            if let Some(op) = op {
+                return self.checked_operation(bb, op, lhs, rhs);
             }
         }
+        let value = if ty.scalar_type().is_integer() {
+            let rhs = match op {
+                ExpressionOperation::ShiftLeft | ExpressionOperation::ShiftRight => {
+                    Self::shift_amount(bb, rhs)?
+                }
+                _ => rhs,
+            };
+            match op {
+                ExpressionOperation::Plus => bb.add(&lhs, &rhs)?,
+                ExpressionOperation::Minus => bb.sub(&lhs, &rhs)?,
+                ExpressionOperation::Multiply => bb.mul(&lhs, &rhs)?,
+                ExpressionOperation::Divide => bb.sdiv(&lhs, &rhs)?,
+                ExpressionOperation::ShiftLeft => bb.shl(&lhs, &rhs)?,
+                ExpressionOperation::ShiftRight => bb.ashr(&lhs, &rhs)?,
+            }
+        } else if ty.scalar_type().is_floating_point() {
+            match op {
+                ExpressionOperation::Plus => bb.fadd(&lhs, &rhs)?,
+                ExpressionOperation::Minus => bb.fsub(&lhs, &rhs)?,
+                ExpressionOperation::Multiply => bb.fmul(&lhs, &rhs)?,
+                ExpressionOperation::Divide => bb.fdiv(&lhs, &rhs)?,
+                _ => return Err(CodegenError::UnsupportedOperation(op.clone(), ty)),
+            }
+        } else {
+            return Err(CodegenError::UnsupportedOperation(op.clone(), ty));
+        };
+        Ok(value)
     }
 
-    pub fn value_expression(&mut self, vle: &ValueExpression) -> VecInstructionSet {
+    /// Single value as expression operand. Literals represented as
+    /// constants, integer literals out of `int` range are 64-bit.
+    /// Strings are pointers to global constants, let-values and
+    /// parameters fetched from local or global values.
+    pub fn value_operand(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        vle: &ValueExpression,
+    ) -> ExpressionResult {
         #[cfg(feature = "value_expression")]
-        println!("\t#[call] value_expression (ValueExpression)");
-        let res: VecInstructionSet = match vle {
+        eprintln!("\t#[call] value_operand (ValueExpression)");
+        match vle {
+            ValueExpression::TypeExpression(te) => {
+                #[cfg(feature = "type_expression")]
+                eprintln!("\t#[value_operand] TypeExpression: {:#?}", te.expr);
+                let value = match te.expr {
+                    // Hexadecimal form is exact representation of double
+                    BasicTypeExpression::Float(n) => Value::new(
+                        Type::FloatingPoint(FloatingPointType::Double),
+                        &format!("0x{:016X}", n.to_bits()),
+                    ),
+                    BasicTypeExpression::Number(n) => {
+                        // `i64::MAX` is rounded to 2^63 by conversion
+                        if n >= f64::from(i32::MIN) && n <= f64::from(i32::MAX) {
+                            Value::new(Integer32, &(n as i32).to_string())
+                        } else if n >= i64::MIN as f64 && n < i64::MAX as f64 {
+                            Value::new(Integer64, &(n as i64).to_string())
+                        } else {
+                            return Err(CodegenError::IntegerLiteralOutOfRange(format!(
+                                "{:.0}",
+                                n
+                            )));
+                        }
+                    }
+                    BasicTypeExpression::Bool(b) => Value::new(Integer1, &b.to_string()),
+                    BasicTypeExpression::String(ref s) => {
+                        self.set_position(bb, Some(Self::value_position(vle)));
+                        return self.string_operand(bb, s);
+                    }
+                };
+                Ok(value)
+            }
             ValueExpression::ParameterValue(pv) => {
-                let value_key = pv.fragment();
-                if let Some(x) = self.global_let_values.get(&value_key.to_string()) {
-                    #[cfg(feature = "value_expression")]
-                    println!("\t#[value_expression] ParameterValue: {:#?}", x);
-                    vec![Box::new(FunctionParameter {
-                        name: x.value.clone(),
-                        global: true,
-                    })]
+                let name = pv.fragment().to_string();
+                if let Some(v) = self.let_values.get(&name) {
+                    Ok(v.clone())
+                } else if let Some(v) = self.global_values.get(&name).cloned() {
+                    self.set_position(bb, Some(Self::span_position(pv)));
+                    Ok(bb.load(&v)?)
                 } else {
-                    #[cfg(feature = "value_expression")]
-                    println!(
-                        "\t#[value_expression] ParameterValue [doesn't exist]: {}",
-                        value_key
-                    );
-                    vec![]
+                    Err(CodegenError::ValueNotFound(name))
                 }
             }
-            ValueExpression::TypeExpression(te) => {
-                #[cfg(feature = "value_expression")]
-                println!("\t#[value_expression] TypeExpression");
-                self.type_expression(te)
+        }
+    }
+
+    /// String literal stored as private global constant. Operand
+    /// value is pointer to first character of string.
+    fn string_operand(&mut self, bb: &mut BasicBlockBuilder, s: &str) -> ExpressionResult {
+        let ptr = self.module.string_constant(s);
+        Ok(bb.getelementptr(&ptr, &[0, 0])?)
+    }
+
+    /// Expression operand: value, expression in brackets or function call
+    pub fn expression_operand(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        efvc: &ExpressionFunctionValueCall,
+    ) -> ExpressionResult {
+        #[cfg(feature = "function_value_call")]
+        eprintln!("\t#[call] expression_operand (ExpressionFunctionValueCall)");
+        match efvc {
+            ExpressionFunctionValueCall::FunctionValue(FunctionValue::ValueList(vl)) => {
+                if vl.len() != 1 {
+                    return Err(CodegenError::UnsupportedOperand(format!("{:?}", vl)));
+                }
+                self.value_operand(bb, &vl[0])
             }
-        };
-        #[cfg(feature = "value_expression")]
-        println!("\t#[end_value_expression]");
-        res
+            ExpressionFunctionValueCall::FunctionValue(FunctionValue::Expression(e)) => {
+                self.expression(bb, e)
+            }
+            ExpressionFunctionValueCall::FunctionCall(fc) => match self.function_call(bb, fc)? {
+                Some(value) => Ok(value),
+                None => Err(CodegenError::UnsupportedOperand(format!(
+                    "{:?}",
+                    fc.function_call_name
+                ))),
+            },
+        }
+    }
+
+    /// Expression codegen. Expression is sequence of operands
+    /// and operations. All operands calculated from left to right,
+    /// and then operations applied according to precedence.
+    pub fn expression(&mut self, bb: &mut BasicBlockBuilder, e: &Expression) -> ExpressionResult {
+        let mut operands = vec![];
+        let mut operations = vec![];
+        let mut expr = Some(e);
+        while let Some(e) = expr {
+            operands.push(self.expression_operand(bb, &e.function_statement)?);
+            expr = match (&e.operation_statement, &e.expression) {
+                (Some(op), Some(next)) => {
+                    operations.push(op.clone());
+                    Some(next)
+                }
+                (None, _) => None,
+                (Some(op), None) => {
+                    return Err(CodegenError::UnsupportedOperand(format!("{:?}", op)))
+                }
+            };
+        }
+
+        // Operations located at start of expression
+        if !operations.is_empty() {
+            self.set_position(bb, Self::expression_position(e));
+        }
+        let mut operands = operands.into_iter();
+        let mut values = vec![operands.next().unwrap()];
+        let mut stack: Vec<ExpressionOperation> = vec![];
+        for (op, value) in operations.into_iter().zip(operands) {
+            while let Some(last_op) = stack.last() {
+                if Self::operation_precedence(last_op) < Self::operation_precedence(&op) {
+                    break;
+                }
+                let last_op = stack.pop().unwrap();
+                let rhs = values.pop().unwrap();
+                let lhs = values.pop().unwrap();
+                values.push(self.binary_operation(bb, &last_op, lhs, rhs)?);
+            }
+            stack.push(op);
+            values.push(value);
+        }
+        while let Some(op) = stack.pop() {
+            let rhs = values.pop().unwrap();
+            let lhs = values.pop().unwrap();
+            values.push(self.binary_operation(bb, &op, lhs, rhs)?);
+        }
+        Ok(values.pop().unwrap())
     }
 
-    pub fn function_value(&mut self, fv: &FunctionValue) -> VecInstructionSet {
+    /// Function value: list of values or expression.
+    /// Return: values
+    pub fn function_value(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        fv: &FunctionValue,
+    ) -> std::result::Result<Vec<Value>, CodegenError> {
         #[cfg(feature = "function_value")]
-        println!("\t#[call] function_value (FunctionValue)");
-        let res = match fv {
-            FunctionValue::ValueList(vl) => vl.iter().fold(vec![], |s, vle| {
+        eprintln!("\t#[call] function_value (FunctionValue)");
+        let mut values = vec![];
+        match fv {
+            FunctionValue::ValueList(vl) => {
                 #[cfg(feature = "function_value")]
-                println!("\t#[function_value] ValueList");
-                let mut res = self.value_expression(vle);
-                let mut x = s;
-                x.append(&mut res);
-                x
-            }),
+                eprintln!("\t#[function_value] ValueList");
+                for vle in vl.iter() {
+                    values.push(self.value_operand(bb, vle)?);
+                }
+            }
             FunctionValue::Expression(expr) => {
                 #[cfg(feature = "function_value")]
-                println!("\t#[function_value] Expression [not impl]: {:#?}", expr);
-                vec![]
+                eprintln!("\t#[function_value] Expression");
+                values.push(self.expression(bb, expr)?);
             }
-        };
+        }
         #[cfg(feature = "function_value")]
-        println!(
+        eprintln!(
             "\t#[function_value] ValueList [{}]\n\t#[end_function_value]",
-            res.len()
+            values.len()
         );
-        res
+        Ok(values)
+    }
+
+    /// Is function defined in current module
+    fn is_defined_function(&self, fn_name: &str) -> bool {
+        self.ast.iter().any(|v| match v {
+            MainStatement::Function(f) => *f.function_name.fragment() == fn_name,
+            _ => false,
+        })
+    }
+
+    /// Declare external function once. External functions are
+    /// `printf`-like: `i32` result and variadic arguments.
+    #[allow(clippy::vec_init_then_push)]
+    fn declare_function(&mut self, fn_name: &str) -> FunctionRef {
+        let ty1 = Type::pointer1(Integer8);
+        let mut fn_decl = decl!(Integer32 fn_name);
+        decl!(fn_decl.argument_list arg!(ty1, ...));
+        self.module.declare(fn_decl)
+    }
+
+    /// Build-in `printfn`: `printf` with line feed. Result is count of
+    /// written bytes, as for `printf`.
+    #[allow(clippy::vec_init_then_push)]
+    fn printfn(&mut self, bb: &mut BasicBlockBuilder, args: Vec<Value>) -> ExpressionResult {
+        let printf = self.declare_function("printf");
+        let name = "putchar";
+        let mut putchar = decl!(Integer32 name);
+        decl!(putchar.argument_list arg!(Integer32));
+        let putchar = self.module.declare(putchar);
+        let count = bb.call(&printf, &args)?.unwrap();
+        bb.call(&putchar, &[Value::new(Integer32, "10")])?;
+        Ok(bb.add(&count, &Value::new(Integer32, "1"))?)
+    }
+
+    /// Target type of build-in numeric conversion function
+    fn conversion_function_type(fn_name: &str) -> Option<Type> {
+        match fn_name {
+            "int" => Some(Integer32),
+            "float" => Some(Type::FloatingPoint(FloatingPointType::Double)),
+            "byte" => Some(Integer8),
+            _ => None,
+        }
+    }
+
+    /// Convert value to numeric type. Integers extended with sign,
+    /// except booleans and bytes which are unsigned.
+    pub fn conversion(
+        &self,
+        bb: &mut BasicBlockBuilder,
+        value: Value,
+        ty: Type,
+    ) -> ExpressionResult {
+        let numeric = |ty: &Type| ty.integer_width().is_some() || ty.is_floating_point();
+        if !numeric(&value.ty) || !numeric(&ty) {
+            return Err(CodegenError::UnsupportedConversion(value.ty, ty));
+        }
+        let unsigned = |ty: &Type| matches!(ty, Integer1 | Integer8);
+        // Sign of floating point conversion defined by integer type
+        let signed = if value.ty.is_floating_point() {
+            !unsigned(&ty)
+        } else {
+            !unsigned(&value.ty)
+        };
+        Ok(bb.cast(&value, &ty, signed)?)
+    }
+
+    /// Type of build-in SIMD vector constructor function: `float4`
+    /// for four `float` lanes, `int4` for four `int` lanes
+    fn vector_function_type(fn_name: &str) -> Option<VectorType> {
+        let element_type = match fn_name {
+            "float4" => Type::FloatingPoint(FloatingPointType::Double),
+            "int4" => Integer32,
+            _ => return None,
+        };
+        Some(VectorType {
+            elemetns: 4,
+            element_type: Box::new(element_type),
+            vscale: false,
+        })
+    }
+
+    /// Build SIMD vector from lane values. Single value is
+    /// broadcasted to all lanes. Lane values converted to element
+    /// type of vector.
+    pub fn vector(
+        &self,
+        bb: &mut BasicBlockBuilder,
+        fn_name: &str,
+        args: Vec<Value>,
+        ty: VectorType,
+    ) -> ExpressionResult {
+        let lanes = ty.elemetns as usize;
+        if args.len() != 1 && args.len() != lanes {
+            return Err(CodegenError::ArgumentsCountMismatch(
+                fn_name.to_string(),
+                args.len(),
+            ));
+        }
+        let undef = Value::new(Type::Vector(ty.clone()), "undef");
+        let mut value = undef.clone();
+        let splat = args.len() == 1;
+        for (i, arg) in args.into_iter().enumerate() {
+            let elt = self.conversion(bb, arg, *ty.element_type.clone())?;
+            let idx = Value::new(Integer32, &i.to_string());
+            value = bb.insertelement(&value, &elt, &idx)?;
+        }
+        if splat {
+            value = bb.shufflevector(&value, &undef, vec![Some(0); lanes])?;
+        }
+        Ok(value)
+    }
+
+    /// Extract lane of SIMD vector: `lane vector index`
+    pub fn vector_lane(
+        &self,
+        bb: &mut BasicBlockBuilder,
+        vec: Value,
+        idx: Value,
+    ) -> ExpressionResult {
+        if !matches!(vec.ty, Type::Vector(_)) {
+            return Err(CodegenError::UnsupportedOperand(vec.ty.to_string()));
+        }
+        if !idx.ty.is_integer() {
+            return Err(CodegenError::UnsupportedOperand(idx.ty.to_string()));
+        }
+        Ok(bb.extractelement(&vec, &idx)?)
+    }
+
+    /// Is function build-in: numeric conversions and SIMD vectors
+    fn is_builtin_function(fn_name: &str) -> bool {
+        Self::conversion_function_type(fn_name).is_some()
+            || Self::vector_function_type(fn_name).is_some()
+            || fn_name == "lane"
+    }
+
+    /// Build-in function call with already evaluated arguments
+    fn builtin_function(
+        &self,
+        bb: &mut BasicBlockBuilder,
+        fn_name: &str,
+        mut args: Vec<Value>,
+    ) -> ExpressionResult {
+        if let Some(ty) = Self::vector_function_type(fn_name) {
+            return self.vector(bb, fn_name, args, ty);
+        }
+        let count = if fn_name == "lane" { 2 } else { 1 };
+        if args.len() != count {
+            return Err(CodegenError::ArgumentsCountMismatch(
+                fn_name.to_string(),
+                args.len(),
+            ));
+        }
+        let value = args.remove(0);
+        match Self::conversion_function_type(fn_name) {
+            Some(ty) => self.conversion(bb, value, ty),
+            None => self.vector_lane(bb, value, args.remove(0)),
+        }
     }
 
-    pub fn function_value_call(
+    /// Floating point intrinsic of build-in `Math` module function
+    fn math_function_type(fn_name: &str) -> Option<MathFunction> {
+        match fn_name {
+            "sqrt" => Some(MathFunction::Sqrt),
+            "sin" => Some(MathFunction::Sin),
+            "cos" => Some(MathFunction::Cos),
+            "exp" => Some(MathFunction::Exp),
+            "log" => Some(MathFunction::Log),
+            "floor" => Some(MathFunction::Floor),
+            "ceil" => Some(MathFunction::Ceil),
+            "round" => Some(MathFunction::Round),
+            "pow" => Some(MathFunction::Pow),
+            "min" => Some(MathFunction::Minnum),
+            "max" => Some(MathFunction::Maxnum),
+            _ => None,
+        }
+    }
+
+    /// Floating point operand. Integers converted to `float`.
+    fn float_operand(&self, bb: &mut BasicBlockBuilder, value: Value) -> ExpressionResult {
+        if value.ty.scalar_type().is_floating_point() {
+            return Ok(value);
+        }
+        self.conversion(bb, value, Type::FloatingPoint(FloatingPointType::Double))
+    }
+
+    /// Build-in `Math` module functions, implemented with LLVM
+    /// intrinsics:
+    /// * `Math.sqrt x`, `Math.pow x y`, `Math.min x y`, ... -
+    ///   floating point functions, integer arguments converted to
+    ///   `float`
+    /// * `Math.abs x` - absolute value of integer or float
+    /// * `Math.popcount x` - count of set bits of integer
+    pub fn math_function(
         &mut self,
-        ctx: &Context,
-        efvc: &ExpressionFunctionValueCall,
-    ) -> (Context, VecInstructionSet) {
-        #[cfg(feature = "function_value_call")]
-        println!("\t#[call] function_value_call (ExpressionFunctionValueCall)");
-        let mut raw_ctx = ctx.clone().val();
-        let mut res = match efvc {
-            ExpressionFunctionValueCall::FunctionValue(ref fv) => {
-                #[cfg(feature = "function_value_call")]
-                println!("\t#[function_value_call] FunctionValue");
-                self.function_value(fv)
-            }
-            ExpressionFunctionValueCall::FunctionCall(ref fc) => {
-                let _ = fc;
-                #[cfg(feature = "function_value_call")]
-                println!(
-                    "\t#[function_value_call] FunctionCall [not_impl]: {:#?}",
-                    fc
-                );
-                vec![]
+        bb: &mut BasicBlockBuilder,
+        fn_name: &str,
+        mut args: Vec<Value>,
+    ) -> ExpressionResult {
+        let qualified_name = format!("Math.{}", fn_name);
+        let math_function = Self::math_function_type(fn_name);
+        let count = match (fn_name, math_function) {
+            ("abs", _) | ("popcount", _) => 1,
+            (_, Some(f)) => f.arity(),
+            (_, None) => return Err(CodegenError::ValueNotFound(qualified_name)),
+        };
+        if args.len() != count {
+            return Err(CodegenError::ArgumentsCountMismatch(
+                qualified_name,
+                args.len(),
+            ));
+        }
+        let ty = args[0].ty.clone();
+        let intrinsic = match (fn_name, math_function) {
+            ("abs", _) if ty.scalar_type().is_floating_point() => {
+                Intrinsic::Math(MathFunction::Fabs, ty)
+            }
+            ("abs", _) => {
+                // Absolute value of minimal integer is not poison
+                args.push(Value::new(Integer1, "false"));
+                Intrinsic::Abs(ty)
+            }
+            ("popcount", _) => {
+                // Booleans aren't integers in source code
+                if ty.scalar_type().integer_width().unwrap_or(1) == 1 {
+                    return Err(CodegenError::ExpressionTypeMismatch(Integer32, ty));
+                }
+                Intrinsic::Ctpop(ty)
+            }
+            (_, f) => {
+                let mut values = vec![];
+                for v in args.into_iter() {
+                    values.push(self.float_operand(bb, v)?);
+                }
+                args = values;
+                let ty = args[0].ty.clone();
+                if let Some(v) = args.iter().find(|v| v.ty != ty) {
+                    return Err(CodegenError::ExpressionTypeMismatch(ty, v.ty.clone()));
+                }
+                Intrinsic::Math(f.unwrap(), ty)
             }
         };
-        res.iter_mut().for_each(|v| {
-            raw_ctx += raw_ctx;
-            v.set_context(raw_ctx);
-            #[cfg(feature = "function_value_call")]
-            println!("\t#[function_value_call] value: {:#?}", v);
-        });
+        let f = self.module.intrinsic(&intrinsic)?;
+        Ok(bb.call(&f, &args)?.unwrap())
+    }
 
-        let mut ctx = Context::new();
-        ctx.set(raw_ctx);
-        #[cfg(feature = "function_value_call")]
-        println!("\t#[end_function_value_call]");
-        (ctx, res)
+    /// Build-in `Atomic` module functions with sequentially
+    /// consistent ordering. First argument is global let-value
+    /// used as memory location:
+    /// * `Atomic.increment x` - increment value, return new value
+    /// * `Atomic.compareExchange x cmp new` - store `new` if value
+    ///   equal to `cmp`, return original value
+    pub fn atomic_function(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        fn_name: &str,
+        fc: &FunctionCall,
+    ) -> ExpressionResult {
+        let qualified_name = format!("Atomic.{}", fn_name);
+        // Memory location is not loaded, other arguments evaluated
+        // as values
+        let (location, rest) = match fc.function_value.split_first() {
+            Some((FunctionValue::ValueList(vl), rest)) if !vl.is_empty() => (vl, rest),
+            _ => return Err(CodegenError::ArgumentsCountMismatch(qualified_name, 0)),
+        };
+        let pointer = match &location[0] {
+            ValueExpression::ParameterValue(pv) => {
+                let name = pv.fragment().to_string();
+                match self.global_values.get(&name) {
+                    Some(v) => v.clone(),
+                    None => return Err(CodegenError::ValueNotFound(name)),
+                }
+            }
+            ValueExpression::TypeExpression(te) => {
+                return Err(CodegenError::UnsupportedOperand(format!("{:?}", te.expr)))
+            }
+        };
+        let mut args = vec![];
+        for vle in location.iter().skip(1) {
+            args.push(self.value_operand(bb, vle)?);
+        }
+        for v in rest.iter() {
+            args.append(&mut self.function_value(bb, v)?);
+        }
+        let ty = match pointer.ty.pointee_type() {
+            Some(ty) => ty.clone(),
+            None => return Err(CodegenError::UnsupportedOperand(pointer.ty.to_string())),
+        };
+        if let Some(v) = args.iter().find(|v| v.ty != ty) {
+            return Err(CodegenError::ExpressionTypeMismatch(ty, v.ty.clone()));
+        }
+        self.set_position(bb, fc.function_call_name.first().map(Self::span_position));
+        match (fn_name, args.len()) {
+            ("increment", 0) if ty.is_integer() => {
+                let one = Value::new(ty, "1");
+                let old = bb.atomicrmw(
+                    AtomicRMWOperation::Add,
+                    &pointer,
+                    &one,
+                    AtomicOrdering::SeqCst,
+                )?;
+                self.binary_operation(bb, &ExpressionOperation::Plus, old, one)
+            }
+            ("increment", 0) => Err(CodegenError::UnsupportedOperand(ty.to_string())),
+            ("compareExchange", 2) => {
+                let res = bb.cmpxchg(
+                    &pointer,
+                    &args[0],
+                    &args[1],
+                    AtomicOrdering::SeqCst,
+                    AtomicOrdering::SeqCst,
+                )?;
+                Ok(bb.extractvalue(&res, &[0])?)
+            }
+            ("increment", _) | ("compareExchange", _) => Err(CodegenError::ArgumentsCountMismatch(
+                qualified_name,
+                args.len() + 1,
+            )),
+            _ => Err(CodegenError::ValueNotFound(qualified_name)),
+        }
+    }
+
+    /// Is function of other module, which isn't overridden by
+    /// current module
+    fn is_external_function(&self, fn_name: &str) -> bool {
+        !self.is_defined_function(fn_name)
+            && self
+                .options
+                .external_functions
+                .iter()
+                .any(|f| f.name == fn_name)
+    }
+
+    /// Call function of other module by qualified name. Function is
+    /// declared with signature of its definition.
+    fn external_function_call(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        name: &FunctionCallName,
+        args: Vec<Value>,
+    ) -> StatementResult {
+        let qualified_name = name
+            .iter()
+            .map(|v| v.fragment().to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+        let f = match self
+            .options
+            .external_functions
+            .iter()
+            .find(|f| f.name == qualified_name)
+        {
+            Some(f) => f,
+            None => return Err(CodegenError::ValueNotFound(qualified_name)),
+        };
+        let mut fn_decl = decl!(Void qualified_name);
+        fn_decl.function_name = f.symbol.clone();
+        fn_decl.result_type = f.result_type.clone();
+        fn_decl.argument_list = f
+            .parameter_types
+            .iter()
+            .map(|ty| ArgumentList {
+                parameter_type: Some(ty.clone()),
+                attributes: None,
+                name: None,
+                variable_argument: false,
+            })
+            .collect();
+        let f = self.module.declare(fn_decl);
+        Self::check_arguments(&qualified_name, &f, &args)?;
+        Ok(bb.call(&f, &args)?)
+    }
+
+    /// Check count and types of arguments of function, which has no
+    /// variadic arguments
+    fn check_arguments(
+        fn_name: &str,
+        f: &FunctionRef,
+        args: &[Value],
+    ) -> std::result::Result<(), CodegenError> {
+        let params = &f.ty.parameter_list;
+        if params.len() != args.len() {
+            return Err(CodegenError::ArgumentsCountMismatch(
+                fn_name.to_string(),
+                args.len(),
+            ));
+        }
+        for (ty, arg) in params.iter().zip(args.iter()) {
+            if !ty.is_compatible(&arg.ty) {
+                return Err(CodegenError::ExpressionTypeMismatch(
+                    ty.clone(),
+                    arg.ty.clone(),
+                ));
+            }
+        }
+        Ok(())
     }
 
-    // TODO: read values from local/global variable stor, and return VecInst insted String instructions
-    #[allow(clippy::vec_init_then_push)]
     pub fn function_call(
         &mut self,
-        ctx: &Context,
+        bb: &mut BasicBlockBuilder,
         fc: &FunctionCall,
-    ) -> (Context, VecInstructionSet) {
-        use crate::llvm::functions::ArgumentList;
-
+    ) -> StatementResult {
         #[cfg(feature = "function_call")]
-        print!("\t#[call] function_call (FunctionCall):");
+        eprint!("\t#[call] function_call (FunctionCall):");
         if fc.function_call_name.is_empty() {
-            return (ctx.clone(), vec![]);
+            return Ok(None);
         }
-        let mut raw_ctx = ctx.clone().val();
         let fn_name = fc.function_call_name[0].fragment();
         #[cfg(feature = "function_call")]
-        println!("\t#[function_call] fn_name: {}", fn_name);
-        let data: (Vec<String>, Vec<ArgumentList>) = fc
-            .function_value
-            .iter()
-            .fold(vec![], |instr, v| {
-                let mut x = instr;
-                x.append(&mut self.function_value(v));
-                x
-            })
-            .iter_mut()
-            .fold((vec![], vec![]), |d, param| {
-                let mut new_data = d;
-                raw_ctx += 1;
-                //params[i].set_context(raw_ctx);
-                param.set_context(raw_ctx);
-                #[cfg(feature = "function_call")]
-                print!("\t->{} [{}] ", raw_ctx, param);
-                if let Some(p) = param.get_type() {
-                    #[cfg(feature = "function_call")]
-                    println!("type: {}", p);
-                    let n1 = param.get_value().unwrap();
-                    raw_ctx += 1;
-                    let val_alias = raw_ctx.to_string();
-                    let p1 = p.clone();
-                    let ge1 =
-                        getelementptr!(p inbounds val_alias, n1 => [Integer64 0, Integer64 0]);
-                    #[cfg(feature = "function_call")]
-                    println!("\t->{}: {} = {}", val_alias, p1, ge1);
-                    let arg = ArgumentList {
-                        parameter_type: Some(p1),
-                        attributes: None,
-                        name: Some(val_alias),
-                        variable_argument: false,
-                    };
-                    new_data.0.push(ge1.to_string());
-                    new_data.1.push(arg);
-                } else {
-                    let ty1 = Type::Pointer(PointerType(Box::new(Integer8)));
-                    let n1 = param.get_value().unwrap();
-                    #[cfg(feature = "function_call")]
-                    println!("no-type: {}: {}", n1, ty1);
+        eprintln!("\t#[function_call] fn_name: {}", fn_name);
+        if let [module, name] = fc.function_call_name.as_slice() {
+            if *module.fragment() == "Atomic" {
+                return Ok(Some(self.atomic_function(bb, name.fragment(), fc)?));
+            }
+        }
+        let mut args = vec![];
+        for v in fc.function_value.iter() {
+            args.append(&mut self.function_value(bb, v)?);
+        }
+        self.set_position(bb, Some(Self::span_position(&fc.function_call_name[0])));
+        if let [module, name] = fc.function_call_name.as_slice() {
+            if *module.fragment() == "Math" {
+                return Ok(Some(self.math_function(bb, name.fragment(), args)?));
+            }
+        }
+        if fc.function_call_name.len() > 1 || self.is_external_function(fn_name) {
+            return self.external_function_call(bb, &fc.function_call_name, args);
+        }
+        // Build-in functions, if not overridden by module
+        if Self::is_builtin_function(fn_name) && !self.is_defined_function(fn_name) {
+            return Ok(Some(self.builtin_function(bb, fn_name, args)?));
+        }
+        if *fn_name == "printfn" && !self.is_defined_function(fn_name) {
+            return Ok(Some(self.printfn(bb, args)?));
+        }
+        // Functions of current module called directly, other
+        // functions should be declared. Signature of function is
+        // known only when it's generated.
+        let f = if self.is_defined_function(fn_name) {
+            match self.functions.get(*fn_name) {
+                Some(f) => f.clone(),
+                None => {
+                    return Err(CodegenError::Unsupported(format!(
+                        "call of function `{}` before end of its definition",
+                        fn_name
+                    )))
                 }
-                new_data
-            });
-        // Declare function
-        let ty1 = Type::Pointer(PointerType(Box::new(Integer8)));
-        let mut fn_decl = decl!(Integer32 fn_name);
-        let args = arg!(ty1, ...);
-        let args_decl = args.clone();
-        decl!(fn_decl.argument_list args);
-        #[cfg(feature = "function_call")]
-        println!("\t->{}", fn_decl);
-        let fn_call = call!(Integer32 => @fn_name args_decl => []);
-        #[cfg(feature = "function_call")]
-        println!("\t->{}\t->{:?}", fn_call, data);
-
-        let mut ctx = Context::new();
-        ctx.set(raw_ctx);
+            }
+        } else {
+            self.declare_function(fn_name)
+        };
+        if !f.ty.variable_argument {
+            Self::check_arguments(fn_name, &f, &args)?;
+        }
+        let value = bb.call(&f, &args)?;
         #[cfg(feature = "function_call")]
-        println!("\t#[end_function_call]");
-        (ctx, vec![])
+        eprintln!("\t->{:?}\n\t#[end_function_call]", value);
+        Ok(value)
     }
 
     pub fn fn_body_statement(
         &mut self,
-        ctx: &Context,
+        bb: &mut BasicBlockBuilder,
         fbs: &FunctionBodyStatement,
-    ) -> (Context, VecInstructionSet) {
+    ) -> StatementResult {
         #[cfg(feature = "fn_body_statement")]
-        println!("\t#[call] fn_body_statement");
+        eprintln!("\t#[call] fn_body_statement");
         #[cfg(feature = "fn_body_statement_dump")]
-        println!("\t[fn_body_statement] {:#?}", fbs);
+        eprintln!("\t[fn_body_statement] {:#?}", fbs);
         let res = match fbs {
             FunctionBodyStatement::Expression(e) => {
                 #[cfg(feature = "fn_body_statement")]
-                println!("\t#[fn_body_statement] Expression");
-                if let Some(op) = &e.operation_statement {
-                    #[cfg(feature = "fn_body_statement")]
-                    println!("\t#[fn_body_statement] operation_statement: {:?}", op);
-                    if let Some(ex) = &e.expression {
-                        println!("\t#[fn_body_statement] expression [not-impl]: {:?}", ex);
-                    } else {
-                        panic!("\t#[fn_body_statement] Expression doesn't exist")
-                    }
-                }
-                self.function_value_call(ctx, &e.function_statement)
+                eprintln!(
+                    "\t#[fn_body_statement] Expression operation_statement: {:?}",
+                    e.operation_statement
+                );
+                Some(self.expression(bb, e)?)
             }
             FunctionBodyStatement::FunctionCall(fc) => {
                 #[cfg(feature = "fn_body_statement")]
-                println!("\t#[fn_body_statement] FunctionCall");
-                self.function_call(ctx, fc)
+                eprintln!("\t#[fn_body_statement] FunctionCall");
+                self.function_call(bb, fc)?
             }
             FunctionBodyStatement::LetBinding(lb) => {
                 #[cfg(feature = "fn_body_statement")]
-                println!("\t#[fn_body_statement] LetBinding");
-                let _ = self.fn_body(&lb.function_body);
+                eprintln!("\t#[fn_body_statement] LetBinding");
+                let position = Self::span_position(&lb.let_position);
+                let value = self.fn_body_statements(bb, &lb.function_body)?;
                 // Add to local variable
-                (ctx.clone(), vec![])
+                if let (Some(ParameterValueList::ParameterValue(name)), Some(value)) =
+                    (lb.value_list.first(), value)
+                {
+                    // Debug info of let-value located at `let`
+                    if self.debug_info.is_some() {
+                        self.set_position(bb, Some(position));
+                        let di = self.debug_info.as_mut().unwrap();
+                        let name = name.fragment();
+                        di.local_variable(&mut self.module, bb, name, position.0, &value)?;
+                    }
+                    self.let_values.insert(name.fragment().to_string(), value);
+                }
+                None
             }
         };
         #[cfg(feature = "fn_body_statement")]
-        println!("\t#[end_fn_body_statement]");
-        res
+        eprintln!("\t#[end_fn_body_statement]");
+        Ok(res)
     }
 
     pub fn fn_parameter_value_type(&self, pvt: &ParameterValueType) -> ValueType {
-        println!("\t#[call] fn_parameter_value_type: ParameterValueType");
+        #[cfg(feature = "fn_global_let")]
+        eprintln!("\t#[call] fn_parameter_value_type: ParameterValueType");
         match pvt {
             ParameterValueType::Value(v) => ValueType {
                 value: v.fragment().to_string(),
@@ -395,7 +1126,8 @@ impl<'a> Codegen<'a> {
     }
 
     pub fn fn_parameter_value_list(&self, pvl: &ParameterValueList) -> Vec<ValueType> {
-        println!("\t#[call] fn_parameter_value_list: ParameterValueList");
+        #[cfg(feature = "fn_global_let")]
+        eprintln!("\t#[call] fn_parameter_value_list: ParameterValueList");
         match pvl {
             ParameterValueList::ParameterValue(p) => {
                 vec![ValueType {
@@ -410,79 +1142,167 @@ impl<'a> Codegen<'a> {
         }
     }
 
-    /// Body of any kind expression.
-    /// Return: Instructions, last Type, last Value
+    /// Sequence of function body statements.
+    /// Return: value of last statement
+    pub fn fn_body_statements(
+        &mut self,
+        bb: &mut BasicBlockBuilder,
+        ast: &FunctionBody,
+    ) -> StatementResult {
+        let mut last_body_value: Option<Value> = None;
+        for b in ast.iter() {
+            last_body_value = self.fn_body_statement(bb, b)?;
+        }
+        Ok(last_body_value)
+    }
+
+    /// Body of any kind expression. Parameters are local values of
+    /// body.
+    /// Return: last value
     pub fn fn_body(
         &mut self,
+        bb: &mut BasicBlockBuilder,
         ast: &FunctionBody,
-    ) -> (VecInstructionSet, Option<Type>, Option<String>) {
+        params: Vec<(String, Value)>,
+    ) -> StatementResult {
         #[cfg(feature = "fn_body")]
-        println!("\t#[call] fn_body: FunctionBody");
-        let mut entry_ctx = Context::new();
-        //let src = entry!(entry_ctx.get());
-        let mut last_body_type: Option<Type> = None;
-        let mut last_body_value: Option<String> = None;
-        let body_instr: VecInstructionSet = ast.iter().fold(vec![], |v, b| {
-            let (ctx, mut statement) = self.fn_body_statement(&entry_ctx, b);
-            entry_ctx = ctx;
-            for i in (0..statement.len()).rev() {
-                if let Some(ty) = statement[i].get_type() {
-                    last_body_type = Some(ty);
-                    last_body_value = statement[i].get_value();
-                }
-            }
-            let mut v = v;
-            v.append(&mut statement);
-            v
-        });
+        eprintln!("\t#[call] fn_body: FunctionBody");
+        // Local values visible only for current body
+        self.let_values = params.into_iter().collect();
+        let last_body_value = self.fn_body_statements(bb, ast)?;
         #[cfg(feature = "fn_body")]
-        println!("\t#[fn_body] fn_body: {:#?} \n\t#[end_fn_body]", body_instr);
-        (body_instr, last_body_type, last_body_value)
-    }
-
-    pub fn fn_module(&self) -> Result {
-        let src: String;
-        if !self.ast.is_empty() {
-            if let MainStatement::Module(m) = &self.ast[0] {
-                let s = m.module_name.iter().enumerate().fold(
-                    "; ModuleID = '".to_string(),
-                    |s, (i, name)| {
-                        if i > 0 {
-                            format!("{}.{}", s, name)
-                        } else {
-                            format!("{}{}", s, name)
-                        }
-                    },
-                );
-                let s = format!("{}'", s);
+        eprintln!(
+            "\t#[fn_body] fn_body: {:#?} \n\t#[end_fn_body]",
+            last_body_value
+        );
+        Ok(last_body_value)
+    }
+
+    pub fn fn_module(&mut self) -> std::result::Result<(), CodegenError> {
+        let module = self.ast.iter().find_map(|v| match v {
+            MainStatement::Module(m) => Some(m),
+            _ => None,
+        });
+        // Module may be preceded only by namespace
+        match (Self::module_id(self.ast), module) {
+            (Some(module_id), Some(m)) => {
                 let source_file = format!("{}.i", m.module_name[m.module_name.len() - 1]);
-                let sf = source_file!(source_file);
-                let tt = target_triple!(TARGET_X86_64_UNKNOWN_LINUX_GNU);
-                src = merge!(s sf tt);
-            } else {
-                return Err(CodegenError::ModuleNotFound);
+                self.module = ModuleBuilder::new(&module_id);
+                self.module_id = module_id;
+                self.module.source_filename(&source_file);
+                self.module.typed_pointers(self.options.typed_pointers);
+                if let Some(layout) = &self.options.data_layout {
+                    self.module.data_layout(DataLayout(layout.clone()));
+                }
+                match &self.options.target_triple {
+                    Some(triple) => self.module.target_triple(TargetTriple(triple.clone())),
+                    None => self
+                        .module
+                        .target_triple(target_triple!(TARGET_X86_64_UNKNOWN_LINUX_GNU)),
+                }
+                for ext in self.options.external_globals.iter() {
+                    let ty = ext.value_type.clone();
+                    let mut g = global!(Global ty ext.symbol);
+                    global!(g.linkage @External);
+                    let global_value = self.module.global(g);
+                    self.global_values.insert(ext.name.clone(), global_value);
+                }
+                if self.options.debug_info {
+                    let file = self.options.source_file.clone().unwrap_or(source_file);
+                    self.debug_info = Some(DebugInfoBuilder::new(&mut self.module, &file));
+                }
+                Ok(())
             }
-        } else {
-            return Err(CodegenError::ModuleNotFound);
+            _ => Err(CodegenError::ModuleNotFound),
         }
-        Ok(src)
     }
 
-    fn global_init_fn_def(&self, global_let_statement: u64) -> String {
-        let name = format!("__global_let_init.{}", global_let_statement);
+    fn global_init_fn_def(&self, name: &str) -> Function {
         let mut fn_def = def!(Void name);
         def!(fn_def.linkage @Internal);
         def!(fn_def.attr_group vec![0]);
         def!(fn_def.section_name @".text.startup".to_string());
-        fn_def.to_string()
+        fn_def
     }
 
-    // Very simplified representation
-    fn init_fn_def(&self, fn_name: &str) -> String {
-        let mut fn_def = def!(Integer32 fn_name);
-        def!(fn_def.linkage @Internal);
+    // Very simplified representation. Entry point and functions with
+    // qualified symbols are visible to linker.
+    fn init_fn_def(&self, fn_name: &str) -> Function {
+        let symbol = self.function_symbol(fn_name);
+        let mut fn_def = def!(Integer32 symbol);
+        if fn_name != ENTRY_POINT && !self.options.qualified_symbols {
+            def!(fn_def.linkage @Internal);
+        }
         def!(fn_def.attr_group vec![0]);
-        fn_def.to_string()
+        fn_def
+    }
+
+    /// Source code name of type: `unit`, `int`, `float`, `byte`,
+    /// `bool` or `string`, LLVM type name for other types
+    pub fn source_type_name(ty: &Type) -> String {
+        if *ty == Void {
+            return "unit".to_string();
+        }
+        ["int", "float", "byte", "bool", "string"]
+            .iter()
+            .find(|name| DebugInfoBuilder::source_type(name).as_ref() == Some(ty))
+            .map_or_else(|| ty.to_string(), |name| name.to_string())
+    }
+
+    /// Function parameters and their types. Types aren't inferred,
+    /// so parameters without type are `int`.
+    pub fn function_parameters(
+        pl: &ParameterList,
+    ) -> std::result::Result<Vec<(String, Type)>, CodegenError> {
+        let parameter = |pvt: &ParameterValueType| match pvt {
+            ParameterValueType::Value(v) => Ok((v.fragment().to_string(), Integer32)),
+            ParameterValueType::ValueType(v, t) => {
+                let name = t
+                    .iter()
+                    .map(|v| *v.fragment())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                match DebugInfoBuilder::source_type(&name) {
+                    Some(ty) => Ok((v.fragment().to_string(), ty)),
+                    None => Err(CodegenError::Unsupported(format!(
+                        "type `{}` of parameter `{}`",
+                        name,
+                        v.fragment()
+                    ))),
+                }
+            }
+        };
+        let value_list = |pvl: &ParameterValueList| match pvl {
+            ParameterValueList::ParameterValue(p) => {
+                vec![Ok((p.fragment().to_string(), Integer32))]
+            }
+            ParameterValueList::ParameterList(pl) => pl.iter().map(parameter).collect(),
+        };
+        match pl {
+            ParameterList::ParameterList(pvl) => value_list(pvl).into_iter().collect(),
+            ParameterList::ParameterValueList(v) => v.iter().flat_map(value_list).collect(),
+        }
+    }
+
+    /// Start debug info of function, defined at source line
+    fn begin_function(
+        &mut self,
+        func: &mut FunctionBuilder,
+        name: &str,
+        line: u32,
+        params: &[(String, Option<Type>)],
+        artificial: bool,
+    ) {
+        if let Some(di) = self.debug_info.as_mut() {
+            di.begin_function(&mut self.module, func, name, line, params, artificial);
+        }
+    }
+
+    /// Finish debug info of function with known result type
+    fn end_function(&mut self, result_type: &Type) {
+        if let Some(di) = self.debug_info.as_mut() {
+            di.end_function(&mut self.module, result_type);
+        }
     }
 
     fn set_let_value_types(&mut self, l: &LetBinding) {
@@ -493,119 +1313,177 @@ impl<'a> Codegen<'a> {
         }
     }
 
-    pub fn fn_global_let(&mut self) -> Result {
+    pub fn fn_global_let(&mut self) -> std::result::Result<(), CodegenError> {
         #[cfg(feature = "fn_global_let")]
-        println!("\t#[call] fn_global_let");
-        let mut global_let_statement = 0;
+        eprintln!("\t#[call] fn_global_let");
+        let mut global_inits = vec![];
         // Fetch AST tree and generate source code
-        let let_src = self.ast.iter().fold("".to_string(), |src, v| {
+        let ast = self.ast;
+        for v in ast.iter() {
             // Global let bindings
             #[cfg(feature = "fn_global_let")]
-            println!("\t#[fn_global_let] {:#?}", v);
+            eprintln!("\t#[fn_global_let] {:#?}", v);
             match v {
                 MainStatement::LetBinding(l) => {
                     // Get Let-names & types
                     self.set_let_value_types(l);
                     // Function definition
-                    let fn_def = self.global_init_fn_def(global_let_statement);
+                    let name = format!("__global_let_init.{}", global_inits.len());
+                    let mut func = FunctionBuilder::new(self.global_init_fn_def(&name));
+                    let position = Self::span_position(&l.let_position);
+                    self.begin_function(&mut func, &name, position.0, &[], false);
+                    let mut bb = func.block();
                     // Get function body
-                    let (body_instr, ty, val) = self.fn_body(&l.function_body);
-                    let mut src = "".to_string();
-                    body_instr.iter().for_each(|v| {
-                        src = merge!(src v);
-                    });
-                    let ret = if let Some(ty) = ty {
-                        let val = val.unwrap();
-                        ret!(ty @ val)
-                    } else {
-                        ret!()
-                    };
-                    let body = body!(src ret);
-
-                    // Generate function
-                    let fn_body_src = fn_body!(fn_def body);
-                    global_let_statement += 1;
-                    // Merge generated code
-                    merge!(src fn_body_src)
+                    let value = self.fn_body(&mut bb, &l.function_body, vec![])?;
+                    // Store let-value to global variable
+                    if let (Some(ParameterValueList::ParameterValue(name)), Some(value)) =
+                        (l.value_list.first(), value)
+                    {
+                        let name = name.fragment().to_string();
+                        let ty = value.ty.clone();
+                        let mut g = global!(Global ty self.global_symbol(&name));
+                        if !self.options.qualified_symbols {
+                            global!(g.linkage @Internal);
+                        }
+                        global!(g.initializer_constant @"zeroinitializer".to_string());
+                        let global_value = self.module.global(g);
+                        self.set_position(&mut bb, Some(position));
+                        bb.store(&value, &global_value)?;
+                        self.global_values.insert(name, global_value);
+                    }
+                    bb.ret(None)?;
+                    self.end_function(&Void);
+                    global_inits.push(self.module.function(func)?);
                 }
                 MainStatement::Function(f) => {
-                    let fn_def = self.init_fn_def(&f.function_name);
+                    let params = Self::function_parameters(&f.parameter_list)?;
+                    // Entry point has C `main` signature, only count of
+                    // arguments is passed
+                    let entry_point = self.function_symbol(&f.function_name) == ENTRY_POINT;
+                    if entry_point && !matches!(params.as_slice(), [] | [(_, Integer32)]) {
+                        return Err(CodegenError::Unsupported(format!(
+                            "`{}` parameters except count of arguments",
+                            ENTRY_POINT
+                        )));
+                    }
+                    let mut fn_def = self.init_fn_def(&f.function_name);
+                    fn_def.argument_list = params
+                        .iter()
+                        .map(|(name, ty)| ArgumentList {
+                            parameter_type: Some(ty.clone()),
+                            attributes: None,
+                            name: Some(name.clone()),
+                            variable_argument: false,
+                        })
+                        .collect();
+                    let mut func = FunctionBuilder::new(fn_def);
+                    let line = f.function_name.location_line();
+                    let debug_params = params
+                        .iter()
+                        .map(|(name, ty)| (name.clone(), Some(ty.clone())))
+                        .collect::<Vec<_>>();
+                    self.begin_function(&mut func, &f.function_name, line, &debug_params, false);
+                    let params = params.into_iter().map(|v| v.0).zip(func.params()).collect();
                     // Get function body
-                    let (body_instr, ty, val) = self.fn_body(&f.function_body);
-                    let mut src = "".to_string();
-                    body_instr.iter().for_each(|v| {
-                        src = merge!(src v);
-                    });
-                    let ret = if let Some(ty) = ty {
-                        let val = val.unwrap();
-                        ret!(ty @ val)
-                    } else {
-                        ret!()
-                    };
-                    let body = body!(src ret);
-                    // Generate function
-                    let fn_body_src = fn_body!(fn_def body);
-                    // Merge generated code
-                    merge!(src fn_body_src)
+                    let value = self.fn_body(&mut func.block(), &f.function_body, params)?;
+                    // Result type defined by last value of body. Entry
+                    // point without value returns 0.
+                    match value {
+                        Some(value) if entry_point && value.ty != Integer32 => {
+                            return Err(CodegenError::Unsupported(format!(
+                                "`{}` result of type `{}`",
+                                ENTRY_POINT,
+                                Self::source_type_name(&value.ty)
+                            )));
+                        }
+                        Some(value) => {
+                            func.set_result_type(value.ty.clone());
+                            func.block().ret(Some(&value))?;
+                            self.end_function(&value.ty);
+                        }
+                        None if entry_point => {
+                            func.set_result_type(Integer32);
+                            func.block().ret(Some(&Value::new(Integer32, "0")))?;
+                            self.end_function(&Integer32);
+                        }
+                        None => {
+                            func.set_result_type(Void);
+                            func.block().ret(None)?;
+                            self.end_function(&Void);
+                        }
+                    }
+                    let fn_ref = self.module.function(func)?;
+                    self.functions.insert(f.function_name.to_string(), fn_ref);
                 }
-                _ => src,
+                _ => (),
             }
-        });
-        // TODO: remove
-        self.global_let_values
-            .iter()
-            .for_each(|(n, _)| println!("\t# [glv] {}", n));
-
-        let mut src = let_src;
-        if global_let_statement > 0 {
-            let global_ctors = "@llvm.global_ctors = appending global [1 x { i32, void ()*, i8* }] [{ i32, void ()*, i8* } { i32 65535, void ()* @_GLOBAL_let_main, i8* null }]\n".to_string();
-            let name = "_GLOBAL_let_main";
-            let mut fn_def = def!(Void name);
-            def!(fn_def.linkage @Internal);
-            def!(fn_def.attr_group vec![0]);
-            def!(fn_def.section_name @".text.startup".to_string());
-
-            // Set body of function with @call instr
-            let mut call = vec![];
-            for i in 0..global_let_statement {
-                let name = format!("__global_let_init.{}", i);
-                let call_fn = call!(Void => @name vec![] => []);
-                call.push(call_fn.to_string());
+        }
+        if !global_inits.is_empty() {
+            let name = GLOBAL_INIT_FUNCTION;
+            let mut func = FunctionBuilder::new(self.global_init_fn_def(name));
+            // Generated function has no source position
+            self.begin_function(&mut func, name, 0, &[], true);
+            let mut bb = func.block();
+            self.set_position(&mut bb, Some((0, 0)));
+            for f in global_inits.iter() {
+                bb.call(f, &[])?;
             }
-            call.push(ret!().to_string());
-
-            let body = body!(@ call);
-            let fn_body = fn_body!(fn_def body);
-            src = merge!(src global_ctors fn_body);
+            bb.ret(None)?;
+            self.end_function(&Void);
+            let f = self.module.function(func)?;
+            self.module.global_ctor(65535, &f)?;
         }
-        Ok(src)
+        Ok(())
+    }
+
+    /// Generate IR module model for AST. Module is verified, so
+    /// malformed IR is reported as codegen error.
+    pub fn fn_ir_module(ast: &'a Main) -> std::result::Result<Module, CodegenError> {
+        Self::fn_ir_module_with_options(ast, CodegenOptions::default())
     }
 
-    fn fn_attr_group(&self) -> String {
+    pub fn fn_ir_module_with_options(
+        ast: &'a Main,
+        options: CodegenOptions,
+    ) -> std::result::Result<Module, CodegenError> {
+        let mut codegen = Self::new(ast, options);
+        codegen.fn_module()?;
+        codegen.fn_global_let()?;
         let attr0 = Attributes(0, vec!["noinline".to_string(), "uwtable".to_string()]);
-        merge!(attr0)
+        codegen.module.attribute_group(attr0);
+        let module = codegen.module.build();
+        verifier::verify(&module)?;
+        Ok(module)
     }
 
     pub fn fn_main(ast: &'a Main) -> Result {
         #[cfg(feature = "fn_main")]
-        println!("\t#[call] fn_main");
+        eprintln!("\t#[call] fn_main");
 
-        let mut codegen = Self::new(ast);
-        let module = codegen.fn_module()?;
-        let global_let = codegen.fn_global_let()?;
-        let attrs = codegen.fn_attr_group();
-        let src = module!(module global_let attrs);
+        let src = Self::fn_ir_module(ast)?.to_string();
         #[cfg(feature = "fn_main")]
-        println!("\n[fn_main]: {}", src);
+        eprintln!("\n[fn_main]: {}", src);
         Ok(src)
     }
 
+    /// Parse source code. Whole source code should be parsed.
+    fn parse(source: &str) -> std::result::Result<Main<'_>, CodegenError> {
+        let (rest, ast) = crate::parser::token::main(Span::new(source))
+            .map_err(|_| CodegenError::ParseSourceCode)?;
+        if !rest.fragment().trim().is_empty() {
+            return Err(CodegenError::ParseSourceCode);
+        }
+        Ok(ast)
+    }
+
     pub fn build(source: &str) -> Result {
-        use crate::parser::token::main;
+        Codegen::fn_main(&Self::parse(source)?)
+    }
 
-        let src = main(Span::new(source)).unwrap();
-        if src.0.fragment().is_empty() {}
-        Codegen::fn_main(&src.1)
+    /// Build source code with codegen options
+    pub fn build_with_options(source: &str, options: CodegenOptions) -> Result {
+        let ast = Self::parse(source)?;
+        Codegen::fn_ir_module_with_options(&ast, options).map(|v| v.to_string())
     }
 }
 
@@ -614,6 +1492,7 @@ mod tests {
     use crate::codegen::{
         Codegen,
         CodegenError,
+        CodegenOptions,
     };
     use crate::parser::{
         ast::Span,
@@ -628,6 +1507,22 @@ mod tests {
         assert_eq!(res.unwrap_err(), CodegenError::ModuleNotFound);
     }
 
+    #[test]
+    fn test_codegen_parse_source_code() {
+        assert_eq!(
+            Codegen::build("module main\nlet main () = 1 +"),
+            Err(CodegenError::ParseSourceCode)
+        );
+        // Unparsed rest of source code is error
+        let src = "module main\nlet main () =\n    let y = f() + 2\n    y";
+        assert_eq!(Codegen::build(src), Err(CodegenError::ParseSourceCode));
+        assert_eq!(
+            Codegen::build_with_options(src, CodegenOptions::default()),
+            Err(CodegenError::ParseSourceCode)
+        );
+        assert!(Codegen::build("module main\nlet main () = 1\n\n").is_ok());
+    }
+
     #[test]
     fn test_codegen_global_let_binding() {
         let x = main(Span::new("module name1.name2\nlet x1 = 10")).unwrap();
@@ -638,10 +1533,254 @@ mod tests {
 
     #[test]
     fn test_codegen_global_let_expression() {
+        let x = main(Span::new(
+            "module name1.name2\nlet x1 = 10\nlet x2 = 10 * x1",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("@x1 = internal global i32 zeroinitializer"));
+        assert!(res.contains("%1 = load i32, ptr @x1"));
+        assert!(res.contains("%2 = mul i32 10, %1"));
+        assert!(res.contains("store i32 %2, ptr @x2"));
+    }
+
+    #[test]
+    fn test_codegen_global_let_expression_value_not_found() {
         let x = main(Span::new("module name1.name2\nlet x2 = 10 * x1")).unwrap();
         assert_eq!(x.0.fragment(), &"");
         let res = Codegen::fn_main(&x.1);
-        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap_err(),
+            CodegenError::ValueNotFound("x1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_codegen_expression_precedence() {
+        let x = main(Span::new("module name1.name2\nlet x1 = 1 + 2 * 3 - 4 / 2")).unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = mul i32 2, 3"));
+        assert!(res.contains("%2 = add i32 1, %1"));
+        assert!(res.contains("%3 = sdiv i32 4, 2"));
+        assert!(res.contains("%4 = sub i32 %2, %3"));
+        assert!(res.contains("store i32 %4, ptr @x1"));
+    }
+
+    #[test]
+    fn test_codegen_expression_brackets() {
+        let x = main(Span::new(
+            "module name1.name2\nlet x1 = (1 + 2) * ((3 - 4) <<< 2) >>> 1",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = add i32 1, 2"));
+        assert!(res.contains("%2 = sub i32 3, 4"));
+        assert!(res.contains("%3 = shl i32 %2, 2"));
+        assert!(res.contains("%4 = mul i32 %1, %3"));
+        assert!(res.contains("%5 = ashr i32 %4, 1"));
+    }
+
+    #[test]
+    fn test_codegen_shift_amount() {
+        let src = "module main\nlet f a = (1 <<< 33) + (a >>> 0 - 1)\n\
+            let g () = lane (int4 1 <<< int4 33) 0";
+        let ir = Codegen::build(src).unwrap();
+        assert!(ir.contains("%1 = shl i32 1, 1"));
+        assert!(ir.contains("%2 = sub i32 0, 1"));
+        assert!(ir.contains("%3 = and i32 %2, 31"));
+        assert!(ir.contains("%4 = ashr i32 %a, %3"));
+        assert!(ir.contains("%5 = and <4 x i32> %4, <i32 31, i32 31, i32 31, i32 31>"));
+        assert!(ir.contains("%6 = shl <4 x i32> %2, %5"));
+    }
+
+    #[test]
+    fn test_codegen_expression_float() {
+        let x = main(Span::new(
+            "module name1.name2\nlet x1 = 1.5 + 2.5 / 0.5 - 0.25",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = fdiv double 0x4004000000000000, 0x3FE0000000000000"));
+        assert!(res.contains("%2 = fadd double 0x3FF8000000000000, %1"));
+        assert!(res.contains("%3 = fsub double %2, 0x3FD0000000000000"));
+
+        // Float type is defined by literal syntax, not by value
+        let x = main(Span::new("module name1.name2\nlet x1 = 2.0 * 1.5")).unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = fmul double 0x4000000000000000, 0x3FF8000000000000"));
+    }
+
+    #[test]
+    fn test_codegen_expression_type_mismatch() {
+        use crate::llvm::type_system::single_value::FloatingPointType;
+        use crate::llvm::types::Type;
+
+        let x = main(Span::new("module name1.name2\nlet x1 = 1 + 2.5")).unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1);
+        let err = res.unwrap_err();
+        assert_eq!(
+            err,
+            CodegenError::ExpressionTypeMismatch(
+                Type::Integer32,
+                Type::FloatingPoint(FloatingPointType::Double)
+            )
+        );
+        assert_eq!(
+            err.to_string(),
+            "type mismatch in expression: `i32` and `double`"
+        );
+    }
+
+    #[test]
+    fn test_codegen_error_display() {
+        use crate::llvm::builder::BuildError;
+        use crate::llvm::verifier::{
+            VerifyError,
+            VerifyErrorKind,
+        };
+
+        let err = CodegenError::Verify(Box::new(VerifyError {
+            function: "main".to_string(),
+            block: Some("0".to_string()),
+            instruction: None,
+            kind: VerifyErrorKind::MissingTerminator,
+        }));
+        assert_eq!(
+            err.to_string(),
+            "malformed IR: @main, block %0: basic block has no terminator"
+        );
+        assert_eq!(
+            CodegenError::ValueNotFound("x".to_string()).to_string(),
+            "value `x` not found"
+        );
+        let err = CodegenError::Build(BuildError::ArgumentsMismatch("f".to_string(), 0));
+        assert_eq!(
+            err.to_string(),
+            "failed to build IR: function @f called with 0 arguments"
+        );
+    }
+
+    #[test]
+    fn test_codegen_expression_unsupported_operation() {
+        use crate::llvm::type_system::single_value::FloatingPointType;
+        use crate::llvm::types::Type;
+        use crate::parser::ast::ExpressionOperation;
+
+        let x = main(Span::new("module name1.name2\nlet x1 = 1.5 <<< 2.5")).unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1);
+        assert_eq!(
+            res.unwrap_err(),
+            CodegenError::UnsupportedOperation(
+                ExpressionOperation::ShiftLeft,
+                Type::FloatingPoint(FloatingPointType::Double)
+            )
+        );
+    }
+
+    #[test]
+    fn test_codegen_function_local_let_expression() {
+        let x = main(Span::new(
+            "module name1.name2\nlet main () =\n    let y = 2 * 3\n    y + 1",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = mul i32 2, 3"));
+        assert!(res.contains("%2 = add i32 %1, 1"));
+        assert!(res.contains("ret i32 %2"));
+    }
+
+    #[test]
+    fn test_codegen_function_parameters() {
+        use crate::llvm::type_system::single_value::FloatingPointType;
+        use crate::llvm::types::Type;
+
+        let src = "module main\nlet add a b = a + b\n\
+            let scale (x: float, k: int) = x * (float k)\n\
+            let main () =\n    let s = (scale 1.5 2)\n    add 1 2";
+        let ir = Codegen::build(src).unwrap();
+        assert!(ir.contains("define internal i32 @add(i32 %a, i32 %b) #0 {"));
+        assert!(ir.contains("%1 = add i32 %a, %b"));
+        assert!(ir.contains("define internal double @scale(double %x, i32 %k) #0 {"));
+        assert!(ir.contains("call double @scale(double 0x3FF8000000000000, i32 2)"));
+        assert!(ir.contains("call i32 @add(i32 1, i32 2)"));
+
+        let src = "module main\nlet add a b = a + b\nlet main () = add 1";
+        assert_eq!(
+            Codegen::build(src),
+            Err(CodegenError::ArgumentsCountMismatch("add".to_string(), 1))
+        );
+        let src = "module main\nlet add a b = a + b\nlet main () = add 1 2.5";
+        assert_eq!(
+            Codegen::build(src),
+            Err(CodegenError::ExpressionTypeMismatch(
+                Type::Integer32,
+                Type::FloatingPoint(FloatingPointType::Double)
+            ))
+        );
+        let src = "module main\nlet f (a: list) = 1";
+        let err = Codegen::build(src).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type `list` of parameter `a` isn't supported"
+        );
+        // Signature of function is known only after its definition
+        let src = "module main\nlet main () = f()\nlet f () = 1.5";
+        let err = Codegen::build(src).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "call of function `f` before end of its definition isn't supported"
+        );
+        assert!(Codegen::build("module main\nlet f () = f()").is_err());
+    }
+
+    #[test]
+    fn test_codegen_entry_point() {
+        let ir = Codegen::build("module main\nlet main () =\n    let y = 1").unwrap();
+        assert!(ir.contains("define i32 @main() #0 {\n\tret i32 0\n}"));
+        let ir = Codegen::build("module main\nlet main args = args + 1").unwrap();
+        assert!(ir.contains("define i32 @main(i32 %args) #0 {"));
+        let err = Codegen::build("module main\nlet main () = 1.5").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`main` result of type `float` isn't supported"
+        );
+        assert!(Codegen::build("module main\nlet main () = true").is_err());
+        let err = Codegen::build("module main\nlet main (a: float) = 1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`main` parameters except count of arguments isn't supported"
+        );
+        assert!(Codegen::build("module main\nlet main a b = 1").is_err());
+        // Function `main` of other module isn't entry point
+        let options = CodegenOptions {
+            qualified_symbols: true,
+            ..Default::default()
+        };
+        let ir = Codegen::build_with_options("module m\nlet main () = 1.5", options).unwrap();
+        assert!(ir.contains("define double @m.main()"));
+    }
+
+    #[test]
+    fn test_codegen_integer_literals() {
+        let ir = Codegen::build("module main\nlet x = 2147483647 + 0\nlet y = 2147483648").unwrap();
+        assert!(ir.contains("add i32 2147483647, 0"));
+        assert!(ir.contains("store i64 2147483648, ptr @y"));
+        assert!(Codegen::build("module main\nlet x = -9223372036854775808").is_ok());
+        assert_eq!(
+            Codegen::build("module main\nlet x = 9223372036854775808"),
+            Err(CodegenError::IntegerLiteralOutOfRange(
+                "9223372036854775808".to_string()
+            ))
+        );
+        assert!(Codegen::build("module main\nlet x = 100000000000000000000").is_err());
     }
 
     // Current test
@@ -652,7 +1791,350 @@ mod tests {
         ))
         .unwrap();
         assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains(r#"@.str = private unnamed_addr constant [8 x i8] c"Res: %A\00""#));
+        assert!(res.contains("%1 = getelementptr inbounds [8 x i8], ptr @.str, i64 0, i64 0"));
+        assert!(res.contains("%2 = call i32 (ptr, ...) @printf(ptr %1, i32 10)"));
+        assert!(res.contains("ret i32 %2"));
+        assert!(res.contains("declare i32 @printf(ptr, ...)"));
+    }
+
+    #[test]
+    fn test_codegen_printfn() {
+        let src = std::fs::read_to_string("examples/hello.i").unwrap();
+        let res = Codegen::build(&src).unwrap();
+        assert!(res.contains(r#"c"Hello world\00""#));
+        assert!(res.contains("%2 = call i32 (ptr, ...) @printf(ptr %1)"));
+        assert!(res.contains("%3 = call i32 @putchar(i32 10)"));
+        assert!(res.contains("%4 = add i32 %2, 1"));
+        assert!(res.contains("ret i32 %4"));
+        assert!(res.contains("declare i32 @putchar(i32)"));
+        // Function of module isn't replaced
+        let src = "module main\nlet printfn a = a\nlet main () = printfn 2";
+        let res = Codegen::build(src).unwrap();
+        assert!(res.contains("call i32 @printfn(i32 2)"));
+        assert!(!res.contains("putchar"));
+    }
+
+    #[test]
+    fn test_codegen_sequential_values() {
+        let x = main(Span::new(
+            "module name1.name2\nlet x1 = 2\nlet f () = 1\nlet main () =\n    let y = x1 + f()\n    printf \"%d %d\" y (y * x1)",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = load i32, ptr @x1"));
+        assert!(res.contains("%2 = call i32 @f()"));
+        assert!(res.contains("%3 = add i32 %1, %2"));
+        assert!(res.contains("%4 = getelementptr inbounds [6 x i8], ptr @.str, i64 0, i64 0"));
+        assert!(res.contains("%5 = load i32, ptr @x1"));
+        assert!(res.contains("%6 = mul i32 %3, %5"));
+        assert!(res.contains("%7 = call i32 (ptr, ...) @printf(ptr %4, i32 %3, i32 %6)"));
+        assert!(res.contains("ret i32 %7"));
+        assert_eq!(res.matches("declare i32 @printf").count(), 1);
+    }
+
+    #[test]
+    fn test_codegen_conversion_functions() {
+        let x = main(Span::new(
+            "module name1.name2\nlet main () =\n    let y = 2.5 * 3.5\n    printf \"%d %d %f\" (int y) (int (byte y)) (float (1 + 2))",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%3 = fptosi double %1 to i32"));
+        assert!(res.contains("%4 = fptoui double %1 to i8"));
+        assert!(res.contains("%5 = zext i8 %4 to i32"));
+        assert!(res.contains("%6 = add i32 1, 2"));
+        assert!(res.contains("%7 = sitofp i32 %6 to double"));
+        assert!(res.contains("@printf(ptr %2, i32 %3, i32 %5, double %7)"));
+        assert!(!res.contains("declare i32 @int"));
+    }
+
+    #[test]
+    fn test_codegen_conversion_function_arguments() {
+        let x = main(Span::new("module name1.name2\nlet main () =\n    int 1 2")).unwrap();
+        assert_eq!(x.0.fragment(), &"");
         let res = Codegen::fn_main(&x.1);
-        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap_err(),
+            CodegenError::ArgumentsCountMismatch("int".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn test_codegen_unsupported_conversion() {
+        use crate::llvm::types::Type;
+
+        let x = main(Span::new(
+            "module name1.name2\nlet main () =\n    int \"10\"",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1);
+        assert_eq!(
+            res.unwrap_err(),
+            CodegenError::UnsupportedConversion(Type::pointer1(Type::Integer8), Type::Integer32)
+        );
+    }
+
+    #[test]
+    fn test_codegen_vector_arithmetic() {
+        let x = main(Span::new(
+            "module name1.name2\nlet f () =\n    let v = (float4 1.5 2.5 3.5 4.5)\n    let w = v * (float4 0.5)\n    lane w 3",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(
+            res.contains("%1 = insertelement <4 x double> undef, double 0x3FF8000000000000, i32 0")
+        );
+        assert!(
+            res.contains("%4 = insertelement <4 x double> %3, double 0x4012000000000000, i32 3")
+        );
+        assert!(
+            res.contains("%5 = insertelement <4 x double> undef, double 0x3FE0000000000000, i32 0")
+        );
+        assert!(res.contains(
+            "%6 = shufflevector <4 x double> %5, <4 x double> undef, <4 x i32> <i32 0, i32 0, i32 0, i32 0>"
+        ));
+        assert!(res.contains("%7 = fmul <4 x double> %4, %6"));
+        assert!(res.contains("%8 = extractelement <4 x double> %7, i32 3"));
+    }
+
+    #[test]
+    fn test_codegen_vector_integer() {
+        let x = main(Span::new(
+            "module name1.name2\nlet main () =\n    let v = (int4 7) - (int4 1 2 3 4.5)\n    lane v 0",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = insertelement <4 x i32> undef, i32 7, i32 0"));
+        assert!(res.contains("%6 = fptosi double 0x4012000000000000 to i32"));
+        assert!(res.contains("%7 = insertelement <4 x i32> %5, i32 %6, i32 3"));
+        assert!(res.contains("%8 = sub <4 x i32> %2, %7"));
+        assert!(res.contains("%9 = extractelement <4 x i32> %8, i32 0"));
+    }
+
+    #[test]
+    fn test_codegen_vector_arguments() {
+        let x = main(Span::new("module name1.name2\nlet main () =\n    int4 1 2")).unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1);
+        assert_eq!(
+            res.unwrap_err(),
+            CodegenError::ArgumentsCountMismatch("int4".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn test_codegen_atomic_functions() {
+        let x = main(Span::new(
+            "module name1.name2\nlet counter = 10\nlet main () =\n    let a = (Atomic.increment counter)\n    let b = (Atomic.compareExchange counter a 20)\n    a + b",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1).unwrap();
+        assert!(res.contains("%1 = atomicrmw add ptr @counter, i32 1 seq_cst"));
+        assert!(res.contains("%2 = add i32 %1, 1"));
+        assert!(res.contains("%3 = cmpxchg ptr @counter, i32 %2, i32 20 seq_cst seq_cst"));
+        assert!(res.contains("%4 = extractvalue { i32, i1 } %3, 0"));
+        assert!(res.contains("%5 = add i32 %2, %4"));
+    }
+
+    #[test]
+    fn test_codegen_atomic_errors() {
+        let x = main(Span::new(
+            "module name1.name2\nlet main () =\n    let x = 1\n    Atomic.increment x",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1);
+        assert_eq!(
+            res.unwrap_err(),
+            CodegenError::ValueNotFound("x".to_string())
+        );
+
+        let x = main(Span::new(
+            "module name1.name2\nlet counter = 1\nlet main () =\n    Atomic.compareExchange counter 1",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let res = Codegen::fn_main(&x.1);
+        assert_eq!(
+            res.unwrap_err(),
+            CodegenError::ArgumentsCountMismatch("Atomic.compareExchange".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn test_codegen_ir_module() {
+        let x = main(Span::new(
+            "module name1.name2\nlet x1 = 2\nlet main () =\n    x1 + 1",
+        ))
+        .unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let module = Codegen::fn_ir_module(&x.1).unwrap();
+        assert_eq!(module.module_id, "name1.name2");
+        let names: Vec<&str> = module.functions.iter().map(|f| f.name()).collect();
+        assert_eq!(
+            names,
+            vec!["__global_let_init.0", "main", "_GLOBAL_let_main"]
+        );
+        let f = module.function("main").unwrap();
+        assert_eq!(f.blocks.len(), 1);
+        assert_eq!(f.entry().unwrap().instructions.len(), 2);
+        assert!(f.successors("0").is_empty());
+    }
+
+    #[test]
+    fn test_codegen_ir_round_trip() {
+        let src = "module main\nlet x1 = 10\nlet x2 = x1 * 2\n\
+            let x () = printf \"Test func %d\\n\" x2\n\
+            let main () =\n    let y = 3 + x1\n    x()\n    printf \"%d %d\\n\" y (y * 2)";
+        let x = main(Span::new(src)).unwrap();
+        assert_eq!(x.0.fragment(), &"");
+        let ir = Codegen::fn_main(&x.1).unwrap();
+        assert_eq!(crate::llvm::parser::normalize(&ir).unwrap(), ir);
+    }
+
+    #[test]
+    fn test_codegen_debug_info() {
+        let src = "module main\nlet x1 = 10\n\
+            let main (a: int) =\n    let y = 3 + x1\n    printf \"%d\\n\" y";
+        let options = CodegenOptions {
+            debug_info: true,
+            source_file: Some("/src/main.i".to_string()),
+            ..Default::default()
+        };
+        let ir = Codegen::build_with_options(src, options).unwrap();
+        assert!(ir.contains("!llvm.dbg.cu = !{!1}"));
+        assert!(ir.contains(r#"!0 = !DIFile(filename: "/src/main.i", directory: "/src")"#));
+        assert!(ir.contains("!{i32 2, !\"Debug Info Version\", i32 3}"));
+        assert!(ir.contains(r#"distinct !DISubprogram(name: "main", scope: !0, file: !0, line: 3"#));
+        assert!(ir.contains("define i32 @main(i32 %a) #0 !dbg !8 {"));
+        assert!(ir.contains(
+            r#"!10 = !DILocalVariable(name: "a", arg: 1, scope: !8, file: !0, line: 3, type: !9)"#
+        ));
+        assert!(ir.contains("retainedNodes: !11)"));
+        assert!(ir.contains("!11 = !{!10}"));
+        assert!(ir.contains("!19 = !DISubroutineType(types: !18)"));
+        assert!(ir.contains("!18 = !{!9, !9}"));
+        assert!(
+            ir.contains(r#"!DILocalVariable(name: "y", scope: !8, file: !0, line: 4, type: !9)"#)
+        );
+        assert!(ir.contains("call void @llvm.dbg.value(metadata i32 %2, metadata !15, metadata !DIExpression()), !dbg !14"));
+        assert!(ir.contains("!14 = !DILocation(line: 4, column: 5, scope: !8)"));
+        assert!(ir.contains("\tstore i32 10, ptr @x1, !dbg !5\n"));
+        // Every instruction has debug location
+        let f = ir.split("define i32 @main").nth(1).unwrap();
+        let body = &f[f.find('{').unwrap() + 2..f.find('}').unwrap()];
+        assert!(body.lines().all(|l| l.contains(", !dbg !")));
+        assert_eq!(crate::llvm::parser::normalize(&ir).unwrap(), ir);
+        // Debug info is disabled by default
+        assert!(!Codegen::build(src).unwrap().contains("!dbg"));
+    }
+
+    #[test]
+    fn test_codegen_math_functions() {
+        let src = "module main\nlet main () =\n    let a = (Math.sqrt 2)\n    \
+            let b = (Math.pow a 2.0)\n    let c = (Math.abs -3)\n    Math.popcount c";
+        let ir = Codegen::build(src).unwrap();
+        assert!(ir.contains("declare double @llvm.sqrt.f64(double)"));
+        assert!(ir.contains("declare double @llvm.pow.f64(double, double)"));
+        assert!(ir.contains("declare i32 @llvm.abs.i32(i32, i1)"));
+        assert!(ir.contains("declare i32 @llvm.ctpop.i32(i32)"));
+        assert!(ir.contains("= call double @llvm.sqrt.f64(double %"));
+        assert!(ir.contains(" = call i32 @llvm.abs.i32(i32 -3, i1 false)"));
+        assert_eq!(crate::llvm::parser::normalize(&ir).unwrap(), ir);
+
+        let src = "module main\nlet main () =\n    Math.pow 2.0";
+        assert_eq!(
+            Codegen::build(src).unwrap_err(),
+            CodegenError::ArgumentsCountMismatch("Math.pow".to_string(), 1)
+        );
+        let src = "module main\nlet main () =\n    Math.tan 2.0";
+        assert_eq!(
+            Codegen::build(src).unwrap_err(),
+            CodegenError::ValueNotFound("Math.tan".to_string())
+        );
+        use crate::llvm::types::Type;
+        for arg in ["2.0", "true", "\"s\""].iter() {
+            let src = format!("module main\nlet main () =\n    Math.popcount {}", arg);
+            assert!(matches!(
+                Codegen::build(&src).unwrap_err(),
+                CodegenError::ExpressionTypeMismatch(Type::Integer32, _)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_codegen_overflow_checks() {
+        let src = "module main\nlet main () =\n    let a = 5\n    a * 2 + 1";
+        let options = CodegenOptions {
+            overflow_checks: true,
+            ..Default::default()
+        };
+        let ir = Codegen::build_with_options(src, options).unwrap();
+        assert!(ir.contains("declare { i32, i1 } @llvm.smul.with.overflow.i32(i32, i32)"));
+        assert!(ir.contains("declare { i32, i1 } @llvm.sadd.with.overflow.i32(i32, i32)"));
+        assert!(ir.contains("declare void @llvm.trap()"));
+        assert!(ir.contains(" = call { i32, i1 } @llvm.smul.with.overflow.i32(i32 5, i32 2)"));
+        assert!(ir.contains("\tcall void @llvm.trap()\n\tunreachable\n"));
+        assert_eq!(crate::llvm::parser::normalize(&ir).unwrap(), ir);
+        // Without checks arithmetic is plain
+        assert!(!Codegen::build(src).unwrap().contains("with.overflow"));
+    }
+
+    #[test]
+    fn test_codegen_typed_pointers() {
+        let src = "module main\nlet x1 = 10\nlet main () =\n    printf \"%d\\n\" x1";
+        let ir = Codegen::build(src).unwrap();
+        assert!(ir.contains("@llvm.global_ctors = appending global [1 x { i32, ptr, ptr }] [{ i32, ptr, ptr } { i32 65535, ptr @_GLOBAL_let_main, ptr null }]"));
+        assert!(ir.contains("= load i32, ptr @x1"));
+        assert!(ir.contains("declare i32 @printf(ptr, ...)"));
+        assert!(!ir.contains('*'));
+
+        let options = CodegenOptions {
+            typed_pointers: true,
+            ..Default::default()
+        };
+        let ir = Codegen::build_with_options(src, options).unwrap();
+        assert!(ir.contains("@llvm.global_ctors = appending global [1 x { i32, void ()*, i8* }] [{ i32, void ()*, i8* } { i32 65535, void ()* @_GLOBAL_let_main, i8* null }]"));
+        assert!(ir.contains("= load i32, i32* @x1"));
+        assert!(ir.contains("declare i32 @printf(i8*, ...)"));
+        // Pointer mode is option of module, not of printer thread
+        let ast = Codegen::parse(src).unwrap();
+        let options = CodegenOptions {
+            typed_pointers: true,
+            ..Default::default()
+        };
+        let module = Codegen::fn_ir_module_with_options(&ast, options).unwrap();
+        assert!(module.typed_pointers);
+        assert!(module.to_string().contains("declare i32 @printf(i8*, ...)"));
+        assert!(!Codegen::build(src).unwrap().contains('*'));
+    }
+
+    #[test]
+    fn test_codegen_target() {
+        let src = "module main\nlet main () =\n    printf \"%d\\n\" 1";
+        let ir = Codegen::build(src).unwrap();
+        assert!(ir.contains("target triple = \"x86_64-unknown-linux-gnu\"\n"));
+        assert!(!ir.contains("target datalayout"));
+
+        let options = CodegenOptions {
+            target_triple: Some("aarch64-unknown-linux-gnu".to_string()),
+            data_layout: Some("e-m:e-i8:8:32-i16:16:32-i64:64-i128:128-n32:64-S128".to_string()),
+            ..Default::default()
+        };
+        let ir = Codegen::build_with_options(src, options).unwrap();
+        assert!(ir.contains(
+            "source_filename = \"main.i\"\n\
+            target datalayout = \"e-m:e-i8:8:32-i16:16:32-i64:64-i128:128-n32:64-S128\"\n\
+            target triple = \"aarch64-unknown-linux-gnu\"\n"
+        ));
+        assert_eq!(crate::llvm::parser::normalize(&ir).unwrap(), ir);
     }
 }
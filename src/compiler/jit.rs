@@ -0,0 +1,313 @@
+//! # JIT execution
+//!
+//! Run module in-process with LLVM execution engine, without object
+//! files and linking. External symbols, like `printf` and math
+//! functions, are resolved from host process. JIT session keeps one
+//! engine, so modules are added one by one and call functions of
+//! earlier modules.
+
+use super::{
+    apply_target_to_module,
+    get_target_machine,
+    optimize_module,
+    options::CompileOptions,
+    parse_module,
+};
+use crate::codegen::ENTRY_POINT;
+use crate::llvm::{
+    type_system::single_value::FloatingPointType,
+    types::Type,
+};
+use inkwell::{
+    context::Context,
+    execution_engine::{
+        ExecutionEngine,
+        UnsafeFunctionPointer,
+    },
+    module::Module,
+    targets::TargetMachine,
+};
+use std::{
+    ffi::CStr,
+    os::raw::c_char,
+};
+
+/// Value returned by JIT compiled function
+#[derive(Debug, Clone, PartialEq)]
+pub enum JitValue {
+    Void,
+    Bool(bool),
+    Byte(i8),
+    Int(i32),
+    Int64(i64),
+    Float(f64),
+    String(String),
+}
+
+impl std::fmt::Display for JitValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            JitValue::Void => write!(f, "()"),
+            JitValue::Bool(v) => write!(f, "{}", v),
+            JitValue::Byte(v) => write!(f, "{}", v),
+            JitValue::Int(v) => write!(f, "{}", v),
+            JitValue::Int64(v) => write!(f, "{}", v),
+            JitValue::Float(v) => write!(f, "{:?}", v),
+            JitValue::String(v) => write!(f, "{:?}", v),
+        }
+    }
+}
+
+/// Compile module for host with JIT execution engine. Global
+/// let-values are initialized by static constructors before `f` is
+/// called.
+fn with_engine<T>(
+    src: &str,
+    options: &CompileOptions,
+    f: impl FnOnce(&Module, &ExecutionEngine) -> Result<T, String>,
+) -> Result<T, String> {
+    // JIT runs only on host
+    let options = CompileOptions {
+        target: None,
+        ..options.clone()
+    };
+    ExecutionEngine::link_in_mc_jit();
+    let context = Context::create();
+    let module = parse_module(&context, src)?;
+    let target_machine = get_target_machine(&options)?;
+    apply_target_to_module(&target_machine, &module);
+    optimize_module(&target_machine, &module, &options)?;
+
+    let engine = module
+        .create_jit_execution_engine(options.opt_level.codegen_level())
+        .map_err(|v| v.to_string())?;
+    engine.run_static_constructors();
+    let res = f(&module, &engine);
+    engine.run_static_destructors();
+    res
+}
+
+/// Run `main` function of LLVM IR source code generated by codegen.
+/// `args` are passed as `argv`, first argument is program name. Exit
+/// code of `main` is returned.
+pub fn run_main(src: &str, args: &[&str], options: &CompileOptions) -> Result<i32, String> {
+    with_engine(src, options, |module, engine| {
+        let main = module
+            .get_function(ENTRY_POINT)
+            .ok_or_else(|| format!("Function `{}` not found", ENTRY_POINT))?;
+        // Codegen emits `main` with `int` result and optional count of
+        // arguments, it's compatible with C `main` signature
+        Ok(unsafe { engine.run_function_as_main(main, args) })
+    })
+}
+
+/// Call external function without parameters
+fn call<F: UnsafeFunctionPointer, T>(
+    engine: &ExecutionEngine,
+    name: &str,
+    call: impl FnOnce(F) -> T,
+) -> Result<T, String> {
+    let f = unsafe { engine.get_function::<F>(name) }
+        .map_err(|err| format!("Function `{}` not found: {}", name, err))?;
+    Ok(call(unsafe { f.as_raw() }))
+}
+
+/// Call function without parameters of engine and convert result
+/// of `result_type` to value
+fn call_value(
+    engine: &ExecutionEngine,
+    name: &str,
+    result_type: &Type,
+) -> Result<JitValue, String> {
+    // Function signature is defined by `result_type`
+    unsafe {
+        match result_type {
+            Type::Void => call(engine, name, |f: unsafe extern "C" fn()| {
+                f();
+                JitValue::Void
+            }),
+            Type::Integer1 => call(engine, name, |f: unsafe extern "C" fn() -> bool| {
+                JitValue::Bool(f())
+            }),
+            Type::Integer8 => call(engine, name, |f: unsafe extern "C" fn() -> i8| {
+                JitValue::Byte(f())
+            }),
+            Type::Integer32 => call(engine, name, |f: unsafe extern "C" fn() -> i32| {
+                JitValue::Int(f())
+            }),
+            Type::Integer64 => call(engine, name, |f: unsafe extern "C" fn() -> i64| {
+                JitValue::Int64(f())
+            }),
+            Type::FloatingPoint(FloatingPointType::Double) => {
+                call(engine, name, |f: unsafe extern "C" fn() -> f64| {
+                    JitValue::Float(f())
+                })
+            }
+            Type::Pointer(_) => call(
+                engine,
+                name,
+                |f: unsafe extern "C" fn() -> *const c_char| {
+                    let s = f();
+                    if s.is_null() {
+                        JitValue::String(String::new())
+                    } else {
+                        JitValue::String(CStr::from_ptr(s).to_string_lossy().to_string())
+                    }
+                },
+            ),
+            _ => Err(format!("Unsupported result type: {}", result_type)),
+        }
+    }
+}
+
+/// Call function without parameters and convert result of
+/// `result_type` to value. Function should have external linkage.
+/// Pointer result is read as C string.
+pub fn call_function(
+    src: &str,
+    name: &str,
+    result_type: &Type,
+    options: &CompileOptions,
+) -> Result<JitValue, String> {
+    with_engine(src, options, |_, engine| {
+        call_value(engine, name, result_type)
+    })
+}
+
+/// JIT session with one execution engine for host. Symbols of added
+/// modules are resolved for modules added later. Static constructors
+/// aren't run, initializers should be called explicitly.
+pub struct JitSession<'ctx> {
+    context: &'ctx Context,
+    target_machine: TargetMachine,
+    options: CompileOptions,
+    engine: ExecutionEngine<'ctx>,
+}
+
+impl<'ctx> JitSession<'ctx> {
+    pub fn new(context: &'ctx Context, options: &CompileOptions) -> Result<Self, String> {
+        // JIT runs only on host
+        let options = CompileOptions {
+            target: None,
+            ..options.clone()
+        };
+        ExecutionEngine::link_in_mc_jit();
+        let target_machine = get_target_machine(&options)?;
+        // Engine is created with empty module, it's owned by engine
+        let module = context.create_module("jit");
+        apply_target_to_module(&target_machine, &module);
+        let engine = module
+            .create_jit_execution_engine(options.opt_level.codegen_level())
+            .map_err(|v| v.to_string())?;
+        Ok(Self {
+            context,
+            target_machine,
+            options,
+            engine,
+        })
+    }
+
+    /// Add module of LLVM IR source code to engine
+    pub fn add_module(&mut self, src: &str) -> Result<(), String> {
+        let module = parse_module(self.context, src)?;
+        apply_target_to_module(&self.target_machine, &module);
+        optimize_module(&self.target_machine, &module, &self.options)?;
+        self.engine
+            .add_module(&module)
+            .map_err(|_| "Failed to add module to JIT".to_string())
+    }
+
+    /// Call function of added modules, see `call_function`
+    pub fn call_function(&self, name: &str, result_type: &Type) -> Result<JitValue, String> {
+        call_value(&self.engine, name, result_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::Codegen;
+    use crate::interpreter;
+
+    #[test]
+    fn test_jit_run_main() {
+        let src = "module main\nlet x1 = 40\nlet main () =\n    let y = x1 + 2\n    y";
+        let ir = Codegen::build(src).unwrap();
+        let code = run_main(&ir, &["app"], &CompileOptions::default()).unwrap();
+        assert_eq!(code, 42);
+        // `main` without value returns 0
+        let ir = Codegen::build("module main\nlet main () =\n    let y = 1").unwrap();
+        assert_eq!(run_main(&ir, &["app"], &CompileOptions::default()), Ok(0));
+        let ir = Codegen::build("module main\nlet main argc = argc * 10").unwrap();
+        assert_eq!(
+            run_main(&ir, &["app", "a"], &CompileOptions::default()),
+            Ok(20)
+        );
+
+        let ir = "define i32 @f() {\n  ret i32 0\n}\n";
+        assert!(run_main(ir, &["app"], &CompileOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_jit_call_function() {
+        let ir = "@.str = private constant [3 x i8] c\"ok\\00\"\n\
+            define double @f() {\n  ret double 2.5\n}\n\
+            define ptr @s() {\n  ret ptr @.str\n}\n";
+        let options = CompileOptions::default();
+        let v = call_function(
+            ir,
+            "f",
+            &Type::FloatingPoint(FloatingPointType::Double),
+            &options,
+        );
+        assert_eq!(v, Ok(JitValue::Float(2.5)));
+        let v = call_function(ir, "s", &Type::opaque_pointer(None), &options).unwrap();
+        assert_eq!(v.to_string(), "\"ok\"");
+        assert!(call_function(ir, "g", &Type::Integer32, &options).is_err());
+    }
+
+    #[test]
+    fn test_jit_session() {
+        let context = Context::create();
+        let mut jit = JitSession::new(&context, &CompileOptions::default()).unwrap();
+        jit.add_module(
+            "@x = global i32 40\n\
+            define i32 @f() {\n  %1 = load i32, ptr @x\n  ret i32 %1\n}\n",
+        )
+        .unwrap();
+        // Symbols of earlier module are external for later module
+        jit.add_module(
+            "declare i32 @f()\n\
+            define i32 @g() {\n  %1 = call i32 @f()\n  %2 = add i32 %1, 2\n  ret i32 %2\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            jit.call_function("g", &Type::Integer32),
+            Ok(JitValue::Int(42))
+        );
+        assert!(jit.add_module("define i32 @h() {").is_err());
+        assert!(jit.call_function("h", &Type::Integer32).is_err());
+    }
+
+    #[test]
+    fn test_jit_interpreter_differential() {
+        // Interpreter is reference for exit code of compiled program
+        let programs = [
+            "let main () = 2 + 3 * 4 - 10 / 3 <<< 1",
+            "let main () = 2147483647 + 1 - (0 - 7) / 2",
+            "let f a = a >>> 0 - 1\nlet main () = (1 <<< 33) + (f (0 - 64)) + (1 <<< 31 + 32)",
+            "let x = 20\nlet f () = x * 2\nlet main () =\n    let y = (f()) + 2\n    y",
+            "let main () = (int (Math.pow 2 10)) + Math.popcount 255",
+            "let main () = (int (byte 300)) + lane (int4 1 2 3 4 * int4 2) 3",
+            "let c = 0\nlet main () =\n    let a = (Atomic.increment c)\n    a + (Atomic.compareExchange c 1 10)",
+        ];
+        for program in programs.iter() {
+            let src = format!("module main\n{}", program);
+            let expected =
+                interpreter::run(&src, &["app"], Default::default(), std::io::sink()).unwrap();
+            let ir = Codegen::build(&src).unwrap();
+            let code = run_main(&ir, &["app"], &CompileOptions::default()).unwrap();
+            assert_eq!(code, expected, "program: {}", program);
+        }
+    }
+}
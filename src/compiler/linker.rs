@@ -0,0 +1,124 @@
+//! # Linker
+//!
+//! Link object files to executable with system C compiler driver.
+//! Compiler driver knows dynamic linker, C runtime startup files and
+//! library paths of the host, so they aren't hardcoded.
+
+use std::{
+    env,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::Command,
+};
+
+/// Programs tried in order, when `CC` environment variable isn't set
+pub const LINKER_CANDIDATES: [&str; 3] = ["cc", "gcc", "clang"];
+
+/// Libraries required by generated code: `libm` for math intrinsics.
+/// `libc` is linked by compiler driver.
+pub const RUNTIME_LIBRARIES: [&str; 1] = ["m"];
+
+/// System linker
+#[derive(Debug, Clone, PartialEq)]
+pub struct Linker {
+    program: PathBuf,
+}
+
+impl Linker {
+    pub fn new<P: Into<PathBuf>>(program: P) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Discover linker: `CC` environment variable, or first of
+    /// `LINKER_CANDIDATES` found in `PATH`
+    pub fn detect() -> Result<Self, String> {
+        if let Some(cc) = env::var_os("CC").filter(|cc| !cc.is_empty()) {
+            return Ok(Self::new(cc));
+        }
+        LINKER_CANDIDATES
+            .iter()
+            .find_map(|name| find_program(name))
+            .map(Self::new)
+            .ok_or_else(|| {
+                format!(
+                    "C compiler not found, tried: {}. Set `CC` environment variable",
+                    LINKER_CANDIDATES.join(", ")
+                )
+            })
+    }
+
+    /// Link object files and libraries to executable
+    pub fn link(&self, inputs: &[&Path], libraries: &[&str], output: &Path) -> Result<(), String> {
+        let mut cmd = Command::new(&self.program);
+        cmd.arg("-o").arg(output).args(inputs);
+        cmd.args(libraries.iter().map(|lib| format!("-l{}", lib)));
+        run(&mut cmd)
+    }
+}
+
+/// Find executable in `PATH` directories
+fn find_program(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Run command and check exit status. Command stderr is returned
+/// as error message.
+pub fn run(cmd: &mut Command) -> Result<(), String> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let output = cmd
+        .output()
+        .map_err(|err| format!("Failed to run `{}`: {}", program, err))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "`{}` failed with {}:\n{}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_status() {
+        assert!(run(Command::new("sh").args(["-c", "exit 0"])).is_ok());
+        let err = run(Command::new("sh").args(["-c", "echo oops >&2; exit 3"])).unwrap_err();
+        assert!(err.starts_with("`sh` failed with exit status: 3"));
+        assert!(err.ends_with("oops"));
+    }
+
+    #[test]
+    fn test_run_missing_program() {
+        let err = run(&mut Command::new("i-lang-missing-program")).unwrap_err();
+        assert!(err.starts_with("Failed to run `i-lang-missing-program`"));
+    }
+
+    #[test]
+    fn test_linker_errors() {
+        let linker = Linker::new("false");
+        assert_eq!(linker.program(), Path::new("false"));
+        assert!(linker
+            .link(
+                &[Path::new("missing.o")],
+                &RUNTIME_LIBRARIES,
+                Path::new("app")
+            )
+            .is_err());
+    }
+}
@@ -2,129 +2,409 @@
 //!
 //! Native compilation and builders
 
+pub mod jit;
+pub mod linker;
+pub mod options;
+pub mod project;
+
+use self::linker::{
+    run,
+    Linker,
+    RUNTIME_LIBRARIES,
+};
+use self::options::CompileOptions;
+use crate::codegen::ENTRY_POINT;
 use inkwell::{
     context::Context,
     memory_buffer::MemoryBuffer,
     module::Module,
+    passes::PassBuilderOptions,
     targets::{
-        CodeModel,
         FileType,
         InitializationConfig,
-        RelocMode,
         Target,
         TargetMachine,
+        TargetTriple,
     },
-    OptimizationLevel,
 };
 use std::{
-    path::Path,
+    io::ErrorKind,
+    path::{
+        Path,
+        PathBuf,
+    },
     process::Command,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
 };
 
+/// Default directory of build outputs
+pub const BUILD_DIR: &str = "build";
+
 /// Apply module ot initialized Target Machine
 fn apply_target_to_module(target_machine: &TargetMachine, module: &Module) {
     module.set_triple(&target_machine.get_triple());
     module.set_data_layout(&target_machine.get_target_data().get_data_layout());
 }
 
-// TODO: Set configuration options for target mathin optimization
-/// Init Target Machine for current environment
-fn get_native_target_machine() -> Result<TargetMachine, String> {
-    Target::initialize_native(&InitializationConfig::default())?;
-    let target_triple = TargetMachine::get_default_triple();
+/// Init Target Machine for target triple of options, or for current
+/// environment. All targets are initialized for cross-compilation.
+pub fn get_target_machine(options: &CompileOptions) -> Result<TargetMachine, String> {
+    let config = InitializationConfig::default();
+    let (target_triple, cpu, features) = match &options.target {
+        Some(triple) => {
+            Target::initialize_all(&config);
+            let target_triple = TargetMachine::normalize_triple(&TargetTriple::create(triple));
+            // Empty CPU is target default, `generic` isn't known
+            // for all targets
+            (target_triple, String::new(), String::new())
+        }
+        None => {
+            Target::initialize_native(&config)?;
+            (
+                TargetMachine::get_default_triple(),
+                TargetMachine::get_host_cpu_name().to_string(),
+                TargetMachine::get_host_cpu_features().to_string(),
+            )
+        }
+    };
     let target = Target::from_triple(&target_triple).map_err(|v| v.to_string())?;
     target
         .create_target_machine(
             &target_triple,
-            &TargetMachine::get_host_cpu_name().to_string(),
-            &TargetMachine::get_host_cpu_features().to_string(),
-            OptimizationLevel::Aggressive,
-            RelocMode::PIC,
-            CodeModel::Medium,
+            options.cpu.as_deref().unwrap_or(&cpu),
+            options.features.as_deref().unwrap_or(&features),
+            options.opt_level.codegen_level(),
+            options.reloc_mode,
+            options.code_model,
         )
-        .ok_or_else(|| String::from("Failed to create target machine"))
+        .ok_or_else(|| {
+            format!(
+                "Failed to create target machine for {}",
+                target_triple.as_str().to_string_lossy()
+            )
+        })
 }
 
-/// Build executable code
-pub fn builder(app_name: String, src: String) -> Result<(), String> {
-    let build_dir = "build";
-    let context = Context::create();
+/// Target triple and data layout of target machine, to set them in
+/// generated LLVM IR
+pub fn target_description(target_machine: &TargetMachine) -> (String, String) {
+    let data_layout = target_machine.get_target_data().get_data_layout();
+    (
+        target_machine
+            .get_triple()
+            .as_str()
+            .to_string_lossy()
+            .to_string(),
+        data_layout.as_str().to_string_lossy().to_string(),
+    )
+}
+
+/// Kind of compiler output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// Parsed source code tree
+    Ast,
+    /// LLVM IR text `.ll`
+    Ir,
+    /// LLVM bitcode `.bc`
+    Bitcode,
+    /// Target assembly `.s`
+    Assembly,
+    /// Object file `.o`
+    Object,
+    /// Static library `lib*.a`
+    StaticLib,
+    /// Linked executable
+    Executable,
+}
+
+impl Emit {
+    /// Names accepted by `--emit` option
+    pub const NAMES: [&'static str; 7] = ["ast", "ir", "bc", "asm", "obj", "lib", "exe"];
+
+    /// Default output file name for application
+    pub fn file_name(&self, app_name: &str) -> String {
+        match self {
+            Emit::Ast => format!("{}.ast", app_name),
+            Emit::Ir => format!("{}.ll", app_name),
+            Emit::Bitcode => format!("{}.bc", app_name),
+            Emit::Assembly => format!("{}.s", app_name),
+            Emit::Object => format!("{}.o", app_name),
+            Emit::StaticLib => format!("lib{}.a", app_name),
+            Emit::Executable => app_name.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for Emit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ast" => Ok(Emit::Ast),
+            "ir" => Ok(Emit::Ir),
+            "bc" => Ok(Emit::Bitcode),
+            "asm" => Ok(Emit::Assembly),
+            "obj" => Ok(Emit::Object),
+            "lib" => Ok(Emit::StaticLib),
+            "exe" => Ok(Emit::Executable),
+            _ => Err(format!("Unknown emit kind: {}", s)),
+        }
+    }
+}
+
+/// Application name: module id of `module` declaration, or stem of
+/// source file for `main` module, which is default name of module
+pub fn app_name(module_id: Option<&str>, source_file: &Path) -> String {
+    match module_id {
+        Some(id) if id != ENTRY_POINT => id.to_string(),
+        _ => source_file
+            .file_stem()
+            .map_or_else(|| "app".to_string(), |v| v.to_string_lossy().to_string()),
+    }
+}
+
+/// Counter of temporary directory names in process
+static TEMP_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Temporary directory for intermediate files, removed with its
+/// content on drop. Name is unique, so parallel builds don't share
+/// intermediate files.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create directory in system temporary directory, it's `TMPDIR`
+    /// environment variable on Unix
+    pub fn new() -> Result<Self, String> {
+        Self::new_in(&std::env::temp_dir())
+    }
+
+    /// Create directory in `parent`. Existing directory, left by
+    /// other process with the same id, is never reused: next name is
+    /// tried.
+    fn new_in(parent: &Path) -> Result<Self, String> {
+        loop {
+            let name = format!(
+                "ilang-{}-{}",
+                std::process::id(),
+                TEMP_DIR_COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = parent.join(name);
+            match std::fs::create_dir(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+                Err(err) => {
+                    return Err(format!(
+                        "Can't create temporary directory `{}`: {}",
+                        path.display(),
+                        err
+                    ))
+                }
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Create parent directory of output file
+pub fn create_output_dir(output: &Path) -> Result<(), String> {
+    match output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() && !dir.is_dir() => std::fs::create_dir_all(dir)
+            .map_err(|err| format!("Can't create `{}` directory: {}", dir.display(), err)),
+        _ => Ok(()),
+    }
+}
+
+/// Parse LLVM IR source code to module of context
+fn parse_module<'ctx>(context: &'ctx Context, src: &str) -> Result<Module<'ctx>, String> {
     let memory_buffer = MemoryBuffer::create_from_memory_range(src.as_bytes(), "amin");
-    let module = context
+    context
         .create_module_from_ir(memory_buffer)
-        .map_err(|v| v.to_string())?;
+        .map_err(|v| v.to_string())
+}
+
+/// Run optimization pass pipeline with new pass manager
+fn optimize_module(
+    target_machine: &TargetMachine,
+    module: &Module,
+    options: &CompileOptions,
+) -> Result<(), String> {
+    module
+        .run_passes(
+            options.pipeline(),
+            target_machine,
+            PassBuilderOptions::create(),
+        )
+        .map_err(|v| format!("Failed to run passes `{}`: {}", options.pipeline(), v))
+}
 
-    let target_machine = get_native_target_machine()?;
-    apply_target_to_module(&target_machine, &module);
+/// Compile LLVM IR source code to `output` file with target machine
+/// created by `get_target_machine`. AST isn't known to compiler, it's
+/// emitted by parser.
+pub fn compile(
+    src: &str,
+    emit: Emit,
+    output: &Path,
+    target_machine: &TargetMachine,
+    options: &CompileOptions,
+) -> Result<(), String> {
+    let context = Context::create();
+    let module = parse_module(&context, src)?;
+
+    apply_target_to_module(target_machine, &module);
+    optimize_module(target_machine, &module, options)?;
+    create_output_dir(output)?;
 
-    if !Path::new("build").is_dir() {
-        std::fs::create_dir("build").expect("Can't create `build` directory");
+    let write_to_file = |file_type, path: &Path| {
+        target_machine
+            .write_to_file(&module, file_type, path)
+            .map_err(|v| v.to_string())
     };
+    match emit {
+        Emit::Ast => Err("AST can't be emitted from LLVM IR".to_string()),
+        Emit::Ir => module.print_to_file(output).map_err(|v| v.to_string()),
+        Emit::Bitcode => {
+            if module.write_bitcode_to_path(output) {
+                Ok(())
+            } else {
+                Err(format!("Failed to write bitcode to {}", output.display()))
+            }
+        }
+        Emit::Assembly => write_to_file(FileType::Assembly, output),
+        Emit::Object => write_to_file(FileType::Object, output),
+        Emit::StaticLib | Emit::Executable => {
+            // Object file is intermediate, it's removed with directory
+            let temp_dir = TempDir::new()?;
+            let obj_file = temp_dir.path().join(Emit::Object.file_name("main"));
+            write_to_file(FileType::Object, &obj_file)?;
+            if emit == Emit::StaticLib {
+                ar_builder(&[&obj_file], output)
+            } else {
+                Linker::detect()?.link(&[&obj_file], &RUNTIME_LIBRARIES, output)
+            }
+        }
+    }
+}
+
+/// Run `ar` tool for link object files to static lib
+pub fn ar_builder(objects: &[&Path], output: &Path) -> Result<(), String> {
+    // Archive is updated by `ar`, old members should be removed
+    if output.is_file() {
+        std::fs::remove_file(output).map_err(|err| err.to_string())?;
+    }
+    run(Command::new("ar").arg("crs").arg(output).args(objects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::{
+        Codegen,
+        CodegenOptions,
+    };
+
+    #[test]
+    fn test_cross_compile_object_headers() {
+        // ELF `e_machine` field of object files
+        let targets = [
+            ("aarch64-unknown-linux-gnu", 183u16),
+            ("riscv64-unknown-linux-gnu", 243),
+        ];
+        let src = "module main\nlet main () =\n    printf \"%d\\n\" 1";
+        Target::initialize_all(&InitializationConfig::default());
+        for (triple, machine) in targets.iter() {
+            // LLVM may be built without some targets
+            if Target::from_triple(&TargetTriple::create(triple)).is_err() {
+                eprintln!("skip target {}: not supported by LLVM", triple);
+                continue;
+            }
+            let options = CompileOptions {
+                target: Some(triple.to_string()),
+                ..Default::default()
+            };
+            let target_machine = get_target_machine(&options).unwrap();
+            let (target_triple, data_layout) = target_description(&target_machine);
+            assert_eq!(&target_triple, triple);
+            let codegen_options = CodegenOptions {
+                target_triple: Some(target_triple),
+                data_layout: Some(data_layout),
+                ..Default::default()
+            };
+            let ir = Codegen::build_with_options(src, codegen_options).unwrap();
+            let temp_dir = TempDir::new().unwrap();
+            let output = temp_dir.path().join(format!("{}.o", triple));
+            compile(&ir, Emit::Object, &output, &target_machine, &options).unwrap();
+            let obj = std::fs::read(&output).unwrap();
+            assert_eq!(&obj[..4], b"\x7fELF");
+            assert_eq!(u16::from_le_bytes([obj[18], obj[19]]), *machine);
+        }
+    }
+
+    #[test]
+    fn test_emit_kinds() {
+        let kinds = Emit::NAMES
+            .iter()
+            .map(|name| name.parse::<Emit>().unwrap().file_name("app"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            ["app.ast", "app.ll", "app.bc", "app.s", "app.o", "libapp.a", "app"]
+        );
+        assert!("wasm".parse::<Emit>().is_err());
+    }
+
+    #[test]
+    fn test_app_name() {
+        let source_file = Path::new("examples/hello.i");
+        assert_eq!(app_name(Some("calc"), source_file), "calc");
+        assert_eq!(app_name(Some("App.Main"), source_file), "App.Main");
+        assert_eq!(app_name(Some("main"), source_file), "hello");
+        assert_eq!(app_name(None, source_file), "hello");
+        assert_eq!(app_name(None, Path::new("/")), "app");
+    }
+
+    #[test]
+    fn test_temp_dir() {
+        let dir1 = TempDir::new().unwrap();
+        let dir2 = TempDir::new().unwrap();
+        assert_ne!(dir1.path(), dir2.path());
+        let path = dir1.path().to_path_buf();
+        std::fs::write(path.join("main.o"), b"").unwrap();
+        drop(dir1);
+        assert!(!path.exists());
+        assert!(dir2.path().is_dir());
 
-    let obj_file_name = format!("{}/{}.o", build_dir, app_name);
-    let obj_file = Path::new(&obj_file_name);
-
-    target_machine
-        .write_to_file(&module, FileType::Object, obj_file)
-        .map_err(|v| v.to_string())?;
-
-    ar_builder(app_name, build_dir)?;
-    //ld_builder(app_name, build_dir)
-    Ok(())
-}
-
-/// Run `ar` tool for link libraries to static lib
-pub fn ar_builder(app_name: String, build_dir: &str) -> Result<(), String> {
-    let obj_file_name = format!("{}/{}.o", build_dir, app_name);
-    let a_file_name = format!("{}/lib{}.a", build_dir, app_name);
-
-    Command::new("ar")
-        .args(["crs", &a_file_name, &obj_file_name])
-        .spawn()
-        .map_err(|_| "Failed to run `ar` command".to_string())?
-        .wait()
-        .map_err(|_| "Failed to process `ar` command".to_string())?;
-    Ok(())
-}
-
-/// Run linker with `ld` tool
-pub fn ld_builder(app_name: String, build_dir: &str) -> Result<(), String> {
-    let obj_file_name = format!("{}/{}.o", build_dir, app_name);
-    let a_file_name = format!("{}/lib{}.a", build_dir, app_name);
-    let app_file_name = format!("{}/{}", build_dir, app_name);
-    let obj_file = Path::new(&obj_file_name);
-
-    Command::new("ld")
-        .args([
-            "-o",
-            &app_file_name,
-            "-dynamic-linker",
-            "/lib64/ld-linux-x86-64.so.2",
-            "/usr/lib/x86_64-linux-gnu/crt1.o",
-            "/usr/lib/x86_64-linux-gnu/crti.o",
-            "/usr/lib/x86_64-linux-gnu/crtn.o",
-            "-lc",
-            &a_file_name,
-        ])
-        .spawn()
-        .map_err(|_| "Failed to run `ld` command".to_string())?
-        .wait()
-        .map_err(|_| "Failed to process `ld` command".to_string())?;
-    std::fs::remove_file(obj_file).or::<String>(Ok(()))
-}
-
-/// Run linker with `gcc` tool
-pub fn gcc_builder(app_name: String, build_dir: &str) -> Result<(), String> {
-    let obj_file_name = format!("{}/{}.o", build_dir, app_name);
-    let a_file_name = format!("{}/lib{}.a", build_dir, app_name);
-    let app_file_name = format!("{}/{}", build_dir, app_name);
-    let obj_file = Path::new(&obj_file_name);
-
-    Command::new("gcc")
-        .args(["-o", &app_file_name, &a_file_name])
-        .spawn()
-        .map_err(|_| "Failed to run `gcc` command".to_string())?
-        .wait()
-        .map_err(|_| "Failed to process `gcc` command".to_string())?;
-    std::fs::remove_file(obj_file).or::<String>(Ok(()))
+        // Stale directories of the same process id are skipped
+        let next = TEMP_DIR_COUNTER.load(Ordering::Relaxed);
+        let stale = (next..next + 8)
+            .map(|i| {
+                dir2.path()
+                    .join(format!("ilang-{}-{}", std::process::id(), i))
+            })
+            .collect::<Vec<_>>();
+        for path in stale.iter() {
+            std::fs::create_dir(path).unwrap();
+            std::fs::write(path.join("main.o"), b"").unwrap();
+        }
+        let dir3 = TempDir::new_in(dir2.path()).unwrap();
+        assert!(!stale.iter().any(|v| v == dir3.path()));
+        assert!(std::fs::read_dir(dir3.path()).unwrap().next().is_none());
+        drop(dir3);
+        assert!(stale.iter().all(|v| v.join("main.o").is_file()));
+    }
 }
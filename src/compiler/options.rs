@@ -0,0 +1,179 @@
+//! # Compiler options
+//!
+//! Optimization level, pass pipeline and code generation options of
+//! target machine.
+
+use inkwell::{
+    targets::{
+        CodeModel,
+        RelocMode,
+    },
+    OptimizationLevel,
+};
+
+/// Optimization level `-O`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    /// Optimize for size
+    Os,
+    /// Optimize aggressively for size
+    Oz,
+}
+
+impl OptLevel {
+    /// Names accepted by `-O` option
+    pub const NAMES: [&'static str; 6] = ["0", "1", "2", "3", "s", "z"];
+
+    /// New pass manager pipeline of optimization level
+    pub fn pipeline(&self) -> &'static str {
+        match self {
+            OptLevel::O0 => "default<O0>",
+            OptLevel::O1 => "default<O1>",
+            OptLevel::O2 => "default<O2>",
+            OptLevel::O3 => "default<O3>",
+            OptLevel::Os => "default<Os>",
+            OptLevel::Oz => "default<Oz>",
+        }
+    }
+
+    /// Target machine code generation level. Size levels use default
+    /// code generation, as `clang` does.
+    pub fn codegen_level(&self) -> OptimizationLevel {
+        match self {
+            OptLevel::O0 => OptimizationLevel::None,
+            OptLevel::O1 => OptimizationLevel::Less,
+            OptLevel::O2 | OptLevel::Os | OptLevel::Oz => OptimizationLevel::Default,
+            OptLevel::O3 => OptimizationLevel::Aggressive,
+        }
+    }
+}
+
+impl std::str::FromStr for OptLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(OptLevel::O0),
+            "1" => Ok(OptLevel::O1),
+            "2" => Ok(OptLevel::O2),
+            "3" => Ok(OptLevel::O3),
+            "s" => Ok(OptLevel::Os),
+            "z" => Ok(OptLevel::Oz),
+            _ => Err(format!("Unknown optimization level: {}", s)),
+        }
+    }
+}
+
+/// Names accepted by `--reloc` option
+pub const RELOC_MODES: [&str; 4] = ["default", "static", "pic", "dynamic-no-pic"];
+
+/// Relocation model by name
+pub fn reloc_mode(name: &str) -> Result<RelocMode, String> {
+    match name {
+        "default" => Ok(RelocMode::Default),
+        "static" => Ok(RelocMode::Static),
+        "pic" => Ok(RelocMode::PIC),
+        "dynamic-no-pic" => Ok(RelocMode::DynamicNoPic),
+        _ => Err(format!("Unknown relocation model: {}", name)),
+    }
+}
+
+/// Names accepted by `--code-model` option
+pub const CODE_MODELS: [&str; 5] = ["default", "small", "kernel", "medium", "large"];
+
+/// Code model by name
+pub fn code_model(name: &str) -> Result<CodeModel, String> {
+    match name {
+        "default" => Ok(CodeModel::Default),
+        "small" => Ok(CodeModel::Small),
+        "kernel" => Ok(CodeModel::Kernel),
+        "medium" => Ok(CodeModel::Medium),
+        "large" => Ok(CodeModel::Large),
+        _ => Err(format!("Unknown code model: {}", name)),
+    }
+}
+
+/// Options of native compilation
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileOptions {
+    /// Default is `-O0`, as with C compilers. Previously code was
+    /// generated at aggressive level without IR optimization passes.
+    pub opt_level: OptLevel,
+    /// Custom pass pipeline, like `function(instcombine),dce`. It's
+    /// run instead of optimization level pipeline.
+    pub passes: Option<String>,
+    /// Position independent code is default, as executables are
+    /// linked as PIE by most C compilers
+    pub reloc_mode: RelocMode,
+    /// Default is code model of target. Medium model was used for
+    /// host only, it isn't supported by some targets, like AArch64.
+    pub code_model: CodeModel,
+    /// Target triple, default is host triple
+    pub target: Option<String>,
+    /// Target CPU, default is host CPU for host target and target
+    /// default CPU for cross-compilation
+    pub cpu: Option<String>,
+    /// Target features, like `+avx2,-sse4.1`
+    pub features: Option<String>,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            opt_level: OptLevel::O0,
+            passes: None,
+            reloc_mode: RelocMode::PIC,
+            code_model: CodeModel::Default,
+            target: None,
+            cpu: None,
+            features: None,
+        }
+    }
+}
+
+impl CompileOptions {
+    /// Pass pipeline to run for module
+    pub fn pipeline(&self) -> &str {
+        self.passes
+            .as_deref()
+            .unwrap_or_else(|| self.opt_level.pipeline())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opt_levels() {
+        let levels = OptLevel::NAMES
+            .iter()
+            .map(|name| name.parse::<OptLevel>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(levels[5], OptLevel::Oz);
+        assert_eq!(levels[2].pipeline(), "default<O2>");
+        assert_eq!(levels[4].codegen_level(), OptimizationLevel::Default);
+        assert_eq!(levels[3].codegen_level(), OptimizationLevel::Aggressive);
+        assert!("4".parse::<OptLevel>().is_err());
+    }
+
+    #[test]
+    fn test_compile_options() {
+        let mut options = CompileOptions::default();
+        assert_eq!(options.opt_level, OptLevel::O0);
+        assert_eq!(options.opt_level.codegen_level(), OptimizationLevel::None);
+        assert_eq!(options.reloc_mode, RelocMode::PIC);
+        assert_eq!(options.code_model, CodeModel::Default);
+        assert_eq!(options.pipeline(), "default<O0>");
+        options.passes = Some("function(instcombine),dce".to_string());
+        assert_eq!(options.pipeline(), "function(instcombine),dce");
+        assert!(RELOC_MODES.iter().all(|v| reloc_mode(v).is_ok()));
+        assert!(CODE_MODELS.iter().all(|v| code_model(v).is_ok()));
+        assert_eq!(code_model("medium"), Ok(CodeModel::Medium));
+        assert!(reloc_mode("ropi").is_err());
+    }
+}
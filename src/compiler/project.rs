@@ -0,0 +1,197 @@
+//! # Project build
+//!
+//! Modules of project are compiled in dependency order, each module
+//! to its own object and interface files. Functions have external
+//! linkage and symbols qualified by module id, so functions of
+//! dependencies are declared by dependants with signatures from
+//! interfaces. Unchanged modules aren't recompiled. Object files are
+//! linked to executable or static library.
+
+use super::{
+    ar_builder,
+    compile,
+    create_output_dir,
+    linker::{
+        Linker,
+        RUNTIME_LIBRARIES,
+    },
+    options::CompileOptions,
+    Emit,
+};
+use crate::codegen::{
+    Codegen,
+    CodegenOptions,
+    ExternalFunction,
+};
+use crate::llvm::ir::Module;
+use crate::parser::{
+    ast::Span,
+    token,
+};
+use crate::project::{
+    interface::{
+        self,
+        Interface,
+        INTERFACE_EXTENSION,
+    },
+    manifest::OutputKind,
+    module_references,
+    Project,
+    SourceModule,
+};
+use inkwell::targets::TargetMachine;
+use std::{
+    collections::HashMap,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+/// Codegen options of project module, without functions of
+/// dependencies
+fn module_codegen_options(
+    project: &Project,
+    m: &SourceModule,
+    codegen_options: &CodegenOptions,
+) -> CodegenOptions {
+    CodegenOptions {
+        qualified_symbols: true,
+        entry_module: project.entry.as_ref() == Some(&m.name),
+        source_file: Some(m.path.display().to_string()),
+        external_functions: vec![],
+        ..codegen_options.clone()
+    }
+}
+
+/// Generate LLVM IR module of project module. Functions of
+/// dependencies are declared from their interfaces.
+pub fn codegen_module(
+    m: &SourceModule,
+    dependencies: &[&Interface],
+    mut codegen_options: CodegenOptions,
+) -> Result<Module, String> {
+    for dependency in dependencies.iter() {
+        for reference in module_references(&m.name, &dependency.module) {
+            codegen_options
+                .external_functions
+                .extend(dependency.functions.iter().map(|f| ExternalFunction {
+                    name: format!("{}.{}", reference, f.name),
+                    symbol: f.symbol.clone(),
+                    parameter_types: f.parameter_types.clone(),
+                    result_type: f.result_type.clone(),
+                }));
+        }
+    }
+    let ast = token::main(Span::new(&m.src))
+        .map_err(|_| format!("{}: syntax error", m.path.display()))?
+        .1;
+    Codegen::fn_ir_module_with_options(&ast, codegen_options)
+        .map_err(|err| format!("{}: {}", m.path.display(), err))
+}
+
+/// Build project to `output`. Object and interface files of modules
+/// are written to `objects_dir`. Module is compiled only if
+/// fingerprint of its interface file is changed. Names of compiled
+/// modules are returned.
+pub fn build(
+    project: &Project,
+    objects_dir: &Path,
+    output: &Path,
+    target_machine: &TargetMachine,
+    options: &CompileOptions,
+    codegen_options: &CodegenOptions,
+) -> Result<Vec<String>, String> {
+    let mut interfaces: HashMap<&str, Interface> = HashMap::new();
+    let mut objects: Vec<PathBuf> = vec![];
+    let mut compiled = vec![];
+    for m in project.modules.iter() {
+        let dependencies = m
+            .dependencies
+            .iter()
+            .filter_map(|v| interfaces.get(v.as_str()))
+            .collect::<Vec<_>>();
+        let module_options = module_codegen_options(project, m, codegen_options);
+        let fingerprint = interface::fingerprint(
+            m,
+            &dependencies,
+            &format!("{:?} {:?}", module_options, options),
+        );
+        let object = objects_dir.join(format!("{}.o", m.name));
+        let interface_file = objects_dir.join(format!("{}.{}", m.name, INTERFACE_EXTENSION));
+        let interface = match Interface::read(&interface_file) {
+            Ok(v) if v.fingerprint == fingerprint && object.is_file() => v,
+            _ => {
+                let module = codegen_module(m, &dependencies, module_options)?;
+                compile(
+                    &module.to_string(),
+                    Emit::Object,
+                    &object,
+                    target_machine,
+                    options,
+                )?;
+                // Interface is written after object, so it's valid
+                // only with compiled object
+                let interface = Interface::new(&m.name, fingerprint, &module);
+                interface.write(&interface_file)?;
+                compiled.push(m.name.clone());
+                interface
+            }
+        };
+        interfaces.insert(&m.name, interface);
+        objects.push(object);
+    }
+    create_output_dir(output)?;
+    let objects = objects.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+    match project.manifest.package.output {
+        OutputKind::Executable => Linker::detect()?.link(&objects, &RUNTIME_LIBRARIES, output)?,
+        OutputKind::StaticLib => ar_builder(&objects, output)?,
+    }
+    Ok(compiled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::types::Type;
+    use crate::project::interface::ExportedFunction;
+
+    #[test]
+    fn test_project_codegen_modules() {
+        let path = crate::project::Project::manifest_path(Path::new("./examples/project"));
+        let project = Project::load(&path.unwrap()).unwrap();
+        let mut interfaces: Vec<Interface> = vec![];
+        let mut ir = vec![];
+        for m in project.modules.iter() {
+            let options = module_codegen_options(&project, m, &CodegenOptions::default());
+            let dependencies = interfaces.iter().collect::<Vec<_>>();
+            let module = codegen_module(m, &dependencies, options).unwrap();
+            interfaces.push(Interface::new(&m.name, String::new(), &module));
+            ir.push(module.to_string());
+        }
+        assert_eq!(
+            interfaces[0].functions,
+            [ExportedFunction {
+                name: "answer".to_string(),
+                symbol: "App.Utils.answer".to_string(),
+                parameter_types: vec![],
+                result_type: Type::Integer32,
+            }]
+        );
+        // Entry point isn't exported
+        assert!(interfaces[1].functions.is_empty());
+        assert!(ir[0].contains("define i32 @App.Utils.answer()"));
+        assert!(ir[1].contains("declare i32 @App.Utils.answer()"));
+        assert!(ir[1].contains("call i32 @App.Utils.answer()"));
+        assert!(ir[1].contains("define i32 @main()"));
+
+        // Dependant is compiled against interface only
+        let m = &project.modules[1];
+        let options = module_codegen_options(&project, m, &CodegenOptions::default());
+        let interface = Interface {
+            functions: vec![],
+            ..interfaces[0].clone()
+        };
+        assert!(codegen_module(m, &[&interface], options).is_err());
+    }
+}
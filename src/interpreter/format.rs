@@ -0,0 +1,332 @@
+//! # Formatted output
+//!
+//! `printf` format strings with C semantics, so interpreter output
+//! matches output of compiled code. Conversions `d i u x X o c s f F
+//! e E g G %` are supported, with flags, width and precision. `%A`
+//! prints any value.
+
+use super::{
+    value::Value,
+    InterpreterError,
+};
+
+/// Conversion specification: `%[flags][width][.precision]conversion`
+#[derive(Debug, Default)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    zero: bool,
+    alternate: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    /// Pad formatted value to width. Zeros are inserted after sign
+    /// and prefix.
+    fn pad(&self, prefix: &str, body: &str, numeric: bool) -> String {
+        let len = prefix.chars().count() + body.chars().count();
+        if len >= self.width {
+            return format!("{}{}", prefix, body);
+        }
+        let fill = self.width - len;
+        if self.left {
+            format!("{}{}{}", prefix, body, " ".repeat(fill))
+        } else if self.zero && numeric {
+            format!("{}{}{}", prefix, "0".repeat(fill), body)
+        } else {
+            format!("{}{}{}", " ".repeat(fill), prefix, body)
+        }
+    }
+
+    /// Sign prefix of signed number
+    fn sign(&self, negative: bool) -> &'static str {
+        if negative {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    /// Digits with minimal count of digits from precision
+    fn digits(&self, digits: String) -> String {
+        match self.precision {
+            Some(0) if digits == "0" => String::new(),
+            Some(p) if digits.len() < p => format!("{}{}", "0".repeat(p - digits.len()), digits),
+            _ => digits,
+        }
+    }
+}
+
+/// Mantissa and exponent in C `%e` form: `d.ddde+XX`
+fn exponent_form(v: f64, precision: usize, upper: bool) -> String {
+    let s = format!("{:.*e}", precision, v);
+    let (mantissa, exp) = s.split_at(s.find('e').unwrap());
+    let exp: i32 = exp[1..].parse().unwrap();
+    let e = if upper { 'E' } else { 'e' };
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}{}{}{:02}", mantissa, e, sign, exp.abs())
+}
+
+/// Remove trailing zeros of fraction for `%g`
+fn trim_fraction(s: String) -> String {
+    let (number, exp) = match s.find(&['e', 'E'][..]) {
+        Some(i) => s.split_at(i),
+        None => (s.as_str(), ""),
+    };
+    if !number.contains('.') {
+        return s.clone();
+    }
+    let number = number.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", number, exp)
+}
+
+/// Floating point conversion without sign
+fn float(v: f64, conversion: char, spec: &Spec) -> String {
+    let upper = conversion.is_ascii_uppercase();
+    if !v.is_finite() {
+        let s = if v.is_nan() { "nan" } else { "inf" };
+        return if upper {
+            s.to_uppercase()
+        } else {
+            s.to_string()
+        };
+    }
+    let precision = spec.precision.unwrap_or(6);
+    match conversion {
+        'f' | 'F' => format!("{:.*}", precision, v),
+        'e' | 'E' => exponent_form(v, precision, upper),
+        _ => {
+            // `%g`: shortest of `%e` and `%f` for precision
+            let precision = precision.max(1);
+            let e = exponent_form(v, precision - 1, upper);
+            let exp: i32 = e[e.find(&['e', 'E'][..]).unwrap() + 1..].parse().unwrap();
+            let s = if exp < -4 || exp >= precision as i32 {
+                e
+            } else {
+                format!("{:.*}", (precision as i32 - 1 - exp) as usize, v)
+            };
+            if spec.alternate {
+                s
+            } else {
+                trim_fraction(s)
+            }
+        }
+    }
+}
+
+/// Format value for conversion
+fn convert(conversion: char, spec: &Spec, value: &Value) -> Result<String, InterpreterError> {
+    let mismatch =
+        || InterpreterError::Format(format!("%{} for {}", conversion, value.type_name()));
+    let res = match conversion {
+        'd' | 'i' => {
+            let v = value.as_i64().ok_or_else(mismatch)?;
+            let digits = spec.digits(v.unsigned_abs().to_string());
+            spec.pad(spec.sign(v < 0), &digits, spec.precision.is_none())
+        }
+        'u' | 'x' | 'X' | 'o' => {
+            // Unsigned value of argument type
+            let v = match value {
+                Value::Int(v) => u64::from(*v as u32),
+                v => v.as_i64().ok_or_else(mismatch)? as u64,
+            };
+            let (digits, prefix) = match conversion {
+                'u' => (v.to_string(), ""),
+                'x' => (format!("{:x}", v), "0x"),
+                'X' => (format!("{:X}", v), "0X"),
+                _ => (format!("{:o}", v), "0"),
+            };
+            let digits = spec.digits(digits);
+            let prefix = if spec.alternate && v != 0 && conversion != 'u' {
+                prefix
+            } else {
+                ""
+            };
+            spec.pad(prefix, &digits, spec.precision.is_none())
+        }
+        'c' => {
+            let v = value.as_i64().ok_or_else(mismatch)?;
+            spec.pad("", &char::from(v as u8).to_string(), false)
+        }
+        's' => match value {
+            Value::String(s) => {
+                let s = match spec.precision {
+                    Some(p) => s.chars().take(p).collect(),
+                    None => s.clone(),
+                };
+                spec.pad("", &s, false)
+            }
+            _ => return Err(mismatch()),
+        },
+        'f' | 'F' | 'e' | 'E' | 'g' | 'G' => match value {
+            Value::Float(v) => {
+                let body = float(v.abs(), conversion, spec);
+                spec.pad(spec.sign(v.is_sign_negative()), &body, v.is_finite())
+            }
+            _ => return Err(mismatch()),
+        },
+        'A' => spec.pad("", &value.to_string(), false),
+        _ => {
+            return Err(InterpreterError::Format(format!(
+                "unsupported conversion %{}",
+                conversion
+            )))
+        }
+    };
+    Ok(res)
+}
+
+/// Format values with `printf` format string
+pub fn format(fmt: &str, args: &[Value]) -> Result<String, InterpreterError> {
+    let mut res = String::new();
+    let mut args = args.iter();
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            res.push(c);
+            continue;
+        }
+        let mut spec = Spec::default();
+        while let Some(&c) = chars.peek() {
+            match c {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '0' => spec.zero = true,
+                '#' => spec.alternate = true,
+                _ => break,
+            }
+            chars.next();
+        }
+        while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+            spec.width = spec.width * 10 + d as usize;
+            chars.next();
+        }
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut precision = 0;
+            while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                precision = precision * 10 + d as usize;
+                chars.next();
+            }
+            spec.precision = Some(precision);
+        }
+        // Length modifiers don't change value of argument
+        while matches!(chars.peek(), Some('h') | Some('l') | Some('z')) {
+            chars.next();
+        }
+        match chars.next() {
+            Some('%') => res.push('%'),
+            Some(conversion) => {
+                let value = args.next().ok_or_else(|| {
+                    InterpreterError::Format(format!("missing argument for %{}", conversion))
+                })?;
+                res.push_str(&convert(conversion, &spec, value)?);
+            }
+            None => {
+                return Err(InterpreterError::Format(
+                    "incomplete conversion".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(fmt: &str, args: &[Value], expected: &str) {
+        assert_eq!(format(fmt, args).unwrap(), expected, "format {:?}", fmt);
+    }
+
+    #[test]
+    fn test_format_integers() {
+        check(
+            "%d %i %%",
+            &[Value::Int(-42), Value::Byte(200)],
+            "-42 200 %",
+        );
+        check(
+            "[%5d|%-5d|%05d]",
+            &vec![Value::Int(42); 3],
+            "[   42|42   |00042]",
+        );
+        check("[%+d|% d|%.3d]", &vec![Value::Int(7); 3], "[+7| 7|007]");
+        check(
+            "%u %x %X %#o",
+            &[
+                Value::Int(-1),
+                Value::Int(255),
+                Value::Int(255),
+                Value::Int(8),
+            ],
+            "4294967295 ff FF 010",
+        );
+        check(
+            "%ld %c",
+            &[Value::Int64(1 << 40), Value::Int(65)],
+            "1099511627776 A",
+        );
+        check("%d", &[Value::Bool(true)], "1");
+    }
+
+    #[test]
+    fn test_format_floats() {
+        let v = Value::Float(3.14159);
+        check(
+            "%f %.2f %8.3f %-8.1f|",
+            &[v.clone(), v.clone(), v.clone(), v],
+            "3.141590 3.14    3.142 3.1     |",
+        );
+        check(
+            "%e %.2E",
+            &[Value::Float(12345.678), Value::Float(-0.00012)],
+            "1.234568e+04 -1.20E-04",
+        );
+        check(
+            "%g %g %g %g",
+            &[
+                Value::Float(0.0001),
+                Value::Float(100000.0),
+                Value::Float(1e6),
+                Value::Float(2.5),
+            ],
+            "0.0001 100000 1e+06 2.5",
+        );
+        check(
+            "%f %f",
+            &[Value::Float(f64::INFINITY), Value::Float(-f64::INFINITY)],
+            "inf -inf",
+        );
+        check("%+.1f", &[Value::Float(2.25)], "+2.2");
+    }
+
+    #[test]
+    fn test_format_strings() {
+        let s = Value::String("hello".to_string());
+        check(
+            "[%s|%7s|%-7s|%.2s]",
+            &[s.clone(), s.clone(), s.clone(), s],
+            "[hello|  hello|hello  |he]",
+        );
+        check(
+            "%A %A",
+            &[
+                Value::Float(2.0),
+                Value::Vector(vec![Value::Int(1), Value::Int(2)]),
+            ],
+            "2.0 <1, 2>",
+        );
+        assert!(format("%d", &[]).is_err());
+        assert!(format("%s", &[Value::Int(1)]).is_err());
+        assert!(format("%q", &[Value::Int(1)]).is_err());
+    }
+}
@@ -0,0 +1,1067 @@
+//! # Interpreter
+//!
+//! Tree-walking interpreter of AST. It doesn't depend on LLVM, and
+//! follows semantics of generated code: types of literals, operations
+//! precedence, integer overflow wrapping and build-in functions. So
+//! it's used as reference for compiled programs, and to run programs
+//! without LLVM.
+//!
+//! Functions without definition in module are not external, only
+//! build-in output functions are available:
+//! * `printf format args` - C `printf`, count of written bytes is
+//!   returned
+//! * `printfn format args` - `printf` with line feed
+
+pub mod format;
+pub mod value;
+
+use self::value::Value;
+use crate::codegen::Codegen;
+use crate::parser::ast::*;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Write;
+
+/// Entry point of program
+pub const ENTRY_POINT: &str = "main";
+
+/// Limit of nested function calls. Language has no conditions, so
+/// recursion never ends.
+pub const MAX_CALL_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpreterError {
+    ModuleNotFound,
+    ParseSourceCode,
+    ValueNotFound(String),
+    FunctionNotFound(String),
+    /// Types of values, by source code type names
+    ExpressionTypeMismatch(String, String),
+    UnsupportedOperand(String),
+    UnsupportedOperation(ExpressionOperation, String),
+    UnsupportedConversion(String, String),
+    ArgumentsCountMismatch(String, usize),
+    /// Literal, which doesn't fit 64-bit integer
+    IntegerLiteralOutOfRange(String),
+    /// Language construction, which isn't supported by codegen
+    Unsupported(String),
+    DivisionByZero,
+    /// Signed overflow, if overflow checks enabled
+    IntegerOverflow,
+    CallDepthExceeded(String),
+    /// Malformed `printf` format string or arguments
+    Format(String),
+    /// Failed to write program output
+    Output(String),
+}
+
+impl std::fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InterpreterError::ModuleNotFound => write!(f, "module declaration not found"),
+            InterpreterError::ParseSourceCode => write!(f, "failed to parse source code"),
+            InterpreterError::ValueNotFound(v) => write!(f, "value `{}` not found", v),
+            InterpreterError::FunctionNotFound(v) => write!(f, "function `{}` not found", v),
+            InterpreterError::ExpressionTypeMismatch(x, y) => {
+                write!(f, "type mismatch in expression: `{}` and `{}`", x, y)
+            }
+            InterpreterError::UnsupportedOperand(v) => write!(f, "unsupported operand `{}`", v),
+            InterpreterError::UnsupportedOperation(op, ty) => {
+                write!(f, "operation {:?} isn't supported for `{}`", op, ty)
+            }
+            InterpreterError::UnsupportedConversion(x, y) => {
+                write!(f, "can't convert `{}` to `{}`", x, y)
+            }
+            InterpreterError::ArgumentsCountMismatch(name, count) => {
+                write!(
+                    f,
+                    "function `{}` can't be called with {} arguments",
+                    name, count
+                )
+            }
+            InterpreterError::IntegerLiteralOutOfRange(v) => {
+                write!(f, "integer literal `{}` is out of range", v)
+            }
+            InterpreterError::Unsupported(v) => write!(f, "{} isn't supported", v),
+            InterpreterError::DivisionByZero => write!(f, "division by zero"),
+            InterpreterError::IntegerOverflow => write!(f, "integer overflow"),
+            InterpreterError::CallDepthExceeded(name) => {
+                write!(f, "call depth exceeded in function `{}`", name)
+            }
+            InterpreterError::Format(err) => write!(f, "invalid format: {}", err),
+            InterpreterError::Output(err) => write!(f, "failed to write output: {}", err),
+        }
+    }
+}
+
+pub type ValueResult = Result<Value, InterpreterError>;
+
+/// Interpreter options
+#[derive(Debug, Clone, Default)]
+pub struct InterpreterOptions {
+    /// Integer `+`, `-` and `*` fail on signed overflow
+    pub overflow_checks: bool,
+}
+
+/// Interpreter of module. Program output is written to `output`.
+pub struct Interpreter<'a, W: Write> {
+    ast: &'a Main<'a>,
+    options: InterpreterOptions,
+    output: W,
+    /// Values of current function body
+    let_values: HashMap<String, Value>,
+    global_values: HashMap<String, Value>,
+    call_depth: usize,
+}
+
+/// Operations precedence, same as for codegen
+fn operation_precedence(op: &ExpressionOperation) -> u8 {
+    match op {
+        ExpressionOperation::Multiply | ExpressionOperation::Divide => 3,
+        ExpressionOperation::Plus | ExpressionOperation::Minus => 2,
+        ExpressionOperation::ShiftLeft | ExpressionOperation::ShiftRight => 1,
+    }
+}
+
+/// Names of function parameters
+fn parameter_names(pl: &ParameterList) -> Vec<String> {
+    let value_list = |pvl: &ParameterValueList| match pvl {
+        ParameterValueList::ParameterValue(p) => vec![p.fragment().to_string()],
+        ParameterValueList::ParameterList(pl) => pl
+            .iter()
+            .map(|pvt| match pvt {
+                ParameterValueType::Value(v) | ParameterValueType::ValueType(v, _) => {
+                    v.fragment().to_string()
+                }
+            })
+            .collect(),
+    };
+    match pl {
+        ParameterList::ParameterList(pvl) => value_list(pvl),
+        ParameterList::ParameterValueList(v) => v.iter().flat_map(value_list).collect(),
+    }
+}
+
+/// Integer operation with wrapping, or checked for signed overflow
+macro_rules! integer_operation {
+    ($op:expr, $lhs:expr, $rhs:expr, $checked:expr) => {{
+        let (lhs, rhs) = ($lhs, $rhs);
+        let res = match $op {
+            ExpressionOperation::Plus if $checked => lhs.checked_add(rhs),
+            ExpressionOperation::Minus if $checked => lhs.checked_sub(rhs),
+            ExpressionOperation::Multiply if $checked => lhs.checked_mul(rhs),
+            ExpressionOperation::Plus => Some(lhs.wrapping_add(rhs)),
+            ExpressionOperation::Minus => Some(lhs.wrapping_sub(rhs)),
+            ExpressionOperation::Multiply => Some(lhs.wrapping_mul(rhs)),
+            ExpressionOperation::Divide if rhs == 0 => {
+                return Err(InterpreterError::DivisionByZero)
+            }
+            ExpressionOperation::Divide => lhs.checked_div(rhs),
+            ExpressionOperation::ShiftLeft => Some(lhs.wrapping_shl(rhs as u32)),
+            ExpressionOperation::ShiftRight => Some(lhs.wrapping_shr(rhs as u32)),
+        };
+        res.ok_or(InterpreterError::IntegerOverflow)?
+    }};
+}
+
+/// Binary operation for two values with same types. Integer
+/// operations wrap, as `sdiv` and `ashr` are signed, bytes are
+/// calculated as signed too. Shift amount is taken modulo bit width,
+/// as codegen does. Division overflow and division by zero
+/// are errors. Vectors calculated by lanes.
+pub fn binary_operation(
+    op: &ExpressionOperation,
+    lhs: Value,
+    rhs: Value,
+    overflow_checks: bool,
+) -> ValueResult {
+    if !lhs.same_type(&rhs) {
+        return Err(InterpreterError::ExpressionTypeMismatch(
+            lhs.type_name(),
+            rhs.type_name(),
+        ));
+    }
+    let value = match (lhs, rhs) {
+        (Value::Int(l), Value::Int(r)) => Value::Int(integer_operation!(op, l, r, overflow_checks)),
+        (Value::Int64(l), Value::Int64(r)) => {
+            Value::Int64(integer_operation!(op, l, r, overflow_checks))
+        }
+        (Value::Byte(l), Value::Byte(r)) => {
+            Value::Byte(integer_operation!(op, l as i8, r as i8, overflow_checks) as u8)
+        }
+        (Value::Float(l), Value::Float(r)) => Value::Float(match op {
+            ExpressionOperation::Plus => l + r,
+            ExpressionOperation::Minus => l - r,
+            ExpressionOperation::Multiply => l * r,
+            ExpressionOperation::Divide => l / r,
+            _ => {
+                return Err(InterpreterError::UnsupportedOperation(
+                    op.clone(),
+                    "float".to_string(),
+                ))
+            }
+        }),
+        (Value::Vector(l), Value::Vector(r)) => {
+            let mut lanes = vec![];
+            for (l, r) in l.into_iter().zip(r) {
+                // Overflow checked only for scalars
+                lanes.push(binary_operation(op, l, r, false)?);
+            }
+            Value::Vector(lanes)
+        }
+        (v, _) => {
+            return Err(InterpreterError::UnsupportedOperation(
+                op.clone(),
+                v.type_name(),
+            ))
+        }
+    };
+    Ok(value)
+}
+
+/// Convert value to numeric type by type name. Integers extended with
+/// sign, except booleans and bytes which are unsigned. Floating point
+/// values are truncated.
+pub fn conversion(value: Value, ty: &str) -> ValueResult {
+    let unsupported =
+        |value: &Value| InterpreterError::UnsupportedConversion(value.type_name(), ty.to_string());
+    let res = match &value {
+        Value::Float(v) => match ty {
+            "int" => Value::Int(*v as i32),
+            "byte" => Value::Byte(*v as u8),
+            _ => Value::Float(*v),
+        },
+        v => {
+            let n = v.as_i64().ok_or_else(|| unsupported(&value))?;
+            match ty {
+                "int" => Value::Int(n as i32),
+                "byte" => Value::Byte(n as u8),
+                _ => Value::Float(v.as_f64().unwrap()),
+            }
+        }
+    };
+    Ok(res)
+}
+
+/// Type name of build-in numeric conversion function
+fn conversion_function_type(fn_name: &str) -> Option<&'static str> {
+    match fn_name {
+        "int" => Some("int"),
+        "float" => Some("float"),
+        "byte" => Some("byte"),
+        _ => None,
+    }
+}
+
+/// Is function build-in: numeric conversions and SIMD vectors
+fn is_builtin_function(fn_name: &str) -> bool {
+    conversion_function_type(fn_name).is_some() || ["float4", "int4", "lane"].contains(&fn_name)
+}
+
+/// Is function build-in formatted output
+fn is_output_function(fn_name: &str) -> bool {
+    fn_name == "printf" || fn_name == "printfn"
+}
+
+/// Floating point function of `Math` module
+fn math_float_function(fn_name: &str) -> Option<fn(f64, f64) -> f64> {
+    let f: fn(f64, f64) -> f64 = match fn_name {
+        "sqrt" => |x, _| x.sqrt(),
+        "sin" => |x, _| x.sin(),
+        "cos" => |x, _| x.cos(),
+        "exp" => |x, _| x.exp(),
+        "log" => |x, _| x.ln(),
+        "floor" => |x, _| x.floor(),
+        "ceil" => |x, _| x.ceil(),
+        "round" => |x, _| x.round(),
+        "pow" => f64::powf,
+        "min" => f64::min,
+        "max" => f64::max,
+        _ => return None,
+    };
+    Some(f)
+}
+
+/// Arity of floating point function of `Math` module
+fn math_float_arity(fn_name: &str) -> usize {
+    match fn_name {
+        "pow" | "min" | "max" => 2,
+        _ => 1,
+    }
+}
+
+/// Lanes of vector, scalar value is single lane
+fn lanes(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Vector(v) => v.clone(),
+        v => vec![v.clone()],
+    }
+}
+
+/// Map lanes of vector, or scalar value
+fn map_lanes(value: Value, f: &dyn Fn(Value) -> ValueResult) -> ValueResult {
+    match value {
+        Value::Vector(v) => Ok(Value::Vector(
+            v.into_iter().map(f).collect::<Result<_, _>>()?,
+        )),
+        v => f(v),
+    }
+}
+
+/// Build-in `Math` module functions:
+/// * `Math.sqrt x`, `Math.pow x y`, `Math.min x y`, ... - floating
+///   point functions, integer arguments converted to `float`
+/// * `Math.abs x` - absolute value of integer or float
+/// * `Math.popcount x` - count of set bits of integer
+pub fn math_function(fn_name: &str, args: Vec<Value>) -> ValueResult {
+    let qualified_name = format!("Math.{}", fn_name);
+    let float_function = math_float_function(fn_name);
+    let count = match (fn_name, float_function) {
+        ("abs", _) | ("popcount", _) => 1,
+        (_, Some(_)) => math_float_arity(fn_name),
+        (_, None) => return Err(InterpreterError::ValueNotFound(qualified_name)),
+    };
+    if args.len() != count {
+        return Err(InterpreterError::ArgumentsCountMismatch(
+            qualified_name,
+            args.len(),
+        ));
+    }
+    let unsupported = |v: &Value| InterpreterError::UnsupportedOperand(v.type_name());
+    match (fn_name, float_function) {
+        ("abs", _) => map_lanes(args[0].clone(), &|v| match v {
+            Value::Float(v) => Ok(Value::Float(v.abs())),
+            Value::Int(v) => Ok(Value::Int(v.wrapping_abs())),
+            Value::Int64(v) => Ok(Value::Int64(v.wrapping_abs())),
+            Value::Byte(v) => Ok(Value::Byte((v as i8).wrapping_abs() as u8)),
+            v => Err(unsupported(&v)),
+        }),
+        ("popcount", _) => map_lanes(args[0].clone(), &|v| match v {
+            Value::Int(v) => Ok(Value::Int(v.count_ones() as i32)),
+            Value::Int64(v) => Ok(Value::Int64(i64::from(v.count_ones()))),
+            Value::Byte(v) => Ok(Value::Byte(v.count_ones() as u8)),
+            v => Err(unsupported(&v)),
+        }),
+        (_, f) => {
+            let f = f.unwrap();
+            let to_float = |v: Value| match v {
+                Value::Vector(_) | Value::Float(_) => Ok(v),
+                v => conversion(v, "float"),
+            };
+            let args = args
+                .into_iter()
+                .map(to_float)
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(v) = args.iter().find(|v| !v.same_type(&args[0])) {
+                return Err(InterpreterError::ExpressionTypeMismatch(
+                    args[0].type_name(),
+                    v.type_name(),
+                ));
+            }
+            let x = lanes(&args[0]);
+            let y = args.get(1).map(lanes);
+            let mut res = vec![];
+            for (i, x) in x.iter().enumerate() {
+                res.push(match (x, y.as_ref().map(|y| &y[i])) {
+                    (Value::Float(x), None) => Value::Float(f(*x, 0.0)),
+                    (Value::Float(x), Some(Value::Float(y))) => Value::Float(f(*x, *y)),
+                    (x, _) => return Err(unsupported(x)),
+                });
+            }
+            match args[0] {
+                Value::Vector(_) => Ok(Value::Vector(res)),
+                _ => Ok(res.remove(0)),
+            }
+        }
+    }
+}
+
+/// Build SIMD vector from lane values. Single value is broadcasted
+/// to all lanes. Lane values converted to element type of vector.
+pub fn vector(fn_name: &str, args: Vec<Value>) -> ValueResult {
+    let element_type = if fn_name == "float4" { "float" } else { "int" };
+    let lanes = 4;
+    if args.len() != 1 && args.len() != lanes {
+        return Err(InterpreterError::ArgumentsCountMismatch(
+            fn_name.to_string(),
+            args.len(),
+        ));
+    }
+    let mut values = args
+        .into_iter()
+        .map(|v| conversion(v, element_type))
+        .collect::<Result<Vec<_>, _>>()?;
+    if values.len() == 1 {
+        values = vec![values[0].clone(); lanes];
+    }
+    Ok(Value::Vector(values))
+}
+
+/// Extract lane of SIMD vector: `lane vector index`
+pub fn vector_lane(vec: Value, idx: Value) -> ValueResult {
+    let lanes = match vec {
+        Value::Vector(lanes) => lanes,
+        v => return Err(InterpreterError::UnsupportedOperand(v.type_name())),
+    };
+    if !idx.is_integer() {
+        return Err(InterpreterError::UnsupportedOperand(idx.type_name()));
+    }
+    let i = idx.as_i64().unwrap();
+    usize::try_from(i)
+        .ok()
+        .and_then(|i| lanes.get(i).cloned())
+        .ok_or_else(|| InterpreterError::UnsupportedOperand(format!("lane {}", i)))
+}
+
+/// Build-in function call with already evaluated arguments
+fn builtin_function(fn_name: &str, mut args: Vec<Value>) -> ValueResult {
+    if fn_name == "float4" || fn_name == "int4" {
+        return vector(fn_name, args);
+    }
+    let count = if fn_name == "lane" { 2 } else { 1 };
+    if args.len() != count {
+        return Err(InterpreterError::ArgumentsCountMismatch(
+            fn_name.to_string(),
+            args.len(),
+        ));
+    }
+    let value = args.remove(0);
+    match conversion_function_type(fn_name) {
+        Some(ty) => conversion(value, ty),
+        None => vector_lane(value, args.remove(0)),
+    }
+}
+
+impl<'a, W: Write> Interpreter<'a, W> {
+    pub fn new(ast: &'a Main<'a>, options: InterpreterOptions, output: W) -> Self {
+        Self {
+            ast,
+            options,
+            output,
+            let_values: HashMap::new(),
+            global_values: HashMap::new(),
+            call_depth: 0,
+        }
+    }
+
+    /// Program output
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    /// Single value of literal or let-value
+    pub fn value_operand(&self, vle: &ValueExpression) -> ValueResult {
+        match vle {
+            ValueExpression::TypeExpression(te) => Ok(match te.expr {
+                BasicTypeExpression::Float(n) => Value::Float(n),
+                BasicTypeExpression::Number(n) => {
+                    if n >= f64::from(i32::MIN) && n <= f64::from(i32::MAX) {
+                        Value::Int(n as i32)
+                    } else if n >= i64::MIN as f64 && n < i64::MAX as f64 {
+                        Value::Int64(n as i64)
+                    } else {
+                        return Err(InterpreterError::IntegerLiteralOutOfRange(format!(
+                            "{:.0}",
+                            n
+                        )));
+                    }
+                }
+                BasicTypeExpression::Bool(b) => Value::Bool(b),
+                BasicTypeExpression::String(ref s) => Value::String(s.clone()),
+            }),
+            ValueExpression::ParameterValue(pv) => {
+                let name = pv.fragment().to_string();
+                self.let_values
+                    .get(&name)
+                    .or_else(|| self.global_values.get(&name))
+                    .cloned()
+                    .ok_or(InterpreterError::ValueNotFound(name))
+            }
+        }
+    }
+
+    /// Expression operand: value, expression in brackets or function call
+    pub fn expression_operand(&mut self, efvc: &ExpressionFunctionValueCall) -> ValueResult {
+        match efvc {
+            ExpressionFunctionValueCall::FunctionValue(FunctionValue::ValueList(vl)) => {
+                if vl.len() != 1 {
+                    return Err(InterpreterError::UnsupportedOperand(format!("{:?}", vl)));
+                }
+                self.value_operand(&vl[0])
+            }
+            ExpressionFunctionValueCall::FunctionValue(FunctionValue::Expression(e)) => {
+                self.expression(e)
+            }
+            ExpressionFunctionValueCall::FunctionCall(fc) => match self.function_call(fc)? {
+                Value::Unit => Err(InterpreterError::UnsupportedOperand(format!(
+                    "{:?}",
+                    fc.function_call_name
+                ))),
+                value => Ok(value),
+            },
+        }
+    }
+
+    /// Expression is sequence of operands and operations. All operands
+    /// calculated from left to right, and then operations applied
+    /// according to precedence.
+    pub fn expression(&mut self, e: &Expression) -> ValueResult {
+        let mut operands = vec![];
+        let mut operations = vec![];
+        let mut expr = Some(e);
+        while let Some(e) = expr {
+            operands.push(self.expression_operand(&e.function_statement)?);
+            expr = match (&e.operation_statement, &e.expression) {
+                (Some(op), Some(next)) => {
+                    operations.push(op.clone());
+                    Some(next)
+                }
+                (None, _) => None,
+                (Some(op), None) => {
+                    return Err(InterpreterError::UnsupportedOperand(format!("{:?}", op)))
+                }
+            };
+        }
+
+        let checked = self.options.overflow_checks;
+        let mut operands = operands.into_iter();
+        let mut values = vec![operands.next().unwrap()];
+        let mut stack: Vec<ExpressionOperation> = vec![];
+        for (op, value) in operations.into_iter().zip(operands) {
+            while let Some(last_op) = stack.last() {
+                if operation_precedence(last_op) < operation_precedence(&op) {
+                    break;
+                }
+                let last_op = stack.pop().unwrap();
+                let rhs = values.pop().unwrap();
+                let lhs = values.pop().unwrap();
+                values.push(binary_operation(&last_op, lhs, rhs, checked)?);
+            }
+            stack.push(op);
+            values.push(value);
+        }
+        while let Some(op) = stack.pop() {
+            let rhs = values.pop().unwrap();
+            let lhs = values.pop().unwrap();
+            values.push(binary_operation(&op, lhs, rhs, checked)?);
+        }
+        Ok(values.pop().unwrap())
+    }
+
+    /// Function value: list of values or expression
+    pub fn function_value(&mut self, fv: &FunctionValue) -> Result<Vec<Value>, InterpreterError> {
+        match fv {
+            FunctionValue::ValueList(vl) => vl.iter().map(|v| self.value_operand(v)).collect(),
+            FunctionValue::Expression(expr) => Ok(vec![self.expression(expr)?]),
+        }
+    }
+
+    /// Function of current module
+    fn defined_function(&self, fn_name: &str) -> Option<&'a Function<'a>> {
+        self.ast.iter().find_map(|v| match v {
+            MainStatement::Function(f) if *f.function_name.fragment() == fn_name => Some(f),
+            _ => None,
+        })
+    }
+
+    /// Build-in `Atomic` module functions. First argument is global
+    /// let-value used as memory location:
+    /// * `Atomic.increment x` - increment value, return new value
+    /// * `Atomic.compareExchange x cmp new` - store `new` if value
+    ///   equal to `cmp`, return original value
+    pub fn atomic_function(&mut self, fn_name: &str, fc: &FunctionCall) -> ValueResult {
+        let qualified_name = format!("Atomic.{}", fn_name);
+        let (location, rest) = match fc.function_value.split_first() {
+            Some((FunctionValue::ValueList(vl), rest)) if !vl.is_empty() => (vl, rest),
+            _ => return Err(InterpreterError::ArgumentsCountMismatch(qualified_name, 0)),
+        };
+        let name = match &location[0] {
+            ValueExpression::ParameterValue(pv) => pv.fragment().to_string(),
+            ValueExpression::TypeExpression(te) => {
+                return Err(InterpreterError::UnsupportedOperand(format!(
+                    "{:?}",
+                    te.expr
+                )))
+            }
+        };
+        let mut args = vec![];
+        for vle in location.iter().skip(1) {
+            args.push(self.value_operand(vle)?);
+        }
+        for v in rest.iter() {
+            args.append(&mut self.function_value(v)?);
+        }
+        let old = self
+            .global_values
+            .get(&name)
+            .cloned()
+            .ok_or(InterpreterError::ValueNotFound(name.clone()))?;
+        if let Some(v) = args.iter().find(|v| !v.same_type(&old)) {
+            return Err(InterpreterError::ExpressionTypeMismatch(
+                old.type_name(),
+                v.type_name(),
+            ));
+        }
+        match (fn_name, args.len()) {
+            ("increment", 0) if old.is_integer() => {
+                let one = match old {
+                    Value::Byte(_) => Value::Byte(1),
+                    Value::Int64(_) => Value::Int64(1),
+                    _ => Value::Int(1),
+                };
+                // Stored value wraps, result is checked
+                let new =
+                    binary_operation(&ExpressionOperation::Plus, old.clone(), one.clone(), false)?;
+                self.global_values.insert(name, new);
+                binary_operation(
+                    &ExpressionOperation::Plus,
+                    old,
+                    one,
+                    self.options.overflow_checks,
+                )
+            }
+            ("increment", 0) => Err(InterpreterError::UnsupportedOperand(old.type_name())),
+            ("compareExchange", 2) => {
+                if old == args[0] {
+                    self.global_values.insert(name, args[1].clone());
+                }
+                Ok(old)
+            }
+            ("increment", _) | ("compareExchange", _) => Err(
+                InterpreterError::ArgumentsCountMismatch(qualified_name, args.len() + 1),
+            ),
+            _ => Err(InterpreterError::ValueNotFound(qualified_name)),
+        }
+    }
+
+    /// Formatted output with `printf` and `printfn`. First argument
+    /// is format string, extra arguments are ignored as by C `printf`.
+    fn output_function(&mut self, fn_name: &str, args: &[Value]) -> ValueResult {
+        let mut s = match args.split_first() {
+            Some((Value::String(fmt), args)) => format::format(fmt, args)?,
+            Some((v, _)) => return Err(InterpreterError::UnsupportedOperand(v.type_name())),
+            None => {
+                return Err(InterpreterError::ArgumentsCountMismatch(
+                    fn_name.to_string(),
+                    0,
+                ))
+            }
+        };
+        if fn_name == "printfn" {
+            s.push('\n');
+        }
+        self.output
+            .write_all(s.as_bytes())
+            .map_err(|err| InterpreterError::Output(err.to_string()))?;
+        Ok(Value::Int(s.len() as i32))
+    }
+
+    /// Call function of current module. Arguments are bound to
+    /// parameters, local values of caller are restored after call.
+    pub fn call(&mut self, f: &Function, args: Vec<Value>) -> ValueResult {
+        let fn_name = f.function_name.fragment().to_string();
+        let params = parameter_names(&f.parameter_list);
+        if params.len() != args.len() {
+            return Err(InterpreterError::ArgumentsCountMismatch(
+                fn_name,
+                args.len(),
+            ));
+        }
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(InterpreterError::CallDepthExceeded(fn_name));
+        }
+        let locals = params.into_iter().zip(args).collect();
+        let caller_values = std::mem::replace(&mut self.let_values, locals);
+        self.call_depth += 1;
+        let res = self.fn_body_statements(&f.function_body);
+        self.call_depth -= 1;
+        self.let_values = caller_values;
+        Ok(res?.unwrap_or(Value::Unit))
+    }
+
+    /// Call function by name. Function without value returns unit.
+    pub fn function_call(&mut self, fc: &FunctionCall) -> ValueResult {
+        if fc.function_call_name.is_empty() {
+            return Ok(Value::Unit);
+        }
+        if let [module, name] = fc.function_call_name.as_slice() {
+            if *module.fragment() == "Atomic" {
+                return self.atomic_function(name.fragment(), fc);
+            }
+        }
+        let mut args = vec![];
+        for v in fc.function_value.iter() {
+            args.append(&mut self.function_value(v)?);
+        }
+        if let [module, name] = fc.function_call_name.as_slice() {
+            if *module.fragment() == "Math" {
+                return math_function(name.fragment(), args);
+            }
+        }
+        let fn_name = fc.function_call_name[0].fragment();
+        // Build-in functions, if not overridden by module
+        match self.defined_function(fn_name) {
+            Some(f) => self.call(f, args),
+            None if is_builtin_function(fn_name) => builtin_function(fn_name, args),
+            None if is_output_function(fn_name) => self.output_function(fn_name, &args),
+            None => Err(InterpreterError::FunctionNotFound(fn_name.to_string())),
+        }
+    }
+
+    pub fn fn_body_statement(
+        &mut self,
+        fbs: &FunctionBodyStatement,
+    ) -> Result<Option<Value>, InterpreterError> {
+        let res = match fbs {
+            FunctionBodyStatement::Expression(e) => Some(self.expression(e)?),
+            FunctionBodyStatement::FunctionCall(fc) => match self.function_call(fc)? {
+                Value::Unit => None,
+                v => Some(v),
+            },
+            FunctionBodyStatement::LetBinding(lb) => {
+                let value = self.fn_body_statements(&lb.function_body)?;
+                if let (Some(ParameterValueList::ParameterValue(name)), Some(value)) =
+                    (lb.value_list.first(), value)
+                {
+                    self.let_values.insert(name.fragment().to_string(), value);
+                }
+                None
+            }
+        };
+        Ok(res)
+    }
+
+    /// Sequence of function body statements.
+    /// Return: value of last statement
+    pub fn fn_body_statements(
+        &mut self,
+        ast: &FunctionBody,
+    ) -> Result<Option<Value>, InterpreterError> {
+        let mut last_body_value = None;
+        for b in ast.iter() {
+            last_body_value = self.fn_body_statement(b)?;
+        }
+        Ok(last_body_value)
+    }
+
+    /// Check module and initialize global let-values in order of
+    /// definition
+    pub fn init(&mut self) -> Result<(), InterpreterError> {
+        // Module may be preceded only by namespace
+        if Codegen::module_id(self.ast).is_none() {
+            return Err(InterpreterError::ModuleNotFound);
+        }
+        let ast = self.ast;
+        for v in ast.iter() {
+            if let MainStatement::LetBinding(l) = v {
+                self.let_values.clear();
+                let value = self.fn_body_statements(&l.function_body)?;
+                if let (Some(ParameterValueList::ParameterValue(name)), Some(value)) =
+                    (l.value_list.first(), value)
+                {
+                    self.global_values
+                        .insert(name.fragment().to_string(), value);
+                }
+            }
+        }
+        self.let_values.clear();
+        Ok(())
+    }
+
+    /// Value of global let-value
+    pub fn global_value(&self, name: &str) -> Option<&Value> {
+        self.global_values.get(name)
+    }
+
+    /// Call function of module by name
+    pub fn call_function(&mut self, fn_name: &str, args: Vec<Value>) -> ValueResult {
+        match self.defined_function(fn_name) {
+            Some(f) => self.call(f, args),
+            None => Err(InterpreterError::FunctionNotFound(fn_name.to_string())),
+        }
+    }
+
+    /// Initialize module and run entry point with program arguments,
+    /// first argument is program name. As for compiled program, `main`
+    /// gets count of arguments, if it has parameter, and its `int`
+    /// result is exit code. Unit result is 0.
+    pub fn run_main(&mut self, args: &[&str]) -> Result<i32, InterpreterError> {
+        self.init()?;
+        let f = self
+            .defined_function(ENTRY_POINT)
+            .ok_or_else(|| InterpreterError::FunctionNotFound(ENTRY_POINT.to_string()))?;
+        let args = match parameter_names(&f.parameter_list).len() {
+            0 => vec![],
+            1 => vec![Value::Int(args.len() as i32)],
+            _ => {
+                return Err(InterpreterError::Unsupported(format!(
+                    "`{}` parameters except count of arguments",
+                    ENTRY_POINT
+                )))
+            }
+        };
+        let code = match self.call(f, args)? {
+            Value::Int(v) => v,
+            Value::Unit => 0,
+            v => {
+                return Err(InterpreterError::Unsupported(format!(
+                    "`{}` result of type `{}`",
+                    ENTRY_POINT,
+                    v.type_name()
+                )))
+            }
+        };
+        self.output
+            .flush()
+            .map_err(|err| InterpreterError::Output(err.to_string()))?;
+        Ok(code)
+    }
+}
+
+/// Parse and run program with arguments, output is written to
+/// `output`
+pub fn run<W: Write>(
+    src: &str,
+    args: &[&str],
+    options: InterpreterOptions,
+    output: W,
+) -> Result<i32, InterpreterError> {
+    let (rest, ast) = crate::parser::token::main(Span::new(src))
+        .map_err(|_| InterpreterError::ParseSourceCode)?;
+    // Whole source code should be parsed
+    if !rest.fragment().trim().is_empty() {
+        return Err(InterpreterError::ParseSourceCode);
+    }
+    Interpreter::new(&ast, options, output).run_main(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::token;
+
+    /// Exit code and output of program
+    fn run_program(
+        src: &str,
+        options: InterpreterOptions,
+    ) -> Result<(i32, String), InterpreterError> {
+        let ast = token::main(Span::new(src)).unwrap().1;
+        let mut interpreter = Interpreter::new(&ast, options, vec![]);
+        let code = interpreter.run_main(&["app"])?;
+        Ok((
+            code,
+            String::from_utf8(interpreter.output().clone()).unwrap(),
+        ))
+    }
+
+    fn eval(src: &str) -> Result<i32, InterpreterError> {
+        run_program(src, InterpreterOptions::default()).map(|v| v.0)
+    }
+
+    #[test]
+    fn test_interpreter_hello() {
+        let src = std::fs::read_to_string("./examples/hello.i").unwrap();
+        let res = run_program(&src, InterpreterOptions::default());
+        // `printf` result is exit code
+        assert_eq!(res, Ok((12, "Hello world\n".to_string())));
+
+        let mut output = vec![];
+        assert_eq!(
+            run(&src, &["app"], InterpreterOptions::default(), &mut output),
+            Ok(12)
+        );
+        assert_eq!(output, b"Hello world\n");
+        // Unparsed source code is error
+        let src = "module m\nlet main () =\n    let y = f() + 2\n    y";
+        assert_eq!(
+            run(
+                src,
+                &["app"],
+                InterpreterOptions::default(),
+                std::io::sink()
+            ),
+            Err(InterpreterError::ParseSourceCode)
+        );
+    }
+
+    #[test]
+    fn test_interpreter_expressions() {
+        assert_eq!(eval("module m\nlet main () = 2 + 3 * 4 - 10 / 3"), Ok(11));
+        assert_eq!(eval("module m\nlet main () = (2 + 3) * 4"), Ok(20));
+        assert_eq!(eval("module m\nlet main () = 1 <<< 4 + 1"), Ok(32));
+        assert_eq!(eval("module m\nlet main () = 0 - 7 / 2"), Ok(-3));
+        assert_eq!(eval("module m\nlet main () = 0 - 16 >>> 2"), Ok(-4));
+        assert_eq!(eval("module m\nlet main () = 2147483647 + 1"), Ok(i32::MIN));
+        // Shift amount is modulo bit width
+        assert_eq!(eval("module m\nlet main () = 1 <<< 33"), Ok(2));
+        assert_eq!(eval("module m\nlet main () = 0 - 64 >>> 0 - 1"), Ok(-1));
+        assert_eq!(
+            eval("module m\nlet main () = int (9223372036854775807 - 1)"),
+            Err(InterpreterError::IntegerLiteralOutOfRange(
+                "9223372036854775808".to_string()
+            ))
+        );
+        assert_eq!(eval("module m\nlet main () = int (2.0 * 1.5)"), Ok(3));
+        assert_eq!(
+            eval("module m\nlet main () = 1 + 2.5"),
+            Err(InterpreterError::ExpressionTypeMismatch(
+                "int".to_string(),
+                "float".to_string()
+            ))
+        );
+        assert_eq!(
+            eval("module m\nlet main () = 1.5 <<< 1.5"),
+            Err(InterpreterError::UnsupportedOperation(
+                ExpressionOperation::ShiftLeft,
+                "float".to_string()
+            ))
+        );
+        assert_eq!(
+            eval("module m\nlet main () = 1 / 0"),
+            Err(InterpreterError::DivisionByZero)
+        );
+        assert_eq!(
+            eval("module m\nlet main () = \"a\" + \"b\""),
+            Err(InterpreterError::UnsupportedOperation(
+                ExpressionOperation::Plus,
+                "string".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_interpreter_let_values() {
+        let src = r#"module m
+let x = 20
+let y = x + 1
+let main () =
+    let z = y * 2
+    z - x
+"#;
+        assert_eq!(eval(src), Ok(22));
+        assert_eq!(
+            eval("module m\nlet main () = z"),
+            Err(InterpreterError::ValueNotFound("z".to_string()))
+        );
+        assert_eq!(
+            eval("let main () = 1"),
+            Err(InterpreterError::ModuleNotFound)
+        );
+        assert_eq!(
+            eval("let x = 1\nmodule m\nlet main () = 1"),
+            Err(InterpreterError::ModuleNotFound)
+        );
+        assert_eq!(eval("namespace App\nmodule Main\nlet main () = 2"), Ok(2));
+    }
+
+    #[test]
+    fn test_interpreter_functions() {
+        let src = r#"module m
+let add a b = a + b
+let twice (x: int) = add x x
+let show () = printfn "%d %s %.1f" (twice 21) "is" 2.5
+let main () =
+    show()
+    add 1 2
+"#;
+        assert_eq!(
+            run_program(src, InterpreterOptions::default()),
+            Ok((3, "42 is 2.5\n".to_string()))
+        );
+        assert_eq!(
+            eval("module m\nlet f a = a\nlet main () = f 1 2"),
+            Err(InterpreterError::ArgumentsCountMismatch("f".to_string(), 2))
+        );
+        assert_eq!(
+            eval("module m\nlet main () = foo 1"),
+            Err(InterpreterError::FunctionNotFound("foo".to_string()))
+        );
+        assert_eq!(
+            eval("module m\nlet f () = f()\nlet main () = f()"),
+            Err(InterpreterError::CallDepthExceeded("f".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_interpreter_builtins() {
+        assert_eq!(eval("module m\nlet main () = int (float 7 / 2.5)"), Ok(2));
+        assert_eq!(eval("module m\nlet main () = int (byte 300)"), Ok(44));
+        assert_eq!(eval("module m\nlet main () = (int true) + 1"), Ok(2));
+        assert_eq!(
+            eval("module m\nlet main () = (int (Math.pow 2 10)) + Math.abs (0 - 5)"),
+            Ok(1029)
+        );
+        assert_eq!(eval("module m\nlet main () = Math.popcount 255"), Ok(8));
+        assert_eq!(
+            eval("module m\nlet main () = lane (int4 1 2 3 4 * int4 2) 3"),
+            Ok(8)
+        );
+        assert_eq!(
+            eval("module m\nlet main () = int (lane (Math.sqrt (float4 16)) 0)"),
+            Ok(4)
+        );
+        let src = r#"module m
+let counter = 0
+let main () =
+    let a = (Atomic.increment counter)
+    let b = (Atomic.compareExchange counter a 10)
+    (Atomic.increment counter) + b
+"#;
+        assert_eq!(eval(src), Ok(12));
+        assert_eq!(
+            eval("module m\nlet main () = int \"1\""),
+            Err(InterpreterError::UnsupportedConversion(
+                "string".to_string(),
+                "int".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_interpreter_entry_point() {
+        assert_eq!(eval("module m\nlet main () =\n    let x = 1"), Ok(0));
+        let src = "module m\nlet main args = args * 10";
+        let res = run(
+            src,
+            &["app", "a"],
+            InterpreterOptions::default(),
+            std::io::sink(),
+        );
+        assert_eq!(res, Ok(20));
+        let err = eval("module m\nlet main () = 1.5").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`main` result of type `float` isn't supported"
+        );
+        assert!(eval("module m\nlet main () = true").is_err());
+        assert!(eval("module m\nlet main a b = 1").is_err());
+        assert_eq!(
+            eval("module m\nlet f () = 1"),
+            Err(InterpreterError::FunctionNotFound("main".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_interpreter_overflow_checks() {
+        let options = InterpreterOptions {
+            overflow_checks: true,
+        };
+        let src = "module m\nlet main () = 2147483647 * 2";
+        assert_eq!(
+            run_program(src, options),
+            Err(InterpreterError::IntegerOverflow)
+        );
+        assert_eq!(eval(src), Ok(-2));
+    }
+
+    #[test]
+    fn test_interpreter_error_display() {
+        let err = eval("module m\nlet f () = f()\nlet main () = f()").unwrap_err();
+        assert_eq!(err.to_string(), "call depth exceeded in function `f`");
+        assert_eq!(
+            InterpreterError::ExpressionTypeMismatch("int".to_string(), "float".to_string())
+                .to_string(),
+            "type mismatch in expression: `int` and `float`"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! # Values
+//!
+//! Runtime values of interpreter. Types of values follow types of
+//! generated code: `int` is `i32`, `float` is `double`, `byte` is
+//! `i8`, integer literals out of `int` range are `i64`.
+
+/// Interpreter value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Result of function without value
+    Unit,
+    Bool(bool),
+    Byte(u8),
+    Int(i32),
+    Int64(i64),
+    Float(f64),
+    String(String),
+    /// SIMD vector, all lanes have same type
+    Vector(Vec<Value>),
+}
+
+impl Value {
+    /// Source code name of value type
+    pub fn type_name(&self) -> String {
+        match self {
+            Value::Unit => "unit".to_string(),
+            Value::Bool(_) => "bool".to_string(),
+            Value::Byte(_) => "byte".to_string(),
+            Value::Int(_) => "int".to_string(),
+            Value::Int64(_) => "int64".to_string(),
+            Value::Float(_) => "float".to_string(),
+            Value::String(_) => "string".to_string(),
+            Value::Vector(v) => {
+                let element = v.first().map_or("unit".to_string(), Value::type_name);
+                format!("{}{}", element, v.len())
+            }
+        }
+    }
+
+    /// Is type of values same. Vectors should have same lanes count
+    /// and type.
+    pub fn same_type(&self, other: &Value) -> bool {
+        self.type_name() == other.type_name()
+    }
+
+    /// Integer types, except boolean
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Value::Byte(_) | Value::Int(_) | Value::Int64(_))
+    }
+
+    /// Numeric value as `f64`. Integers are signed, except booleans
+    /// and bytes.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Bool(v) => Some(f64::from(u8::from(*v))),
+            Value::Byte(v) => Some(f64::from(*v)),
+            Value::Int(v) => Some(f64::from(*v)),
+            Value::Int64(v) => Some(*v as f64),
+            Value::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Integer value as `i64`. Booleans and bytes are unsigned.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Bool(v) => Some(i64::from(*v)),
+            Value::Byte(v) => Some(i64::from(*v)),
+            Value::Int(v) => Some(i64::from(*v)),
+            Value::Int64(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::Byte(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Int64(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{:?}", v),
+            Value::String(v) => write!(f, "{}", v),
+            Value::Vector(v) => {
+                let lanes = v.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                write!(f, "<{}>", lanes.join(", "))
+            }
+        }
+    }
+}
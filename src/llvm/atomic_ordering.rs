@@ -0,0 +1,48 @@
+//! # Atomic Memory Ordering Constraints
+//!
+//! Atomic instructions (cmpxchg, atomicrmw, fence, atomic load, and
+//! atomic store) take ordering parameters that determine which other
+//! atomic instructions on the same address they synchronize with.
+//!
+//! https://llvm.org/docs/LangRef.html#ordering
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum AtomicOrdering {
+    Unordered,
+    Monotonic,
+    Acquire,
+    Release,
+    AcqRel,
+    SeqCst,
+}
+
+/// Synchronization scope. If an atomic operation is marked
+/// `syncscope("singlethread")`, it only synchronizes with and only
+/// participates in the seq_cst total orderings of other operations
+/// running in the same thread. Otherwise, target specific scope
+/// is used. Default scope (system) is omitted.
+///
+/// https://llvm.org/docs/LangRef.html#syncscope
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SyncScope(pub String);
+
+impl std::fmt::Display for AtomicOrdering {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            AtomicOrdering::Unordered => "unordered",
+            AtomicOrdering::Monotonic => "monotonic",
+            AtomicOrdering::Acquire => "acquire",
+            AtomicOrdering::Release => "release",
+            AtomicOrdering::AcqRel => "acq_rel",
+            AtomicOrdering::SeqCst => "seq_cst",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for SyncScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "syncscope(\"{}\")", self.0)
+    }
+}
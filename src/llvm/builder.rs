@@ -0,0 +1,1567 @@
+//! # IR builder
+//!
+//! Typed builder for LLVM IR: `ModuleBuilder` collects globals,
+//! declarations and functions, `FunctionBuilder` owns basic blocks
+//! and SSA naming context of function, `BasicBlockBuilder` appends
+//! instructions to basic block.
+//!
+//! Every value produced by builder carries its `Type`. Operand types
+//! are checked when instruction is built, so type mismatches are
+//! reported as `BuildError` instead of producing malformed IR.
+//! `ModuleBuilder::build` returns `ir::Module`, which is printed as
+//! text IR.
+//!
+//! ```ignore
+//! let mut module = ModuleBuilder::new("app");
+//! let mut f = FunctionBuilder::new(def!(Integer32 main));
+//! let mut bb = f.block();
+//! let one = Value::new(Type::Integer32, "1");
+//! let res = bb.add(&one, &one)?;
+//! bb.ret(Some(&res))?;
+//! module.function(f)?;
+//! let ir = module.build().to_string();
+//! ```
+
+use crate::llvm::{
+    atomic_ordering::AtomicOrdering,
+    attribute_groups::Attributes,
+    context::Context,
+    data_layout::DataLayout,
+    functions::{
+        ArgumentList,
+        Function,
+        FunctionDefinitionType,
+    },
+    global_variables::{
+        GlobalVariable,
+        UnnamedAddr::UnnamedAddr,
+    },
+    instructions::{
+        aggregate_operations::{
+            Extractvalue,
+            Insertvalue,
+        },
+        binary_operations::*,
+        bitwise_binary_operations::*,
+        memory_access_addressing_operations::*,
+        other_operations::{
+            Call,
+            Fcmp,
+            FcmpCondition,
+            Icmp,
+            IcmpCondition,
+            Phi,
+            Select,
+        },
+        terminator::{
+            Br,
+            FunctionArg,
+            Ret,
+            Unreachable,
+        },
+        unary_operations::FNeg,
+        vector_operations::{
+            Extractelement,
+            Insertelement,
+            Shufflevector,
+        },
+    },
+    intrinsics::Intrinsic,
+    ir::{
+        self,
+        BasicBlock,
+        Module,
+    },
+    linkage_types::LinkageTypes,
+    metadata::{
+        AttachedInstruction,
+        Attachment,
+        Metadata,
+        MetadataKind,
+        MetadataNode,
+        MetadataRef,
+        ModuleFlagBehavior,
+    },
+    source_filename::SourceFileName,
+    target_triple::TargetTriple,
+    type_system::{
+        aggregate::{
+            ArrayType,
+            StructureType,
+            TypeDefinition,
+        },
+        single_value::print_with_pointers,
+        FunctionType,
+    },
+    types::Type,
+    InstructionSet,
+};
+
+/// Typed value: constant, SSA value or global value name
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Value {
+    pub ty: Type,
+    pub value: String,
+}
+
+impl Value {
+    pub fn new(ty: Type, value: &str) -> Self {
+        Self {
+            ty,
+            value: value.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.ty, self.value)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BuildError {
+    /// Expected and actual types
+    TypeMismatch(Type, Type),
+    UnsupportedType(Type),
+    InvalidConversion(Type, Type),
+    InvalidIndex(Type, u64),
+    /// Function name and count of arguments
+    ArgumentsMismatch(String, usize),
+    /// Block label
+    BlockTerminated(String),
+    /// Function name and block label
+    MissingTerminator(String, String),
+    /// Block label
+    EmptyBlock(String),
+    /// Block label
+    UnknownBlock(String),
+    /// Name of identified structure type
+    UnknownType(String),
+    /// Name of identified structure type
+    TypeRedefinition(String),
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BuildError::TypeMismatch(expected, actual) => {
+                write!(f, "expected type {}, found {}", expected, actual)
+            }
+            BuildError::UnsupportedType(ty) => write!(f, "unsupported type {}", ty),
+            BuildError::InvalidConversion(from, to) => {
+                write!(f, "invalid conversion from {} to {}", from, to)
+            }
+            BuildError::InvalidIndex(ty, idx) => write!(f, "invalid index {} of type {}", idx, ty),
+            BuildError::ArgumentsMismatch(name, count) => {
+                write!(f, "function @{} called with {} arguments", name, count)
+            }
+            BuildError::BlockTerminated(v) => write!(f, "block %{} is already terminated", v),
+            BuildError::MissingTerminator(name, v) => {
+                write!(f, "block %{} of function @{} has no terminator", v, name)
+            }
+            BuildError::EmptyBlock(v) => write!(f, "block %{} is empty", v),
+            BuildError::UnknownBlock(v) => write!(f, "unknown block %{}", v),
+            BuildError::UnknownType(v) => write!(f, "unknown type %{}", v),
+            BuildError::TypeRedefinition(v) => write!(f, "redefinition of type %{}", v),
+        }
+    }
+}
+
+pub type BuildResult<T> = std::result::Result<T, BuildError>;
+
+/// Function signature, used for calls
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FunctionRef {
+    pub name: String,
+    pub ty: FunctionType,
+}
+
+impl From<&Function> for FunctionRef {
+    fn from(f: &Function) -> Self {
+        Self {
+            name: f.function_name.clone(),
+            ty: FunctionType {
+                return_type: Box::new(f.result_type.clone()),
+                parameter_list: f
+                    .argument_list
+                    .iter()
+                    .filter_map(|a| a.parameter_type.clone())
+                    .collect(),
+                variable_argument: f.argument_list.iter().any(|a| a.variable_argument),
+            },
+        }
+    }
+}
+
+/// Module builder. Functions, globals and declarations are kept
+/// in order of adding.
+pub struct ModuleBuilder {
+    module: Module,
+    /// Unique global names
+    names: Context,
+    global_ctors: Vec<(u32, FunctionRef)>,
+}
+
+impl ModuleBuilder {
+    pub fn new(module_id: &str) -> Self {
+        Self {
+            module: Module::new(module_id),
+            names: Context::new(),
+            global_ctors: vec![],
+        }
+    }
+
+    pub fn source_filename(&mut self, name: &str) {
+        self.module.source_filename = Some(SourceFileName(name.to_string()));
+    }
+
+    pub fn target_triple(&mut self, triple: TargetTriple) {
+        self.module.target_triple = Some(triple);
+    }
+
+    /// Data layout of target, it should match target triple
+    pub fn data_layout(&mut self, layout: DataLayout) {
+        self.module.data_layout = Some(layout);
+    }
+
+    /// Print pointers with pointee type: `i8*` instead of `ptr`, for
+    /// LLVM versions prior to 15
+    pub fn typed_pointers(&mut self, enabled: bool) {
+        self.module.typed_pointers = enabled;
+    }
+
+    /// Identified structure type `%name`. Type is opaque until its
+    /// body is set, so it can be referred in its own body.
+    pub fn struct_type(&mut self, name: &str) -> Type {
+        if self.module.type_definition(name).is_none() {
+            self.module.types.push(TypeDefinition {
+                name: name.to_string(),
+                body: None,
+            });
+        }
+        Type::Identified(name.to_string())
+    }
+
+    /// Set body of opaque identified structure type
+    pub fn set_struct_body(
+        &mut self,
+        name: &str,
+        type_list: Vec<Type>,
+        packed: bool,
+    ) -> BuildResult<()> {
+        let t = match self.module.types.iter_mut().find(|t| t.name == name) {
+            Some(t) => t,
+            None => return Err(BuildError::UnknownType(name.to_string())),
+        };
+        if t.body.is_some() {
+            return Err(BuildError::TypeRedefinition(name.to_string()));
+        }
+        t.body = Some(StructureType {
+            literal: false,
+            packed,
+            type_list,
+        });
+        Ok(())
+    }
+
+    /// Identified structure types of module
+    pub fn types(&self) -> &[TypeDefinition] {
+        &self.module.types
+    }
+
+    /// Add global variable. Global name is made unique for module.
+    /// Return pointer to global value.
+    pub fn global(&mut self, mut g: GlobalVariable) -> Value {
+        g.name = self.names.unique_name(&g.name);
+        let value = Value {
+            ty: Type::pointer1(g.value_type.clone()),
+            value: g.get_value_name().unwrap(),
+        };
+        self.module.globals.push(g);
+        value
+    }
+
+    /// Private string constant with zero terminator. Return pointer
+    /// to array of characters.
+    pub fn string_constant(&mut self, s: &str) -> Value {
+        let ty = Type::Array(ArrayType((s.len() + 1) as i32, Box::new(Type::Integer8)));
+        let mut g = global!(Constant ty ".str");
+        global!(g.linkage @LinkageTypes::Private);
+        global!(g.unnamed_addr @UnnamedAddr);
+        global!(g.initializer_constant @Type::raw_string(s));
+        self.global(g)
+    }
+
+    /// Declare external function once
+    pub fn declare(&mut self, mut f: Function) -> FunctionRef {
+        f.definition_type = FunctionDefinitionType::Declare;
+        let res = FunctionRef::from(&f);
+        if !self
+            .module
+            .declarations
+            .iter()
+            .any(|d| d.function_name == f.function_name)
+        {
+            self.module.declarations.push(f);
+        }
+        res
+    }
+
+    /// Declare intrinsic function once
+    pub fn intrinsic(&mut self, intrinsic: &Intrinsic) -> BuildResult<FunctionRef> {
+        Ok(self.declare(intrinsic.declaration(self.module.typed_pointers)?))
+    }
+
+    /// Add function. All basic blocks of function must be terminated.
+    pub fn function(&mut self, f: FunctionBuilder) -> BuildResult<FunctionRef> {
+        let f = f.function;
+        if let Some(bb) = f.blocks.iter().find(|bb| bb.terminator.is_none()) {
+            return Err(BuildError::MissingTerminator(
+                f.name().to_string(),
+                bb.label.clone(),
+            ));
+        }
+        let res = FunctionRef::from(&f.definition);
+        self.module.functions.push(f);
+        Ok(res)
+    }
+
+    /// Call `void ()` function on module initialization with
+    /// priority (`llvm.global_ctors`)
+    pub fn global_ctor(&mut self, priority: u32, f: &FunctionRef) -> BuildResult<()> {
+        let expected = FunctionType {
+            return_type: Box::new(Type::Void),
+            parameter_list: vec![],
+            variable_argument: false,
+        };
+        if !f.ty.is_compatible(&expected) {
+            return Err(BuildError::TypeMismatch(
+                Type::Function(expected),
+                Type::Function(f.ty.clone()),
+            ));
+        }
+        self.global_ctors.push((priority, f.clone()));
+        Ok(())
+    }
+
+    pub fn attribute_group(&mut self, attrs: Attributes) {
+        self.module.attribute_groups.push(attrs);
+    }
+
+    /// Add metadata node. Equal nodes are merged, unless they are
+    /// distinct.
+    pub fn metadata(&mut self, node: MetadataNode) -> MetadataRef {
+        self.module.metadata_node(node)
+    }
+
+    /// Replace metadata node. Used to complete distinct node, which
+    /// is referred before all its fields are known.
+    pub fn replace_metadata(&mut self, node_ref: MetadataRef, node: MetadataNode) {
+        self.module.metadata[node_ref.0] = node;
+    }
+
+    /// Add node to named metadata
+    pub fn named_metadata(&mut self, name: &str, node: MetadataRef) {
+        self.module.named_metadata(name, node)
+    }
+
+    /// Add module flag to `!llvm.module.flags`
+    pub fn module_flag(
+        &mut self,
+        behavior: ModuleFlagBehavior,
+        name: &str,
+        value: Metadata,
+    ) -> MetadataRef {
+        self.module.module_flag(behavior, name, value)
+    }
+
+    /// `llvm.global_ctors` array of constructors
+    fn global_ctors_variable(&self) -> GlobalVariable {
+        let ctor_ty = Type::pointer1(Type::Function(FunctionType {
+            return_type: Box::new(Type::Void),
+            parameter_list: vec![],
+            variable_argument: false,
+        }));
+        let data_ty = Type::pointer1(Type::Integer8);
+        let ty = Type::Structure(StructureType {
+            literal: true,
+            packed: false,
+            type_list: vec![Type::Integer32, ctor_ty.clone(), data_ty.clone()],
+        });
+        // Initializer is text, so types are printed with pointer mode
+        // of module
+        let ctors = print_with_pointers(self.module.typed_pointers, || {
+            self.global_ctors
+                .iter()
+                .enumerate()
+                .fold("".to_string(), |s, (i, (priority, f))| {
+                    let ctor = format!(
+                        "{} {{ i32 {}, {} @{}, {} null }}",
+                        ty, priority, ctor_ty, f.name, data_ty
+                    );
+                    if i > 0 {
+                        format!("{}, {}", s, ctor)
+                    } else {
+                        ctor
+                    }
+                })
+        });
+        let ty = Type::Array(ArrayType(self.global_ctors.len() as i32, Box::new(ty)));
+        let mut g = global!(Global ty "llvm.global_ctors");
+        global!(g.linkage @LinkageTypes::Appending);
+        global!(g.initializer_constant @format!("[{}]", ctors));
+        g
+    }
+
+    /// Finish module
+    pub fn build(mut self) -> Module {
+        if !self.global_ctors.is_empty() {
+            let g = self.global_ctors_variable();
+            self.module.globals.push(g);
+        }
+        self.module
+    }
+}
+
+/// Function builder. Arguments without names get SSA numbers, the
+/// entry block is created implicitly and takes next number.
+pub struct FunctionBuilder {
+    function: ir::Function,
+    ctx: Context,
+    current: usize,
+    /// Attached as `!dbg` to every appended instruction
+    debug_location: Option<MetadataRef>,
+    /// Identified structure types, to resolve types of elements
+    types: Vec<TypeDefinition>,
+}
+
+impl FunctionBuilder {
+    pub fn new(mut definition: Function) -> Self {
+        let mut ctx = Context::new();
+        definition.definition_type = FunctionDefinitionType::Define;
+        for arg in definition.argument_list.iter_mut() {
+            if arg.variable_argument {
+                continue;
+            }
+            arg.name = Some(match &arg.name {
+                Some(name) => ctx.named_value(name.trim_start_matches('%')),
+                None => ctx.value(),
+            });
+        }
+        let mut function = ir::Function::new(definition);
+        function.blocks.push(BasicBlock::new(&ctx.label()));
+        Self {
+            function,
+            ctx,
+            current: 0,
+            debug_location: None,
+            types: vec![],
+        }
+    }
+
+    /// Identified structure types, used by `getelementptr`,
+    /// `extractvalue` and `insertvalue`
+    pub fn set_types(&mut self, types: &[TypeDefinition]) {
+        self.types = types.to_vec();
+    }
+
+    /// Function arguments as values
+    pub fn params(&self) -> Vec<Value> {
+        self.function
+            .definition
+            .argument_list
+            .iter()
+            .filter_map(|a| match (&a.parameter_type, &a.name) {
+                (Some(ty), Some(name)) => Some(Value::new(ty.clone(), name)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Change result type of function. Used when result type is
+    /// known only after body is built.
+    pub fn set_result_type(&mut self, ty: Type) {
+        self.function.definition.result_type = ty;
+    }
+
+    /// Attach metadata to function definition
+    pub fn attach(&mut self, kind: MetadataKind, node: MetadataRef) {
+        self.function
+            .definition
+            .metadata
+            .push(Attachment(kind, node));
+    }
+
+    /// Builder for current basic block
+    pub fn block(&mut self) -> BasicBlockBuilder<'_> {
+        BasicBlockBuilder { func: self }
+    }
+
+    /// Append new named basic block and make it current. Label is
+    /// made unique for function.
+    pub fn append_block(&mut self, name: &str) -> BasicBlockBuilder<'_> {
+        let label = self.ctx.named_label(name);
+        self.function.blocks.push(BasicBlock::new(&label));
+        self.current = self.function.blocks.len() - 1;
+        self.block()
+    }
+}
+
+/// Builder of basic block. Appends instructions to current block
+/// of function.
+pub struct BasicBlockBuilder<'f> {
+    func: &'f mut FunctionBuilder,
+}
+
+/// Check that operands have compatible types and return type of
+/// left operand
+fn same_type(lhs: &Value, rhs: &Value) -> BuildResult<Type> {
+    if !lhs.ty.is_compatible(&rhs.ty) {
+        return Err(BuildError::TypeMismatch(lhs.ty.clone(), rhs.ty.clone()));
+    }
+    Ok(lhs.ty.clone())
+}
+
+/// Type of integer operation: integer or vector of integers
+fn integer_type(lhs: &Value, rhs: &Value) -> BuildResult<Type> {
+    let ty = same_type(lhs, rhs)?;
+    if ty.scalar_type().integer_width().is_none() {
+        return Err(BuildError::UnsupportedType(ty));
+    }
+    Ok(ty)
+}
+
+/// Type of floating point operation: floating point or vector of
+/// floating point values
+fn floating_point_type(lhs: &Value, rhs: &Value) -> BuildResult<Type> {
+    let ty = same_type(lhs, rhs)?;
+    if !ty.scalar_type().is_floating_point() {
+        return Err(BuildError::UnsupportedType(ty));
+    }
+    Ok(ty)
+}
+
+/// Pointee type of pointer
+fn pointee_type(ptr: &Value) -> BuildResult<Type> {
+    ptr.ty
+        .pointee_type()
+        .cloned()
+        .ok_or_else(|| BuildError::UnsupportedType(ptr.ty.clone()))
+}
+
+/// Check that value type is compatible with expected type
+fn expect_type(expected: &Type, value: &Value) -> BuildResult<()> {
+    if !expected.is_compatible(&value.ty) {
+        return Err(BuildError::TypeMismatch(expected.clone(), value.ty.clone()));
+    }
+    Ok(())
+}
+
+impl<'f> BasicBlockBuilder<'f> {
+    /// Label of current block
+    pub fn label(&self) -> String {
+        self.func.function.blocks[self.func.current].label.clone()
+    }
+
+    /// Append new named basic block to function. Current block is
+    /// not changed. Return unique label of new block.
+    pub fn create_block(&mut self, name: &str) -> String {
+        let label = self.func.ctx.named_label(name);
+        self.func.function.blocks.push(BasicBlock::new(&label));
+        label
+    }
+
+    /// Continue appending instructions to end of block with label
+    pub fn position_at_end(&mut self, label: &str) -> BuildResult<()> {
+        match self
+            .func
+            .function
+            .blocks
+            .iter()
+            .position(|bb| bb.label == label)
+        {
+            Some(i) => {
+                self.func.current = i;
+                Ok(())
+            }
+            None => Err(BuildError::UnknownBlock(label.to_string())),
+        }
+    }
+
+    /// Set source location (`DILocation` node) of instructions,
+    /// appended to function after this call. `None` clears location.
+    pub fn set_debug_location(&mut self, location: Option<MetadataRef>) {
+        self.func.debug_location = location;
+    }
+
+    /// Current source location of appended instructions
+    pub fn debug_location(&self) -> Option<MetadataRef> {
+        self.func.debug_location
+    }
+
+    /// Append instruction. Unnamed result gets next SSA value.
+    /// Terminator instruction closes basic block. Instruction gets
+    /// current debug location.
+    fn push<T: InstructionSet + 'static>(&mut self, instr: T) -> BuildResult<Option<Value>> {
+        self.push_typed(instr, None)
+    }
+
+    /// Append instruction with result type, which is resolved by
+    /// builder. Instruction itself can't resolve element types of
+    /// identified structure types.
+    fn push_typed<T: InstructionSet + 'static>(
+        &mut self,
+        mut instr: T,
+        ty: Option<Type>,
+    ) -> BuildResult<Option<Value>> {
+        let bb = &mut self.func.function.blocks[self.func.current];
+        if bb.terminator.is_some() {
+            return Err(BuildError::BlockTerminated(bb.label.clone()));
+        }
+        instr.set_context(&mut self.func.ctx);
+        let value = match (ty.or_else(|| instr.get_type()), instr.get_value()) {
+            (Some(ty), Some(value)) if instr.is_assignment() => Some(Value { ty, value }),
+            _ => None,
+        };
+        let is_terminator = instr.is_terminator();
+        let instr: Box<dyn InstructionSet> = match self.func.debug_location {
+            Some(location) => Box::new(AttachedInstruction {
+                instruction: Box::new(instr),
+                attachments: vec![Attachment(MetadataKind::Dbg, location)],
+            }),
+            None => Box::new(instr),
+        };
+        if is_terminator {
+            bb.terminator = Some(instr);
+        } else {
+            bb.instructions.push(instr);
+        }
+        Ok(value)
+    }
+
+    /// Attach metadata to last appended instruction of block
+    pub fn attach(&mut self, kind: MetadataKind, node: MetadataRef) -> BuildResult<()> {
+        let bb = &mut self.func.function.blocks[self.func.current];
+        let label = bb.label.clone();
+        let last = match bb.terminator.as_mut() {
+            Some(t) => t,
+            None => bb
+                .instructions
+                .last_mut()
+                .ok_or(BuildError::EmptyBlock(label))?,
+        };
+        let instruction = std::mem::replace(last, Box::new(Unreachable()));
+        *last = Box::new(AttachedInstruction {
+            instruction,
+            attachments: vec![Attachment(kind, node)],
+        });
+        Ok(())
+    }
+
+    /// Append instruction with result value
+    fn push_value<T: InstructionSet + 'static>(&mut self, instr: T) -> BuildResult<Value> {
+        Ok(self.push(instr)?.unwrap())
+    }
+
+    /// Append terminator instruction
+    fn push_terminator<T: InstructionSet + 'static>(&mut self, instr: T) -> BuildResult<()> {
+        self.push(instr)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(Add {
+            result: String::new(),
+            nuw: None,
+            nsw: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn sub(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(Sub {
+            result: String::new(),
+            nuw: None,
+            nsw: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn mul(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(Mul {
+            result: String::new(),
+            nuw: None,
+            nsw: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn udiv(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(UDiv {
+            result: String::new(),
+            exact: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn sdiv(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(SDiv {
+            result: String::new(),
+            exact: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn urem(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(URem {
+            result: String::new(),
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn srem(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(SRem {
+            result: String::new(),
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn shl(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(Shl {
+            result: String::new(),
+            nuw: None,
+            nsw: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn lshr(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(LShl {
+            result: String::new(),
+            exact: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn ashr(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(AShr {
+            result: String::new(),
+            exact: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn and(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(And {
+            result: String::new(),
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn or(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(Or {
+            result: String::new(),
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn xor(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = integer_type(lhs, rhs)?;
+        self.push_value(Xor {
+            result: String::new(),
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn fadd(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = floating_point_type(lhs, rhs)?;
+        self.push_value(FAdd {
+            result: String::new(),
+            fast_math_flags: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn fsub(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = floating_point_type(lhs, rhs)?;
+        self.push_value(FSub {
+            result: String::new(),
+            fast_math_flags: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn fmul(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = floating_point_type(lhs, rhs)?;
+        self.push_value(FMul {
+            result: String::new(),
+            fast_math_flags: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn fdiv(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = floating_point_type(lhs, rhs)?;
+        self.push_value(FDiv {
+            result: String::new(),
+            fast_math_flags: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn frem(&mut self, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = floating_point_type(lhs, rhs)?;
+        self.push_value(FRem {
+            result: String::new(),
+            fast_math_flags: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn fneg(&mut self, value: &Value) -> BuildResult<Value> {
+        let ty = floating_point_type(value, value)?;
+        self.push_value(FNeg {
+            result: String::new(),
+            fast_math_flags: vec![],
+            ty,
+            operand: value.value.clone(),
+        })
+    }
+
+    pub fn icmp(&mut self, cond: IcmpCondition, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = same_type(lhs, rhs)?;
+        if ty.scalar_type().integer_width().is_none() && !matches!(ty, Type::Pointer(_)) {
+            return Err(BuildError::UnsupportedType(ty));
+        }
+        self.push_value(Icmp {
+            res_val: String::new(),
+            cond,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    pub fn fcmp(&mut self, cond: FcmpCondition, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        let ty = floating_point_type(lhs, rhs)?;
+        self.push_value(Fcmp {
+            res_val: String::new(),
+            cond,
+            fast_math_flags: None,
+            ty,
+            op1: lhs.value.clone(),
+            op2: rhs.value.clone(),
+        })
+    }
+
+    /// Conversion instruction, selected by source and destination
+    /// types: integers are extended with sign, if `signed` is set.
+    pub fn cast(&mut self, value: &Value, ty: &Type, signed: bool) -> BuildResult<Value> {
+        let from = value.ty.clone();
+        let (ty, val) = (ty.clone(), value.value.clone());
+        if from.is_compatible(&ty) {
+            return Ok(value.clone());
+        }
+        let (s1, s2) = (from.scalar_type(), ty.scalar_type());
+        // Vectors converted only to vectors
+        if (s1 == &from) != (s2 == &ty) {
+            return Err(BuildError::InvalidConversion(from.clone(), ty.clone()));
+        }
+        let is_pointer = |t: &Type| matches!(t, Type::Pointer(_));
+        match (
+            s1.integer_width(),
+            s1.floating_point_width(),
+            s2.integer_width(),
+            s2.floating_point_width(),
+        ) {
+            (Some(w1), _, Some(w2), _) if w1 > w2 => {
+                self.push_value(conversion!(Trunc from val => ty))
+            }
+            (Some(_), _, Some(_), _) if signed => self.push_value(conversion!(SExt from val => ty)),
+            (Some(_), _, Some(_), _) => self.push_value(conversion!(ZExt from val => ty)),
+            (Some(_), _, _, Some(_)) if signed => {
+                self.push_value(conversion!(SIToFP from val => ty))
+            }
+            (Some(_), _, _, Some(_)) => self.push_value(conversion!(UIToFP from val => ty)),
+            (_, Some(_), Some(_), _) if signed => {
+                self.push_value(conversion!(FPToSI from val => ty))
+            }
+            (_, Some(_), Some(_), _) => self.push_value(conversion!(FPToUI from val => ty)),
+            (_, Some(w1), _, Some(w2)) if w1 > w2 => {
+                self.push_value(conversion!(FPTrunc from val => ty))
+            }
+            (_, Some(w1), _, Some(w2)) if w1 < w2 => {
+                self.push_value(conversion!(FPExt from val => ty))
+            }
+            (Some(_), _, _, _) if is_pointer(s2) => {
+                self.push_value(conversion!(IntToPtr from val => ty))
+            }
+            (_, _, Some(_), _) if is_pointer(s1) => {
+                self.push_value(conversion!(PtrToInt from val => ty))
+            }
+            _ if is_pointer(s1) && is_pointer(s2) => {
+                self.push_value(conversion!(BitCast from val => ty))
+            }
+            _ => Err(BuildError::InvalidConversion(from.clone(), ty.clone())),
+        }
+    }
+
+    pub fn alloca(&mut self, ty: &Type) -> BuildResult<Value> {
+        let ty = ty.clone();
+        self.push_value(alloca!(ty ""))
+    }
+
+    pub fn load(&mut self, ptr: &Value) -> BuildResult<Value> {
+        let ty = pointee_type(ptr)?;
+        self.push_value(load!(ty "", ptr.value))
+    }
+
+    pub fn store(&mut self, value: &Value, ptr: &Value) -> BuildResult<()> {
+        let ty = pointee_type(ptr)?;
+        expect_type(&ty, value)?;
+        self.push(store!(ty value.value, ptr.value))?;
+        Ok(())
+    }
+
+    /// `getelementptr inbounds` with constant `i64` indices. First
+    /// index addresses pointer itself.
+    pub fn getelementptr(&mut self, ptr: &Value, indices: &[u64]) -> BuildResult<Value> {
+        let ty = pointee_type(ptr)?;
+        let types = &self.func.types;
+        let element_ty = indices.iter().skip(1).try_fold(ty.clone(), |t, idx| {
+            t.indexed_type_in(*idx, types)
+                .ok_or_else(|| BuildError::InvalidIndex(t.clone(), *idx))
+        })?;
+        let instr = GetElementPtr {
+            result: String::new(),
+            inbounds: Some(()),
+            ty: ty.clone(),
+            ty_pointer: ty,
+            ptr_val: ptr.value.clone(),
+            range_val: indices
+                .iter()
+                .map(|i| (None, Type::Integer64, *i))
+                .collect(),
+        };
+        Ok(self
+            .push_typed(instr, Some(Type::pointer1(element_ty)))?
+            .unwrap())
+    }
+
+    pub fn atomicrmw(
+        &mut self,
+        operation: AtomicRMWOperation,
+        ptr: &Value,
+        value: &Value,
+        ordering: AtomicOrdering,
+    ) -> BuildResult<Value> {
+        let ty = pointee_type(ptr)?;
+        expect_type(&ty, value)?;
+        self.push_value(AtomicRMW {
+            result: String::new(),
+            volatile: None,
+            operation,
+            ty,
+            pointer: ptr.value.clone(),
+            value: value.value.clone(),
+            syncscope: None,
+            ordering,
+            align: None,
+        })
+    }
+
+    /// Compare and exchange. Result is `{ ty, i1 }` structure of
+    /// original value and success flag.
+    pub fn cmpxchg(
+        &mut self,
+        ptr: &Value,
+        cmp: &Value,
+        new: &Value,
+        success_ordering: AtomicOrdering,
+        failure_ordering: AtomicOrdering,
+    ) -> BuildResult<Value> {
+        let ty = pointee_type(ptr)?;
+        expect_type(&ty, cmp)?;
+        expect_type(&ty, new)?;
+        self.push_value(Cmpxchg {
+            result: String::new(),
+            weak: None,
+            volatile: None,
+            ty,
+            pointer: ptr.value.clone(),
+            cmp: cmp.value.clone(),
+            new: new.value.clone(),
+            syncscope: None,
+            success_ordering,
+            failure_ordering,
+            align: None,
+        })
+    }
+
+    pub fn fence(&mut self, ordering: AtomicOrdering) -> BuildResult<()> {
+        self.push(Fence {
+            syncscope: None,
+            ordering,
+        })?;
+        Ok(())
+    }
+
+    pub fn extractvalue(&mut self, aggregate: &Value, idx: &[u64]) -> BuildResult<Value> {
+        let types = &self.func.types;
+        let ty = idx.iter().try_fold(aggregate.ty.clone(), |t, i| {
+            t.indexed_type_in(*i, types)
+                .ok_or_else(|| BuildError::InvalidIndex(t.clone(), *i))
+        })?;
+        let instr = Extractvalue {
+            result: String::new(),
+            aggregate_type: aggregate.ty.clone(),
+            val: aggregate.value.clone(),
+            idx: idx.to_vec(),
+        };
+        Ok(self.push_typed(instr, Some(ty))?.unwrap())
+    }
+
+    pub fn insertvalue(
+        &mut self,
+        aggregate: &Value,
+        elt: &Value,
+        idx: &[u64],
+    ) -> BuildResult<Value> {
+        let types = &self.func.types;
+        let ty = idx.iter().try_fold(aggregate.ty.clone(), |t, i| {
+            t.indexed_type_in(*i, types)
+                .ok_or_else(|| BuildError::InvalidIndex(t.clone(), *i))
+        })?;
+        expect_type(&ty, elt)?;
+        self.push_value(Insertvalue {
+            result: String::new(),
+            aggregate_type: aggregate.ty.clone(),
+            val: aggregate.value.clone(),
+            ty,
+            elt: elt.value.clone(),
+            idx: idx.to_vec(),
+        })
+    }
+
+    pub fn extractelement(&mut self, vector: &Value, idx: &Value) -> BuildResult<Value> {
+        let ty = match &vector.ty {
+            Type::Vector(ty) => ty.clone(),
+            ty => return Err(BuildError::UnsupportedType(ty.clone())),
+        };
+        if idx.ty.integer_width().is_none() {
+            return Err(BuildError::UnsupportedType(idx.ty.clone()));
+        }
+        self.push_value(Extractelement {
+            result: String::new(),
+            ty,
+            val: vector.value.clone(),
+            ty2: idx.ty.clone(),
+            idx: idx.value.clone(),
+        })
+    }
+
+    pub fn insertelement(
+        &mut self,
+        vector: &Value,
+        elt: &Value,
+        idx: &Value,
+    ) -> BuildResult<Value> {
+        let ty = match &vector.ty {
+            Type::Vector(ty) => ty.clone(),
+            ty => return Err(BuildError::UnsupportedType(ty.clone())),
+        };
+        expect_type(&ty.element_type, elt)?;
+        if idx.ty.integer_width().is_none() {
+            return Err(BuildError::UnsupportedType(idx.ty.clone()));
+        }
+        self.push_value(Insertelement {
+            result: String::new(),
+            ty,
+            val: vector.value.clone(),
+            elt: elt.value.clone(),
+            ty2: idx.ty.clone(),
+            idx: idx.value.clone(),
+        })
+    }
+
+    pub fn shufflevector(
+        &mut self,
+        v1: &Value,
+        v2: &Value,
+        mask: Vec<Option<u32>>,
+    ) -> BuildResult<Value> {
+        let ty = match same_type(v1, v2)? {
+            Type::Vector(ty) => ty,
+            ty => return Err(BuildError::UnsupportedType(ty)),
+        };
+        self.push_value(Shufflevector {
+            result: String::new(),
+            ty,
+            v1: v1.value.clone(),
+            v2: v2.value.clone(),
+            mask,
+        })
+    }
+
+    pub fn select(&mut self, cond: &Value, lhs: &Value, rhs: &Value) -> BuildResult<Value> {
+        expect_type(&lhs.ty.compare_result(), cond)?;
+        let ty = same_type(lhs, rhs)?;
+        self.push_value(Select {
+            res_val: String::new(),
+            fast_math_flags: None,
+            selty: cond.ty.clone(),
+            cond: cond.value.clone(),
+            ty1: ty.clone(),
+            val1: lhs.value.clone(),
+            ty2: ty,
+            val2: rhs.value.clone(),
+        })
+    }
+
+    /// PHI node with incoming values and labels of predecessor blocks
+    pub fn phi(&mut self, ty: &Type, incoming: &[(Value, String)]) -> BuildResult<Value> {
+        for (v, _) in incoming.iter() {
+            expect_type(ty, v)?;
+        }
+        self.push_value(Phi {
+            res_val: String::new(),
+            fast_math_flags: None,
+            ty: ty.clone(),
+            params: incoming
+                .iter()
+                .map(|(v, label)| (v.value.clone(), label.clone()))
+                .collect(),
+        })
+    }
+
+    /// Call function. Arguments checked with function signature,
+    /// variadic arguments have any types. Return `None` for `void`
+    /// functions.
+    pub fn call(&mut self, f: &FunctionRef, args: &[Value]) -> BuildResult<Option<Value>> {
+        let params = &f.ty.parameter_list;
+        if args.len() < params.len() || (args.len() > params.len() && !f.ty.variable_argument) {
+            return Err(BuildError::ArgumentsMismatch(f.name.clone(), args.len()));
+        }
+        for (ty, arg) in params.iter().zip(args.iter()) {
+            expect_type(ty, arg)?;
+        }
+        // Function type required only for variadic functions
+        let mut fnty: Vec<ArgumentList> = vec![];
+        if f.ty.variable_argument {
+            fnty = params
+                .iter()
+                .map(|ty| ArgumentList {
+                    parameter_type: Some(ty.clone()),
+                    attributes: None,
+                    name: None,
+                    variable_argument: false,
+                })
+                .collect();
+            fnty.push(ArgumentList {
+                parameter_type: None,
+                attributes: None,
+                name: None,
+                variable_argument: true,
+            });
+        }
+        let ty = *f.ty.return_type.clone();
+        let name = f.name.clone();
+        let mut instr = call!(ty => @name fnty => []);
+        instr.function_args = args
+            .iter()
+            .map(|v| FunctionArg(v.ty.clone(), v.value.clone()))
+            .collect();
+        self.push(instr)
+    }
+
+    /// Return from function. Value type checked with function
+    /// result type.
+    pub fn ret(&mut self, value: Option<&Value>) -> BuildResult<()> {
+        let ty = self.func.function.definition.result_type.clone();
+        match value {
+            Some(v) => {
+                expect_type(&ty, v)?;
+                self.push_terminator(Ret(Some((v.ty.clone(), v.value.clone()))))
+            }
+            None if ty == Type::Void => self.push_terminator(Ret(None)),
+            None => Err(BuildError::TypeMismatch(ty, Type::Void)),
+        }
+    }
+
+    pub fn br(&mut self, label: &str) -> BuildResult<()> {
+        self.push_terminator(Br::Unconditional(label.to_string()))
+    }
+
+    pub fn cond_br(&mut self, cond: &Value, then_label: &str, else_label: &str) -> BuildResult<()> {
+        expect_type(&Type::Integer1, cond)?;
+        self.push_terminator(Br::Conditional(
+            cond.value.clone(),
+            then_label.to_string(),
+            else_label.to_string(),
+        ))
+    }
+
+    pub fn unreachable(&mut self) -> BuildResult<()> {
+        self.push_terminator(Unreachable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::{
+        intrinsics::MathFunction,
+        linkage_types::LinkageTypes::Internal,
+        type_system::single_value::FloatingPointType,
+        types::Type::{
+            Integer32,
+            Integer8,
+            Void,
+        },
+    };
+
+    #[test]
+    fn test_builder_module() {
+        let mut module = ModuleBuilder::new("app");
+        module.source_filename("app.i");
+        let s = module.string_constant("%d\n");
+        let ty = Type::pointer1(Integer8);
+        let name = "printf";
+        let mut printf = decl!(Integer32 name);
+        decl!(printf.argument_list arg!(ty, ...));
+        let printf = module.declare(printf);
+
+        let name = "main";
+        let mut f = def!(Integer32 name);
+        def!(f.linkage @Internal);
+        let mut f = FunctionBuilder::new(f);
+        let mut bb = f.block();
+        let one = Value::new(Integer32, "1");
+        let res = bb.add(&one, &one).unwrap();
+        let fmt = bb.getelementptr(&s, &[0, 0]).unwrap();
+        let res = bb.call(&printf, &[fmt, res]).unwrap().unwrap();
+        bb.ret(Some(&res)).unwrap();
+        module.function(f).unwrap();
+
+        let ir = module.build().to_string();
+        assert_eq!(
+            ir,
+            r#"; ModuleID = 'app'
+source_filename = "app.i"
+
+@.str = private unnamed_addr constant [4 x i8] c"%d\0A\00"
+
+define internal i32 @main() {
+	%1 = add i32 1, 1
+	%2 = getelementptr inbounds [4 x i8], ptr @.str, i64 0, i64 0
+	%3 = call i32 (ptr, ...) @printf(ptr %2, i32 %1)
+	ret i32 %3
+}
+
+declare i32 @printf(ptr, ...)
+"#
+        );
+    }
+
+    #[test]
+    fn test_builder_blocks() {
+        let name = "max";
+        let mut f = def!(Integer32 name);
+        def!(f.argument_list arg!(Integer32, Integer32));
+        let mut f = FunctionBuilder::new(f);
+        let params = f.params();
+        assert_eq!(params[1], Value::new(Integer32, "%1"));
+        let mut bb = f.block();
+        let cond = bb.icmp(IcmpCondition::Sgt, &params[0], &params[1]).unwrap();
+        bb.cond_br(&cond, "first", "second").unwrap();
+        assert_eq!(
+            bb.add(&params[0], &params[1]),
+            Err(BuildError::BlockTerminated("2".to_string()))
+        );
+        f.append_block("first").ret(Some(&params[0])).unwrap();
+        let mut bb = f.append_block("second");
+        bb.ret(Some(&params[1])).unwrap();
+        let mut module = ModuleBuilder::new("app");
+        module.function(f).unwrap();
+        let module = module.build();
+        let f = module.function("max").unwrap();
+        assert_eq!(f.predecessors("second"), vec!["2"]);
+        assert_eq!(
+            f.to_string(),
+            "define i32 @max(i32 %0, i32 %1) {\n\t%3 = icmp sgt i32 %0, %1\n\tbr i1 %3, label %first, label %second\nfirst:\n\tret i32 %0\nsecond:\n\tret i32 %1\n}"
+        );
+    }
+
+    #[test]
+    fn test_builder_type_errors() {
+        let double = Type::FloatingPoint(FloatingPointType::Double);
+        let name = "f";
+        let mut f = FunctionBuilder::new(def!(Void name));
+        let mut bb = f.block();
+        let i = Value::new(Integer32, "1");
+        let d = Value::new(double.clone(), "1.0");
+        assert_eq!(
+            bb.add(&i, &d),
+            Err(BuildError::TypeMismatch(Integer32, double.clone()))
+        );
+        assert_eq!(bb.fadd(&i, &i), Err(BuildError::UnsupportedType(Integer32)));
+        assert_eq!(bb.load(&i), Err(BuildError::UnsupportedType(Integer32)));
+        let ptr = bb.alloca(&Integer32).unwrap();
+        assert_eq!(
+            bb.store(&d, &ptr),
+            Err(BuildError::TypeMismatch(Integer32, double.clone()))
+        );
+        assert_eq!(
+            bb.ret(Some(&i)),
+            Err(BuildError::TypeMismatch(Void, Integer32))
+        );
+        assert_eq!(
+            bb.cast(&ptr, &Type::Integer8, true),
+            Ok(Value::new(Integer8, "%2"))
+        );
+
+        let mut module = ModuleBuilder::new("app");
+        let err = module.function(f).unwrap_err();
+        assert_eq!(
+            err,
+            BuildError::MissingTerminator("f".to_string(), "0".to_string())
+        );
+        assert_eq!(err.to_string(), "block %0 of function @f has no terminator");
+        assert_eq!(
+            BuildError::TypeMismatch(Integer32, double).to_string(),
+            "expected type i32, found double"
+        );
+    }
+
+    #[test]
+    fn test_builder_call_arguments() {
+        let name = "f";
+        let mut decl = decl!(Integer32 name);
+        decl!(decl.argument_list arg!(Integer32));
+        let callee = FunctionRef::from(&decl);
+
+        let name = "main";
+        let mut f = FunctionBuilder::new(def!(Void name));
+        let mut bb = f.block();
+        assert_eq!(
+            bb.call(&callee, &[]),
+            Err(BuildError::ArgumentsMismatch("f".to_string(), 0))
+        );
+        let res = bb.call(&callee, &[Value::new(Integer32, "1")]).unwrap();
+        assert_eq!(res, Some(Value::new(Integer32, "%1")));
+        bb.ret(None).unwrap();
+
+        let mut module = ModuleBuilder::new("app");
+        let ctor = module.function(f).unwrap();
+        module.global_ctor(65535, &ctor).unwrap();
+        assert_eq!(
+            module.global_ctor(65535, &callee),
+            Err(BuildError::TypeMismatch(
+                Type::Function(FunctionType {
+                    return_type: Box::new(Void),
+                    parameter_list: vec![],
+                    variable_argument: false,
+                }),
+                Type::Function(callee.ty.clone())
+            ))
+        );
+        assert!(module.build().to_string().contains(
+            "@llvm.global_ctors = appending global [1 x { i32, ptr, ptr }] [{ i32, ptr, ptr } { i32 65535, ptr @main, ptr null }]"
+        ));
+    }
+
+    #[test]
+    fn test_builder_metadata() {
+        let mut module = ModuleBuilder::new("app");
+        let value = Metadata::Value(Integer32, "2".to_string());
+        let flag = module.module_flag(ModuleFlagBehavior::Max, "PIC Level", value);
+        let node = module.metadata(MetadataNode {
+            distinct: true,
+            value: Metadata::Tuple(vec![Metadata::String("main".to_string())]),
+        });
+        assert_eq!((flag, node), (MetadataRef(0), MetadataRef(1)));
+
+        let name = "main";
+        let mut f = FunctionBuilder::new(def!(Integer32 name));
+        f.attach(MetadataKind::Dbg, node);
+        let mut bb = f.block();
+        assert_eq!(
+            bb.attach(MetadataKind::Dbg, node),
+            Err(BuildError::EmptyBlock("0".to_string()))
+        );
+        let one = Value::new(Integer32, "1");
+        let res = bb.add(&one, &one).unwrap();
+        bb.attach(MetadataKind::Dbg, node).unwrap();
+        bb.attach(MetadataKind::Tbaa, flag).unwrap();
+        bb.ret(Some(&res)).unwrap();
+        bb.attach(MetadataKind::Dbg, node).unwrap();
+        module.function(f).unwrap();
+        assert_eq!(
+            module.build().to_string(),
+            "; ModuleID = 'app'\n\ndefine i32 @main() !dbg !1 {\n\t%1 = add i32 1, 1, !dbg !1, !tbaa !0\n\tret i32 %1, !dbg !1\n}\n\n!llvm.module.flags = !{!0}\n\n!0 = !{i32 7, !\"PIC Level\", i32 2}\n!1 = distinct !{!\"main\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_builder_debug_location() {
+        let mut module = ModuleBuilder::new("app");
+        let scope = module.metadata(MetadataNode {
+            distinct: true,
+            value: Metadata::Tuple(vec![]),
+        });
+        let location = module.metadata(MetadataNode {
+            distinct: false,
+            value: Metadata::Tuple(vec![Metadata::Node(scope)]),
+        });
+        module.replace_metadata(
+            scope,
+            MetadataNode {
+                distinct: true,
+                value: Metadata::Tuple(vec![Metadata::String("main".to_string())]),
+            },
+        );
+
+        let name = "main";
+        let mut f = FunctionBuilder::new(def!(Integer32 name));
+        let mut bb = f.block();
+        let one = Value::new(Integer32, "1");
+        let res = bb.add(&one, &one).unwrap();
+        bb.set_debug_location(Some(location));
+        assert_eq!(bb.debug_location(), Some(location));
+        let res = bb.add(&res, &one).unwrap();
+        bb.ret(Some(&res)).unwrap();
+        module.function(f).unwrap();
+        assert_eq!(
+            module.build().to_string(),
+            "; ModuleID = 'app'\n\ndefine i32 @main() {\n\t%1 = add i32 1, 1\n\t%2 = add i32 %1, 1, !dbg !1\n\tret i32 %2, !dbg !1\n}\n\n!0 = distinct !{!\"main\"}\n!1 = !{!0}\n"
+        );
+    }
+
+    #[test]
+    fn test_builder_intrinsics() {
+        let mut module = ModuleBuilder::new("app");
+        let ty = Type::FloatingPoint(FloatingPointType::Double);
+        let sqrt = Intrinsic::Math(MathFunction::Sqrt, ty.clone());
+        let f1 = module.intrinsic(&sqrt).unwrap();
+        let f2 = module.intrinsic(&sqrt).unwrap();
+        assert_eq!(f1, f2);
+        let trap = module.intrinsic(&Intrinsic::Trap).unwrap();
+
+        let name = "main";
+        let mut fn_def = def!(Void name);
+        def!(fn_def.result_type ty.clone());
+        let mut f = FunctionBuilder::new(fn_def);
+        let mut bb = f.block();
+        let x = Value::new(ty.clone(), "4.0");
+        let res = bb.call(&f1, &[x]).unwrap().unwrap();
+        let label = bb.create_block("fail");
+        assert_eq!(
+            bb.position_at_end("none"),
+            Err(BuildError::UnknownBlock("none".to_string()))
+        );
+        bb.ret(Some(&res)).unwrap();
+        bb.position_at_end(&label).unwrap();
+        bb.call(&trap, &[]).unwrap();
+        bb.unreachable().unwrap();
+        module.function(f).unwrap();
+        assert_eq!(
+            module.build().to_string(),
+            "; ModuleID = 'app'\n\ndefine double @main() {\n\t%1 = call double @llvm.sqrt.f64(double 4.0)\n\tret double %1\nfail:\n\tcall void @llvm.trap()\n\tunreachable\n}\n\ndeclare double @llvm.sqrt.f64(double)\ndeclare void @llvm.trap()\n"
+        );
+    }
+
+    #[test]
+    fn test_builder_struct_types() {
+        let mut module = ModuleBuilder::new("app");
+        let node = module.struct_type("Node");
+        assert_eq!(node, Type::Identified("Node".to_string()));
+        let list = vec![Integer32, Type::pointer1(node.clone())];
+        module.set_struct_body("Node", list.clone(), false).unwrap();
+        assert_eq!(
+            module.set_struct_body("Node", list, false),
+            Err(BuildError::TypeRedefinition("Node".to_string()))
+        );
+        assert_eq!(
+            module.set_struct_body("Leaf", vec![], false),
+            Err(BuildError::UnknownType("Leaf".to_string()))
+        );
+        let pair = module.struct_type("Pair");
+        module
+            .set_struct_body("Pair", vec![Integer8, Integer32], true)
+            .unwrap();
+        module.struct_type("Opaque");
+
+        let name = "next";
+        let mut f = def!(Integer32 name);
+        let node_ptr = Type::pointer1(node.clone());
+        let pair_ty = pair.clone();
+        def!(f.argument_list arg!(node_ptr, pair_ty));
+        let mut f = FunctionBuilder::new(f);
+        f.set_types(module.types());
+        let params = f.params();
+        let mut bb = f.block();
+        let ptr = bb.getelementptr(&params[0], &[0, 1]).unwrap();
+        assert_eq!(ptr.ty, Type::pointer2(node));
+        let x = bb.extractvalue(&params[1], &[1]).unwrap();
+        assert_eq!(x.ty, Integer32);
+        assert_eq!(
+            bb.extractvalue(&params[1], &[2]),
+            Err(BuildError::InvalidIndex(pair, 2))
+        );
+        bb.ret(Some(&x)).unwrap();
+        module.function(f).unwrap();
+        assert_eq!(
+            module.build().to_string(),
+            "; ModuleID = 'app'\n\n%Node = type { i32, ptr }\n%Pair = type <{ i8, i32 }>\n%Opaque = type opaque\n\ndefine i32 @next(ptr %0, %Pair %1) {\n\t%3 = getelementptr inbounds %Node, ptr %0, i64 0, i64 1\n\t%4 = extractvalue %Pair %1, 1\n\tret i32 %4\n}\n"
+        );
+    }
+}
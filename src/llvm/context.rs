@@ -1,33 +1,78 @@
+//! # Context
+//!
+//! Per-function SSA naming context. LLVM requires unnamed values
+//! (temporaries and basic block labels) of a function to be numbered
+//! sequentially, starting from `0`, in order of appearance.
+//! Named values and labels share the same local namespace, so all of
+//! them must be unique inside function.
+//!
+//! https://llvm.org/docs/LangRef.html#identifiers
+
+use std::collections::HashMap;
+
 #[derive(Debug, Clone)]
 pub struct Context {
-    val: u64,
+    /// Next number for unnamed value or label
+    counter: u64,
+    /// Already used names with count of usage
+    names: HashMap<String, u64>,
 }
 
 impl Context {
-    /// Init Contect
+    /// Init context for new function
     pub fn new() -> Self {
-        Self { val: 0 }
+        Self {
+            counter: 0,
+            names: HashMap::new(),
+        }
+    }
+
+    /// Next unnamed temporary value: `%0`, `%1`, ...
+    pub fn value(&mut self) -> String {
+        format!("%{}", self.next())
+    }
+
+    /// Named value, unique for current context: `%name`,
+    /// `%name.1`, ...
+    pub fn named_value(&mut self, name: &str) -> String {
+        format!("%{}", self.unique_name(name))
+    }
+
+    /// Next unnamed basic block label. Labels share numbering
+    /// with unnamed values. Label returned without `%` sigil.
+    pub fn label(&mut self) -> String {
+        self.next().to_string()
     }
 
-    /// Get current context as string
-    pub fn get(&self) -> String {
-        format!("{}", self.val)
+    /// Named basic block label, unique for current context.
+    /// Label returned without `%` sigil.
+    pub fn named_label(&mut self, name: &str) -> String {
+        self.unique_name(name)
     }
 
-    /// Increment context value
-    pub fn inc(&mut self) -> Self {
-        self.val += 1;
-        Context { val: self.val }
+    /// Unique name without sigil. Repeated names get numeric
+    /// suffix: `name`, `name.1`, `name.2`, ...
+    pub fn unique_name(&mut self, name: &str) -> String {
+        let mut count = self.names.get(name).copied().unwrap_or(0);
+        let mut res = name.to_string();
+        while self.names.contains_key(&res) {
+            count += 1;
+            res = format!("{}.{}", name, count);
+        }
+        self.names.insert(name.to_string(), count);
+        self.names.entry(res.clone()).or_insert(0);
+        res
     }
 
-    /// Set context value
-    pub fn set(&mut self, val: u64) {
-        self.val = val;
+    /// Count of unnamed values and labels allocated in context
+    pub fn count(&self) -> u64 {
+        self.counter
     }
 
-    /// Get current context value
-    pub fn val(&mut self) -> u64 {
-        self.val
+    fn next(&mut self) -> u64 {
+        let val = self.counter;
+        self.counter += 1;
+        val
     }
 }
 
@@ -36,3 +81,30 @@ impl Default for Context {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Context;
+
+    #[test]
+    fn test_context_sequential_values_and_labels() {
+        let mut ctx = Context::new();
+        assert_eq!(ctx.label(), "0");
+        assert_eq!(ctx.value(), "%1");
+        assert_eq!(ctx.value(), "%2");
+        assert_eq!(ctx.label(), "3");
+        assert_eq!(ctx.named_value("x"), "%x");
+        assert_eq!(ctx.value(), "%4");
+        assert_eq!(ctx.count(), 5);
+    }
+
+    #[test]
+    fn test_context_unique_names() {
+        let mut ctx = Context::new();
+        assert_eq!(ctx.named_value("x"), "%x");
+        assert_eq!(ctx.named_value("x"), "%x.1");
+        assert_eq!(ctx.named_label("x"), "x.2");
+        assert_eq!(ctx.named_value("x.1"), "%x.1.1");
+        assert_eq!(ctx.unique_name("entry"), "entry");
+    }
+}
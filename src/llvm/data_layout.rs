@@ -6,7 +6,7 @@
 //! https://llvm.org/docs/LangRef.html#data-layout
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-pub struct DataLayout(String);
+pub struct DataLayout(pub String);
 
 impl std::fmt::Display for DataLayout {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
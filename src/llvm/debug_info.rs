@@ -0,0 +1,383 @@
+//! # Debug information
+//!
+//! Source level debug information is represented with specialized
+//! metadata nodes. Nodes describe source files, compile unit, types,
+//! functions and variables in terms of DWARF, and instructions refer
+//! source positions with `!dbg` attachments:
+//! ```html
+//! !0 = distinct !DICompileUnit(language: DW_LANG_C, file: !1, producer: "iLang", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)
+//! !1 = !DIFile(filename: "main.i", directory: "/src")
+//! !5 = !DILocation(line: 3, column: 5, scope: !4)
+//! ```
+//!
+//! https://llvm.org/docs/LangRef.html#specialized-metadata-nodes
+//! https://llvm.org/docs/SourceLevelDebugging.html
+
+use crate::llvm::metadata::MetadataRef;
+
+/// Specialized metadata node
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum DebugInfo {
+    CompileUnit(DICompileUnit),
+    File(DIFile),
+    BasicType(DIBasicType),
+    DerivedType(DIDerivedType),
+    SubroutineType(DISubroutineType),
+    Subprogram(DISubprogram),
+    LocalVariable(DILocalVariable),
+    Location(DILocation),
+    Expression(DIExpression),
+}
+
+/// Kind of debug info emitted for compile unit
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum EmissionKind {
+    NoDebug,
+    FullDebug,
+    LineTablesOnly,
+}
+
+/// DWARF encoding of basic type
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum DwarfEncoding {
+    Boolean,
+    Float,
+    Signed,
+    Unsigned,
+    UnsignedChar,
+}
+
+/// `DICompileUnit` nodes represent a compile unit. Compile unit must
+/// be `distinct` and listed in `!llvm.dbg.cu` named metadata.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DICompileUnit {
+    /// DWARF language name: `DW_LANG_C`
+    pub language: String,
+    pub file: MetadataRef,
+    pub producer: String,
+    pub is_optimized: bool,
+    pub runtime_version: u32,
+    pub emission_kind: EmissionKind,
+}
+
+/// `DIFile` nodes represent files
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DIFile {
+    pub filename: String,
+    pub directory: String,
+}
+
+/// `DIBasicType` nodes represent primitive types, such as `int`,
+/// `bool` and `float`. Size in bits.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DIBasicType {
+    pub name: String,
+    pub size: u64,
+    pub encoding: DwarfEncoding,
+}
+
+/// `DIDerivedType` nodes represent types derived from other types,
+/// such as pointers
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DIDerivedType {
+    /// DWARF tag: `DW_TAG_pointer_type`
+    pub tag: String,
+    pub base_type: MetadataRef,
+    pub size: u64,
+}
+
+/// `DISubroutineType` nodes represent subroutine types. Types is
+/// tuple of result type (`null` for `void`) and parameter types.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DISubroutineType {
+    pub types: MetadataRef,
+}
+
+/// `DISubprogram` nodes represent functions from the source
+/// language. Definitions must be `distinct`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DISubprogram {
+    pub name: String,
+    pub scope: MetadataRef,
+    pub file: MetadataRef,
+    pub line: u32,
+    pub ty: Option<MetadataRef>,
+    pub scope_line: u32,
+    /// Function generated by compiler: `DIFlagArtificial`
+    pub artificial: bool,
+    /// Function with internal linkage: `DISPFlagLocalToUnit`
+    pub local_to_unit: bool,
+    /// Function definition: `DISPFlagDefinition`
+    pub definition: bool,
+    pub unit: Option<MetadataRef>,
+    /// Tuple of variables, which are kept even if optimized away
+    pub retained_nodes: Option<MetadataRef>,
+}
+
+/// `DILocalVariable` nodes represent local variables in the source
+/// language. Parameters have number of argument, starting from 1.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DILocalVariable {
+    pub name: String,
+    pub arg: Option<u32>,
+    pub scope: MetadataRef,
+    pub file: MetadataRef,
+    pub line: u32,
+    pub ty: Option<MetadataRef>,
+}
+
+/// `DILocation` nodes represent source debug locations
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DILocation {
+    pub line: u32,
+    pub column: u32,
+    pub scope: MetadataRef,
+}
+
+/// `DIExpression` nodes represent expressions, which describe how
+/// to compute variable value from location: `!DIExpression(DW_OP_deref)`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DIExpression(pub Vec<String>);
+
+impl EmissionKind {
+    pub fn new(name: &str) -> Option<Self> {
+        match name {
+            "NoDebug" => Some(EmissionKind::NoDebug),
+            "FullDebug" => Some(EmissionKind::FullDebug),
+            "LineTablesOnly" => Some(EmissionKind::LineTablesOnly),
+            _ => None,
+        }
+    }
+}
+
+impl DwarfEncoding {
+    pub fn new(name: &str) -> Option<Self> {
+        match name {
+            "DW_ATE_boolean" => Some(DwarfEncoding::Boolean),
+            "DW_ATE_float" => Some(DwarfEncoding::Float),
+            "DW_ATE_signed" => Some(DwarfEncoding::Signed),
+            "DW_ATE_unsigned" => Some(DwarfEncoding::Unsigned),
+            "DW_ATE_unsigned_char" => Some(DwarfEncoding::UnsignedChar),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EmissionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            EmissionKind::NoDebug => "NoDebug",
+            EmissionKind::FullDebug => "FullDebug",
+            EmissionKind::LineTablesOnly => "LineTablesOnly",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for DwarfEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            DwarfEncoding::Boolean => "DW_ATE_boolean",
+            DwarfEncoding::Float => "DW_ATE_float",
+            DwarfEncoding::Signed => "DW_ATE_signed",
+            DwarfEncoding::Unsigned => "DW_ATE_unsigned",
+            DwarfEncoding::UnsignedChar => "DW_ATE_unsigned_char",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for DICompileUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "!DICompileUnit(language: {}, file: {}, producer: \"{}\", isOptimized: {}, runtimeVersion: {}, emissionKind: {})",
+            self.language,
+            self.file,
+            self.producer,
+            self.is_optimized,
+            self.runtime_version,
+            self.emission_kind
+        )
+    }
+}
+
+impl std::fmt::Display for DIFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "!DIFile(filename: \"{}\", directory: \"{}\")",
+            self.filename, self.directory
+        )
+    }
+}
+
+impl std::fmt::Display for DIBasicType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "!DIBasicType(name: \"{}\", size: {}, encoding: {})",
+            self.name, self.size, self.encoding
+        )
+    }
+}
+
+impl std::fmt::Display for DIDerivedType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "!DIDerivedType(tag: {}, baseType: {}, size: {})",
+            self.tag, self.base_type, self.size
+        )
+    }
+}
+
+impl std::fmt::Display for DISubroutineType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "!DISubroutineType(types: {})", self.types)
+    }
+}
+
+impl std::fmt::Display for DISubprogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = format!(
+            "!DISubprogram(name: \"{}\", scope: {}, file: {}, line: {}",
+            self.name, self.scope, self.file, self.line
+        );
+        if let Some(ty) = &self.ty {
+            s = format!("{}, type: {}", s, ty);
+        }
+        s = format!("{}, scopeLine: {}", s, self.scope_line);
+        if self.artificial {
+            s = format!("{}, flags: DIFlagArtificial", s);
+        }
+        let sp_flags = [
+            (self.local_to_unit, "DISPFlagLocalToUnit"),
+            (self.definition, "DISPFlagDefinition"),
+        ]
+        .iter()
+        .filter(|(v, _)| *v)
+        .map(|(_, flag)| *flag)
+        .collect::<Vec<_>>();
+        if !sp_flags.is_empty() {
+            s = format!("{}, spFlags: {}", s, sp_flags.join(" | "));
+        }
+        if let Some(unit) = &self.unit {
+            s = format!("{}, unit: {}", s, unit);
+        }
+        if let Some(nodes) = &self.retained_nodes {
+            s = format!("{}, retainedNodes: {}", s, nodes);
+        }
+        write!(f, "{})", s)
+    }
+}
+
+impl std::fmt::Display for DILocalVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = format!("!DILocalVariable(name: \"{}\"", self.name);
+        if let Some(arg) = &self.arg {
+            s = format!("{}, arg: {}", s, arg);
+        }
+        s = format!(
+            "{}, scope: {}, file: {}, line: {}",
+            s, self.scope, self.file, self.line
+        );
+        if let Some(ty) = &self.ty {
+            s = format!("{}, type: {}", s, ty);
+        }
+        write!(f, "{})", s)
+    }
+}
+
+impl std::fmt::Display for DILocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "!DILocation(line: {}, column: {}, scope: {})",
+            self.line, self.column, self.scope
+        )
+    }
+}
+
+impl std::fmt::Display for DIExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "!DIExpression({})", self.0.join(", "))
+    }
+}
+
+impl std::fmt::Display for DebugInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DebugInfo::CompileUnit(v) => write!(f, "{}", v),
+            DebugInfo::File(v) => write!(f, "{}", v),
+            DebugInfo::BasicType(v) => write!(f, "{}", v),
+            DebugInfo::DerivedType(v) => write!(f, "{}", v),
+            DebugInfo::SubroutineType(v) => write!(f, "{}", v),
+            DebugInfo::Subprogram(v) => write!(f, "{}", v),
+            DebugInfo::LocalVariable(v) => write!(f, "{}", v),
+            DebugInfo::Location(v) => write!(f, "{}", v),
+            DebugInfo::Expression(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_info() {
+        let v = DICompileUnit {
+            language: "DW_LANG_C".to_string(),
+            file: MetadataRef(1),
+            producer: "iLang".to_string(),
+            is_optimized: false,
+            runtime_version: 0,
+            emission_kind: EmissionKind::FullDebug,
+        };
+        assert_eq!(
+            v.to_string(),
+            r#"!DICompileUnit(language: DW_LANG_C, file: !1, producer: "iLang", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)"#
+        );
+        let v = DISubprogram {
+            name: "main".to_string(),
+            scope: MetadataRef(1),
+            file: MetadataRef(1),
+            line: 3,
+            ty: Some(MetadataRef(5)),
+            scope_line: 3,
+            artificial: false,
+            local_to_unit: true,
+            definition: true,
+            unit: Some(MetadataRef(0)),
+            retained_nodes: None,
+        };
+        assert_eq!(
+            v.to_string(),
+            r#"!DISubprogram(name: "main", scope: !1, file: !1, line: 3, type: !5, scopeLine: 3, spFlags: DISPFlagLocalToUnit | DISPFlagDefinition, unit: !0)"#
+        );
+        let v = DILocalVariable {
+            name: "x".to_string(),
+            arg: Some(1),
+            scope: MetadataRef(4),
+            file: MetadataRef(1),
+            line: 3,
+            ty: None,
+        };
+        assert_eq!(
+            v.to_string(),
+            r#"!DILocalVariable(name: "x", arg: 1, scope: !4, file: !1, line: 3)"#
+        );
+        let v = DILocation {
+            line: 4,
+            column: 13,
+            scope: MetadataRef(4),
+        };
+        assert_eq!(v.to_string(), "!DILocation(line: 4, column: 13, scope: !4)");
+        assert_eq!(DIExpression(vec![]).to_string(), "!DIExpression()");
+        assert_eq!(
+            DwarfEncoding::new("DW_ATE_float"),
+            Some(DwarfEncoding::Float)
+        );
+    }
+}
@@ -55,6 +55,7 @@ use crate::llvm::{
     gc_stratagy_name::GcStrategyName,
     global_variables::UnnamedAddr,
     linkage_types::LinkageTypes,
+    metadata::Attachment,
     parameter_attributes::ParameterAttributes,
     prefix::Prefix,
     runtime_preemption::RuntimePreemptionSpecifier,
@@ -84,7 +85,7 @@ pub enum FunctionDefinitionType {
 }
 
 /// Most commin unction specification
-/// NOTE: prologue, metadata is simple strings without specific Rust types
+/// NOTE: prologue is simple string without specific Rust type
 /// personality field is not clear is it right or not
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Function {
@@ -109,7 +110,8 @@ pub struct Function {
     pub prefix: Option<Prefix>,
     pub prologue: Option<String>,
     pub personality: Option<Personality>,
-    pub metadata: Option<String>,
+    /// Metadata attachments
+    pub metadata: Vec<Attachment>,
 }
 
 impl std::fmt::Display for Function {
@@ -162,13 +164,13 @@ impl std::fmt::Display for Function {
         let fn_attrs = self
             .fn_attrs
             .iter()
-            .fold(" ".to_string(), |s, x| format!("{}{} ", s, x));
+            .fold("".to_string(), |s, x| format!("{} {}", s, x));
         s = format!("{}{}", s, fn_attrs);
 
         let attr_group = self
             .attr_group
             .iter()
-            .fold("".to_string(), |s, x| format!("{}#{}", s, x));
+            .fold("".to_string(), |s, x| format!("{} #{}", s, x));
         s = format!("{}{}", s, attr_group);
 
         if let Some(x) = &self.section_name {
@@ -192,9 +194,11 @@ impl std::fmt::Display for Function {
         if let Some(x) = &self.personality {
             s = format!("{} personality {}", s, x);
         }
-        if let Some(x) = &self.metadata {
-            s = format!("{} {}", s, x);
-        }
+        let metadata = self
+            .metadata
+            .iter()
+            .fold("".to_string(), |s, x| format!("{} {}", s, x));
+        s = format!("{}{}", s, metadata);
 
         write!(f, "{}", s)
     }
@@ -24,13 +24,17 @@ use super::{
     comdat::ComDat,
     dll_storage_classes::DllStorageClasses,
     linkage_types::LinkageTypes,
+    metadata::Attachment,
     runtime_preemption::RuntimePreemptionSpecifier,
     section::Section,
     thread_local_storage::ThreadLocalStorage,
     types::Type,
     visibility_styles::VisibilityStyles,
 };
-use crate::llvm::InstructionSet;
+use crate::llvm::{
+    context::Context,
+    InstructionSet,
+};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum UnnamedAddr {
@@ -60,24 +64,20 @@ pub struct GlobalVariable {
     pub section: Option<Section>,
     pub comdat: Option<ComDat>,
     pub alignment: Option<Alignment>,
-    pub metadata: Option<String>,
-    pub ctx: Option<u64>,
+    /// Metadata attachments
+    pub metadata: Vec<Attachment>,
 }
 
 impl GlobalVariable {
     pub fn get_value_name(&self) -> Option<String> {
-        if let Some(ctx) = self.ctx {
-            Some(format!("@{:?}", ctx))
-        } else {
-            Some(format!("@{}", self.name))
-        }
+        Some(format!("@{}", self.name))
     }
 }
 
 impl InstructionSet for GlobalVariable {
-    fn set_context(&mut self, ctx: u64) {
-        self.ctx = Some(ctx);
-    }
+    /// Global variables are module level values, so function
+    /// context isn't used.
+    fn set_context(&mut self, _ctx: &mut Context) {}
     fn is_assignment(&self) -> bool {
         true
     }
@@ -116,11 +116,7 @@ impl std::fmt::Display for GlobalVariableKind {
 
 impl std::fmt::Display for GlobalVariable {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut s = if let Some(ctx) = self.ctx {
-            format!("@{:?} =", ctx)
-        } else {
-            format!("@{} =", self.name)
-        };
+        let mut s = format!("@{} =", self.name);
         if self.linkage.is_some() {
             s = format!("{} {}", s, self.linkage.as_ref().unwrap());
         }
@@ -155,9 +151,7 @@ impl std::fmt::Display for GlobalVariable {
         if self.alignment.is_some() {
             s = format!("{}, {}", s, self.alignment.as_ref().unwrap());
         }
-        if self.metadata.is_some() {
-            s = format!("{}, {}", s, self.metadata.as_ref().unwrap());
-        }
+        s = self.metadata.iter().fold(s, |s, x| format!("{}, {}", s, x));
 
         write!(f, "{}", s)
     }
@@ -4,7 +4,11 @@
 //!
 //! https://llvm.org/docs/LangRef.html#aggregate-operations
 
-use crate::llvm::types::Type;
+use crate::llvm::{
+    context::Context,
+    types::Type,
+    InstructionSet,
+};
 
 /// The ‘extractvalue’ instruction extracts the value of a member
 /// field from an aggregate value.
@@ -26,7 +30,7 @@ use crate::llvm::types::Type;
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Extractvalue {
     pub result: String,
-    pub aggregate_type: String,
+    pub aggregate_type: Type,
     pub val: String,
     pub idx: Vec<u64>,
 }
@@ -49,7 +53,7 @@ pub struct Extractvalue {
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Insertvalue {
     pub result: String,
-    pub aggregate_type: String,
+    pub aggregate_type: Type,
     pub val: String,
     pub ty: Type,
     pub elt: String,
@@ -63,7 +67,7 @@ impl std::fmt::Display for Extractvalue {
             .iter()
             .fold("".to_string(), |s, v| format!("{}, {}", s, v));
         let s = format!(
-            "{} = extractvalue {} {} {}",
+            "{} = extractvalue {} {}{}",
             self.result, self.aggregate_type, self.val, idx
         );
         write!(f, "{}", s)
@@ -77,9 +81,103 @@ impl std::fmt::Display for Insertvalue {
             .iter()
             .fold("".to_string(), |s, v| format!("{}, {}", s, v));
         let s = format!(
-            "{} = insertvalue {} {}, {} {} {}",
+            "{} = insertvalue {} {}, {} {}{}",
             self.result, self.aggregate_type, self.val, self.ty, self.elt, idx
         );
         write!(f, "{}", s)
     }
 }
+
+impl InstructionSet for Extractvalue {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        self.idx
+            .iter()
+            .try_fold(self.aggregate_type.clone(), |ty, idx| ty.indexed_type(*idx))
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![(self.aggregate_type.clone(), self.val.clone())]
+    }
+}
+
+impl InstructionSet for Insertvalue {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.aggregate_type.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (self.aggregate_type.clone(), self.val.clone()),
+            (self.ty.clone(), self.elt.clone()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::type_system::aggregate::StructureType;
+
+    fn pair() -> Type {
+        Type::Structure(StructureType {
+            literal: true,
+            packed: false,
+            type_list: vec![Type::Integer32, Type::Integer8],
+        })
+    }
+
+    #[test]
+    fn test_extractvalue() {
+        let mut v = Extractvalue {
+            result: "".to_string(),
+            aggregate_type: pair(),
+            val: "%agg".to_string(),
+            idx: vec![1],
+        };
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%0 = extractvalue { i32, i8 } %agg, 1");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer8));
+        assert_eq!(v.get_value(), Some("%0".to_string()));
+    }
+
+    #[test]
+    fn test_insertvalue() {
+        let v = Insertvalue {
+            result: "%1".to_string(),
+            aggregate_type: pair(),
+            val: "undef".to_string(),
+            ty: Type::Integer32,
+            elt: "1".to_string(),
+            idx: vec![0],
+        };
+        assert_eq!(
+            v.to_string(),
+            "%1 = insertvalue { i32, i8 } undef, i32 1, 0"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(pair()));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+}
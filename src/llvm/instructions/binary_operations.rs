@@ -48,6 +48,22 @@ pub struct FAdd {
     pub op2: String,
 }
 
+/// The ‘fsub’ instruction returns the difference of its two operands.
+///
+/// The two arguments to the ‘fsub’ instruction must be floating-point
+/// or vector of floating-point values. Both arguments must have
+/// identical types.
+///
+/// https://llvm.org/docs/LangRef.html#fsub-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FSub {
+    pub result: String,
+    pub fast_math_flags: Option<FastMathFlags>,
+    pub ty: Type,
+    pub op1: String,
+    pub op2: String,
+}
+
 /// Note that the ‘sub’ instruction is used to represent the ‘neg’
 /// instruction present in most other intermediate representations.
 ///
@@ -238,6 +254,20 @@ impl std::fmt::Display for FAdd {
     }
 }
 
+impl std::fmt::Display for FSub {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = "fsub".to_string();
+        if let Some(v) = &self.fast_math_flags {
+            s = format!("{} {}", s, v)
+        }
+        s = format!(
+            "{} = {} {} {}, {}",
+            self.result, s, self.ty, self.op1, self.op2
+        );
+        write!(f, "{}", s)
+    }
+}
+
 impl std::fmt::Display for Sub {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut s = "sub".to_string();
@@ -363,3 +393,174 @@ impl std::fmt::Display for FRem {
         write!(f, "{}", s)
     }
 }
+
+assignment_instruction_set!(Add FAdd Sub FSub Mul FMul UDiv SDiv FDiv URem SRem FRem);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::{
+        context::Context,
+        type_system::single_value::FloatingPointType,
+        InstructionSet,
+    };
+
+    fn double() -> Type {
+        Type::FloatingPoint(FloatingPointType::Double)
+    }
+
+    fn check(v: &dyn InstructionSet, display: &str, ty: Type) {
+        assert_eq!(v.to_string(), display);
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(ty));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_add() {
+        let mut v = Add {
+            result: "".to_string(),
+            nuw: Some(()),
+            nsw: Some(()),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "2".to_string(),
+        };
+        let mut ctx = Context::new();
+        ctx.value();
+        v.set_context(&mut ctx);
+        check(&v, "%1 = add nuw nsw i32 %0, 2", Type::Integer32);
+    }
+
+    #[test]
+    fn test_fadd() {
+        let v = FAdd {
+            result: "%1".to_string(),
+            fast_math_flags: Some(FastMathFlags::Fast),
+            ty: double(),
+            op1: "%0".to_string(),
+            op2: "1.0".to_string(),
+        };
+        check(&v, "%1 = fadd fast double %0, 1.0", double());
+    }
+
+    #[test]
+    fn test_sub() {
+        let v = Sub {
+            result: "%1".to_string(),
+            nuw: None,
+            nsw: Some(()),
+            ty: Type::Integer64,
+            op1: "%0".to_string(),
+            op2: "2".to_string(),
+        };
+        check(&v, "%1 = sub nsw i64 %0, 2", Type::Integer64);
+    }
+
+    #[test]
+    fn test_fsub() {
+        let v = FSub {
+            result: "%1".to_string(),
+            fast_math_flags: None,
+            ty: double(),
+            op1: "%0".to_string(),
+            op2: "1.0".to_string(),
+        };
+        check(&v, "%1 = fsub double %0, 1.0", double());
+    }
+
+    #[test]
+    fn test_mul() {
+        let v = Mul {
+            result: "%1".to_string(),
+            nuw: Some(()),
+            nsw: None,
+            ty: Type::Integer8,
+            op1: "%0".to_string(),
+            op2: "3".to_string(),
+        };
+        check(&v, "%1 = mul nuw i8 %0, 3", Type::Integer8);
+    }
+
+    #[test]
+    fn test_fmul() {
+        let v = FMul {
+            result: "%1".to_string(),
+            fast_math_flags: Some(FastMathFlags::Nsz),
+            ty: double(),
+            op1: "%0".to_string(),
+            op2: "%0".to_string(),
+        };
+        check(&v, "%1 = fmul nsz double %0, %0", double());
+    }
+
+    #[test]
+    fn test_udiv() {
+        let v = UDiv {
+            result: "%1".to_string(),
+            exact: Some(()),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "4".to_string(),
+        };
+        check(&v, "%1 = udiv exact i32 %0, 4", Type::Integer32);
+    }
+
+    #[test]
+    fn test_sdiv() {
+        let v = SDiv {
+            result: "%1".to_string(),
+            exact: None,
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "-4".to_string(),
+        };
+        check(&v, "%1 = sdiv i32 %0, -4", Type::Integer32);
+    }
+
+    #[test]
+    fn test_fdiv() {
+        let v = FDiv {
+            result: "%1".to_string(),
+            fast_math_flags: Some(FastMathFlags::Arcp),
+            ty: double(),
+            op1: "%0".to_string(),
+            op2: "2.0".to_string(),
+        };
+        check(&v, "%1 = fdiv arcp double %0, 2.0", double());
+    }
+
+    #[test]
+    fn test_urem() {
+        let v = URem {
+            result: "%1".to_string(),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "3".to_string(),
+        };
+        check(&v, "%1 = urem i32 %0, 3", Type::Integer32);
+    }
+
+    #[test]
+    fn test_srem() {
+        let v = SRem {
+            result: "%1".to_string(),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "3".to_string(),
+        };
+        check(&v, "%1 = srem i32 %0, 3", Type::Integer32);
+    }
+
+    #[test]
+    fn test_frem() {
+        let v = FRem {
+            result: "%1".to_string(),
+            fast_math_flags: None,
+            ty: double(),
+            op1: "%0".to_string(),
+            op2: "3.0".to_string(),
+        };
+        check(&v, "%1 = frem double %0, 3.0", double());
+    }
+}
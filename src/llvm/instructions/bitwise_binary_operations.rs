@@ -140,7 +140,7 @@ impl std::fmt::Display for Shl {
 
 impl std::fmt::Display for LShl {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut s = "lshl".to_string();
+        let mut s = "lshr".to_string();
         if self.exact.is_some() {
             s = format!("{} exact", s)
         }
@@ -198,3 +198,94 @@ impl std::fmt::Display for Xor {
         write!(f, "{}", s)
     }
 }
+
+assignment_instruction_set!(Shl LShl AShr And Or Xor);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::{
+        context::Context,
+        InstructionSet,
+    };
+
+    fn check(v: &dyn InstructionSet, display: &str) {
+        assert_eq!(v.to_string(), display);
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer32));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_shl() {
+        let mut v = Shl {
+            result: "".to_string(),
+            nuw: None,
+            nsw: Some(()),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "2".to_string(),
+        };
+        let mut ctx = Context::new();
+        ctx.value();
+        v.set_context(&mut ctx);
+        check(&v, "%1 = shl nsw i32 %0, 2");
+    }
+
+    #[test]
+    fn test_lshr() {
+        let v = LShl {
+            result: "%1".to_string(),
+            exact: Some(()),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "2".to_string(),
+        };
+        check(&v, "%1 = lshr exact i32 %0, 2");
+    }
+
+    #[test]
+    fn test_ashr() {
+        let v = AShr {
+            result: "%1".to_string(),
+            exact: None,
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "2".to_string(),
+        };
+        check(&v, "%1 = ashr i32 %0, 2");
+    }
+
+    #[test]
+    fn test_and() {
+        let v = And {
+            result: "%1".to_string(),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "255".to_string(),
+        };
+        check(&v, "%1 = and i32 %0, 255");
+    }
+
+    #[test]
+    fn test_or() {
+        let v = Or {
+            result: "%1".to_string(),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "1".to_string(),
+        };
+        check(&v, "%1 = or i32 %0, 1");
+    }
+
+    #[test]
+    fn test_xor() {
+        let v = Xor {
+            result: "%1".to_string(),
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "-1".to_string(),
+        };
+        check(&v, "%1 = xor i32 %0, -1");
+    }
+}
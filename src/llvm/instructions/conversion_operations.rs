@@ -38,3 +38,515 @@ impl std::fmt::Display for Trunc {
         write!(f, "{}", s)
     }
 }
+
+/// The ‘zext’ instruction zero extends its operand to type ty2.
+///
+/// The ‘zext’ instruction takes a value to cast, and a type to cast
+/// it to. Both types must be of integer types, or vectors of the same
+/// number of integers. The bit size of the value must be smaller than
+/// the bit size of the destination type, ty2.
+///
+/// The zext fills the high order bits of the value with zero bits
+/// until it reaches the size of the destination type, ty2.
+///
+/// https://llvm.org/docs/LangRef.html#zext-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ZExt {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for ZExt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = zext {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘sext’ sign extends value to the type ty2.
+///
+/// The ‘sext’ instruction takes a value to cast, and a type to cast
+/// it to. Both types must be of integer types, or vectors of the same
+/// number of integers. The bit size of the value must be smaller than
+/// the bit size of the destination type, ty2.
+///
+/// The ‘sext’ instruction performs a sign extension by copying the
+/// sign bit (highest order bit) of the value until it reaches the bit
+/// size of the type ty2.
+///
+/// https://llvm.org/docs/LangRef.html#sext-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SExt {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for SExt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = sext {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘fptrunc’ instruction truncates value to type ty2.
+///
+/// The ‘fptrunc’ instruction takes a floating-point value to cast and
+/// a floating-point type to cast it to. The size of value must be
+/// larger than the size of ty2. This implies that fptrunc cannot be
+/// used to make a no-op cast.
+///
+/// The ‘fptrunc’ instruction casts a value from a larger
+/// floating-point type to a smaller floating-point type.
+///
+/// https://llvm.org/docs/LangRef.html#fptrunc-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FPTrunc {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for FPTrunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = fptrunc {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘fpext’ extends a floating-point value to a larger
+/// floating-point value.
+///
+/// The ‘fpext’ instruction takes a floating-point value to cast, and
+/// a floating-point type to cast it to. The source type must be
+/// smaller than the destination type.
+///
+/// The ‘fpext’ instruction extends the value from a smaller
+/// floating-point type to a larger floating-point type.
+///
+/// https://llvm.org/docs/LangRef.html#fpext-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FPExt {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for FPExt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = fpext {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘fptoui’ converts a floating-point value to its unsigned
+/// integer equivalent of type ty2.
+///
+/// The ‘fptoui’ instruction takes a value to cast, which must be a
+/// scalar or vector floating-point value, and a type to cast it to
+/// ty2, which must be an integer type.
+///
+/// The ‘fptoui’ instruction converts its floating-point operand into
+/// the nearest (rounding towards zero) unsigned integer value. If the
+/// value cannot fit in ty2, the result is a poison value.
+///
+/// https://llvm.org/docs/LangRef.html#fptoui-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FPToUI {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for FPToUI {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = fptoui {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘fptosi’ instruction converts floating-point value to type
+/// ty2.
+///
+/// The ‘fptosi’ instruction takes a value to cast, which must be a
+/// scalar or vector floating-point value, and a type to cast it to
+/// ty2, which must be an integer type.
+///
+/// The ‘fptosi’ instruction converts its floating-point operand into
+/// the nearest (rounding towards zero) signed integer value. If the
+/// value cannot fit in ty2, the result is a poison value.
+///
+/// https://llvm.org/docs/LangRef.html#fptosi-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FPToSI {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for FPToSI {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = fptosi {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘uitofp’ instruction regards value as an unsigned integer
+/// and converts that value to the ty2 type.
+///
+/// The ‘uitofp’ instruction takes a value to cast, which must be a
+/// scalar or vector integer value, and a type to cast it to ty2,
+/// which must be a floating-point type.
+///
+/// The ‘uitofp’ instruction interprets its operand as an unsigned
+/// integer quantity and converts it to the corresponding
+/// floating-point value. If the value cannot be exactly represented,
+/// it is rounded using the default rounding mode.
+///
+/// https://llvm.org/docs/LangRef.html#uitofp-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UIToFP {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for UIToFP {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = uitofp {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘sitofp’ instruction regards value as a signed integer and
+/// converts that value to the ty2 type.
+///
+/// The ‘sitofp’ instruction takes a value to cast, which must be a
+/// scalar or vector integer value, and a type to cast it to ty2,
+/// which must be a floating-point type.
+///
+/// The ‘sitofp’ instruction interprets its operand as a signed
+/// integer quantity and converts it to the corresponding
+/// floating-point value. If the value cannot be exactly represented,
+/// it is rounded using the default rounding mode.
+///
+/// https://llvm.org/docs/LangRef.html#sitofp-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SIToFP {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for SIToFP {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = sitofp {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘ptrtoint’ instruction converts the pointer or a vector of
+/// pointers value to the integer (or vector of integers) type ty2.
+///
+/// The ‘ptrtoint’ instruction takes a value to cast, which must be a
+/// value of type pointer or a vector of pointers, and a type to cast
+/// it to ty2, which must be an integer or a vector of integers type.
+///
+/// The ‘ptrtoint’ instruction converts value to integer type ty2 by
+/// interpreting the pointer value as an integer and either truncating
+/// or zero extending that value to the size of the integer type.
+///
+/// https://llvm.org/docs/LangRef.html#ptrtoint-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct PtrToInt {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for PtrToInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = ptrtoint {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘inttoptr’ instruction converts an integer value to a
+/// pointer type, ty2.
+///
+/// The ‘inttoptr’ instruction takes an integer value to cast, and a
+/// type to cast it to, which must be a pointer type.
+///
+/// The ‘inttoptr’ instruction converts value to type ty2 by applying
+/// either a zero extension or a truncation depending on the size of
+/// the integer value.
+///
+/// https://llvm.org/docs/LangRef.html#inttoptr-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct IntToPtr {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for IntToPtr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = inttoptr {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘bitcast’ instruction converts value to type ty2 without
+/// changing any bits.
+///
+/// The ‘bitcast’ instruction takes a value to cast, which must be a
+/// non-aggregate first class value, and a type to cast it to, which
+/// must also be a non-aggregate first class type. The bit sizes of
+/// value and the destination type, ty2, must be identical. If the
+/// source type is a pointer, the destination type must also be a
+/// pointer of the same size.
+///
+/// The ‘bitcast’ instruction converts value to type ty2. It is always
+/// a no-op cast because no bits change with this conversion.
+///
+/// https://llvm.org/docs/LangRef.html#bitcast-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct BitCast {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for BitCast {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = bitcast {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘addrspacecast’ instruction converts ptrval from pty in
+/// address space n to type pty2 in address space m.
+///
+/// The ‘addrspacecast’ instruction takes a pointer or vector of
+/// pointer value to cast and a pointer type to cast it to, which must
+/// have a different address space.
+///
+/// The ‘addrspacecast’ instruction converts the pointer value ptrval
+/// to type pty2. It can be a no-op cast or a complex value
+/// modification, depending on the target and the address space
+/// pair.
+///
+/// https://llvm.org/docs/LangRef.html#addrspacecast-to-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AddrSpaceCast {
+    pub result: String,
+    pub ty: Type,
+    pub value: String,
+    pub ty2: Type,
+}
+
+impl std::fmt::Display for AddrSpaceCast {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = addrspacecast {} {} to {}",
+            self.result, self.ty, self.value, self.ty2
+        );
+        write!(f, "{}", s)
+    }
+}
+
+conversion_instruction_set!(
+    Trunc ZExt SExt FPTrunc FPExt FPToUI FPToSI UIToFP SIToFP PtrToInt IntToPtr BitCast AddrSpaceCast
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::{
+        type_system::single_value::FloatingPointType,
+        InstructionSet,
+    };
+
+    #[test]
+    fn test_trunc() {
+        let v = Trunc {
+            result: "%1".to_string(),
+            ty: Type::Integer32,
+            value: "257".to_string(),
+            ty2: Type::Integer8,
+        };
+        assert_eq!(v.to_string(), "%1 = trunc i32 257 to i8");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer8));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_integer_extension() {
+        let v = ZExt {
+            result: "%1".to_string(),
+            ty: Type::Integer1,
+            value: "true".to_string(),
+            ty2: Type::Integer32,
+        };
+        assert_eq!(v.to_string(), "%1 = zext i1 true to i32");
+        let v = SExt {
+            result: "%2".to_string(),
+            ty: Type::Integer8,
+            value: "-1".to_string(),
+            ty2: Type::Integer64,
+        };
+        assert_eq!(v.to_string(), "%2 = sext i8 -1 to i64");
+        assert_eq!(v.get_type(), Some(Type::Integer64));
+    }
+
+    #[test]
+    fn test_floating_point_conversion() {
+        let v = FPTrunc {
+            result: "%1".to_string(),
+            ty: Type::FloatingPoint(FloatingPointType::Double),
+            value: "%0".to_string(),
+            ty2: Type::FloatingPoint(FloatingPointType::Float),
+        };
+        assert_eq!(v.to_string(), "%1 = fptrunc double %0 to float");
+        let v = FPExt {
+            result: "%2".to_string(),
+            ty: Type::FloatingPoint(FloatingPointType::Float),
+            value: "%1".to_string(),
+            ty2: Type::FloatingPoint(FloatingPointType::Double),
+        };
+        assert_eq!(v.to_string(), "%2 = fpext float %1 to double");
+        let v = FPToUI {
+            result: "%3".to_string(),
+            ty: Type::FloatingPoint(FloatingPointType::Double),
+            value: "%2".to_string(),
+            ty2: Type::Integer8,
+        };
+        assert_eq!(v.to_string(), "%3 = fptoui double %2 to i8");
+        let v = FPToSI {
+            result: "%4".to_string(),
+            ty: Type::FloatingPoint(FloatingPointType::Double),
+            value: "%2".to_string(),
+            ty2: Type::Integer32,
+        };
+        assert_eq!(v.to_string(), "%4 = fptosi double %2 to i32");
+        let v = UIToFP {
+            result: "%5".to_string(),
+            ty: Type::Integer8,
+            value: "%3".to_string(),
+            ty2: Type::FloatingPoint(FloatingPointType::Double),
+        };
+        assert_eq!(v.to_string(), "%5 = uitofp i8 %3 to double");
+        let v = SIToFP {
+            result: "%6".to_string(),
+            ty: Type::Integer32,
+            value: "%4".to_string(),
+            ty2: Type::FloatingPoint(FloatingPointType::Double),
+        };
+        assert_eq!(v.to_string(), "%6 = sitofp i32 %4 to double");
+        assert_eq!(
+            v.get_type(),
+            Some(Type::FloatingPoint(FloatingPointType::Double))
+        );
+    }
+
+    #[test]
+    fn test_pointer_conversion() {
+        let v = PtrToInt {
+            result: "%1".to_string(),
+            ty: Type::pointer1(Type::Integer8),
+            value: "%0".to_string(),
+            ty2: Type::Integer64,
+        };
+        assert_eq!(v.to_string(), "%1 = ptrtoint ptr %0 to i64");
+        let v = IntToPtr {
+            result: "%2".to_string(),
+            ty: Type::Integer64,
+            value: "%1".to_string(),
+            ty2: Type::pointer1(Type::Integer32),
+        };
+        assert_eq!(v.to_string(), "%2 = inttoptr i64 %1 to ptr");
+        let v = BitCast {
+            result: "%3".to_string(),
+            ty: Type::pointer1(Type::Integer32),
+            value: "%2".to_string(),
+            ty2: Type::pointer1(Type::Integer8),
+        };
+        assert_eq!(v.to_string(), "%3 = bitcast ptr %2 to ptr");
+        let v = AddrSpaceCast {
+            result: "%4".to_string(),
+            ty: Type::pointer1(Type::Integer8),
+            value: "%3".to_string(),
+            ty2: Type::pointer1(Type::Integer8),
+        };
+        assert_eq!(v.to_string(), "%4 = addrspacecast ptr %3 to ptr");
+        assert_eq!(v.get_type(), Some(Type::pointer1(Type::Integer8)));
+    }
+
+    #[test]
+    fn test_conversion_macro() {
+        use crate::llvm::{
+            context::Context,
+            types::Type::{
+                Integer32,
+                Integer8,
+            },
+        };
+
+        let double = Type::FloatingPoint(FloatingPointType::Double);
+        let v = conversion!(SIToFP Integer32 "%1" => double, "%res");
+        assert_eq!(v.to_string(), "%res = sitofp i32 %1 to double");
+
+        let mut ctx = Context::new();
+        ctx.value();
+        let mut v = conversion!(ZExt Integer8 "%0" => Integer32);
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%1 = zext i8 %0 to i32");
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+}
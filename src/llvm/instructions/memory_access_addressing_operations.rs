@@ -10,6 +10,12 @@
 use crate::llvm::{
     addrspace::AddrSpace,
     align::Alignment,
+    atomic_ordering::{
+        AtomicOrdering,
+        SyncScope,
+    },
+    context::Context,
+    type_system::aggregate::StructureType,
     types::Type,
     InstructionSet,
 };
@@ -50,16 +56,23 @@ pub struct Alloca {
     pub elements: Option<Vec<(Type, i64)>>,
     pub align: Option<Alignment>,
     pub addrspace: Option<AddrSpace>,
-    pub ctx: Option<u64>,
 }
 
 impl InstructionSet for Alloca {
-    fn set_context(&mut self, ctx: u64) {
-        self.ctx = Some(ctx);
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
     }
     fn is_assignment(&self) -> bool {
         true
     }
+    fn get_type(&self) -> Option<Type> {
+        Some(Type::pointer1(self.alloc_ty.clone()))
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
 }
 
 /// The ‘load’ instruction is used to read from memory.
@@ -70,10 +83,17 @@ impl InstructionSet for Alloca {
 /// marked as volatile, then the optimizer is not allowed to modify the number
 /// or order of execution of this load with other volatile operations.
 ///
+/// If the load is marked as atomic, it takes an extra ordering and
+/// optional syncscope argument. The release and acq_rel orderings
+/// are not valid on load instructions. Atomic loads require explicit
+/// alignment.
+///
 /// https://llvm.org/docs/LangRef.html#load-instruction
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Load {
     pub result: String,
+    pub atomic: Option<AtomicOrdering>,
+    pub syncscope: Option<SyncScope>,
     pub volatile: Option<()>,
     pub ty: Type,
     pub ty_pointer: Type,
@@ -81,6 +101,29 @@ pub struct Load {
     pub align: Option<Alignment>,
 }
 
+impl InstructionSet for Load {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.ty.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![(
+            Type::pointer1(self.ty_pointer.clone()),
+            self.pointer.clone(),
+        )]
+    }
+}
+
 /// The ‘store’ instruction is used to write to memory.
 ///
 /// There are two arguments to the store instruction: a value to
@@ -92,41 +135,34 @@ pub struct Load {
 /// values of first class types of known size (i.e. not containing
 /// an opaque structural type) can be stored.
 ///
+/// If the store is marked as atomic, it takes an extra ordering and
+/// optional syncscope argument. The acquire and acq_rel orderings
+/// aren’t valid on store instructions. Atomic stores require explicit
+/// alignment.
+///
 /// https://llvm.org/docs/LangRef.html#store-instruction
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Store {
+    pub atomic: Option<AtomicOrdering>,
+    pub syncscope: Option<SyncScope>,
     pub volatile: Option<()>,
     pub ty: Type,
     pub value: String,
     pub ty_pointer: Type,
     pub pointer: String,
     pub align: Option<Alignment>,
-    pub ctx: Option<u64>,
-}
-
-impl Store {
-    pub fn get_value_name(&self) -> Option<String> {
-        let res = if let Some(ctx) = self.ctx {
-            ctx.to_string()
-        } else {
-            self.pointer.clone()
-        };
-        Some(res)
-    }
 }
 
 impl InstructionSet for Store {
-    fn set_context(&mut self, ctx: u64) {
-        self.ctx = Some(ctx);
-    }
-    fn is_read_only_context(&self) -> bool {
-        true
-    }
-    fn get_type(&self) -> Option<Type> {
-        Some(self.ty.clone())
-    }
-    fn get_value(&self) -> Option<String> {
-        self.get_value_name()
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (self.ty.clone(), self.value.clone()),
+            (
+                Type::pointer1(self.ty_pointer.clone()),
+                self.pointer.clone(),
+            ),
+        ]
     }
 }
 
@@ -156,19 +192,202 @@ pub struct GetElementPtr {
     pub range_val: Vec<(Option<()>, Type, u64)>,
 }
 
+impl InstructionSet for GetElementPtr {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    /// Pointer to element addressed by indices. First index
+    /// addresses pointer itself, so it doesn't change type.
+    fn get_type(&self) -> Option<Type> {
+        self.range_val
+            .iter()
+            .skip(1)
+            .try_fold(self.ty.clone(), |ty, (_, _, idx)| ty.indexed_type(*idx))
+            .map(Type::pointer1)
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![(
+            Type::pointer1(self.ty_pointer.clone()),
+            self.ptr_val.clone(),
+        )]
+    }
+}
+
+/// The ‘fence’ instruction is used to introduce happens-before edges
+/// between operations.
+///
+/// ‘fence’ instructions take an ordering argument which defines what
+/// synchronizes-with edges they add. They can only be given acquire,
+/// release, acq_rel, and seq_cst orderings.
+///
+/// A fence which has seq_cst ordering, in addition to having both
+/// acquire and release semantics specified above, participates in
+/// the global program order of other seq_cst operations and/or
+/// fences.
+///
+/// https://llvm.org/docs/LangRef.html#fence-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Fence {
+    pub syncscope: Option<SyncScope>,
+    pub ordering: AtomicOrdering,
+}
+
+impl InstructionSet for Fence {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+}
+
+/// The ‘cmpxchg’ instruction is used to atomically modify memory.
+/// It loads a value in memory and compares it to a given value. If
+/// they are equal, it tries to store a new value into the memory.
+///
+/// There are three arguments to the ‘cmpxchg’ instruction: an
+/// address to operate on, a value to compare to the value currently
+/// be at that address, and a new value to place at that address if
+/// the compared values are equal. The type of ‘<cmp>’ must be an
+/// integer or pointer type whose bit width is a power of two greater
+/// than or equal to eight. ‘<cmp>’ and ‘<new>’ must have the same
+/// type, and the type of ‘<pointer>’ must be a pointer to that type.
+///
+/// The ordering arguments specify what synchronization each
+/// ‘cmpxchg’ operation has on success and on failure. The failure
+/// ordering can't be release or acq_rel.
+///
+/// The original value at the location is returned, together with a
+/// flag indicating success (true) or failure (false): `{ ty, i1 }`.
+/// If the cmpxchg operation is marked as weak, then a spurious
+/// failure is permitted.
+///
+/// https://llvm.org/docs/LangRef.html#cmpxchg-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Cmpxchg {
+    pub result: String,
+    pub weak: Option<()>,
+    pub volatile: Option<()>,
+    pub ty: Type,
+    pub pointer: String,
+    pub cmp: String,
+    pub new: String,
+    pub syncscope: Option<SyncScope>,
+    pub success_ordering: AtomicOrdering,
+    pub failure_ordering: AtomicOrdering,
+    pub align: Option<Alignment>,
+}
+
+impl InstructionSet for Cmpxchg {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(Type::Structure(StructureType {
+            literal: true,
+            packed: false,
+            type_list: vec![self.ty.clone(), Type::Integer1],
+        }))
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (Type::pointer1(self.ty.clone()), self.pointer.clone()),
+            (self.ty.clone(), self.cmp.clone()),
+            (self.ty.clone(), self.new.clone()),
+        ]
+    }
+}
+
+/// Operation of ‘atomicrmw’ instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum AtomicRMWOperation {
+    Xchg,
+    Add,
+    Sub,
+    And,
+    Nand,
+    Or,
+    Xor,
+    Max,
+    Min,
+    UMax,
+    UMin,
+    FAdd,
+    FSub,
+    FMax,
+    FMin,
+}
+
+/// The ‘atomicrmw’ instruction is used to atomically modify memory.
+///
+/// There are three arguments to the ‘atomicrmw’ instruction: an
+/// operation to apply, an address whose value to modify, an argument
+/// to the operation. For xchg, this may be any integer or floating
+/// point type whose bit width is a power of two greater than or
+/// equal to eight. For add, sub, and, nand, or, xor, max, min, umax,
+/// and umin, the type must be an integer type. For fadd/fsub/fmax/fmin,
+/// the type must be a floating point type.
+///
+/// The contents of memory at the location specified by the
+/// ‘<pointer>’ operand are atomically read, modified, and written
+/// back. The original value at the location is returned.
+///
+/// https://llvm.org/docs/LangRef.html#atomicrmw-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AtomicRMW {
+    pub result: String,
+    pub volatile: Option<()>,
+    pub operation: AtomicRMWOperation,
+    pub ty: Type,
+    pub pointer: String,
+    pub value: String,
+    pub syncscope: Option<SyncScope>,
+    pub ordering: AtomicOrdering,
+    pub align: Option<Alignment>,
+}
+
+impl InstructionSet for AtomicRMW {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.ty.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (Type::pointer1(self.ty.clone()), self.pointer.clone()),
+            (self.ty.clone(), self.value.clone()),
+        ]
+    }
+}
+
 impl std::fmt::Display for Alloca {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let result = if let Some(ctx) = self.ctx {
-            ctx.to_string()
-        } else {
-            self.result.clone()
-        };
-        let mut s = format!("%{} = alloca {}", result, self.alloc_ty);
+        let mut s = format!("{} = alloca {}", self.result, self.alloc_ty);
         if let Some(el) = &self.elements {
             let els = el
                 .iter()
                 .fold("".to_string(), |s, v| format!("{}, {} {}", s, v.0, v.1));
-            s = format!("{} {}", s, els);
+            s = format!("{}{}", s, els);
         }
         if let Some(v) = &self.align {
             s = format!("{}, {}", s, v);
@@ -182,11 +401,21 @@ impl std::fmt::Display for Alloca {
 
 impl std::fmt::Display for Load {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut s = format!("%{} = load", self.result);
+        let mut s = format!("{} = load", self.result);
+        if self.atomic.is_some() {
+            s = format!("{} atomic", s);
+        }
         if self.volatile.is_some() {
             s = format!("{} volatile", s);
         }
-        s = format!("{} {}, {}* {}", s, self.ty, self.ty_pointer, self.pointer);
+        let ty_pointer = Type::pointer1(self.ty_pointer.clone());
+        s = format!("{} {}, {} {}", s, self.ty, ty_pointer, self.pointer);
+        if let Some(v) = &self.syncscope {
+            s = format!("{} {}", s, v);
+        }
+        if let Some(v) = &self.atomic {
+            s = format!("{} {}", s, v);
+        }
         if let Some(v) = &self.align {
             s = format!("{}, {}", s, v);
         }
@@ -197,18 +426,23 @@ impl std::fmt::Display for Load {
 impl std::fmt::Display for Store {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut s = "store".to_string();
+        if self.atomic.is_some() {
+            s = format!("{} atomic", s);
+        }
         if self.volatile.is_some() {
             s = format!("{} volatile", s);
         }
-        let pointer = if let Some(ctx) = self.ctx {
-            ctx.to_string()
-        } else {
-            self.pointer.clone()
-        };
+        let ty_pointer = Type::pointer1(self.ty_pointer.clone());
         s = format!(
-            "{} {} {}, {}* {}",
-            s, self.ty, self.value, self.ty_pointer, pointer
+            "{} {} {}, {} {}",
+            s, self.ty, self.value, ty_pointer, self.pointer
         );
+        if let Some(v) = &self.syncscope {
+            s = format!("{} {}", s, v);
+        }
+        if let Some(v) = &self.atomic {
+            s = format!("{} {}", s, v);
+        }
         if let Some(v) = &self.align {
             s = format!("{}, {}", s, v);
         }
@@ -218,11 +452,12 @@ impl std::fmt::Display for Store {
 
 impl std::fmt::Display for GetElementPtr {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut s = format!("%{} = getelementptr", self.result);
+        let mut s = format!("{} = getelementptr", self.result);
         if self.inbounds.is_some() {
             s = format!("{} inbounds", s);
         }
-        let s_val = format!("{}, {}* {}", self.ty, self.ty_pointer, self.ptr_val);
+        let ty_pointer = Type::pointer1(self.ty_pointer.clone());
+        let s_val = format!("{}, {} {}", self.ty, ty_pointer, self.ptr_val);
         let r = self.range_val.iter().fold("".to_string(), |s, v| {
             if v.0.is_some() {
                 format!("{}, inrange {} {}", s, v.1, v.2)
@@ -230,6 +465,283 @@ impl std::fmt::Display for GetElementPtr {
                 format!("{}, {} {}", s, v.1, v.2)
             }
         });
-        write!(f, "{} ({}{})", s, s_val, r)
+        write!(f, "{} {}{}", s, s_val, r)
+    }
+}
+
+impl std::fmt::Display for Fence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = "fence".to_string();
+        if let Some(v) = &self.syncscope {
+            s = format!("{} {}", s, v);
+        }
+        write!(f, "{} {}", s, self.ordering)
+    }
+}
+
+impl std::fmt::Display for Cmpxchg {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = format!("{} = cmpxchg", self.result);
+        if self.weak.is_some() {
+            s = format!("{} weak", s);
+        }
+        if self.volatile.is_some() {
+            s = format!("{} volatile", s);
+        }
+        let ty_pointer = Type::pointer1(self.ty.clone());
+        s = format!(
+            "{} {} {}, {} {}, {} {}",
+            s, ty_pointer, self.pointer, self.ty, self.cmp, self.ty, self.new
+        );
+        if let Some(v) = &self.syncscope {
+            s = format!("{} {}", s, v);
+        }
+        s = format!("{} {} {}", s, self.success_ordering, self.failure_ordering);
+        if let Some(v) = &self.align {
+            s = format!("{}, {}", s, v);
+        }
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for AtomicRMWOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            AtomicRMWOperation::Xchg => "xchg",
+            AtomicRMWOperation::Add => "add",
+            AtomicRMWOperation::Sub => "sub",
+            AtomicRMWOperation::And => "and",
+            AtomicRMWOperation::Nand => "nand",
+            AtomicRMWOperation::Or => "or",
+            AtomicRMWOperation::Xor => "xor",
+            AtomicRMWOperation::Max => "max",
+            AtomicRMWOperation::Min => "min",
+            AtomicRMWOperation::UMax => "umax",
+            AtomicRMWOperation::UMin => "umin",
+            AtomicRMWOperation::FAdd => "fadd",
+            AtomicRMWOperation::FSub => "fsub",
+            AtomicRMWOperation::FMax => "fmax",
+            AtomicRMWOperation::FMin => "fmin",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for AtomicRMW {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = format!("{} = atomicrmw", self.result);
+        if self.volatile.is_some() {
+            s = format!("{} volatile", s);
+        }
+        let ty_pointer = Type::pointer1(self.ty.clone());
+        s = format!(
+            "{} {} {} {}, {} {}",
+            s, self.operation, ty_pointer, self.pointer, self.ty, self.value
+        );
+        if let Some(v) = &self.syncscope {
+            s = format!("{} {}", s, v);
+        }
+        s = format!("{} {}", s, self.ordering);
+        if let Some(v) = &self.align {
+            s = format!("{}, {}", s, v);
+        }
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::type_system::aggregate::ArrayType;
+
+    #[test]
+    fn test_alloca() {
+        let mut v = Alloca {
+            result: "".to_string(),
+            alloc_ty: Type::Integer32,
+            elements: Some(vec![(Type::Integer32, 4)]),
+            align: Some(Alignment(4)),
+            addrspace: None,
+        };
+        let mut ctx = Context::new();
+        ctx.label();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%1 = alloca i32, i32 4, align 4");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::pointer1(Type::Integer32)));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_load() {
+        let mut v = Load {
+            result: "%2".to_string(),
+            atomic: None,
+            syncscope: None,
+            volatile: Some(()),
+            ty: Type::Integer32,
+            ty_pointer: Type::Integer32,
+            pointer: "%1".to_string(),
+            align: Some(Alignment(4)),
+        };
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%2 = load volatile i32, ptr %1, align 4");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer32));
+        assert_eq!(v.get_value(), Some("%2".to_string()));
+        assert_eq!(ctx.count(), 0);
+    }
+
+    #[test]
+    fn test_store() {
+        let mut v = Store {
+            atomic: None,
+            syncscope: None,
+            volatile: None,
+            ty: Type::Integer32,
+            value: "33".to_string(),
+            ty_pointer: Type::Integer32,
+            pointer: "%1".to_string(),
+            align: None,
+        };
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "store i32 33, ptr %1");
+        assert!(!v.is_assignment());
+        assert_eq!(v.get_type(), None);
+        assert_eq!(v.get_value(), None);
+        assert_eq!(ctx.count(), 0);
+    }
+
+    #[test]
+    fn test_getelementptr() {
+        let ty = Type::Array(ArrayType(6, Box::new(Type::Integer8)));
+        let mut v = GetElementPtr {
+            result: "".to_string(),
+            inbounds: Some(()),
+            ty: ty.clone(),
+            ty_pointer: ty,
+            ptr_val: "@.str".to_string(),
+            range_val: vec![(None, Type::Integer64, 0), (None, Type::Integer64, 2)],
+        };
+        let mut ctx = Context::new();
+        ctx.label();
+        v.set_context(&mut ctx);
+        assert_eq!(
+            v.to_string(),
+            "%1 = getelementptr inbounds [6 x i8], ptr @.str, i64 0, i64 2"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::pointer1(Type::Integer8)));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_atomic_load_store() {
+        let v = Load {
+            result: "%1".to_string(),
+            atomic: Some(AtomicOrdering::Acquire),
+            syncscope: Some(SyncScope("singlethread".to_string())),
+            volatile: None,
+            ty: Type::Integer32,
+            ty_pointer: Type::Integer32,
+            pointer: "@x".to_string(),
+            align: Some(Alignment(4)),
+        };
+        assert_eq!(
+            v.to_string(),
+            r#"%1 = load atomic i32, ptr @x syncscope("singlethread") acquire, align 4"#
+        );
+        let v = Store {
+            atomic: Some(AtomicOrdering::Release),
+            syncscope: None,
+            volatile: Some(()),
+            ty: Type::Integer32,
+            value: "%1".to_string(),
+            ty_pointer: Type::Integer32,
+            pointer: "@x".to_string(),
+            align: Some(Alignment(4)),
+        };
+        assert_eq!(
+            v.to_string(),
+            "store atomic volatile i32 %1, ptr @x release, align 4"
+        );
+    }
+
+    #[test]
+    fn test_fence() {
+        let mut v = Fence {
+            syncscope: None,
+            ordering: AtomicOrdering::SeqCst,
+        };
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "fence seq_cst");
+        assert!(!v.is_assignment());
+        assert_eq!(ctx.count(), 0);
+        let v = Fence {
+            syncscope: Some(SyncScope("singlethread".to_string())),
+            ordering: AtomicOrdering::AcqRel,
+        };
+        assert_eq!(v.to_string(), r#"fence syncscope("singlethread") acq_rel"#);
+    }
+
+    #[test]
+    fn test_cmpxchg() {
+        let mut v = Cmpxchg {
+            result: "".to_string(),
+            weak: Some(()),
+            volatile: None,
+            ty: Type::Integer32,
+            pointer: "%ptr".to_string(),
+            cmp: "%cmp".to_string(),
+            new: "%new".to_string(),
+            syncscope: None,
+            success_ordering: AtomicOrdering::AcqRel,
+            failure_ordering: AtomicOrdering::Monotonic,
+            align: Some(Alignment(4)),
+        };
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(
+            v.to_string(),
+            "%0 = cmpxchg weak ptr %ptr, i32 %cmp, i32 %new acq_rel monotonic, align 4"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(
+            v.get_type(),
+            Some(Type::Structure(StructureType {
+                literal: true,
+                packed: false,
+                type_list: vec![Type::Integer32, Type::Integer1],
+            }))
+        );
+        assert_eq!(v.get_value(), Some("%0".to_string()));
+    }
+
+    #[test]
+    fn test_atomicrmw() {
+        let mut v = AtomicRMW {
+            result: "".to_string(),
+            volatile: None,
+            operation: AtomicRMWOperation::Add,
+            ty: Type::Integer32,
+            pointer: "@counter".to_string(),
+            value: "1".to_string(),
+            syncscope: None,
+            ordering: AtomicOrdering::SeqCst,
+            align: None,
+        };
+        let mut ctx = Context::new();
+        ctx.label();
+        v.set_context(&mut ctx);
+        assert_eq!(
+            v.to_string(),
+            "%1 = atomicrmw add ptr @counter, i32 1 seq_cst"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer32));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
     }
 }
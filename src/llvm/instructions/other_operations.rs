@@ -9,11 +9,13 @@ use crate::llvm::functions::ArgumentList;
 use crate::llvm::{
     addrspace::AddrSpace,
     calling_convention::CallingConvention,
+    context::Context,
     fast_math_flags::FastMathFlags,
     function_attributes::FunctionAttributes,
     instructions::terminator::FunctionArg,
     parameter_attributes::ParameterAttributes,
     types::Type,
+    InstructionSet,
 };
 
 /// The ‘icmp’ instruction returns a boolean value or a vector of
@@ -244,6 +246,135 @@ pub struct Call {
     pub operand_bundles: Option<String>,
 }
 
+impl InstructionSet for Icmp {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.res_val.is_empty() {
+            self.res_val = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.ty.compare_result())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.res_val.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (self.ty.clone(), self.op1.clone()),
+            (self.ty.clone(), self.op2.clone()),
+        ]
+    }
+}
+
+impl InstructionSet for Fcmp {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.res_val.is_empty() {
+            self.res_val = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.ty.compare_result())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.res_val.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (self.ty.clone(), self.op1.clone()),
+            (self.ty.clone(), self.op2.clone()),
+        ]
+    }
+}
+
+impl InstructionSet for Phi {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.res_val.is_empty() {
+            self.res_val = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.ty.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.res_val.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        self.params
+            .iter()
+            .map(|(value, _)| (self.ty.clone(), value.clone()))
+            .collect()
+    }
+    fn is_phi(&self) -> bool {
+        true
+    }
+}
+
+impl InstructionSet for Select {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.res_val.is_empty() {
+            self.res_val = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.ty1.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.res_val.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (self.selty.clone(), self.cond.clone()),
+            (self.ty1.clone(), self.val1.clone()),
+            (self.ty2.clone(), self.val2.clone()),
+        ]
+    }
+}
+
+impl InstructionSet for Call {
+    /// Non-void calls always produce value, so unnamed result
+    /// value allocated for them.
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.ty != Type::Void && self.ret_val.as_deref().unwrap_or_default().is_empty() {
+            self.ret_val = Some(ctx.value());
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        self.ret_val.is_some()
+    }
+    fn get_type(&self) -> Option<Type> {
+        if self.ty == Type::Void {
+            None
+        } else {
+            Some(self.ty.clone())
+        }
+    }
+    fn get_value(&self) -> Option<String> {
+        self.ret_val.clone()
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        self.function_args
+            .iter()
+            .map(|FunctionArg(ty, value)| (ty.clone(), value.clone()))
+            .collect()
+    }
+    fn callee(&self) -> Option<String> {
+        let sigil = if self.fnptrval.0 { "%" } else { "@" };
+        Some(format!("{}{}", sigil, self.fnptrval.1))
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum TailCall {
     Tail,
@@ -259,6 +390,29 @@ pub struct Landingpad {
     pub clause: Vec<Clause>,
 }
 
+impl InstructionSet for Landingpad {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.resultval.is_empty() {
+            self.resultval = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.resultty.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.resultval.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        self.clause
+            .iter()
+            .map(|c| (c.clause_type.clone(), c.value.clone()))
+            .collect()
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Clause {
     pub clause_type: Type,
@@ -268,7 +422,7 @@ pub struct Clause {
 impl std::fmt::Display for Icmp {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let s = format!(
-            "%{} = icmp {} {} {}, {}",
+            "{} = icmp {} {} {}, {}",
             self.res_val, self.cond, self.ty, self.op1, self.op2
         );
         write!(f, "{}", s)
@@ -277,14 +431,13 @@ impl std::fmt::Display for Icmp {
 
 impl std::fmt::Display for Fcmp {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let fast_math = if let Some(v) = &self.fast_math_flags {
-            format!("{}", v)
-        } else {
-            "".to_string()
-        };
+        let mut s = "fcmp".to_string();
+        if let Some(v) = &self.fast_math_flags {
+            s = format!("{} {}", s, v)
+        }
         let s = format!(
-            "%{} = fcmp {} {} {} {}, {}",
-            self.res_val, fast_math, self.cond, self.ty, self.op1, self.op2
+            "{} = {} {} {} {}, {}",
+            self.res_val, s, self.cond, self.ty, self.op1, self.op2
         );
         write!(f, "{}", s)
     }
@@ -334,48 +487,36 @@ impl std::fmt::Display for FcmpCondition {
 
 impl std::fmt::Display for Phi {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let fast_math = if let Some(v) = &self.fast_math_flags {
-            format!("{}", v)
-        } else {
-            "".to_string()
-        };
+        let mut s = "phi".to_string();
+        if let Some(v) = &self.fast_math_flags {
+            s = format!("{} {}", s, v)
+        }
         let params = self
             .params
             .iter()
             .enumerate()
             .fold("".to_string(), |s, (i, v)| {
                 if i > 0 {
-                    format!("{}, [{}, {}]", s, v.0, v.1)
+                    format!("{}, [ {}, %{} ]", s, v.0, v.1)
                 } else {
-                    format!("{} [{}, {}]", s, v.0, v.1)
+                    format!("[ {}, %{} ]", v.0, v.1)
                 }
             });
 
-        let s = format!(
-            "%{} = phi {} {} {}",
-            self.res_val, fast_math, self.ty, params
-        );
+        let s = format!("{} = {} {} {}", self.res_val, s, self.ty, params);
         write!(f, "{}", s)
     }
 }
 
 impl std::fmt::Display for Select {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let fast_math = if let Some(v) = &self.fast_math_flags {
-            format!("{}", v)
-        } else {
-            "".to_string()
-        };
+        let mut s = "select".to_string();
+        if let Some(v) = &self.fast_math_flags {
+            s = format!("{} {}", s, v)
+        }
         let s = format!(
-            "%{} = select {} {} {}, {} {}, {} {}",
-            self.res_val,
-            fast_math,
-            self.selty,
-            self.cond,
-            self.ty1,
-            self.val1,
-            self.ty2,
-            self.val2,
+            "{} = {} {} {}, {} {}, {} {}",
+            self.res_val, s, self.selty, self.cond, self.ty1, self.val1, self.ty2, self.val2,
         );
         write!(f, "{}", s)
     }
@@ -399,17 +540,14 @@ impl std::fmt::Display for Call {
         } else {
             "".to_string()
         };
-        let fast_math = if let Some(v) = &self.fast_math_flags {
-            format!("{}", v)
-        } else {
-            "".to_string()
-        };
-
         let mut s = if let Some(v) = &self.ret_val {
-            format!("%{} = {}call {}", v, tail, fast_math)
+            format!("{} = {}call", v, tail)
         } else {
-            format!("{}call {}", tail, fast_math)
+            format!("{}call", tail)
         };
+        if let Some(v) = &self.fast_math_flags {
+            s = format!("{} {}", s, v)
+        }
         if let Some(v) = &self.cconv {
             s = format!("{} {}", s, v)
         }
@@ -419,7 +557,7 @@ impl std::fmt::Display for Call {
         if let Some(v) = &self.addrspace {
             s = format!("{} {}", s, v)
         }
-        s = format!("{}{}", s, &self.ty);
+        s = format!("{} {}", s, &self.ty);
         if !self.fnty.is_empty() {
             let arg = self
                 .fnty
@@ -465,16 +603,11 @@ impl std::fmt::Display for Call {
 
 impl std::fmt::Display for Landingpad {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut s = format!("{} = landingpad {} ", self.resultval, self.resultty);
+        let mut s = format!("{} = landingpad {}", self.resultval, self.resultty);
         if self.cleanup.is_some() {
             s = format!("{} cleanup", s);
         }
-        let clause = self
-            .clause
-            .iter()
-            .fold("".to_string(), |s, v| format!("{} {}", s, v));
-
-        s = format!("{} {}", s, clause);
+        let s = self.clause.iter().fold(s, |s, v| format!("{} {}", s, v));
 
         write!(f, "{}", s)
     }
@@ -485,3 +618,188 @@ impl std::fmt::Display for Clause {
         write!(f, "catch {} {}", self.clause_type, self.value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::type_system::{
+        aggregate::StructureType,
+        single_value::{
+            FloatingPointType,
+            VectorType,
+        },
+    };
+
+    fn double() -> Type {
+        Type::FloatingPoint(FloatingPointType::Double)
+    }
+
+    #[test]
+    fn test_icmp() {
+        let mut v = Icmp {
+            res_val: "".to_string(),
+            cond: IcmpCondition::Sge,
+            ty: Type::Integer32,
+            op1: "%0".to_string(),
+            op2: "10".to_string(),
+        };
+        let mut ctx = Context::new();
+        ctx.value();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%1 = icmp sge i32 %0, 10");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer1));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_icmp_vector() {
+        let vector = |ty| {
+            Type::Vector(VectorType {
+                elemetns: 4,
+                element_type: Box::new(ty),
+                vscale: false,
+            })
+        };
+        let v = Icmp {
+            res_val: "%1".to_string(),
+            cond: IcmpCondition::Eq,
+            ty: vector(Type::Integer32),
+            op1: "%0".to_string(),
+            op2: "zeroinitializer".to_string(),
+        };
+        assert_eq!(v.to_string(), "%1 = icmp eq <4 x i32> %0, zeroinitializer");
+        assert_eq!(v.get_type(), Some(vector(Type::Integer1)));
+    }
+
+    #[test]
+    fn test_fcmp() {
+        let v = Fcmp {
+            res_val: "%1".to_string(),
+            cond: FcmpCondition::Olt,
+            fast_math_flags: Some(FastMathFlags::Fast),
+            ty: double(),
+            op1: "%0".to_string(),
+            op2: "1.0".to_string(),
+        };
+        assert_eq!(v.to_string(), "%1 = fcmp fast olt double %0, 1.0");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer1));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_phi() {
+        let v = Phi {
+            res_val: "%3".to_string(),
+            fast_math_flags: None,
+            ty: Type::Integer32,
+            params: vec![
+                ("0".to_string(), "entry".to_string()),
+                ("%2".to_string(), "1".to_string()),
+            ],
+        };
+        assert_eq!(v.to_string(), "%3 = phi i32 [ 0, %entry ], [ %2, %1 ]");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer32));
+        assert_eq!(v.get_value(), Some("%3".to_string()));
+    }
+
+    #[test]
+    fn test_select() {
+        let v = Select {
+            res_val: "%2".to_string(),
+            fast_math_flags: Some(FastMathFlags::Nnan),
+            selty: Type::Integer1,
+            cond: "%1".to_string(),
+            ty1: double(),
+            val1: "1.0".to_string(),
+            ty2: double(),
+            val2: "2.0".to_string(),
+        };
+        assert_eq!(
+            v.to_string(),
+            "%2 = select nnan i1 %1, double 1.0, double 2.0"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(double()));
+        assert_eq!(v.get_value(), Some("%2".to_string()));
+    }
+
+    fn call(ty: Type) -> Call {
+        Call {
+            ret_val: None,
+            tail: Some(TailCall::Tail),
+            fast_math_flags: None,
+            cconv: None,
+            ret_attr: None,
+            addrspace: None,
+            ty,
+            fnty: vec![],
+            fnptrval: (false, "f".to_string()),
+            function_args: vec![FunctionArg(Type::Integer32, "%0".to_string())],
+            function_attrs: None,
+            operand_bundles: None,
+        }
+    }
+
+    #[test]
+    fn test_call() {
+        let mut v = call(Type::Integer32);
+        let mut ctx = Context::new();
+        ctx.value();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%1 = tail call i32 @f(i32 %0)");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer32));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+
+    #[test]
+    fn test_call_void() {
+        let mut v = call(Type::Void);
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "tail call void @f(i32 %0)");
+        assert!(!v.is_assignment());
+        assert_eq!(v.get_type(), None);
+        assert_eq!(v.get_value(), None);
+        assert_eq!(ctx.count(), 0);
+    }
+
+    #[test]
+    fn test_call_variadic() {
+        let ty1 = Type::pointer1(Type::Integer8);
+        let ty2 = ty1.clone();
+        let printf = "printf";
+        use crate::llvm::types::Type::Integer32;
+        let v =
+            call!(Integer32 "%2".to_string() => @printf arg!(ty1, ...) => [ty2 "%1".to_string()]);
+        assert_eq!(v.to_string(), "%2 = call i32 (ptr, ...) @printf(ptr %1)");
+    }
+
+    #[test]
+    fn test_landingpad() {
+        let ty = Type::Structure(StructureType {
+            literal: true,
+            packed: false,
+            type_list: vec![Type::pointer1(Type::Integer8), Type::Integer32],
+        });
+        let v = Landingpad {
+            resultval: "%1".to_string(),
+            resultty: ty.clone(),
+            cleanup: Some(()),
+            clause: vec![Clause {
+                clause_type: Type::pointer1(Type::Integer8),
+                value: "null".to_string(),
+            }],
+        };
+        assert_eq!(
+            v.to_string(),
+            "%1 = landingpad { ptr, i32 } cleanup catch ptr null"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(ty));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+    }
+}
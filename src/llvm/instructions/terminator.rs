@@ -9,9 +9,11 @@
 //! https://llvm.org/docs/LangRef.html#terminator-instructions
 use crate::llvm::addrspace::AddrSpace;
 use crate::llvm::calling_convention::CallingConvention;
+use crate::llvm::context::Context;
 use crate::llvm::function_attributes::FunctionAttributes;
 use crate::llvm::parameter_attributes::ParameterAttributes;
 use crate::llvm::types::Type;
+use crate::llvm::InstructionSet;
 
 /// The ‘ret’ instruction is used to return control flow (and optionally
 /// a value) from a function back to the caller.
@@ -32,10 +34,12 @@ pub struct Ret(pub Option<(Type, String)>);
 /// br i1 <cond>, label <iftrue>, label <iffalse>
 /// br label <dest>
 /// ```
+/// Condition is `i1` value, labels are basic block names.
+///
 /// https://llvm.org/docs/LangRef.html#br-instruction
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Br {
-    Conditional(bool, String, String),
+    Conditional(String, String, String),
     Unconditional(String),
 }
 
@@ -46,9 +50,14 @@ pub enum Br {
 /// ```html
 /// switch <intty> <value>, label <defaultdest> [ <intty> <val>, label <dest> ... ]
 /// ```
+/// Choices contain constant value and destination label.
+///
 /// https://llvm.org/docs/LangRef.html#switch-instruction
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Switch {
+    pub ty: Type,
+    pub value: String,
+    pub default_label: String,
     pub choices: Vec<(String, String)>,
 }
 
@@ -62,9 +71,9 @@ pub struct Switch {
 /// https://llvm.org/docs/LangRef.html#indirectbr-instruction
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct IndirectBr {
-    pub ty: String,
+    pub ty: Type,
     pub address: String,
-    pub choices: Vec<(String, String)>,
+    pub labels: Vec<String>,
 }
 
 /// The ‘invoke’ instruction causes control to transfer to a specified
@@ -144,6 +153,49 @@ pub struct Invoke {
     pub exception_label: String,
 }
 
+impl InstructionSet for Invoke {
+    /// Non-void invoke always produce value, so unnamed result
+    /// value allocated for it.
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.ty != Type::Void && self.ret_val.is_empty() {
+            self.ret_val = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        !self.ret_val.is_empty()
+    }
+    fn get_type(&self) -> Option<Type> {
+        if self.ty == Type::Void {
+            None
+        } else {
+            Some(self.ty.clone())
+        }
+    }
+    fn get_value(&self) -> Option<String> {
+        if self.ret_val.is_empty() {
+            None
+        } else {
+            Some(self.ret_val.clone())
+        }
+    }
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn successors(&self) -> Vec<String> {
+        vec![self.normal_label.clone(), self.exception_label.clone()]
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        self.function_args
+            .iter()
+            .map(|FunctionArg(ty, value)| (ty.clone(), value.clone()))
+            .collect()
+    }
+    fn callee(&self) -> Option<String> {
+        let sigil = if self.fnptrval.0 { "%" } else { "@" };
+        Some(format!("{}{}", sigil, self.fnptrval.1))
+    }
+}
+
 /// Fucntion argument contain type and their value
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct FunctionArg(pub Type, pub String);
@@ -235,6 +287,31 @@ pub struct CatchSwitch {
     pub default_label: Option<String>,
 }
 
+impl InstructionSet for CatchSwitch {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result_val.is_empty() {
+            self.result_val = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(Type::Token)
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result_val.clone())
+    }
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn successors(&self) -> Vec<String> {
+        let mut labels = self.handler_labels.clone();
+        labels.extend(self.default_label.clone());
+        labels
+    }
+}
+
 /// The ‘catchret’ instruction is a terminator instruction that has a
 /// single successor.
 ///
@@ -289,7 +366,7 @@ impl std::fmt::Display for Br {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let s = match self {
             Br::Conditional(cond, if_true, if_false) => {
-                format!("i1 %{}, label %{}, label %{}", cond, if_true, if_false)
+                format!("i1 {}, label %{}, label %{}", cond, if_true, if_false)
             }
             Br::Unconditional(dest) => format!("label %{}", dest),
         };
@@ -299,53 +376,41 @@ impl std::fmt::Display for Br {
 
 impl std::fmt::Display for Switch {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let s = self
-            .choices
-            .iter()
-            .enumerate()
-            .fold("".to_string(), |s, (i, (int_ty, label))| {
-                // First item
-                let s = if i < 1 {
-                    format!("i32 {}, label %{} [\n", int_ty, label)
-                } else {
-                    format!("{} i32 {}, label %{}\n", s, int_ty, label)
-                };
-                if i + 1 == self.choices.len() {
-                    format!("{} \n]", s)
-                } else {
-                    s
-                }
-            });
-        write!(f, "switch {}", s)
+        let choices = self.choices.iter().fold("".to_string(), |s, (val, label)| {
+            format!("{} {} {}, label %{}", s, self.ty, val, label)
+        });
+        write!(
+            f,
+            "switch {} {}, label %{} [{} ]",
+            self.ty, self.value, self.default_label, choices
+        )
     }
 }
 
 impl std::fmt::Display for IndirectBr {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let s = self
-            .choices
+        let labels = self
+            .labels
             .iter()
             .enumerate()
-            .fold("".to_string(), |s, (i, (int_ty, label))| {
-                // First item
-                let s = if i < 1 {
-                    format!("i32 {}, label %{} [\n", int_ty, label)
-                } else {
-                    format!("{} i32 {}, label %{}\n", s, int_ty, label)
-                };
-                if i + 1 == self.choices.len() {
-                    format!("{} \n]", s)
+            .fold("".to_string(), |s, (i, label)| {
+                if i > 0 {
+                    format!("{}, label %{}", s, label)
                 } else {
-                    s
+                    format!("label %{}", label)
                 }
             });
-        write!(f, "switch {}", s)
+        write!(f, "indirectbr {} {}, [ {} ]", self.ty, self.address, labels)
     }
 }
 
 impl std::fmt::Display for Invoke {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let mut s = format!("%{} = invoke", self.ret_val);
+        let mut s = if self.ret_val.is_empty() {
+            "invoke".to_string()
+        } else {
+            format!("{} = invoke", self.ret_val)
+        };
         if let Some(v) = &self.cconv {
             s = format!("{} {}", s, v)
         }
@@ -373,10 +438,10 @@ impl std::fmt::Display for Invoke {
                 if i > 0 {
                     format!("{}, {} {}", s, v.0, v.1)
                 } else {
-                    format!("{} {} {}", s, v.0, v.1)
+                    format!("{} {}", v.0, v.1)
                 }
             });
-        s = format!("{} ({})", s, args);
+        s = format!("{}({})", s, args);
         if let Some(v) = &self.function_attrs {
             s = format!("{} {}", s, v)
         }
@@ -385,7 +450,7 @@ impl std::fmt::Display for Invoke {
         }
 
         let s = format!(
-            "{} to label {} unwind label {}",
+            "{} to label %{} unwind label %{}",
             s, self.normal_label, self.exception_label
         );
 
@@ -402,7 +467,7 @@ impl std::fmt::Display for CallBr {
 
 impl std::fmt::Display for Resume {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "resume {} %{}", self.resume_type, self.value)
+        write!(f, "resume {} {}", self.resume_type, self.value)
     }
 }
 
@@ -413,7 +478,7 @@ impl std::fmt::Display for CatchSwitch {
             .iter()
             .enumerate()
             .fold("".to_string(), |s, (i, v)| {
-                if i > 1 {
+                if i > 0 {
                     format!("{}, label %{}", s, v)
                 } else {
                     format!("label %{}", v)
@@ -426,7 +491,7 @@ impl std::fmt::Display for CatchSwitch {
         };
         write!(
             f,
-            "%{} = catchswitch within %{} [{}] {}",
+            "{} = catchswitch within {} [{}] {}",
             self.result_val, self.parent, labels, default
         )
     }
@@ -436,7 +501,7 @@ impl std::fmt::Display for CatchRet {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "catchret from %{} label %{}",
+            "catchret from {} to label %{}",
             self.catch, self.continue_label
         )
     }
@@ -445,9 +510,9 @@ impl std::fmt::Display for CatchRet {
 impl std::fmt::Display for CleanupRet {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let s = if let Some(v) = &self.continue_label {
-            format!("%{} unwind label %{}", self.values, v)
+            format!("{} unwind label %{}", self.values, v)
         } else {
-            format!("%{} unwind to caller", self.values)
+            format!("{} unwind to caller", self.values)
         };
         write!(f, "cleanupret from {}", s)
     }
@@ -458,3 +523,273 @@ impl std::fmt::Display for Unreachable {
         write!(f, "unreachable")
     }
 }
+
+impl InstructionSet for Br {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn successors(&self) -> Vec<String> {
+        match self {
+            Br::Conditional(_, if_true, if_false) => vec![if_true.clone(), if_false.clone()],
+            Br::Unconditional(dest) => vec![dest.clone()],
+        }
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        match self {
+            Br::Conditional(cond, _, _) => vec![(Type::Integer1, cond.clone())],
+            Br::Unconditional(_) => vec![],
+        }
+    }
+}
+
+impl InstructionSet for Switch {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn successors(&self) -> Vec<String> {
+        let mut labels = vec![self.default_label.clone()];
+        labels.extend(self.choices.iter().map(|(_, label)| label.clone()));
+        labels
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![(self.ty.clone(), self.value.clone())]
+    }
+}
+
+impl InstructionSet for IndirectBr {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn successors(&self) -> Vec<String> {
+        self.labels.clone()
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![(self.ty.clone(), self.address.clone())]
+    }
+}
+
+impl InstructionSet for CatchRet {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn successors(&self) -> Vec<String> {
+        vec![self.continue_label.clone()]
+    }
+}
+
+impl InstructionSet for CleanupRet {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn successors(&self) -> Vec<String> {
+        self.continue_label.iter().cloned().collect()
+    }
+}
+
+impl InstructionSet for Ret {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        self.0.iter().cloned().collect()
+    }
+}
+
+impl InstructionSet for Resume {
+    fn set_context(&mut self, _ctx: &mut Context) {}
+    fn is_terminator(&self) -> bool {
+        true
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![(self.resume_type.clone(), self.value.clone())]
+    }
+}
+
+terminator_instruction_set!(Unreachable);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ret() {
+        let v = Ret(Some((Type::Integer32, "%1".to_string())));
+        assert_eq!(v.to_string(), "ret i32 %1");
+        assert!(!v.is_assignment());
+        assert_eq!(v.get_type(), None);
+        assert_eq!(v.get_value(), None);
+        assert_eq!(Ret(None).to_string(), "ret void");
+        assert!(v.is_terminator());
+        assert!(v.successors().is_empty());
+    }
+
+    #[test]
+    fn test_br() {
+        let v = Br::Conditional("%1".to_string(), "then".to_string(), "2".to_string());
+        assert_eq!(v.to_string(), "br i1 %1, label %then, label %2");
+        assert!(!v.is_assignment());
+        assert_eq!(v.get_value(), None);
+        assert!(v.is_terminator());
+        assert_eq!(v.successors(), vec!["then", "2"]);
+        let v = Br::Unconditional("exit".to_string());
+        assert_eq!(v.to_string(), "br label %exit");
+        assert_eq!(v.successors(), vec!["exit"]);
+    }
+
+    #[test]
+    fn test_switch() {
+        let v = Switch {
+            ty: Type::Integer32,
+            value: "%0".to_string(),
+            default_label: "default".to_string(),
+            choices: vec![
+                ("0".to_string(), "zero".to_string()),
+                ("1".to_string(), "one".to_string()),
+            ],
+        };
+        assert_eq!(
+            v.to_string(),
+            "switch i32 %0, label %default [ i32 0, label %zero i32 1, label %one ]"
+        );
+        assert!(!v.is_assignment());
+        assert_eq!(v.get_value(), None);
+        assert_eq!(v.successors(), vec!["default", "zero", "one"]);
+    }
+
+    #[test]
+    fn test_indirectbr() {
+        let v = IndirectBr {
+            ty: Type::pointer1(Type::Integer8),
+            address: "%addr".to_string(),
+            labels: vec!["bb1".to_string(), "bb2".to_string()],
+        };
+        assert_eq!(
+            v.to_string(),
+            "indirectbr ptr %addr, [ label %bb1, label %bb2 ]"
+        );
+        assert!(!v.is_assignment());
+        assert_eq!(v.get_value(), None);
+        assert_eq!(v.successors(), vec!["bb1", "bb2"]);
+    }
+
+    fn invoke(ty: Type) -> Invoke {
+        Invoke {
+            ret_val: "".to_string(),
+            cconv: None,
+            ret_attr: None,
+            addrspace: None,
+            ty,
+            fnty: None,
+            fnptrval: (false, "f".to_string()),
+            function_args: vec![FunctionArg(Type::Integer32, "1".to_string())],
+            function_attrs: None,
+            operand_bundles: None,
+            normal_label: "ok".to_string(),
+            exception_label: "fail".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_invoke() {
+        let mut v = invoke(Type::Integer32);
+        let mut ctx = Context::new();
+        ctx.label();
+        v.set_context(&mut ctx);
+        assert_eq!(
+            v.to_string(),
+            "%1 = invoke i32 @f(i32 1) to label %ok unwind label %fail"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Integer32));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+        assert!(v.is_terminator());
+        assert_eq!(v.successors(), vec!["ok", "fail"]);
+    }
+
+    #[test]
+    fn test_invoke_void() {
+        let mut v = invoke(Type::Void);
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(
+            v.to_string(),
+            "invoke void @f(i32 1) to label %ok unwind label %fail"
+        );
+        assert!(!v.is_assignment());
+        assert_eq!(v.get_type(), None);
+        assert_eq!(v.get_value(), None);
+        assert_eq!(ctx.count(), 0);
+    }
+
+    #[test]
+    fn test_resume() {
+        let v = Resume {
+            resume_type: Type::Integer32,
+            value: "%1".to_string(),
+        };
+        assert_eq!(v.to_string(), "resume i32 %1");
+        assert!(!v.is_assignment());
+    }
+
+    #[test]
+    fn test_catchswitch() {
+        let mut v = CatchSwitch {
+            result_val: "".to_string(),
+            parent: "none".to_string(),
+            handler_labels: vec!["handler1".to_string(), "handler2".to_string()],
+            default_label: None,
+        };
+        let mut ctx = Context::new();
+        ctx.label();
+        v.set_context(&mut ctx);
+        assert_eq!(
+            v.to_string(),
+            "%1 = catchswitch within none [label %handler1, label %handler2] unwind to caller"
+        );
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(Type::Token));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
+        assert_eq!(v.successors(), vec!["handler1", "handler2"]);
+    }
+
+    #[test]
+    fn test_catchret() {
+        let v = CatchRet {
+            catch: "%1".to_string(),
+            continue_label: "continue".to_string(),
+        };
+        assert_eq!(v.to_string(), "catchret from %1 to label %continue");
+        assert!(!v.is_assignment());
+        assert_eq!(v.successors(), vec!["continue"]);
+    }
+
+    #[test]
+    fn test_cleanupret() {
+        let v = CleanupRet {
+            values: "%1".to_string(),
+            continue_label: Some("next".to_string()),
+        };
+        assert_eq!(v.to_string(), "cleanupret from %1 unwind label %next");
+        assert!(!v.is_assignment());
+        let v = CleanupRet {
+            values: "%1".to_string(),
+            continue_label: None,
+        };
+        assert_eq!(v.to_string(), "cleanupret from %1 unwind to caller");
+        assert!(v.successors().is_empty());
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let v = Unreachable();
+        assert_eq!(v.to_string(), "unreachable");
+        assert!(!v.is_assignment());
+        assert!(v.is_terminator());
+    }
+}
@@ -7,8 +7,10 @@
 //!
 //! https://llvm.org/docs/LangRef.html#unary-operations
 
+use crate::llvm::context::Context;
 use crate::llvm::fast_math_flags::FastMathFlags;
 use crate::llvm::types::Type;
+use crate::llvm::InstructionSet;
 
 /// The ‘fneg’ instruction returns the negation of its operand.
 /// Syntax:
@@ -29,11 +31,51 @@ impl std::fmt::Display for FNeg {
         let s = self
             .fast_math_flags
             .iter()
-            .fold("".to_string(), |s, x| format!("{} {}", s, x));
-        write!(
-            f,
-            "{} = fneg {} {} %{}",
-            self.result, s, self.ty, self.operand
-        )
+            .fold("fneg".to_string(), |s, x| format!("{} {}", s, x));
+        write!(f, "{} = {} {} {}", self.result, s, self.ty, self.operand)
+    }
+}
+
+impl InstructionSet for FNeg {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(self.ty.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![(self.ty.clone(), self.operand.clone())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::type_system::single_value::FloatingPointType;
+
+    #[test]
+    fn test_fneg() {
+        let ty = Type::FloatingPoint(FloatingPointType::Double);
+        let mut v = FNeg {
+            result: "".to_string(),
+            fast_math_flags: vec![FastMathFlags::Nnan, FastMathFlags::Ninf],
+            ty: ty.clone(),
+            operand: "%x".to_string(),
+        };
+        let mut ctx = Context::new();
+        ctx.value();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%1 = fneg nnan ninf double %x");
+        assert!(v.is_assignment());
+        assert_eq!(v.get_type(), Some(ty));
+        assert_eq!(v.get_value(), Some("%1".to_string()));
     }
 }
@@ -8,3 +8,307 @@
 //! to take full advantage of a specific target.
 //!
 //! https://llvm.org/docs/LangRef.html#vector-operations
+
+use crate::llvm::{
+    context::Context,
+    type_system::single_value::VectorType,
+    types::Type,
+    InstructionSet,
+};
+
+/// The ‘extractelement’ instruction extracts a single scalar element
+/// from a vector at a specified index.
+///
+/// The first operand of an ‘extractelement’ instruction is a value of
+/// vector type. The second operand is an index indicating the
+/// position from which to extract the element. The index may be a
+/// variable of any integer type.
+///
+/// The result is a scalar of the same type as the element type of
+/// val. Its value is the value at position idx of val. If idx exceeds
+/// the length of val for a fixed-length vector, the result is a
+/// poison value.
+///
+/// https://llvm.org/docs/LangRef.html#extractelement-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Extractelement {
+    pub result: String,
+    pub ty: VectorType,
+    pub val: String,
+    pub ty2: Type,
+    pub idx: String,
+}
+
+impl std::fmt::Display for Extractelement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = extractelement {} {}, {} {}",
+            self.result, self.ty, self.val, self.ty2, self.idx
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘insertelement’ instruction inserts a scalar element into a
+/// vector at a specified index.
+///
+/// The first operand of an ‘insertelement’ instruction is a value of
+/// vector type. The second operand is a scalar value whose type must
+/// equal the element type of the first operand. The third operand is
+/// an index indicating the position at which to insert the value.
+/// The index may be a variable of any integer type.
+///
+/// The result is a vector of the same type as val. Its element
+/// values are those of val except at position idx, where it gets the
+/// value elt. If idx exceeds the length of val for a fixed-length
+/// vector, the result is a poison value.
+///
+/// https://llvm.org/docs/LangRef.html#insertelement-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Insertelement {
+    pub result: String,
+    pub ty: VectorType,
+    pub val: String,
+    pub elt: String,
+    pub ty2: Type,
+    pub idx: String,
+}
+
+impl std::fmt::Display for Insertelement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = format!(
+            "{} = insertelement {} {}, {} {}, {} {}",
+            self.result, self.ty, self.val, self.ty.element_type, self.elt, self.ty2, self.idx
+        );
+        write!(f, "{}", s)
+    }
+}
+
+/// The ‘shufflevector’ instruction constructs a permutation of
+/// elements from two input vectors, returning a vector with the same
+/// element type as the input and length that is the same as the
+/// shuffle mask.
+///
+/// The first two operands of a ‘shufflevector’ instruction are
+/// vectors with the same type. The third argument is a shuffle mask
+/// vector constant whose element type is i32. The result of the
+/// instruction is a vector whose length is the same as the shuffle
+/// mask and whose element type is the same as the element type of
+/// the first two operands.
+///
+/// The elements of the two input vectors are numbered from left to
+/// right across both of the vectors. For each element of the result
+/// vector, the shuffle mask selects an element from one of the input
+/// vectors to copy to the result. Undefined mask elements (`None`)
+/// are printed as `undef` and produce undefined result elements.
+///
+/// https://llvm.org/docs/LangRef.html#shufflevector-instruction
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Shufflevector {
+    pub result: String,
+    pub ty: VectorType,
+    pub v1: String,
+    pub v2: String,
+    pub mask: Vec<Option<u32>>,
+}
+
+impl Shufflevector {
+    /// Result vector type: element type of operands and length of
+    /// shuffle mask
+    pub fn result_type(&self) -> VectorType {
+        VectorType {
+            elemetns: self.mask.len() as i64,
+            element_type: self.ty.element_type.clone(),
+            vscale: self.ty.vscale,
+        }
+    }
+}
+
+impl std::fmt::Display for Shufflevector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mask_ty = VectorType {
+            elemetns: self.mask.len() as i64,
+            element_type: Box::new(Type::Integer32),
+            vscale: self.ty.vscale,
+        };
+        let mask = self
+            .mask
+            .iter()
+            .enumerate()
+            .fold("".to_string(), |s, (i, v)| {
+                let v = v.map_or("undef".to_string(), |v| v.to_string());
+                if i > 0 {
+                    format!("{}, i32 {}", s, v)
+                } else {
+                    format!("i32 {}", v)
+                }
+            });
+        let s = format!(
+            "{} = shufflevector {} {}, {} {}, {} <{}>",
+            self.result, self.ty, self.v1, self.ty, self.v2, mask_ty, mask
+        );
+        write!(f, "{}", s)
+    }
+}
+
+impl InstructionSet for Extractelement {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(*self.ty.element_type.clone())
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (Type::Vector(self.ty.clone()), self.val.clone()),
+            (self.ty2.clone(), self.idx.clone()),
+        ]
+    }
+}
+
+impl InstructionSet for Insertelement {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(Type::Vector(self.ty.clone()))
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (Type::Vector(self.ty.clone()), self.val.clone()),
+            (*self.ty.element_type.clone(), self.elt.clone()),
+            (self.ty2.clone(), self.idx.clone()),
+        ]
+    }
+}
+
+impl InstructionSet for Shufflevector {
+    fn set_context(&mut self, ctx: &mut Context) {
+        if self.result.is_empty() {
+            self.result = ctx.value();
+        }
+    }
+    fn is_assignment(&self) -> bool {
+        true
+    }
+    fn get_type(&self) -> Option<Type> {
+        Some(Type::Vector(self.result_type()))
+    }
+    fn get_value(&self) -> Option<String> {
+        Some(self.result.clone())
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![
+            (Type::Vector(self.ty.clone()), self.v1.clone()),
+            (Type::Vector(self.ty.clone()), self.v2.clone()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::type_system::single_value::FloatingPointType;
+
+    fn float4() -> VectorType {
+        VectorType {
+            elemetns: 4,
+            element_type: Box::new(Type::FloatingPoint(FloatingPointType::Float)),
+            vscale: false,
+        }
+    }
+
+    #[test]
+    fn test_extractelement() {
+        let mut v = Extractelement {
+            result: "".to_string(),
+            ty: float4(),
+            val: "%vec".to_string(),
+            ty2: Type::Integer32,
+            idx: "0".to_string(),
+        };
+        let mut ctx = Context::new();
+        v.set_context(&mut ctx);
+        assert_eq!(v.to_string(), "%0 = extractelement <4 x float> %vec, i32 0");
+        assert!(v.is_assignment());
+        assert_eq!(
+            v.get_type(),
+            Some(Type::FloatingPoint(FloatingPointType::Float))
+        );
+        assert_eq!(v.get_value(), Some("%0".to_string()));
+    }
+
+    #[test]
+    fn test_insertelement() {
+        let v = Insertelement {
+            result: "%res".to_string(),
+            ty: float4(),
+            val: "undef".to_string(),
+            elt: "1.0".to_string(),
+            ty2: Type::Integer32,
+            idx: "%1".to_string(),
+        };
+        assert_eq!(
+            v.to_string(),
+            "%res = insertelement <4 x float> undef, float 1.0, i32 %1"
+        );
+        assert_eq!(v.get_type(), Some(Type::Vector(float4())));
+    }
+
+    #[test]
+    fn test_shufflevector() {
+        let ty = VectorType {
+            elemetns: 4,
+            element_type: Box::new(Type::Integer32),
+            vscale: false,
+        };
+        let v = Shufflevector {
+            result: "%res".to_string(),
+            ty: ty.clone(),
+            v1: "%v1".to_string(),
+            v2: "%v2".to_string(),
+            mask: vec![Some(0), Some(4), Some(1), Some(5)],
+        };
+        assert_eq!(
+            v.to_string(),
+            "%res = shufflevector <4 x i32> %v1, <4 x i32> %v2, <4 x i32> <i32 0, i32 4, i32 1, i32 5>"
+        );
+        assert_eq!(v.get_type(), Some(Type::Vector(ty.clone())));
+
+        let v = Shufflevector {
+            result: "%res".to_string(),
+            ty,
+            v1: "%v1".to_string(),
+            v2: "undef".to_string(),
+            mask: vec![Some(0), None],
+        };
+        assert_eq!(
+            v.to_string(),
+            "%res = shufflevector <4 x i32> %v1, <4 x i32> undef, <2 x i32> <i32 0, i32 undef>"
+        );
+        assert_eq!(
+            v.get_type(),
+            Some(Type::Vector(VectorType {
+                elemetns: 2,
+                element_type: Box::new(Type::Integer32),
+                vscale: false,
+            }))
+        );
+    }
+}
@@ -0,0 +1,423 @@
+//! # Intrinsic functions
+//!
+//! LLVM supports the notion of an “intrinsic function”. These
+//! functions have well known names and semantics and are required to
+//! follow certain restrictions. Intrinsic function names must all
+//! start with an “llvm.” prefix.
+//!
+//! Some intrinsic functions can be overloaded, i.e., the intrinsic
+//! represents a family of functions that perform the same operation
+//! but on different data types. Overloaded types are encoded in name
+//! of intrinsic: `llvm.sqrt.f64`, `llvm.ctpop.v4i32`.
+//!
+//! Attributes of intrinsics are known to LLVM, so declarations are
+//! generated without attributes:
+//! ```html
+//! declare { i32, i1 } @llvm.sadd.with.overflow.i32(i32, i32)
+//! ```
+//!
+//! https://llvm.org/docs/LangRef.html#intrinsic-functions
+
+use crate::llvm::{
+    builder::{
+        BuildError,
+        BuildResult,
+    },
+    functions::{
+        ArgumentList,
+        Function,
+    },
+    type_system::{
+        aggregate::StructureType,
+        single_value::{
+            FloatingPointType,
+            VectorType,
+        },
+        FunctionType,
+    },
+    types::Type,
+};
+
+/// Standard C library math intrinsics. Operands and result have
+/// same floating point type or vector of floating point values.
+///
+/// https://llvm.org/docs/LangRef.html#standard-c-c-library-intrinsics
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum MathFunction {
+    Sqrt,
+    Sin,
+    Cos,
+    Exp,
+    Exp2,
+    Log,
+    Log2,
+    Log10,
+    Fabs,
+    Floor,
+    Ceil,
+    Trunc,
+    Round,
+    Pow,
+    Minnum,
+    Maxnum,
+    Copysign,
+}
+
+/// Arithmetic with overflow intrinsics. Result is structure of
+/// operation result and overflow bit.
+///
+/// https://llvm.org/docs/LangRef.html#arithmetic-with-overflow-intrinsics
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum OverflowOperation {
+    SAdd,
+    UAdd,
+    SSub,
+    USub,
+    SMul,
+    UMul,
+}
+
+/// Intrinsic function with types of overloaded operands
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Intrinsic {
+    /// `void @llvm.memcpy.p0i8.p0i8.i64(i8* dest, i8* src, i64 len, i1 isvolatile)`
+    Memcpy(Type),
+    /// `void @llvm.memmove.p0i8.p0i8.i64(i8* dest, i8* src, i64 len, i1 isvolatile)`
+    Memmove(Type),
+    /// `void @llvm.memset.p0i8.i64(i8* dest, i8 val, i64 len, i1 isvolatile)`
+    Memset(Type),
+    /// `double @llvm.sqrt.f64(double %x)`
+    Math(MathFunction, Type),
+    /// `i32 @llvm.abs.i32(i32 %x, i1 is_int_min_poison)`
+    Abs(Type),
+    /// `i32 @llvm.ctpop.i32(i32 %x)`
+    Ctpop(Type),
+    /// `i32 @llvm.ctlz.i32(i32 %x, i1 is_zero_poison)`
+    Ctlz(Type),
+    /// `i32 @llvm.cttz.i32(i32 %x, i1 is_zero_poison)`
+    Cttz(Type),
+    /// `{ i32, i1 } @llvm.sadd.with.overflow.i32(i32 %a, i32 %b)`
+    WithOverflow(OverflowOperation, Type),
+    /// `void @llvm.trap()`: abnormal program termination
+    Trap,
+    /// `void @llvm.debugtrap()`: breakpoint in debugger
+    DebugTrap,
+    /// `void @llvm.dbg.value(metadata, metadata, metadata)`: value
+    /// of source variable
+    DbgValue,
+}
+
+impl MathFunction {
+    pub fn name(&self) -> &str {
+        match self {
+            MathFunction::Sqrt => "sqrt",
+            MathFunction::Sin => "sin",
+            MathFunction::Cos => "cos",
+            MathFunction::Exp => "exp",
+            MathFunction::Exp2 => "exp2",
+            MathFunction::Log => "log",
+            MathFunction::Log2 => "log2",
+            MathFunction::Log10 => "log10",
+            MathFunction::Fabs => "fabs",
+            MathFunction::Floor => "floor",
+            MathFunction::Ceil => "ceil",
+            MathFunction::Trunc => "trunc",
+            MathFunction::Round => "round",
+            MathFunction::Pow => "pow",
+            MathFunction::Minnum => "minnum",
+            MathFunction::Maxnum => "maxnum",
+            MathFunction::Copysign => "copysign",
+        }
+    }
+
+    /// Count of operands
+    pub fn arity(&self) -> usize {
+        match self {
+            MathFunction::Pow
+            | MathFunction::Minnum
+            | MathFunction::Maxnum
+            | MathFunction::Copysign => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl OverflowOperation {
+    pub fn name(&self) -> &str {
+        match self {
+            OverflowOperation::SAdd => "sadd",
+            OverflowOperation::UAdd => "uadd",
+            OverflowOperation::SSub => "ssub",
+            OverflowOperation::USub => "usub",
+            OverflowOperation::SMul => "smul",
+            OverflowOperation::UMul => "umul",
+        }
+    }
+}
+
+/// Name of type in overloaded intrinsic name: `i32`, `f64`,
+/// `v4f32`, `p0` (`p0i8` for typed pointers)
+pub fn type_suffix(ty: &Type, typed_pointers: bool) -> Option<String> {
+    let s = match ty {
+        Type::FloatingPoint(x) => match x {
+            FloatingPointType::Half => "f16".to_string(),
+            FloatingPointType::Bfloat => "bf16".to_string(),
+            FloatingPointType::Float => "f32".to_string(),
+            FloatingPointType::Double => "f64".to_string(),
+            FloatingPointType::X86fp80 => "f80".to_string(),
+            FloatingPointType::Fp128 => "f128".to_string(),
+            FloatingPointType::PpcFp128 => "ppcf128".to_string(),
+        },
+        Type::Vector(x) => {
+            let scalable = if x.vscale { "nx" } else { "" };
+            format!(
+                "{}v{}{}",
+                scalable,
+                x.elemetns,
+                type_suffix(&x.element_type, typed_pointers)?
+            )
+        }
+        Type::Pointer(x) => {
+            let addrspace = x.addrspace.as_ref().map_or(0, |v| v.0);
+            match &x.pointee {
+                Some(ty) if typed_pointers => {
+                    format!("p{}{}", addrspace, type_suffix(ty, typed_pointers)?)
+                }
+                _ => format!("p{}", addrspace),
+            }
+        }
+        _ => format!("i{}", ty.integer_width()?),
+    };
+    Some(s)
+}
+
+/// Check that type is integer or vector of integers
+fn integer_type(ty: &Type) -> BuildResult<()> {
+    if !ty.scalar_type().is_integer() {
+        return Err(BuildError::UnsupportedType(ty.clone()));
+    }
+    Ok(())
+}
+
+impl Intrinsic {
+    /// Overloaded operand type of intrinsic
+    pub fn overloaded_type(&self) -> Option<&Type> {
+        match self {
+            Intrinsic::Memcpy(ty)
+            | Intrinsic::Memmove(ty)
+            | Intrinsic::Memset(ty)
+            | Intrinsic::Math(_, ty)
+            | Intrinsic::Abs(ty)
+            | Intrinsic::Ctpop(ty)
+            | Intrinsic::Ctlz(ty)
+            | Intrinsic::Cttz(ty)
+            | Intrinsic::WithOverflow(_, ty) => Some(ty),
+            Intrinsic::Trap | Intrinsic::DebugTrap | Intrinsic::DbgValue => None,
+        }
+    }
+
+    /// Name of intrinsic function without `@` sigil. Pointer types
+    /// are mangled with pointee type for typed pointers.
+    pub fn name(&self, typed_pointers: bool) -> String {
+        let suffix = self
+            .overloaded_type()
+            .and_then(|ty| type_suffix(ty, typed_pointers))
+            .unwrap_or_default();
+        let ptr = type_suffix(&Type::pointer1(Type::Integer8), typed_pointers).unwrap_or_default();
+        match self {
+            Intrinsic::Memcpy(_) => format!("llvm.memcpy.{}.{}.{}", ptr, ptr, suffix),
+            Intrinsic::Memmove(_) => format!("llvm.memmove.{}.{}.{}", ptr, ptr, suffix),
+            Intrinsic::Memset(_) => format!("llvm.memset.{}.{}", ptr, suffix),
+            Intrinsic::Math(f, _) => format!("llvm.{}.{}", f.name(), suffix),
+            Intrinsic::Abs(_) => format!("llvm.abs.{}", suffix),
+            Intrinsic::Ctpop(_) => format!("llvm.ctpop.{}", suffix),
+            Intrinsic::Ctlz(_) => format!("llvm.ctlz.{}", suffix),
+            Intrinsic::Cttz(_) => format!("llvm.cttz.{}", suffix),
+            Intrinsic::WithOverflow(op, _) => {
+                format!("llvm.{}.with.overflow.{}", op.name(), suffix)
+            }
+            Intrinsic::Trap => "llvm.trap".to_string(),
+            Intrinsic::DebugTrap => "llvm.debugtrap".to_string(),
+            Intrinsic::DbgValue => "llvm.dbg.value".to_string(),
+        }
+    }
+
+    /// Function type of intrinsic. Overloaded type is checked.
+    pub fn function_type(&self) -> BuildResult<FunctionType> {
+        let ptr = || Type::pointer1(Type::Integer8);
+        let (return_type, parameter_list) = match self {
+            Intrinsic::Memcpy(len) | Intrinsic::Memmove(len) => {
+                if !len.is_integer() {
+                    return Err(BuildError::UnsupportedType(len.clone()));
+                }
+                (Type::Void, vec![ptr(), ptr(), len.clone(), Type::Integer1])
+            }
+            Intrinsic::Memset(len) => {
+                if !len.is_integer() {
+                    return Err(BuildError::UnsupportedType(len.clone()));
+                }
+                (
+                    Type::Void,
+                    vec![ptr(), Type::Integer8, len.clone(), Type::Integer1],
+                )
+            }
+            Intrinsic::Math(f, ty) => {
+                if !ty.scalar_type().is_floating_point() {
+                    return Err(BuildError::UnsupportedType(ty.clone()));
+                }
+                (ty.clone(), vec![ty.clone(); f.arity()])
+            }
+            Intrinsic::Ctpop(ty) => {
+                integer_type(ty)?;
+                (ty.clone(), vec![ty.clone()])
+            }
+            Intrinsic::Abs(ty) | Intrinsic::Ctlz(ty) | Intrinsic::Cttz(ty) => {
+                integer_type(ty)?;
+                (ty.clone(), vec![ty.clone(), Type::Integer1])
+            }
+            Intrinsic::WithOverflow(_, ty) => {
+                integer_type(ty)?;
+                let overflow = match ty {
+                    Type::Vector(v) => Type::Vector(VectorType {
+                        element_type: Box::new(Type::Integer1),
+                        ..v.clone()
+                    }),
+                    _ => Type::Integer1,
+                };
+                let res = Type::Structure(StructureType {
+                    literal: true,
+                    packed: false,
+                    type_list: vec![ty.clone(), overflow],
+                });
+                (res, vec![ty.clone(), ty.clone()])
+            }
+            Intrinsic::Trap | Intrinsic::DebugTrap => (Type::Void, vec![]),
+            Intrinsic::DbgValue => (Type::Void, vec![Type::Metadata; 3]),
+        };
+        Ok(FunctionType {
+            return_type: Box::new(return_type),
+            parameter_list,
+            variable_argument: false,
+        })
+    }
+
+    /// Declaration of intrinsic function, see `name`
+    pub fn declaration(&self, typed_pointers: bool) -> BuildResult<Function> {
+        let ty = self.function_type()?;
+        let name = self.name(typed_pointers);
+        let mut f = decl!(Void name);
+        f.result_type = *ty.return_type;
+        f.argument_list = ty
+            .parameter_list
+            .into_iter()
+            .map(|ty| ArgumentList {
+                parameter_type: Some(ty),
+                attributes: None,
+                name: None,
+                variable_argument: false,
+            })
+            .collect();
+        Ok(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::addrspace::AddrSpace;
+    use crate::llvm::types::Type::{
+        Integer1,
+        Integer32,
+        Integer64,
+    };
+
+    fn double() -> Type {
+        Type::FloatingPoint(FloatingPointType::Double)
+    }
+
+    #[test]
+    fn test_intrinsic_names() {
+        let v4f64 = Type::Vector(VectorType {
+            elemetns: 4,
+            element_type: Box::new(double()),
+            vscale: false,
+        });
+        assert_eq!(
+            Intrinsic::Math(MathFunction::Sqrt, double()).name(false),
+            "llvm.sqrt.f64"
+        );
+        assert_eq!(
+            Intrinsic::Math(MathFunction::Pow, v4f64).name(false),
+            "llvm.pow.v4f64"
+        );
+        assert_eq!(Intrinsic::Ctpop(Integer32).name(false), "llvm.ctpop.i32");
+        assert_eq!(
+            Intrinsic::WithOverflow(OverflowOperation::SAdd, Integer64).name(false),
+            "llvm.sadd.with.overflow.i64"
+        );
+        assert_eq!(
+            Intrinsic::Memcpy(Integer64).name(false),
+            "llvm.memcpy.p0.p0.i64"
+        );
+        assert_eq!(Intrinsic::Trap.name(false), "llvm.trap");
+        assert_eq!(
+            type_suffix(&Type::pointer1(Type::Integer8), false),
+            Some("p0".to_string())
+        );
+        assert_eq!(
+            type_suffix(&Type::opaque_pointer(Some(AddrSpace(1))), true),
+            Some("p1".to_string())
+        );
+        assert_eq!(
+            Intrinsic::Memcpy(Integer64).name(true),
+            "llvm.memcpy.p0i8.p0i8.i64"
+        );
+        assert_eq!(type_suffix(&Type::Void, false), None);
+    }
+
+    #[test]
+    fn test_intrinsic_declarations() {
+        let decl = |i: Intrinsic| i.declaration(false).unwrap().to_string();
+        assert_eq!(
+            decl(Intrinsic::Math(MathFunction::Sqrt, double())),
+            "declare double @llvm.sqrt.f64(double)"
+        );
+        assert_eq!(
+            decl(Intrinsic::Math(MathFunction::Maxnum, double())),
+            "declare double @llvm.maxnum.f64(double, double)"
+        );
+        assert_eq!(
+            decl(Intrinsic::Memcpy(Integer64)),
+            "declare void @llvm.memcpy.p0.p0.i64(ptr, ptr, i64, i1)"
+        );
+        assert_eq!(
+            decl(Intrinsic::Memset(Integer32)),
+            "declare void @llvm.memset.p0.i32(ptr, i8, i32, i1)"
+        );
+        assert_eq!(
+            decl(Intrinsic::Ctlz(Integer32)),
+            "declare i32 @llvm.ctlz.i32(i32, i1)"
+        );
+        assert_eq!(
+            decl(Intrinsic::WithOverflow(OverflowOperation::SMul, Integer32)),
+            "declare { i32, i1 } @llvm.smul.with.overflow.i32(i32, i32)"
+        );
+        assert_eq!(decl(Intrinsic::Trap), "declare void @llvm.trap()");
+        assert_eq!(
+            decl(Intrinsic::DbgValue),
+            "declare void @llvm.dbg.value(metadata, metadata, metadata)"
+        );
+        assert_eq!(
+            Intrinsic::Math(MathFunction::Sin, Integer32).function_type(),
+            Err(BuildError::UnsupportedType(Integer32))
+        );
+        assert_eq!(
+            Intrinsic::Ctpop(Integer1).function_type(),
+            Err(BuildError::UnsupportedType(Integer1))
+        );
+        assert_eq!(
+            Intrinsic::Memcpy(double()).function_type(),
+            Err(BuildError::UnsupportedType(double()))
+        );
+    }
+}
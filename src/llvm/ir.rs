@@ -0,0 +1,430 @@
+//! # IR module model
+//!
+//! In-memory representation of LLVM IR module. Module consists of
+//! global variables, function definitions, external declarations,
+//! attribute groups and metadata. Function body is list of basic
+//! blocks, each basic block is sequence of instructions ended with
+//! single terminator instruction.
+//!
+//! Model can be inspected and transformed before it is printed.
+//! Text IR produced by `Display` implementation of `Module`.
+//!
+//! https://llvm.org/docs/LangRef.html#module-structure
+
+use crate::llvm::{
+    attribute_groups::Attributes,
+    data_layout::DataLayout,
+    functions,
+    global_variables::GlobalVariable,
+    metadata::{
+        Metadata,
+        MetadataNode,
+        MetadataRef,
+        ModuleFlagBehavior,
+        NamedMetadata,
+    },
+    source_filename::SourceFileName,
+    target_triple::TargetTriple,
+    type_system::{
+        aggregate::TypeDefinition,
+        single_value::print_with_pointers,
+    },
+    types::Type,
+    InstructionSet,
+};
+
+/// LLVM programs are composed of Module’s, each of which is a
+/// translation unit of the input programs. Each module consists of
+/// functions, global variables, and symbol table entries.
+#[derive(Debug)]
+pub struct Module {
+    pub module_id: String,
+    pub source_filename: Option<SourceFileName>,
+    pub data_layout: Option<DataLayout>,
+    pub target_triple: Option<TargetTriple>,
+    /// Identified structure types
+    pub types: Vec<TypeDefinition>,
+    pub globals: Vec<GlobalVariable>,
+    pub functions: Vec<Function>,
+    pub declarations: Vec<functions::Function>,
+    pub attribute_groups: Vec<Attributes>,
+    /// Numbered metadata nodes, node `!N` is N-th element
+    pub metadata: Vec<MetadataNode>,
+    pub named_metadata: Vec<NamedMetadata>,
+    /// Print pointers with pointee type: `i8*` instead of `ptr`, for
+    /// LLVM versions prior to 15
+    pub typed_pointers: bool,
+}
+
+/// Function definition: function header and basic blocks. First
+/// basic block is entry block of function.
+#[derive(Debug)]
+pub struct Function {
+    pub definition: functions::Function,
+    pub blocks: Vec<BasicBlock>,
+}
+
+/// A basic block is a sequence of instructions with single entry
+/// point and terminator instruction at the end.
+/// Label stored without `%` sigil. Unnamed labels are numeric.
+#[derive(Debug)]
+pub struct BasicBlock {
+    pub label: String,
+    pub instructions: Vec<Box<dyn InstructionSet>>,
+    pub terminator: Option<Box<dyn InstructionSet>>,
+}
+
+impl Module {
+    pub fn new(module_id: &str) -> Self {
+        Self {
+            module_id: module_id.to_string(),
+            source_filename: None,
+            data_layout: None,
+            target_triple: None,
+            types: vec![],
+            globals: vec![],
+            functions: vec![],
+            declarations: vec![],
+            attribute_groups: vec![],
+            metadata: vec![],
+            named_metadata: vec![],
+            typed_pointers: false,
+        }
+    }
+
+    /// Add metadata node. Equal nodes, which are not distinct, are
+    /// merged, so numbering of nodes depends only on order of
+    /// adding.
+    pub fn metadata_node(&mut self, node: MetadataNode) -> MetadataRef {
+        if !node.distinct {
+            if let Some(i) = self.metadata.iter().position(|v| *v == node) {
+                return MetadataRef(i);
+            }
+        }
+        self.metadata.push(node);
+        MetadataRef(self.metadata.len() - 1)
+    }
+
+    /// Add node to named metadata. Named metadata is created, if
+    /// it doesn't exist. Node is added once.
+    pub fn named_metadata(&mut self, name: &str, node: MetadataRef) {
+        match self.named_metadata.iter_mut().find(|v| v.name == name) {
+            Some(v) if v.nodes.contains(&node) => {}
+            Some(v) => v.nodes.push(node),
+            None => self.named_metadata.push(NamedMetadata {
+                name: name.to_string(),
+                nodes: vec![node],
+            }),
+        }
+    }
+
+    /// Add module flag to `!llvm.module.flags`. Flag names are
+    /// unique, flag with the same name is replaced.
+    ///
+    /// https://llvm.org/docs/LangRef.html#module-flags-metadata
+    pub fn module_flag(
+        &mut self,
+        behavior: ModuleFlagBehavior,
+        name: &str,
+        value: Metadata,
+    ) -> MetadataRef {
+        let node = self.metadata_node(MetadataNode {
+            distinct: false,
+            value: Metadata::Tuple(vec![
+                Metadata::Value(Type::Integer32, (behavior as u32).to_string()),
+                Metadata::String(name.to_string()),
+                value,
+            ]),
+        });
+        let key = Metadata::String(name.to_string());
+        let metadata = &self.metadata;
+        let same_key = |v: &MetadataRef| matches!(&metadata[v.0].value, Metadata::Tuple(v) if v.get(1) == Some(&key));
+        if let Some(flags) = self
+            .named_metadata
+            .iter_mut()
+            .find(|v| v.name == "llvm.module.flags")
+        {
+            if let Some(v) = flags.nodes.iter_mut().find(|v| same_key(v)) {
+                *v = node;
+                return node;
+            }
+        }
+        self.named_metadata("llvm.module.flags", node);
+        node
+    }
+
+    /// Function definition by name
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions
+            .iter()
+            .find(|f| f.definition.function_name == name)
+    }
+
+    /// Identified structure type by name
+    pub fn type_definition(&self, name: &str) -> Option<&TypeDefinition> {
+        self.types.iter().find(|t| t.name == name)
+    }
+}
+
+impl Function {
+    pub fn new(definition: functions::Function) -> Self {
+        Self {
+            definition,
+            blocks: vec![],
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.definition.function_name
+    }
+
+    /// Entry basic block
+    pub fn entry(&self) -> Option<&BasicBlock> {
+        self.blocks.first()
+    }
+
+    /// Basic block by label
+    pub fn block(&self, label: &str) -> Option<&BasicBlock> {
+        self.blocks.iter().find(|bb| bb.label == label)
+    }
+
+    /// Labels of successors of basic block
+    pub fn successors(&self, label: &str) -> Vec<String> {
+        self.block(label)
+            .map(|bb| bb.successors())
+            .unwrap_or_default()
+    }
+
+    /// Labels of basic blocks, which transfer control to basic
+    /// block, in order of blocks in function
+    pub fn predecessors(&self, label: &str) -> Vec<String> {
+        self.blocks
+            .iter()
+            .filter(|bb| bb.successors().iter().any(|s| s == label))
+            .map(|bb| bb.label.clone())
+            .collect()
+    }
+}
+
+impl BasicBlock {
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            instructions: vec![],
+            terminator: None,
+        }
+    }
+
+    /// Labels of basic blocks, where terminator transfers control
+    pub fn successors(&self) -> Vec<String> {
+        self.terminator
+            .as_ref()
+            .map(|t| t.successors())
+            .unwrap_or_default()
+    }
+
+    /// Unnamed (numbered) label
+    pub fn is_unnamed(&self) -> bool {
+        self.label.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// Instructions of basic block, including terminator
+    fn body(&self) -> String {
+        let s = self
+            .instructions
+            .iter()
+            .fold("".to_string(), |s, instr| format!("{}\t{}\n", s, instr));
+        match &self.terminator {
+            Some(t) => format!("{}\t{}\n", s, t),
+            None => s,
+        }
+    }
+}
+
+impl std::fmt::Display for BasicBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:\n{}", self.label, self.body())
+    }
+}
+
+impl std::fmt::Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let body = self
+            .blocks
+            .iter()
+            .enumerate()
+            .fold("".to_string(), |s, (i, bb)| {
+                // Unnamed entry block label is implicit
+                if i == 0 && bb.is_unnamed() {
+                    format!("{}{}", s, bb.body())
+                } else {
+                    format!("{}{}", s, bb)
+                }
+            });
+        write!(f, "{} {{\n{}}}", self.definition, body)
+    }
+}
+
+impl Module {
+    /// IR text of module with pointer mode of module
+    fn print(&self) -> String {
+        let mut s = format!("; ModuleID = '{}'\n", self.module_id);
+        if let Some(v) = &self.source_filename {
+            s = format!("{}{}\n", s, v);
+        }
+        if let Some(v) = &self.data_layout {
+            s = format!("{}{}\n", s, v);
+        }
+        if let Some(v) = &self.target_triple {
+            s = format!("{}{}\n", s, v);
+        }
+        if !self.types.is_empty() {
+            s = self
+                .types
+                .iter()
+                .fold(format!("{}\n", s), |s, t| format!("{}{}\n", s, t));
+        }
+        if !self.globals.is_empty() {
+            s = self
+                .globals
+                .iter()
+                .fold(format!("{}\n", s), |s, g| format!("{}{}\n", s, g));
+        }
+        s = self
+            .functions
+            .iter()
+            .fold(s, |s, func| format!("{}\n{}\n", s, func));
+        if !self.declarations.is_empty() {
+            s = self
+                .declarations
+                .iter()
+                .fold(format!("{}\n", s), |s, d| format!("{}{}\n", s, d));
+        }
+        if !self.attribute_groups.is_empty() {
+            s = self
+                .attribute_groups
+                .iter()
+                .fold(format!("{}\n", s), |s, a| format!("{}{}\n", s, a));
+        }
+        if !self.named_metadata.is_empty() {
+            s = self
+                .named_metadata
+                .iter()
+                .fold(format!("{}\n", s), |s, m| format!("{}{}\n", s, m));
+        }
+        if !self.metadata.is_empty() {
+            s = self
+                .metadata
+                .iter()
+                .enumerate()
+                .fold(format!("{}\n", s), |s, (i, m)| {
+                    format!("{}{} = {}\n", s, MetadataRef(i), m)
+                });
+        }
+        s
+    }
+}
+
+impl std::fmt::Display for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = print_with_pointers(self.typed_pointers, || self.print());
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::instructions::terminator::{
+        Br,
+        Ret,
+    };
+    use crate::llvm::types::Type::Void;
+
+    fn block(label: &str, terminator: Box<dyn InstructionSet>) -> BasicBlock {
+        let mut bb = BasicBlock::new(label);
+        bb.terminator = Some(terminator);
+        bb
+    }
+
+    fn diamond() -> Function {
+        let name = "f";
+        let mut f = Function::new(def!(Void name));
+        f.blocks = vec![
+            block(
+                "0",
+                Box::new(Br::Conditional(
+                    "true".to_string(),
+                    "then".to_string(),
+                    "else".to_string(),
+                )),
+            ),
+            block("then", Box::new(Br::Unconditional("exit".to_string()))),
+            block("else", Box::new(Br::Unconditional("exit".to_string()))),
+            block("exit", Box::new(Ret(None))),
+        ];
+        f
+    }
+
+    #[test]
+    fn test_ir_cfg() {
+        let f = diamond();
+        assert_eq!(f.entry().unwrap().label, "0");
+        assert_eq!(f.successors("0"), vec!["then", "else"]);
+        assert_eq!(f.successors("exit"), Vec::<String>::new());
+        assert_eq!(f.predecessors("exit"), vec!["then", "else"]);
+        assert_eq!(f.predecessors("then"), vec!["0"]);
+        assert_eq!(f.predecessors("0"), Vec::<String>::new());
+        assert!(f.block("none").is_none());
+    }
+
+    #[test]
+    fn test_ir_printer() {
+        let mut m = Module::new("app");
+        m.functions.push(diamond());
+        m.metadata_node(MetadataNode {
+            distinct: false,
+            value: Metadata::Tuple(vec![]),
+        });
+        assert_eq!(
+            m.to_string(),
+            "; ModuleID = 'app'\n\ndefine void @f() {\n\tbr i1 true, label %then, label %else\nthen:\n\tbr label %exit\nelse:\n\tbr label %exit\nexit:\n\tret void\n}\n\n!0 = !{}\n"
+        );
+        assert!(m.function("f").is_some());
+    }
+
+    #[test]
+    fn test_ir_metadata() {
+        let mut m = Module::new("app");
+        let empty = MetadataNode {
+            distinct: false,
+            value: Metadata::Tuple(vec![]),
+        };
+        assert_eq!(m.metadata_node(empty.clone()), MetadataRef(0));
+        assert_eq!(m.metadata_node(empty.clone()), MetadataRef(0));
+        let distinct = MetadataNode {
+            distinct: true,
+            ..empty
+        };
+        assert_eq!(m.metadata_node(distinct.clone()), MetadataRef(1));
+        assert_eq!(m.metadata_node(distinct), MetadataRef(2));
+        let value = Metadata::Value(Type::Integer32, "4".to_string());
+        let flag = m.module_flag(ModuleFlagBehavior::Error, "wchar_size", value.clone());
+        assert_eq!(flag, MetadataRef(3));
+        assert_eq!(
+            m.module_flag(ModuleFlagBehavior::Error, "wchar_size", value),
+            flag
+        );
+        let value = Metadata::Value(Type::Integer32, "3".to_string());
+        m.module_flag(ModuleFlagBehavior::Warning, "Dwarf Version", value.clone());
+        // Flag with the same name is replaced
+        let value = Metadata::Value(Type::Integer32, "5".to_string());
+        m.module_flag(ModuleFlagBehavior::Warning, "Dwarf Version", value);
+        m.named_metadata("llvm.ident", MetadataRef(0));
+        m.named_metadata("llvm.ident", MetadataRef(0));
+        assert_eq!(
+            m.to_string(),
+            "; ModuleID = 'app'\n\n!llvm.module.flags = !{!3, !5}\n!llvm.ident = !{!0}\n\n!0 = !{}\n!1 = distinct !{}\n!2 = distinct !{}\n!3 = !{i32 1, !\"wchar_size\", i32 4}\n!4 = !{i32 2, !\"Dwarf Version\", i32 3}\n!5 = !{i32 2, !\"Dwarf Version\", i32 5}\n"
+        );
+    }
+}
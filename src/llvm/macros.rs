@@ -4,10 +4,13 @@
 /// `alloca` macros
 ///
 /// ```ignore
-/// // Allocate
-/// let res = alloca!(Integer32 3);
+/// // Allocate: %3 = alloca i32
+/// let res = alloca!(Integer32 "%3");
 /// // Allocate with align
-/// let res = alloca!(Integer32 3, 8);
+/// let res = alloca!(Integer32 "%3", 8);
+/// // Unnamed result allocated by function context
+/// let mut res = alloca!(Integer32 "");
+/// res.set_context(&mut ctx);
 /// ```
 #[macro_export]
 macro_rules! alloca {
@@ -18,7 +21,6 @@ macro_rules! alloca {
             elements: None,
             align: None,
             addrspace: None,
-            ctx: None,
         }
     };
     ($ty:ident $res:expr, $align:expr) => {
@@ -28,7 +30,6 @@ macro_rules! alloca {
             elements: None,
             align: Some(super::align::Alignment($align)),
             addrspace: None,
-            ctx: None,
         }
     };
 }
@@ -168,7 +169,7 @@ macro_rules! def {
             prefix: None,
             prologue: None,
             personality: None,
-            metadata: None,
+            metadata: vec![],
         }
     }};
 }
@@ -264,8 +265,7 @@ macro_rules! global {
             section: None,
             comdat: None,
             alignment: None,
-            metadata: None,
-            ctx: None,
+            metadata: vec![],
         }
     };
 }
@@ -287,13 +287,14 @@ macro_rules! store {
     }};
     ($ty:ident $val:expr, $ptrval:expr) => {{
         $crate::llvm::instructions::memory_access_addressing_operations::Store {
+            atomic: None,
+            syncscope: None,
             volatile: None,
-            ty: $ty,
+            ty: $ty.clone(),
             value: $val.to_string(),
             ty_pointer: $ty,
             pointer: $ptrval.to_string(),
             align: None,
-            ctx: None,
         }
     }};
 }
@@ -302,7 +303,7 @@ macro_rules! store {
 ///
 /// ```igonre
 /// // load to `res` bu addr %3`:  %res = load i32, i32* %3
-/// let res = load!(Integer32 "res", "%3");
+/// let res = load!(Integer32 "%res", "%3");
 /// // Extend `load` instruction for optional field `volatile`
 /// let res = load!(res.volatile @());
 /// ```
@@ -314,8 +315,10 @@ macro_rules! load {
     ($ty:ident $res:expr, $ptrval:expr) => {{
         $crate::llvm::instructions::memory_access_addressing_operations::Load {
             result: $res.to_string(),
+            atomic: None,
+            syncscope: None,
             volatile: None,
-            ty: $ty,
+            ty: $ty.clone(),
             ty_pointer: $ty,
             pointer: $ptrval.to_string(),
             align: None,
@@ -361,13 +364,13 @@ macro_rules! b {
 ///
 /// ```igonre
 /// // Empty range values
-/// let res = getelementptr!(Integer64 "el", "@.str" => []);
+/// let res = getelementptr!(Integer64 "%el", "@.str" => []);
 /// // With range values
-/// let res = getelementptr!(Integer64 "el", "@.str" => [Integer64 0, Integer64 0]);
+/// let res = getelementptr!(Integer64 "%el", "@.str" => [Integer64 0, Integer64 0]);
 /// // With inbounds & range values
-/// let res = getelementptr!(Integer64 inbounds "el", "@.str" => [Integer64 0, Integer64 0]);
+/// let res = getelementptr!(Integer64 inbounds "%el", "@.str" => [Integer64 0, Integer64 0]);
 /// // With inbounds & range values & `inrange` values
-/// let res = getelementptr!(Integer64 inbounds "el", "@.str" => [Integer64 0, => Integer64 0]);
+/// let res = getelementptr!(Integer64 inbounds "%el", "@.str" => [Integer64 0, => Integer64 0]);
 /// ```
 #[macro_export]
 macro_rules! getelementptr {
@@ -407,7 +410,7 @@ macro_rules! getelementptr {
 ///
 /// ```ignore
 /// // Basic `call` invokation:  %5 =  call  i32 (i8*, ...) @printf ( i8* %el, i32 %4)
-/// let mut res = call!(Integer32 "5" => @printf arg!(ty2, ...) => [ty3 "%el".to_string(), Integer32 "%4".to_string()]);
+/// let mut res = call!(Integer32 "%5" => @printf arg!(ty2, ...) => [ty3 "%el".to_string(), Integer32 "%4".to_string()]);
 /// // Extend with optional value for field `tail`
 /// call!(res.tail @());
 /// ```
@@ -461,134 +464,119 @@ macro_rules! call {
     }};
 }
 
-/// `entry` macros
-/// Label entry. Used for BR
+/// `conversion` macros
+/// Build conversion (cast) instruction by its struct name. Without
+/// result name, result is allocated by `InstructionSet::set_context`.
 ///
 /// ```ignore
-/// // Basic declaration: entry1:
-/// let res = entry!(1);
+/// // Named result: %res = sitofp i32 %1 to double
+/// let res = conversion!(SIToFP Integer32 "%1" => Double, "%res");
+/// // Unnamed result: %2 = zext i8 %1 to i32
+/// let res = conversion!(ZExt Integer8 "%1" => Integer32);
 /// ```
 #[macro_export]
-macro_rules! entry {
-    ($val:expr) => {{
-        format!("entry{}:", $val.to_string())
+macro_rules! conversion {
+    ($kind:ident $ty:ident $val:expr => $ty2:ident $(, $res:expr)?) => {{
+        #[allow(unused_mut, unused_assignments)]
+        let mut result = String::new();
+        $(
+            result = $res.to_string();
+        )?
+        $crate::llvm::instructions::conversion_operations::$kind {
+            result,
+            ty: $ty,
+            value: $val.to_string(),
+            ty2: $ty2,
+        }
     }};
 }
 
-/// `body` macros
-/// Function body aggregator
+/// `assignment_instruction_set` macros
+/// Implement `InstructionSet` for binary instructions with result
+/// value assignment, where result type is the same as operands type
+/// (field `ty`).
 ///
 /// ```ignore
-/// let ret1 = ret!(Integer32 @0);
-/// let entry1 = entry!(0);
-/// // Function body with instructions inside
-/// let body = body!(entry1 ret1);
+/// assignment_instruction_set!(Add Sub);
 /// ```
-#[macro_export]
-macro_rules! body {
-    ($($el:expr)*) => {{
-    	let s = "";
-    	$(
-    		let s = if s.is_empty() {
-				format!("{}\n{}", s, $el)
-    		} else {
-    			format!("{}\n\t{}", s, $el)
-    		};
-    	)*
-        format!("{{{}\n}}", s)
-    }};
-    (@$el:expr) => {{
-    	let s = $el
-    		.iter()
-    		.fold("".to_string(), |s, x|
-    			format!("{}\n\t{}", s, x)
-    		);
-        format!("{{{}\n}}", s)
-    }};
-}
-
-/// `fn_body` - declare function with definition and body
-/// ```ignore
-/// let ret = ret!();
-/// let body = body!(ret);
-/// let fn_body = fn_body!(fn_def body);
-/// ```
-#[macro_export]
-macro_rules! fn_body {
-    ($fn_def:ident $fn_body:ident) => {{
-        format!("{} {}", $fn_def.to_string(), $fn_body.to_string())
-    }};
+macro_rules! assignment_instruction_set {
+    ($($name:ident)*) => {
+        $(
+            impl $crate::llvm::InstructionSet for $name {
+                fn set_context(&mut self, ctx: &mut $crate::llvm::context::Context) {
+                    if self.result.is_empty() {
+                        self.result = ctx.value();
+                    }
+                }
+                fn is_assignment(&self) -> bool {
+                    true
+                }
+                fn get_type(&self) -> Option<$crate::llvm::types::Type> {
+                    Some(self.ty.clone())
+                }
+                fn get_value(&self) -> Option<String> {
+                    Some(self.result.clone())
+                }
+                fn operands(&self) -> Vec<($crate::llvm::types::Type, String)> {
+                    vec![
+                        (self.ty.clone(), self.op1.clone()),
+                        (self.ty.clone(), self.op2.clone()),
+                    ]
+                }
+            }
+        )*
+    };
 }
 
-/// `body` macros
-/// Module declaration - contain main code for file
+/// `terminator_instruction_set` macros
+/// Implement `InstructionSet` for terminator instructions without
+/// result value, operands and successors.
 ///
 /// ```ignore
-/// // Example of module part combinations
-/// module!(fn body);
+/// terminator_instruction_set!(Unreachable);
 /// ```
-#[macro_export]
-macro_rules! module {
-    ($($el:expr)*) => {{
-    	let s = "";
-    	$(
-    		let s = format!("{}{}\n", s, $el);
-    	)*
-        format!("{}", s)
-    }};
-}
-
-/// Merge different blocks. Same as `module` macros - simply
-/// different naming
-#[macro_export]
-macro_rules! merge {
-    ($($el:expr)*) => {{
-    	let s = "";
-    	$(
-    		let s = if !$el.to_string().is_empty() {
-    			format!("{}{}\n", s, $el)
-    		} else {
-    			"".into()
-    		};
-    	)*
-        format!("{}", s)
-    }};
+macro_rules! terminator_instruction_set {
+    ($($name:ident)*) => {
+        $(
+            impl $crate::llvm::InstructionSet for $name {
+                fn set_context(&mut self, _ctx: &mut $crate::llvm::context::Context) {}
+                fn is_terminator(&self) -> bool {
+                    true
+                }
+            }
+        )*
+    };
 }
 
-/// `bf` macros
-/// Simple Function body aggregator
+/// `conversion_instruction_set` macros
+/// Implement `InstructionSet` for conversion instructions, where
+/// result type is the destination type (field `ty2`).
 ///
 /// ```ignore
-/// let ret1 = ret!(Integer32 @0);
-/// let entry1 = entry!(0);
-/// // Function body with instructions inside
-/// let body = bf!(entry1 ret1);
+/// conversion_instruction_set!(Trunc ZExt);
 /// ```
-#[macro_export]
-macro_rules! bf {
-    ($($el:expr)*) => {{
-    	let s = "";
-    	$(
-    		let s = if !$el.to_string().is_empty() {
-				format!("{}\n\t{}", s, $el)
-			} else {
-				"".into()
-			};
-    	)*
-        s
-    }};
-    (@$($el:expr)*) => {{
-    	let s = "";
-    	$(
-    		let s = format!("{}\n{}", s, $el);
-    	)*
-        s
-    }};
-    (=$($el:expr)*) => {{
-    	let s = "";
-    	$(
-    		let s = format!("{}{}", s, $el);
-    	)*
-        s
-    }};
+macro_rules! conversion_instruction_set {
+    ($($name:ident)*) => {
+        $(
+            impl $crate::llvm::InstructionSet for $name {
+                fn set_context(&mut self, ctx: &mut $crate::llvm::context::Context) {
+                    if self.result.is_empty() {
+                        self.result = ctx.value();
+                    }
+                }
+                fn is_assignment(&self) -> bool {
+                    true
+                }
+                fn get_type(&self) -> Option<$crate::llvm::types::Type> {
+                    Some(self.ty2.clone())
+                }
+                fn get_value(&self) -> Option<String> {
+                    Some(self.result.clone())
+                }
+                fn operands(&self) -> Vec<($crate::llvm::types::Type, String)> {
+                    vec![(self.ty.clone(), self.value.clone())]
+                }
+            }
+        )*
+    };
 }
@@ -0,0 +1,272 @@
+//! # Metadata
+//!
+//! LLVM IR allows metadata to be attached to instructions and global
+//! objects in the program that can convey extra information about
+//! the code to the optimizers and code generator.
+//!
+//! Metadata nodes are numbered in module (`!0`, `!1`, ...) and
+//! referenced by number. Named metadata is a collection of metadata
+//! nodes, which can be looked up in the module symbol table.
+//!
+//! Metadata can be attached to instructions, functions and global
+//! variables:
+//! ```html
+//! %indvar.next = add i64 %indvar, 1, !dbg !21
+//! define void @f() !dbg !4 { ... }
+//! ```
+//!
+//! https://llvm.org/docs/LangRef.html#metadata
+
+use crate::llvm::{
+    context::Context,
+    debug_info::DebugInfo,
+    types::Type,
+    InstructionSet,
+};
+
+/// Reference to numbered metadata node `!N`
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub struct MetadataRef(pub usize);
+
+/// Metadata value
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Metadata {
+    /// Metadata string: `!"test"`
+    String(String),
+    /// Constant value with type: `i32 1`
+    Value(Type, String),
+    /// Reference to metadata node: `!0`
+    Node(MetadataRef),
+    /// Metadata tuple: `!{!0, i32 1, !"test"}`
+    Tuple(Vec<Metadata>),
+    /// Empty element of metadata tuple: `null`
+    Null,
+    /// Specialized node: `!DILocation(line: 2, column: 3, scope: !4)`
+    DebugInfo(Box<DebugInfo>),
+}
+
+/// Numbered metadata node:
+/// ```html
+/// !0 = !{!"zero", !1}
+/// !1 = distinct !{!"one"}
+/// ```
+/// Distinct nodes are never merged with equal nodes.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct MetadataNode {
+    pub distinct: bool,
+    pub value: Metadata,
+}
+
+/// Named metadata: `!name = !{!0, !1}`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct NamedMetadata {
+    pub name: String,
+    pub nodes: Vec<MetadataRef>,
+}
+
+/// Kind of metadata attachment
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum MetadataKind {
+    /// Debug location or debug info of function and global
+    Dbg,
+    /// Type-based alias analysis
+    Tbaa,
+    /// Possible values of loaded integer
+    Range,
+    /// Branch weights and function entry counts
+    Prof,
+    Other(String),
+}
+
+/// Metadata attachment: `!dbg !3`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Attachment(pub MetadataKind, pub MetadataRef);
+
+/// Behavior of module flag, when two modules with same flag are
+/// linked.
+///
+/// https://llvm.org/docs/LangRef.html#module-flags-metadata
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ModuleFlagBehavior {
+    Error        = 1,
+    Warning      = 2,
+    Require      = 3,
+    Override     = 4,
+    Append       = 5,
+    AppendUnique = 6,
+    Max          = 7,
+    Min          = 8,
+}
+
+/// Instruction with metadata attachments. Printed as instruction
+/// followed by attachments: `ret void, !dbg !5`.
+#[derive(Debug)]
+pub struct AttachedInstruction {
+    pub instruction: Box<dyn InstructionSet>,
+    pub attachments: Vec<Attachment>,
+}
+
+impl MetadataKind {
+    pub fn new(name: &str) -> Self {
+        match name {
+            "dbg" => MetadataKind::Dbg,
+            "tbaa" => MetadataKind::Tbaa,
+            "range" => MetadataKind::Range,
+            "prof" => MetadataKind::Prof,
+            _ => MetadataKind::Other(name.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for MetadataRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "!{}", self.0)
+    }
+}
+
+impl std::fmt::Display for Metadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Metadata::String(s) => write!(f, "!\"{}\"", s),
+            Metadata::Value(ty, v) => write!(f, "{} {}", ty, v),
+            Metadata::Node(v) => write!(f, "{}", v),
+            Metadata::Tuple(v) => {
+                let s = v.iter().enumerate().fold("".to_string(), |s, (i, x)| {
+                    if i == 0 {
+                        format!("{}", x)
+                    } else {
+                        format!("{}, {}", s, x)
+                    }
+                });
+                write!(f, "!{{{}}}", s)
+            }
+            Metadata::Null => write!(f, "null"),
+            Metadata::DebugInfo(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl std::fmt::Display for MetadataNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.distinct {
+            write!(f, "distinct {}", self.value)
+        } else {
+            write!(f, "{}", self.value)
+        }
+    }
+}
+
+impl std::fmt::Display for NamedMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|v| Metadata::Node(*v))
+            .collect::<Vec<_>>();
+        write!(f, "!{} = {}", self.name, Metadata::Tuple(nodes))
+    }
+}
+
+impl std::fmt::Display for MetadataKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            MetadataKind::Dbg => "dbg",
+            MetadataKind::Tbaa => "tbaa",
+            MetadataKind::Range => "range",
+            MetadataKind::Prof => "prof",
+            MetadataKind::Other(v) => v,
+        };
+        write!(f, "!{}", s)
+    }
+}
+
+impl std::fmt::Display for Attachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.0, self.1)
+    }
+}
+
+impl std::fmt::Display for AttachedInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = self
+            .attachments
+            .iter()
+            .fold(self.instruction.to_string(), |s, x| format!("{}, {}", s, x));
+        write!(f, "{}", s)
+    }
+}
+
+impl InstructionSet for AttachedInstruction {
+    fn set_context(&mut self, ctx: &mut Context) {
+        self.instruction.set_context(ctx)
+    }
+    fn is_assignment(&self) -> bool {
+        self.instruction.is_assignment()
+    }
+    fn is_global(&self) -> bool {
+        self.instruction.is_global()
+    }
+    fn get_type(&self) -> Option<Type> {
+        self.instruction.get_type()
+    }
+    fn get_value(&self) -> Option<String> {
+        self.instruction.get_value()
+    }
+    fn is_terminator(&self) -> bool {
+        self.instruction.is_terminator()
+    }
+    fn successors(&self) -> Vec<String> {
+        self.instruction.successors()
+    }
+    fn operands(&self) -> Vec<(Type, String)> {
+        self.instruction.operands()
+    }
+    fn is_phi(&self) -> bool {
+        self.instruction.is_phi()
+    }
+    fn callee(&self) -> Option<String> {
+        self.instruction.callee()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::instructions::terminator::Ret;
+
+    #[test]
+    fn test_metadata() {
+        let v = Metadata::Tuple(vec![
+            Metadata::Value(Type::Integer32, "1".to_string()),
+            Metadata::String("wchar_size".to_string()),
+            Metadata::Node(MetadataRef(2)),
+            Metadata::Null,
+        ]);
+        assert_eq!(v.to_string(), r#"!{i32 1, !"wchar_size", !2, null}"#);
+        assert_eq!(Metadata::Tuple(vec![]).to_string(), "!{}");
+        let node = MetadataNode {
+            distinct: true,
+            value: Metadata::Tuple(vec![]),
+        };
+        assert_eq!(node.to_string(), "distinct !{}");
+        let named = NamedMetadata {
+            name: "llvm.module.flags".to_string(),
+            nodes: vec![MetadataRef(0), MetadataRef(1)],
+        };
+        assert_eq!(named.to_string(), "!llvm.module.flags = !{!0, !1}");
+    }
+
+    #[test]
+    fn test_attached_instruction() {
+        let v = AttachedInstruction {
+            instruction: Box::new(Ret(None)),
+            attachments: vec![
+                Attachment(MetadataKind::Dbg, MetadataRef(5)),
+                Attachment(MetadataKind::new("srcloc"), MetadataRef(6)),
+            ],
+        };
+        assert_eq!(v.to_string(), "ret void, !dbg !5, !srcloc !6");
+        assert!(v.is_terminator());
+        assert_eq!(MetadataKind::new("prof"), MetadataKind::Prof);
+    }
+}
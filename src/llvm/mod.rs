@@ -1,4 +1,7 @@
-use crate::llvm::types::Type;
+use crate::llvm::{
+    context::Context,
+    types::Type,
+};
 use std::fmt::{
     Debug,
     Display,
@@ -10,12 +13,15 @@ pub mod macros;
 pub mod addrspace;
 pub mod aliases;
 pub mod align;
+pub mod atomic_ordering;
 pub mod attribute_groups;
+pub mod builder;
 pub mod calling_convention;
 pub mod codegen;
 pub mod comdat;
 pub mod context;
 pub mod data_layout;
+pub mod debug_info;
 pub mod dll_storage_classes;
 pub mod fast_math_flags;
 pub mod function_attributes;
@@ -24,9 +30,13 @@ pub mod gc_stratagy_name;
 pub mod global_variables;
 pub mod ifunc;
 pub mod instructions;
+pub mod intrinsics;
+pub mod ir;
 pub mod linkage_types;
+pub mod metadata;
 pub mod module_inline_asm;
 pub mod parameter_attributes;
+pub mod parser;
 pub mod prefix;
 pub mod runtime_preemption;
 pub mod section;
@@ -35,17 +45,15 @@ pub mod target_triple;
 pub mod thread_local_storage;
 pub mod type_system;
 pub mod types;
+pub mod verifier;
 pub mod visibility_styles;
 
 pub trait InstructionSet: Debug + Display {
-    /// Set context of input values (it mean increment flow of
-    /// context values)
-    fn set_context(&mut self, ctx: u64);
-    /// Is context flow read only. So it mean previous context
-    /// should not be changed/incremented
-    fn is_read_only_context(&self) -> bool {
-        false
-    }
+    /// Allocate result value of instruction from function context.
+    /// Only unnamed results (empty value name) get next SSA
+    /// temporary, named results and instructions without
+    /// assignment leave context unchanged.
+    fn set_context(&mut self, ctx: &mut Context);
     /// For current instruction applicable assignment for value
     fn is_assignment(&self) -> bool {
         false
@@ -62,4 +70,27 @@ pub trait InstructionSet: Debug + Display {
     fn get_value(&self) -> Option<String> {
         None
     }
+    /// Is it terminator instruction of basic block
+    fn is_terminator(&self) -> bool {
+        false
+    }
+    /// Labels of basic blocks, where control can be transferred
+    /// by terminator instruction
+    fn successors(&self) -> Vec<String> {
+        vec![]
+    }
+    /// Typed operands of instruction, in order of appearance.
+    /// Operand is value name (with sigil) or constant text. Labels
+    /// and callee of call instructions are not operands.
+    fn operands(&self) -> Vec<(Type, String)> {
+        vec![]
+    }
+    /// Is it `phi` instruction
+    fn is_phi(&self) -> bool {
+        false
+    }
+    /// Called function value (with sigil) of call instructions
+    fn callee(&self) -> Option<String> {
+        None
+    }
 }
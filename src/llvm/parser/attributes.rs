@@ -0,0 +1,337 @@
+//! # Attributes parser
+//!
+//! Parse linkage types, visibility, calling conventions, parameter
+//! attributes, fast-math flags, atomic orderings and other keywords,
+//! used by global values and instructions.
+
+use nom::{
+    branch::alt,
+    bytes::complete::take_while1,
+    character::complete::{
+        char,
+        space1,
+    },
+    combinator::{
+        map,
+        value,
+    },
+    sequence::{
+        delimited,
+        preceded,
+        tuple,
+    },
+};
+
+use super::{
+    integer,
+    keyword,
+    quoted_string,
+    ws,
+    ParseResult,
+};
+use crate::llvm::{
+    addrspace::AddrSpace,
+    align::Alignment,
+    atomic_ordering::{
+        AtomicOrdering,
+        SyncScope,
+    },
+    calling_convention::CallingConvention,
+    dll_storage_classes::DllStorageClasses,
+    fast_math_flags::FastMathFlags,
+    global_variables::UnnamedAddr,
+    linkage_types::LinkageTypes,
+    parameter_attributes::ParameterAttributes,
+    runtime_preemption::RuntimePreemptionSpecifier,
+    thread_local_storage::ThreadLocalStorage,
+    visibility_styles::VisibilityStyles,
+};
+
+/// ## RULE:
+/// ```js
+/// linkage = "private" | "internal" | "available_externally" |
+///     "linkonce" | "weak" | "common" | "appending" | "extern_weak" |
+///     "linkonce_odr" | "weak_odr" | "external"
+/// ```
+pub fn linkage(data: &str) -> ParseResult<'_, LinkageTypes> {
+    alt((
+        value(LinkageTypes::Private, keyword("private")),
+        value(LinkageTypes::Internal, keyword("internal")),
+        value(
+            LinkageTypes::AvailableExternally,
+            keyword("available_externally"),
+        ),
+        value(LinkageTypes::LinkOnce, keyword("linkonce")),
+        value(LinkageTypes::Weak, keyword("weak")),
+        value(LinkageTypes::Common, keyword("common")),
+        value(LinkageTypes::Appending, keyword("appending")),
+        value(LinkageTypes::ExternWeak, keyword("extern_weak")),
+        value(LinkageTypes::LinkonceOdr, keyword("linkonce_odr")),
+        value(LinkageTypes::WeakOdr, keyword("weak_odr")),
+        value(LinkageTypes::External, keyword("external")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// preemption-specifier = "dso_preemptable" | "dso_local"
+/// ```
+pub fn preemption_specifier(data: &str) -> ParseResult<'_, RuntimePreemptionSpecifier> {
+    alt((
+        value(
+            RuntimePreemptionSpecifier::DsoPreemptable,
+            keyword("dso_preemptable"),
+        ),
+        value(RuntimePreemptionSpecifier::DsoLocal, keyword("dso_local")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// visibility = "default" | "hidden" | "protected"
+/// ```
+pub fn visibility(data: &str) -> ParseResult<'_, VisibilityStyles> {
+    alt((
+        value(VisibilityStyles::Default, keyword("default")),
+        value(VisibilityStyles::Hidden, keyword("hidden")),
+        value(VisibilityStyles::Protected, keyword("protected")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// dll-storage-class = "dllimport" | "dllexport"
+/// ```
+pub fn dll_storage_class(data: &str) -> ParseResult<'_, DllStorageClasses> {
+    alt((
+        value(DllStorageClasses::DllImport, keyword("dllimport")),
+        value(DllStorageClasses::DllExport, keyword("dllexport")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// thread-local = "thread_local(" ( "localdynamic" | "initialexec" | "localexec" ) ")"
+/// ```
+pub fn thread_local(data: &str) -> ParseResult<'_, ThreadLocalStorage> {
+    delimited(
+        tuple((keyword("thread_local"), char('('))),
+        alt((
+            value(ThreadLocalStorage::LocalDynamic, keyword("localdynamic")),
+            value(ThreadLocalStorage::InitialExec, keyword("initialexec")),
+            value(ThreadLocalStorage::LocalExec, keyword("localexec")),
+        )),
+        char(')'),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// unnamed-addr = "unnamed_addr" | "local_unnamed_addr"
+/// ```
+pub fn unnamed_addr(data: &str) -> ParseResult<'_, UnnamedAddr> {
+    alt((
+        value(UnnamedAddr::UnnamedAddr, keyword("unnamed_addr")),
+        value(UnnamedAddr::LocalUnnamedAddr, keyword("local_unnamed_addr")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// addrspace = "addrspace(" integer ")"
+/// ```
+pub fn addrspace(data: &str) -> ParseResult<'_, AddrSpace> {
+    map(
+        delimited(tuple((keyword("addrspace"), char('('))), integer, char(')')),
+        |v| AddrSpace(v as u32),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// alignment = "align" integer
+/// ```
+pub fn alignment(data: &str) -> ParseResult<'_, Alignment> {
+    map(preceded(tuple((keyword("align"), space1)), integer), |v| {
+        Alignment(v as u32)
+    })(data)
+}
+
+/// ## RULE:
+/// ```js
+/// calling-convention = "ccc" | "fastcc" | "cc 10" | "cc 11" | "webkit_jscc" |
+///     "anyregcc" | "preserve_mostcc" | "preserve_allcc" | "cxx_fast_tlscc" |
+///     "swiftcc" | "tailcc" | "cfguard_checkcc"
+/// ```
+pub fn calling_convention(data: &str) -> ParseResult<'_, CallingConvention> {
+    alt((
+        value(CallingConvention::CCc, keyword("ccc")),
+        value(CallingConvention::FastCc, keyword("fastcc")),
+        value(
+            CallingConvention::Cc10,
+            tuple((keyword("cc"), ws(keyword("10")))),
+        ),
+        value(
+            CallingConvention::Cc11,
+            tuple((keyword("cc"), ws(keyword("11")))),
+        ),
+        value(CallingConvention::WebkitJsCc, keyword("webkit_jscc")),
+        value(CallingConvention::AnyRegCc, keyword("anyregcc")),
+        value(
+            CallingConvention::PreserveMostCc,
+            keyword("preserve_mostcc"),
+        ),
+        value(CallingConvention::PreserveAllCc, keyword("preserve_allcc")),
+        value(CallingConvention::CxxFastYlsCc, keyword("cxx_fast_tlscc")),
+        value(CallingConvention::SwiftCc, keyword("swiftcc")),
+        value(CallingConvention::YailCc, keyword("tailcc")),
+        value(
+            CallingConvention::CfGuardCheckCc,
+            keyword("cfguard_checkcc"),
+        ),
+    ))(data)
+}
+
+/// Argument of parameter attribute in brackets, kept as text
+fn attribute_argument<'a>(kw: &'static str) -> impl Fn(&'a str) -> ParseResult<'a, String> {
+    map(
+        delimited(
+            tuple((keyword(kw), char('('))),
+            take_while1(|c| c != ')'),
+            char(')'),
+        ),
+        String::from,
+    )
+}
+
+/// ## RULE:
+/// ```js
+/// parameter-attribute = "zeroext" | "signext" | "inreg" | "byval" |
+///     "byref(" type ")" | "preallocated(" type ")" | "inalloca" | "sret" |
+///     "align" integer | "noalias" | "nocapture" | "nofree" | "nest" |
+///     "returned" | "nonnull" | "dereferenceable(" integer ")" |
+///     "dereferenceable_or_null(" integer ")" | "swiftself" | "swifterror" |
+///     "immarg" | "noundef"
+/// ```
+pub fn parameter_attribute(data: &str) -> ParseResult<'_, ParameterAttributes> {
+    alt((
+        alt((
+            value(ParameterAttributes::ZeroExt, keyword("zeroext")),
+            value(ParameterAttributes::SignExt, keyword("signext")),
+            value(ParameterAttributes::InReg, keyword("inreg")),
+            value(ParameterAttributes::ByVal, keyword("byval")),
+            map(attribute_argument("byref"), ParameterAttributes::ByRef),
+            map(
+                attribute_argument("preallocated"),
+                ParameterAttributes::PreAllocated,
+            ),
+            value(ParameterAttributes::InAlloca, keyword("inalloca")),
+            value(ParameterAttributes::Sret, keyword("sret")),
+            map(alignment, |v| ParameterAttributes::Allign(v.0.to_string())),
+            value(ParameterAttributes::NoAlias, keyword("noalias")),
+        )),
+        alt((
+            value(ParameterAttributes::NoCapture, keyword("nocapture")),
+            value(ParameterAttributes::NoFree, keyword("nofree")),
+            value(ParameterAttributes::Nest, keyword("nest")),
+            value(ParameterAttributes::Returned, keyword("returned")),
+            value(ParameterAttributes::NonNull, keyword("nonnull")),
+            map(
+                attribute_argument("dereferenceable"),
+                ParameterAttributes::Dereferenceable,
+            ),
+            map(
+                attribute_argument("dereferenceable_or_null"),
+                ParameterAttributes::DereferenceableOrNull,
+            ),
+            value(ParameterAttributes::SwiftSelf, keyword("swiftself")),
+            value(ParameterAttributes::SwiftError, keyword("swifterror")),
+            value(ParameterAttributes::ImmArg, keyword("immarg")),
+            value(ParameterAttributes::NoUndef, keyword("noundef")),
+        )),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// fast-math-flag = "nnan" | "ninf" | "nsz" | "arcp" | "contract" |
+///     "afn" | "reassoc" | "fast"
+/// ```
+pub fn fast_math_flag(data: &str) -> ParseResult<'_, FastMathFlags> {
+    alt((
+        value(FastMathFlags::Nnan, keyword("nnan")),
+        value(FastMathFlags::Ninf, keyword("ninf")),
+        value(FastMathFlags::Nsz, keyword("nsz")),
+        value(FastMathFlags::Arcp, keyword("arcp")),
+        value(FastMathFlags::Contract, keyword("contract")),
+        value(FastMathFlags::Afn, keyword("afn")),
+        value(FastMathFlags::Reassoc, keyword("reassoc")),
+        value(FastMathFlags::Fast, keyword("fast")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// atomic-ordering = "unordered" | "monotonic" | "acquire" | "release" |
+///     "acq_rel" | "seq_cst"
+/// ```
+pub fn atomic_ordering(data: &str) -> ParseResult<'_, AtomicOrdering> {
+    alt((
+        value(AtomicOrdering::Unordered, keyword("unordered")),
+        value(AtomicOrdering::Monotonic, keyword("monotonic")),
+        value(AtomicOrdering::Acquire, keyword("acquire")),
+        value(AtomicOrdering::Release, keyword("release")),
+        value(AtomicOrdering::AcqRel, keyword("acq_rel")),
+        value(AtomicOrdering::SeqCst, keyword("seq_cst")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// syncscope = "syncscope(" quoted-string ")"
+/// ```
+pub fn syncscope(data: &str) -> ParseResult<'_, SyncScope> {
+    map(
+        delimited(
+            tuple((keyword("syncscope"), char('('))),
+            quoted_string,
+            char(')'),
+        ),
+        |s| SyncScope(s.trim_matches('"').to_string()),
+    )(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: std::fmt::Display>(parser: fn(&str) -> ParseResult<T>, s: &str) {
+        let (i, v) = parser(s).unwrap();
+        assert_eq!(i, "");
+        assert_eq!(v.to_string(), s);
+    }
+
+    #[test]
+    fn test_attributes() {
+        round_trip(linkage, "linkonce_odr");
+        round_trip(linkage, "extern_weak");
+        round_trip(linkage, "external");
+        round_trip(preemption_specifier, "dso_local");
+        round_trip(visibility, "hidden");
+        round_trip(dll_storage_class, "dllexport");
+        round_trip(thread_local, "thread_local(initialexec)");
+        round_trip(unnamed_addr, "local_unnamed_addr");
+        round_trip(addrspace, "addrspace(1)");
+        round_trip(alignment, "align 8");
+        round_trip(calling_convention, "cc 10");
+        round_trip(calling_convention, "tailcc");
+        round_trip(parameter_attribute, "noundef");
+        round_trip(parameter_attribute, "align 4");
+        round_trip(parameter_attribute, "dereferenceable_or_null(8)");
+        round_trip(parameter_attribute, "byref(i32)");
+        round_trip(fast_math_flag, "reassoc");
+        round_trip(atomic_ordering, "acq_rel");
+        round_trip(syncscope, r#"syncscope("agent")"#);
+        assert!(linkage("weakness").is_err());
+    }
+}
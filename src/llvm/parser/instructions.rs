@@ -0,0 +1,1496 @@
+//! # Instructions parser
+//!
+//! Parse instructions of basic block to instructions structs.
+//! Function attributes and operand bundles of calls are not
+//! supported.
+//!
+//! https://llvm.org/docs/LangRef.html#instruction-reference
+
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::{
+        map,
+        map_opt,
+        opt,
+        value as constant,
+    },
+    multi::{
+        many0,
+        many1,
+        separated_list,
+    },
+    sequence::{
+        delimited,
+        preceded,
+        terminated,
+        tuple,
+    },
+};
+
+use super::{
+    attributes::{
+        addrspace,
+        alignment,
+        atomic_ordering,
+        calling_convention,
+        fast_math_flag,
+        parameter_attribute,
+        syncscope,
+    },
+    comma,
+    global_value,
+    integer,
+    keyword,
+    label,
+    local_value,
+    metadata::metadata,
+    signed_integer,
+    types::{
+        ty,
+        vector_type,
+    },
+    value,
+    ws,
+    ParseResult,
+};
+use crate::llvm::{
+    functions::ArgumentList,
+    instructions::{
+        aggregate_operations::*,
+        binary_operations::*,
+        bitwise_binary_operations::*,
+        conversion_operations::*,
+        memory_access_addressing_operations::*,
+        other_operations::*,
+        terminator::*,
+        unary_operations::*,
+        vector_operations::*,
+    },
+    type_system::FunctionType,
+    types::Type,
+    InstructionSet,
+};
+
+/// Parse instruction
+/// ## RULE:
+/// ```js
+/// instruction = binary-operation | conversion-operation |
+///     memory-operation | aggregate-operation | other-operation |
+///     terminator
+/// ```
+pub fn instruction(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    alt((
+        binary_operation,
+        conversion_operation,
+        memory_operation,
+        aggregate_operation,
+        other_operation,
+        terminator,
+    ))(data)
+}
+
+fn boxed<'a, O, F>(func: F) -> impl Fn(&'a str) -> ParseResult<'a, Box<dyn InstructionSet>>
+where
+    O: InstructionSet + 'static,
+    F: Fn(&'a str) -> ParseResult<'a, O>,
+{
+    map(func, |v| Box::new(v) as Box<dyn InstructionSet>)
+}
+
+/// Result value of instruction
+/// ## RULE:
+/// ```js
+/// result = local-value "="
+/// ```
+fn result(data: &str) -> ParseResult<'_, String> {
+    terminated(local_value, ws(char('=')))(data)
+}
+
+/// Optional keyword flag of instruction
+fn flag<'a>(kw: &'static str) -> impl Fn(&'a str) -> ParseResult<'a, Option<()>> {
+    map(opt(ws(keyword(kw))), |v| v.map(|_| ()))
+}
+
+/// Type of pointer operand, returns type of pointee. Opaque pointer
+/// has no pointee, so it is `None`.
+fn pointer(data: &str) -> ParseResult<'_, Option<Type>> {
+    map_opt(ty, |t| match t {
+        Type::Pointer(x) => Some(x.pointee.map(|v| *v)),
+        _ => None,
+    })(data)
+}
+
+/// ## RULE:
+/// ```js
+/// operands = type value "," value
+/// ```
+fn operands(data: &str) -> ParseResult<'_, (Type, String, String)> {
+    map(
+        tuple((ws(ty), ws(value), comma, ws(value))),
+        |(ty, op1, _, op2)| (ty, op1, op2),
+    )(data)
+}
+
+/// Typed value
+/// ## RULE:
+/// ```js
+/// typed-value = type value
+/// ```
+fn typed_value(data: &str) -> ParseResult<'_, (Type, String)> {
+    tuple((ws(ty), ws(value)))(data)
+}
+
+macro_rules! binary_operation {
+    ($func:ident $name:ident $op:literal) => {
+        fn $func(data: &str) -> ParseResult<'_, $name> {
+            map(
+                tuple((result, ws(keyword($op)), operands)),
+                |(result, _, (ty, op1, op2))| $name {
+                    result,
+                    ty,
+                    op1,
+                    op2,
+                },
+            )(data)
+        }
+    };
+    ($func:ident $name:ident $op:literal nuw nsw) => {
+        fn $func(data: &str) -> ParseResult<'_, $name> {
+            map(
+                tuple((result, ws(keyword($op)), flag("nuw"), flag("nsw"), operands)),
+                |(result, _, nuw, nsw, (ty, op1, op2))| $name {
+                    result,
+                    nuw,
+                    nsw,
+                    ty,
+                    op1,
+                    op2,
+                },
+            )(data)
+        }
+    };
+    ($func:ident $name:ident $op:literal exact) => {
+        fn $func(data: &str) -> ParseResult<'_, $name> {
+            map(
+                tuple((result, ws(keyword($op)), flag("exact"), operands)),
+                |(result, _, exact, (ty, op1, op2))| $name {
+                    result,
+                    exact,
+                    ty,
+                    op1,
+                    op2,
+                },
+            )(data)
+        }
+    };
+    ($func:ident $name:ident $op:literal fast_math_flags) => {
+        fn $func(data: &str) -> ParseResult<'_, $name> {
+            map(
+                tuple((result, ws(keyword($op)), opt(ws(fast_math_flag)), operands)),
+                |(result, _, fast_math_flags, (ty, op1, op2))| $name {
+                    result,
+                    fast_math_flags,
+                    ty,
+                    op1,
+                    op2,
+                },
+            )(data)
+        }
+    };
+}
+
+binary_operation!(add Add "add" nuw nsw);
+binary_operation!(sub Sub "sub" nuw nsw);
+binary_operation!(mul Mul "mul" nuw nsw);
+binary_operation!(shl Shl "shl" nuw nsw);
+binary_operation!(udiv UDiv "udiv" exact);
+binary_operation!(sdiv SDiv "sdiv" exact);
+binary_operation!(lshr LShl "lshr" exact);
+binary_operation!(ashr AShr "ashr" exact);
+binary_operation!(urem URem "urem");
+binary_operation!(srem SRem "srem");
+binary_operation!(and And "and");
+binary_operation!(or Or "or");
+binary_operation!(xor Xor "xor");
+binary_operation!(fadd FAdd "fadd" fast_math_flags);
+binary_operation!(fsub FSub "fsub" fast_math_flags);
+binary_operation!(fmul FMul "fmul" fast_math_flags);
+binary_operation!(fdiv FDiv "fdiv" fast_math_flags);
+binary_operation!(frem FRem "frem" fast_math_flags);
+
+/// ## RULE:
+/// ```js
+/// fneg = result "fneg" fast-math-flag* type value
+/// ```
+fn fneg(data: &str) -> ParseResult<'_, FNeg> {
+    map(
+        tuple((
+            result,
+            ws(keyword("fneg")),
+            many0(ws(fast_math_flag)),
+            typed_value,
+        )),
+        |(result, _, fast_math_flags, (ty, operand))| FNeg {
+            result,
+            fast_math_flags,
+            ty,
+            operand,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// binary-operation = result binary-opcode [flags] type value "," value |
+///     fneg
+/// ```
+fn binary_operation(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    alt((
+        boxed(add),
+        boxed(sub),
+        boxed(mul),
+        boxed(shl),
+        boxed(udiv),
+        boxed(sdiv),
+        boxed(lshr),
+        boxed(ashr),
+        boxed(urem),
+        boxed(srem),
+        boxed(and),
+        boxed(or),
+        boxed(xor),
+        boxed(fadd),
+        boxed(fsub),
+        boxed(fmul),
+        boxed(fdiv),
+        boxed(frem),
+        boxed(fneg),
+    ))(data)
+}
+
+macro_rules! conversion_operation {
+    ($($func:ident $name:ident $op:literal)*) => {
+        $(
+            fn $func(data: &str) -> ParseResult<'_, $name> {
+                map(
+                    tuple((
+                        result,
+                        ws(keyword($op)),
+                        typed_value,
+                        ws(keyword("to")),
+                        ws(ty),
+                    )),
+                    |(result, _, (ty, value), _, ty2)| $name {
+                        result,
+                        ty,
+                        value,
+                        ty2,
+                    },
+                )(data)
+            }
+        )*
+    };
+}
+
+conversion_operation!(
+    trunc Trunc "trunc"
+    zext ZExt "zext"
+    sext SExt "sext"
+    fptrunc FPTrunc "fptrunc"
+    fpext FPExt "fpext"
+    fptoui FPToUI "fptoui"
+    fptosi FPToSI "fptosi"
+    uitofp UIToFP "uitofp"
+    sitofp SIToFP "sitofp"
+    ptrtoint PtrToInt "ptrtoint"
+    inttoptr IntToPtr "inttoptr"
+    bitcast BitCast "bitcast"
+    addrspacecast AddrSpaceCast "addrspacecast"
+);
+
+/// ## RULE:
+/// ```js
+/// conversion-operation = result conversion-opcode type value "to" type
+/// ```
+fn conversion_operation(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    alt((
+        boxed(trunc),
+        boxed(zext),
+        boxed(sext),
+        boxed(fptrunc),
+        boxed(fpext),
+        boxed(fptoui),
+        boxed(fptosi),
+        boxed(uitofp),
+        boxed(sitofp),
+        boxed(ptrtoint),
+        boxed(inttoptr),
+        boxed(bitcast),
+        boxed(addrspacecast),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// alloca = result "alloca" type ( "," type integer )* [ "," alignment ] [ "," addrspace ]
+/// ```
+fn alloca(data: &str) -> ParseResult<'_, Alloca> {
+    map(
+        tuple((
+            result,
+            ws(keyword("alloca")),
+            ws(ty),
+            many0(preceded(comma, tuple((ws(ty), ws(signed_integer))))),
+            opt(preceded(comma, ws(alignment))),
+            opt(preceded(comma, ws(addrspace))),
+        )),
+        |(result, _, alloc_ty, elements, align, addrspace)| Alloca {
+            result,
+            alloc_ty,
+            elements: if elements.is_empty() {
+                None
+            } else {
+                Some(elements)
+            },
+            align,
+            addrspace,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// load = result "load" [ "atomic" ] [ "volatile" ] type "," pointer-type value
+///     [ syncscope ] [ atomic-ordering ] [ "," alignment ]
+/// ```
+fn load(data: &str) -> ParseResult<'_, Load> {
+    map(
+        tuple((
+            result,
+            ws(keyword("load")),
+            flag("atomic"),
+            flag("volatile"),
+            ws(ty),
+            comma,
+            ws(pointer),
+            ws(value),
+            opt(ws(syncscope)),
+            opt(ws(atomic_ordering)),
+            opt(preceded(comma, ws(alignment))),
+        )),
+        |(result, _, _, volatile, ty, _, ty_pointer, pointer, syncscope, atomic, align)| Load {
+            result,
+            atomic,
+            syncscope,
+            volatile,
+            ty_pointer: ty_pointer.unwrap_or_else(|| ty.clone()),
+            ty,
+            pointer,
+            align,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// store = "store" [ "atomic" ] [ "volatile" ] type value "," pointer-type value
+///     [ syncscope ] [ atomic-ordering ] [ "," alignment ]
+/// ```
+fn store(data: &str) -> ParseResult<'_, Store> {
+    map(
+        tuple((
+            ws(keyword("store")),
+            flag("atomic"),
+            flag("volatile"),
+            typed_value,
+            comma,
+            ws(pointer),
+            ws(value),
+            opt(ws(syncscope)),
+            opt(ws(atomic_ordering)),
+            opt(preceded(comma, ws(alignment))),
+        )),
+        |(_, _, volatile, (ty, value), _, ty_pointer, pointer, syncscope, atomic, align)| Store {
+            atomic,
+            syncscope,
+            volatile,
+            ty_pointer: ty_pointer.unwrap_or_else(|| ty.clone()),
+            ty,
+            value,
+            pointer,
+            align,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// getelementptr = result "getelementptr" [ "inbounds" ] type "," pointer-type value
+///     ( "," [ "inrange" ] type integer )*
+/// ```
+fn getelementptr(data: &str) -> ParseResult<'_, GetElementPtr> {
+    map(
+        tuple((
+            result,
+            ws(keyword("getelementptr")),
+            flag("inbounds"),
+            ws(ty),
+            comma,
+            ws(pointer),
+            ws(value),
+            many0(preceded(
+                comma,
+                tuple((flag("inrange"), ws(ty), ws(integer))),
+            )),
+        )),
+        |(result, _, inbounds, ty, _, ty_pointer, ptr_val, range_val)| GetElementPtr {
+            result,
+            inbounds,
+            ty_pointer: ty_pointer.unwrap_or_else(|| ty.clone()),
+            ty,
+            ptr_val,
+            range_val,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// fence = "fence" [ syncscope ] atomic-ordering
+/// ```
+fn fence(data: &str) -> ParseResult<'_, Fence> {
+    map(
+        tuple((
+            ws(keyword("fence")),
+            opt(ws(syncscope)),
+            ws(atomic_ordering),
+        )),
+        |(_, syncscope, ordering)| Fence {
+            syncscope,
+            ordering,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// cmpxchg = result "cmpxchg" [ "weak" ] [ "volatile" ] pointer-type value ","
+///     type value "," type value [ syncscope ] atomic-ordering atomic-ordering
+///     [ "," alignment ]
+/// ```
+fn cmpxchg(data: &str) -> ParseResult<'_, Cmpxchg> {
+    map(
+        tuple((
+            result,
+            ws(keyword("cmpxchg")),
+            flag("weak"),
+            flag("volatile"),
+            ws(pointer),
+            ws(value),
+            comma,
+            typed_value,
+            comma,
+            typed_value,
+            opt(ws(syncscope)),
+            ws(atomic_ordering),
+            ws(atomic_ordering),
+            opt(preceded(comma, ws(alignment))),
+        )),
+        |(
+            result,
+            _,
+            weak,
+            volatile,
+            ty,
+            pointer,
+            _,
+            (ty_cmp, cmp),
+            _,
+            (_, new),
+            syncscope,
+            success_ordering,
+            failure_ordering,
+            align,
+        )| Cmpxchg {
+            result,
+            weak,
+            volatile,
+            ty: ty.unwrap_or(ty_cmp),
+            pointer,
+            cmp,
+            new,
+            syncscope,
+            success_ordering,
+            failure_ordering,
+            align,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// atomicrmw-operation = "xchg" | "add" | "sub" | "and" | "nand" | "or" |
+///     "xor" | "max" | "min" | "umax" | "umin" | "fadd" | "fsub" |
+///     "fmax" | "fmin"
+/// ```
+fn atomicrmw_operation(data: &str) -> ParseResult<'_, AtomicRMWOperation> {
+    alt((
+        constant(AtomicRMWOperation::Xchg, keyword("xchg")),
+        constant(AtomicRMWOperation::Add, keyword("add")),
+        constant(AtomicRMWOperation::Sub, keyword("sub")),
+        constant(AtomicRMWOperation::And, keyword("and")),
+        constant(AtomicRMWOperation::Nand, keyword("nand")),
+        constant(AtomicRMWOperation::Or, keyword("or")),
+        constant(AtomicRMWOperation::Xor, keyword("xor")),
+        constant(AtomicRMWOperation::Max, keyword("max")),
+        constant(AtomicRMWOperation::Min, keyword("min")),
+        constant(AtomicRMWOperation::UMax, keyword("umax")),
+        constant(AtomicRMWOperation::UMin, keyword("umin")),
+        constant(AtomicRMWOperation::FAdd, keyword("fadd")),
+        constant(AtomicRMWOperation::FSub, keyword("fsub")),
+        constant(AtomicRMWOperation::FMax, keyword("fmax")),
+        constant(AtomicRMWOperation::FMin, keyword("fmin")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// atomicrmw = result "atomicrmw" [ "volatile" ] atomicrmw-operation
+///     pointer-type value "," type value [ syncscope ] atomic-ordering
+///     [ "," alignment ]
+/// ```
+fn atomicrmw(data: &str) -> ParseResult<'_, AtomicRMW> {
+    map(
+        tuple((
+            result,
+            ws(keyword("atomicrmw")),
+            flag("volatile"),
+            ws(atomicrmw_operation),
+            ws(pointer),
+            ws(value),
+            comma,
+            typed_value,
+            opt(ws(syncscope)),
+            ws(atomic_ordering),
+            opt(preceded(comma, ws(alignment))),
+        )),
+        |(
+            result,
+            _,
+            volatile,
+            operation,
+            ty,
+            pointer,
+            _,
+            (ty_value, value),
+            syncscope,
+            ordering,
+            align,
+        )| {
+            AtomicRMW {
+                result,
+                volatile,
+                operation,
+                ty: ty.unwrap_or(ty_value),
+                pointer,
+                value,
+                syncscope,
+                ordering,
+                align,
+            }
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// memory-operation = alloca | load | store | getelementptr | fence |
+///     cmpxchg | atomicrmw
+/// ```
+fn memory_operation(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    alt((
+        boxed(alloca),
+        boxed(load),
+        boxed(store),
+        boxed(getelementptr),
+        boxed(fence),
+        boxed(cmpxchg),
+        boxed(atomicrmw),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// indices = ( "," integer )+
+/// ```
+fn indices(data: &str) -> ParseResult<'_, Vec<u64>> {
+    many1(preceded(comma, ws(integer)))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// extractvalue = result "extractvalue" type value indices
+/// ```
+fn extractvalue(data: &str) -> ParseResult<'_, Extractvalue> {
+    map(
+        tuple((result, ws(keyword("extractvalue")), typed_value, indices)),
+        |(result, _, (aggregate_type, val), idx)| Extractvalue {
+            result,
+            aggregate_type,
+            val,
+            idx,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// insertvalue = result "insertvalue" type value "," type value indices
+/// ```
+fn insertvalue(data: &str) -> ParseResult<'_, Insertvalue> {
+    map(
+        tuple((
+            result,
+            ws(keyword("insertvalue")),
+            typed_value,
+            comma,
+            typed_value,
+            indices,
+        )),
+        |(result, _, (aggregate_type, val), _, (ty, elt), idx)| Insertvalue {
+            result,
+            aggregate_type,
+            val,
+            ty,
+            elt,
+            idx,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// extractelement = result "extractelement" vector-type value "," type value
+/// ```
+fn extractelement(data: &str) -> ParseResult<'_, Extractelement> {
+    map(
+        tuple((
+            result,
+            ws(keyword("extractelement")),
+            ws(vector_type),
+            ws(value),
+            comma,
+            typed_value,
+        )),
+        |(result, _, ty, val, _, (ty2, idx))| Extractelement {
+            result,
+            ty,
+            val,
+            ty2,
+            idx,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// insertelement = result "insertelement" vector-type value "," type value
+///     "," type value
+/// ```
+fn insertelement(data: &str) -> ParseResult<'_, Insertelement> {
+    map(
+        tuple((
+            result,
+            ws(keyword("insertelement")),
+            ws(vector_type),
+            ws(value),
+            comma,
+            typed_value,
+            comma,
+            typed_value,
+        )),
+        |(result, _, ty, val, _, (_, elt), _, (ty2, idx))| Insertelement {
+            result,
+            ty,
+            val,
+            elt,
+            ty2,
+            idx,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// shuffle-mask = "<" "i32" ( integer | "undef" ) ( "," "i32" ( integer | "undef" ) )* ">"
+/// ```
+fn shuffle_mask(data: &str) -> ParseResult<'_, Vec<Option<u32>>> {
+    let element = alt((
+        map(integer, |v| Some(v as u32)),
+        constant(None, keyword("undef")),
+    ));
+    delimited(
+        char('<'),
+        separated_list(comma, preceded(ws(keyword("i32")), ws(element))),
+        ws(char('>')),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// shufflevector = result "shufflevector" vector-type value "," vector-type value
+///     "," vector-type shuffle-mask
+/// ```
+fn shufflevector(data: &str) -> ParseResult<'_, Shufflevector> {
+    map(
+        tuple((
+            result,
+            ws(keyword("shufflevector")),
+            ws(vector_type),
+            ws(value),
+            comma,
+            ws(vector_type),
+            ws(value),
+            comma,
+            ws(vector_type),
+            ws(shuffle_mask),
+        )),
+        |(result, _, ty, v1, _, _, v2, _, _, mask)| Shufflevector {
+            result,
+            ty,
+            v1,
+            v2,
+            mask,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// aggregate-operation = extractvalue | insertvalue | extractelement |
+///     insertelement | shufflevector
+/// ```
+fn aggregate_operation(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    alt((
+        boxed(extractvalue),
+        boxed(insertvalue),
+        boxed(extractelement),
+        boxed(insertelement),
+        boxed(shufflevector),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// icmp-condition = "eq" | "ne" | "ugt" | "uge" | "ult" | "ule" |
+///     "sgt" | "sge" | "slt" | "sle"
+/// ```
+fn icmp_condition(data: &str) -> ParseResult<'_, IcmpCondition> {
+    alt((
+        constant(IcmpCondition::Eq, keyword("eq")),
+        constant(IcmpCondition::Ne, keyword("ne")),
+        constant(IcmpCondition::Ugt, keyword("ugt")),
+        constant(IcmpCondition::Uge, keyword("uge")),
+        constant(IcmpCondition::Ult, keyword("ult")),
+        constant(IcmpCondition::Ule, keyword("ule")),
+        constant(IcmpCondition::Sgt, keyword("sgt")),
+        constant(IcmpCondition::Sge, keyword("sge")),
+        constant(IcmpCondition::Slt, keyword("slt")),
+        constant(IcmpCondition::Sle, keyword("sle")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// fcmp-condition = "false" | "oeq" | "ogt" | "oge" | "olt" | "ole" |
+///     "one" | "ord" | "ueq" | "ugt" | "uge" | "ult" | "ule" | "une" |
+///     "uno" | "true"
+/// ```
+fn fcmp_condition(data: &str) -> ParseResult<'_, FcmpCondition> {
+    alt((
+        constant(FcmpCondition::False, keyword("false")),
+        constant(FcmpCondition::Oeq, keyword("oeq")),
+        constant(FcmpCondition::Ogt, keyword("ogt")),
+        constant(FcmpCondition::Oge, keyword("oge")),
+        constant(FcmpCondition::Olt, keyword("olt")),
+        constant(FcmpCondition::Ole, keyword("ole")),
+        constant(FcmpCondition::One, keyword("one")),
+        constant(FcmpCondition::Ord, keyword("ord")),
+        constant(FcmpCondition::Ueq, keyword("ueq")),
+        constant(FcmpCondition::Ugt, keyword("ugt")),
+        constant(FcmpCondition::Uge, keyword("uge")),
+        constant(FcmpCondition::Ult, keyword("ult")),
+        constant(FcmpCondition::Ule, keyword("ule")),
+        constant(FcmpCondition::Une, keyword("une")),
+        constant(FcmpCondition::Uno, keyword("uno")),
+        constant(FcmpCondition::True, keyword("true")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// icmp = result "icmp" icmp-condition type value "," value
+/// ```
+fn icmp(data: &str) -> ParseResult<'_, Icmp> {
+    map(
+        tuple((result, ws(keyword("icmp")), ws(icmp_condition), operands)),
+        |(res_val, _, cond, (ty, op1, op2))| Icmp {
+            res_val,
+            cond,
+            ty,
+            op1,
+            op2,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// fcmp = result "fcmp" [ fast-math-flag ] fcmp-condition type value "," value
+/// ```
+fn fcmp(data: &str) -> ParseResult<'_, Fcmp> {
+    map(
+        tuple((
+            result,
+            ws(keyword("fcmp")),
+            opt(ws(fast_math_flag)),
+            ws(fcmp_condition),
+            operands,
+        )),
+        |(res_val, _, fast_math_flags, cond, (ty, op1, op2))| Fcmp {
+            res_val,
+            cond,
+            fast_math_flags,
+            ty,
+            op1,
+            op2,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// phi = result "phi" [ fast-math-flag ] type
+///     "[" value "," "%" name "]" ( "," "[" value "," "%" name "]" )*
+/// ```
+fn phi(data: &str) -> ParseResult<'_, Phi> {
+    let incoming = |i| {
+        map(
+            tuple((
+                ws(char('[')),
+                ws(value),
+                comma,
+                ws(local_value),
+                ws(char(']')),
+            )),
+            |(_, v, _, l, _)| (v, l[1..].to_string()),
+        )(i)
+    };
+    map(
+        tuple((
+            result,
+            ws(keyword("phi")),
+            opt(ws(fast_math_flag)),
+            ws(ty),
+            separated_list(comma, incoming),
+        )),
+        |(res_val, _, fast_math_flags, ty, params)| Phi {
+            res_val,
+            fast_math_flags,
+            ty,
+            params,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// select = result "select" [ fast-math-flag ] type value "," type value
+///     "," type value
+/// ```
+fn select(data: &str) -> ParseResult<'_, Select> {
+    map(
+        tuple((
+            result,
+            ws(keyword("select")),
+            opt(ws(fast_math_flag)),
+            typed_value,
+            comma,
+            typed_value,
+            comma,
+            typed_value,
+        )),
+        |(res_val, _, fast_math_flags, (selty, cond), _, (ty1, val1), _, (ty2, val2))| Select {
+            res_val,
+            fast_math_flags,
+            selty,
+            cond,
+            ty1,
+            val1,
+            ty2,
+            val2,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// tail-call = "tail" | "musttail" | "notail"
+/// ```
+fn tail_call(data: &str) -> ParseResult<'_, TailCall> {
+    alt((
+        constant(TailCall::Tail, keyword("tail")),
+        constant(TailCall::MustTail, keyword("musttail")),
+        constant(TailCall::NoTail, keyword("notail")),
+    ))(data)
+}
+
+/// Argument list of function type, used as explicit type of callee
+fn argument_types(ty: &FunctionType) -> Vec<ArgumentList> {
+    let mut args: Vec<ArgumentList> = ty
+        .parameter_list
+        .iter()
+        .map(|t| ArgumentList {
+            parameter_type: Some(t.clone()),
+            attributes: None,
+            name: None,
+            variable_argument: false,
+        })
+        .collect();
+    if ty.variable_argument {
+        args.push(ArgumentList {
+            parameter_type: None,
+            attributes: None,
+            name: None,
+            variable_argument: true,
+        });
+    }
+    args
+}
+
+/// Callee type: return type and optional explicit function type
+fn callee_type(ty: Type) -> (Type, Option<Vec<ArgumentList>>) {
+    match ty {
+        Type::Function(f) => {
+            let args = argument_types(&f);
+            (*f.return_type, Some(args))
+        }
+        t => (t, None),
+    }
+}
+
+/// Callee of call, first field is true for pointer value
+/// ## RULE:
+/// ```js
+/// callee = local-value | global-value
+/// ```
+fn callee(data: &str) -> ParseResult<'_, (bool, String)> {
+    alt((
+        map(local_value, |v| (true, v[1..].to_string())),
+        map(global_value, |v| (false, v[1..].to_string())),
+    ))(data)
+}
+
+/// Metadata arguments are used by intrinsics: `metadata !DIExpression()`
+/// ## RULE:
+/// ```js
+/// function-arg = "metadata" metadata | type value
+/// ```
+fn function_arg(data: &str) -> ParseResult<'_, FunctionArg> {
+    alt((
+        map(preceded(ws(keyword("metadata")), ws(metadata)), |v| {
+            FunctionArg(Type::Metadata, v.to_string())
+        }),
+        map(typed_value, |(t, v)| FunctionArg(t, v)),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// function-args = "(" [ function-arg ( "," function-arg )* ] ")"
+/// ```
+fn function_args(data: &str) -> ParseResult<'_, Vec<FunctionArg>> {
+    delimited(
+        char('('),
+        separated_list(comma, function_arg),
+        ws(char(')')),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// call = [ result ] [ tail-call ] "call" [ fast-math-flag ] [ calling-convention ]
+///     [ parameter-attribute ] [ addrspace ] type callee function-args
+/// ```
+fn call(data: &str) -> ParseResult<'_, Call> {
+    map(
+        tuple((
+            opt(result),
+            opt(ws(tail_call)),
+            ws(keyword("call")),
+            opt(ws(fast_math_flag)),
+            opt(ws(calling_convention)),
+            opt(ws(parameter_attribute)),
+            opt(ws(addrspace)),
+            ws(ty),
+            ws(callee),
+            function_args,
+        )),
+        |(
+            ret_val,
+            tail,
+            _,
+            fast_math_flags,
+            cconv,
+            ret_attr,
+            addrspace,
+            ty,
+            fnptrval,
+            function_args,
+        )| {
+            let (ty, fnty) = callee_type(ty);
+            Call {
+                ret_val,
+                tail,
+                fast_math_flags,
+                cconv,
+                ret_attr,
+                addrspace,
+                ty,
+                fnty: fnty.unwrap_or_default(),
+                fnptrval,
+                function_args,
+                function_attrs: None,
+                operand_bundles: None,
+            }
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// landingpad = result "landingpad" type [ "cleanup" ] ( "catch" type value )*
+/// ```
+fn landingpad(data: &str) -> ParseResult<'_, Landingpad> {
+    let clause = |i| {
+        map(
+            preceded(ws(keyword("catch")), typed_value),
+            |(clause_type, value)| Clause { clause_type, value },
+        )(i)
+    };
+    map(
+        tuple((
+            result,
+            ws(keyword("landingpad")),
+            ws(ty),
+            flag("cleanup"),
+            many0(clause),
+        )),
+        |(resultval, _, resultty, cleanup, clause)| Landingpad {
+            resultval,
+            resultty,
+            cleanup,
+            clause,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// other-operation = icmp | fcmp | phi | select | call | landingpad
+/// ```
+fn other_operation(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    alt((
+        boxed(icmp),
+        boxed(fcmp),
+        boxed(phi),
+        boxed(select),
+        boxed(call),
+        boxed(landingpad),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// ret = "ret" "void" | "ret" type value
+/// ```
+fn ret(data: &str) -> ParseResult<'_, Ret> {
+    preceded(
+        ws(keyword("ret")),
+        alt((
+            constant(Ret(None), ws(keyword("void"))),
+            map(typed_value, |v| Ret(Some(v))),
+        )),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// br = "br" "i1" value "," label "," label | "br" label
+/// ```
+fn br(data: &str) -> ParseResult<'_, Br> {
+    preceded(
+        ws(keyword("br")),
+        alt((
+            map(
+                tuple((
+                    ws(keyword("i1")),
+                    ws(value),
+                    comma,
+                    ws(label),
+                    comma,
+                    ws(label),
+                )),
+                |(_, cond, _, if_true, _, if_false)| Br::Conditional(cond, if_true, if_false),
+            ),
+            map(ws(label), Br::Unconditional),
+        )),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// switch = "switch" type value "," label "[" ( type value "," label )* "]"
+/// ```
+fn switch(data: &str) -> ParseResult<'_, Switch> {
+    map(
+        tuple((
+            ws(keyword("switch")),
+            typed_value,
+            comma,
+            ws(label),
+            ws(char('[')),
+            many0(map(tuple((typed_value, comma, ws(label))), |(v, _, l)| {
+                (v.1, l)
+            })),
+            ws(char(']')),
+        )),
+        |(_, (ty, value), _, default_label, _, choices, _)| Switch {
+            ty,
+            value,
+            default_label,
+            choices,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// indirectbr = "indirectbr" type value "," "[" label ( "," label )* "]"
+/// ```
+fn indirectbr(data: &str) -> ParseResult<'_, IndirectBr> {
+    map(
+        tuple((
+            ws(keyword("indirectbr")),
+            typed_value,
+            comma,
+            ws(char('[')),
+            separated_list(comma, ws(label)),
+            ws(char(']')),
+        )),
+        |(_, (ty, address), _, _, labels, _)| IndirectBr {
+            ty,
+            address,
+            labels,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// invoke = [ result ] "invoke" [ calling-convention ] [ parameter-attribute ]
+///     [ addrspace ] type callee function-args "to" label "unwind" label
+/// ```
+fn invoke(data: &str) -> ParseResult<'_, Invoke> {
+    map(
+        tuple((
+            opt(result),
+            ws(keyword("invoke")),
+            opt(ws(calling_convention)),
+            opt(ws(parameter_attribute)),
+            opt(ws(addrspace)),
+            ws(ty),
+            ws(callee),
+            function_args,
+            ws(keyword("to")),
+            ws(label),
+            ws(keyword("unwind")),
+            ws(label),
+        )),
+        |(
+            ret_val,
+            _,
+            cconv,
+            ret_attr,
+            addrspace,
+            ty,
+            fnptrval,
+            function_args,
+            _,
+            normal_label,
+            _,
+            exception_label,
+        )| {
+            let (ty, fnty) = callee_type(ty);
+            let fnty = fnty.map(|args| {
+                let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                format!("({})", args.join(", "))
+            });
+            Invoke {
+                ret_val: ret_val.unwrap_or_default(),
+                cconv,
+                ret_attr,
+                addrspace,
+                ty,
+                fnty,
+                fnptrval,
+                function_args,
+                function_attrs: None,
+                operand_bundles: None,
+                normal_label,
+                exception_label,
+            }
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// resume = "resume" type value
+/// ```
+fn resume(data: &str) -> ParseResult<'_, Resume> {
+    map(
+        preceded(ws(keyword("resume")), typed_value),
+        |(resume_type, value)| Resume { resume_type, value },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// unwind-label = "unwind" ( label | "to" "caller" )
+/// ```
+fn unwind_label(data: &str) -> ParseResult<'_, Option<String>> {
+    preceded(
+        ws(keyword("unwind")),
+        alt((
+            map(ws(label), Some),
+            constant(None, tuple((ws(keyword("to")), ws(keyword("caller"))))),
+        )),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// catchswitch = result "catchswitch" "within" value
+///     "[" label ( "," label )* "]" unwind-label
+/// ```
+fn catchswitch(data: &str) -> ParseResult<'_, CatchSwitch> {
+    map(
+        tuple((
+            result,
+            ws(keyword("catchswitch")),
+            ws(keyword("within")),
+            ws(value),
+            ws(char('[')),
+            separated_list(comma, ws(label)),
+            ws(char(']')),
+            unwind_label,
+        )),
+        |(result_val, _, _, parent, _, handler_labels, _, default_label)| CatchSwitch {
+            result_val,
+            parent,
+            handler_labels,
+            default_label,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// catchret = "catchret" "from" value "to" label
+/// ```
+fn catchret(data: &str) -> ParseResult<'_, CatchRet> {
+    map(
+        tuple((
+            ws(keyword("catchret")),
+            ws(keyword("from")),
+            ws(value),
+            ws(keyword("to")),
+            ws(label),
+        )),
+        |(_, _, catch, _, continue_label)| CatchRet {
+            catch,
+            continue_label,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// cleanupret = "cleanupret" "from" value unwind-label
+/// ```
+fn cleanupret(data: &str) -> ParseResult<'_, CleanupRet> {
+    map(
+        tuple((
+            ws(keyword("cleanupret")),
+            ws(keyword("from")),
+            ws(value),
+            unwind_label,
+        )),
+        |(_, _, values, continue_label)| CleanupRet {
+            values,
+            continue_label,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// terminator = ret | br | switch | indirectbr | invoke | resume |
+///     catchswitch | catchret | cleanupret | "unreachable"
+/// ```
+fn terminator(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    alt((
+        boxed(ret),
+        boxed(br),
+        boxed(switch),
+        boxed(indirectbr),
+        boxed(invoke),
+        boxed(resume),
+        boxed(catchswitch),
+        boxed(catchret),
+        boxed(cleanupret),
+        boxed(constant(Unreachable(), ws(keyword("unreachable")))),
+    ))(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(s: &str) {
+        let (i, instr) = instruction(s).unwrap();
+        assert_eq!(i, "");
+        assert_eq!(instr.to_string(), s);
+    }
+
+    #[test]
+    fn test_binary_operations() {
+        round_trip("%1 = add nuw nsw i32 %a, 1");
+        round_trip("%2 = sub nsw i64 0, %1");
+        round_trip("%3 = mul i8 %x, -1");
+        round_trip("%4 = shl nuw i32 %3, 2");
+        round_trip("%5 = udiv exact i32 %4, 2");
+        round_trip("%6 = sdiv i32 %4, 2");
+        round_trip("%7 = lshr exact i32 %6, 1");
+        round_trip("%8 = ashr i32 %6, 1");
+        round_trip("%9 = urem i32 %8, 3");
+        round_trip("%10 = srem <4 x i32> %v, %w");
+        round_trip("%11 = and i1 true, false");
+        round_trip("%12 = or i32 %9, 1");
+        round_trip("%13 = xor i32 %12, -1");
+        round_trip("%14 = fadd fast double %x, 1.0");
+        round_trip("%15 = fsub float %x, 0x3FF0000000000000");
+        round_trip("%16 = fmul nnan double %x, %y");
+        round_trip("%17 = fdiv <4 x float> %x, %y");
+        round_trip("%18 = frem double %x, 2.0");
+        round_trip("%19 = fneg nnan ninf double %x");
+        round_trip("%20 = fneg float %x");
+    }
+
+    #[test]
+    fn test_conversion_operations() {
+        round_trip("%1 = trunc i32 257 to i8");
+        round_trip("%2 = zext i1 true to i32");
+        round_trip("%3 = sext i8 -1 to i64");
+        round_trip("%4 = fptrunc double %0 to float");
+        round_trip("%5 = fpext float %4 to double");
+        round_trip("%6 = fptoui double %5 to i8");
+        round_trip("%7 = fptosi double %5 to i32");
+        round_trip("%8 = uitofp i8 %6 to double");
+        round_trip("%9 = sitofp <4 x i32> %v to <4 x float>");
+        round_trip("%10 = ptrtoint ptr %p to i64");
+        round_trip("%11 = inttoptr i64 %10 to ptr");
+        round_trip("%12 = bitcast ptr %11 to ptr");
+        round_trip("%13 = addrspacecast ptr %12 to ptr");
+    }
+
+    #[test]
+    fn test_memory_operations() {
+        round_trip("%1 = alloca i32");
+        round_trip("%2 = alloca [4 x i8], i32 4, align 16, addrspace(5)");
+        round_trip("%3 = load i32, ptr %1");
+        round_trip(r#"%4 = load atomic volatile i32, ptr %1 syncscope("agent") acquire, align 4"#);
+        round_trip("store i32 10, ptr @counter");
+        round_trip("store atomic i32 %3, ptr %1 release, align 4");
+        round_trip("%5 = getelementptr inbounds [13 x i8], ptr @.str, i64 0, i64 0");
+        round_trip("%6 = getelementptr { i32, i8 }, ptr %s, inrange i32 0, i32 1");
+        round_trip("fence acq_rel");
+        round_trip(r#"fence syncscope("singlethread") seq_cst"#);
+        round_trip("%7 = cmpxchg ptr @counter, i32 11, i32 20 seq_cst seq_cst");
+        round_trip("%8 = cmpxchg weak volatile ptr %1, i32 0, i32 1 acq_rel monotonic, align 4");
+        round_trip("%9 = atomicrmw add ptr @counter, i32 1 seq_cst");
+        round_trip(
+            r#"%10 = atomicrmw volatile umax ptr %p, i64 %v syncscope("x") monotonic, align 8"#,
+        );
+    }
+
+    #[test]
+    fn test_aggregate_operations() {
+        round_trip("%1 = extractvalue { i32, i1 } %0, 0");
+        round_trip("%2 = extractvalue [2 x { i32, i1 }] %a, 1, 1");
+        round_trip("%3 = insertvalue { i32, float } undef, i32 1, 0");
+        round_trip("%4 = extractelement <4 x i32> %v, i32 0");
+        round_trip("%5 = insertelement <4 x float> %v, float 1.0, i32 %i");
+        round_trip("%6 = shufflevector <4 x i32> %a, <4 x i32> %b, <2 x i32> <i32 0, i32 undef>");
+    }
+
+    #[test]
+    fn test_other_operations() {
+        round_trip("%1 = icmp sgt i32 %a, %b");
+        round_trip("%2 = fcmp nnan olt double %x, 0.0");
+        round_trip("%3 = fcmp true float %x, %y");
+        round_trip("%4 = phi i32 [ %a, %then ], [ 0, %0 ]");
+        round_trip("%5 = select i1 %c, i32 %a, i32 %b");
+        round_trip("%6 = call i32 (ptr, ...) @printf(ptr %7, i32 %2)");
+        round_trip("call void @__global_let_init.0()");
+        round_trip(
+            "call void @llvm.dbg.value(metadata i32 %1, metadata !12, metadata !DIExpression())",
+        );
+        round_trip("%7 = tail call fastcc noundef i32 %fp(i32 1, double 2.0)");
+        round_trip("%8 = landingpad { ptr, i32 } cleanup catch ptr @typeinfo");
+    }
+
+    #[test]
+    fn test_terminators() {
+        round_trip("ret void");
+        round_trip("ret i32 %9");
+        round_trip("br i1 %c, label %then, label %else");
+        round_trip("br label %exit");
+        round_trip("switch i32 %v, label %d [ i32 1, label %a i32 2, label %b ]");
+        round_trip("switch i8 %v, label %d [ ]");
+        round_trip("indirectbr ptr %addr, [ label %a, label %b ]");
+        round_trip("%1 = invoke i32 @f(i32 1) to label %ok unwind label %err");
+        round_trip("invoke void (ptr, ...) @g(ptr null) to label %ok unwind label %err");
+        round_trip("resume { ptr, i32 } %lp");
+        round_trip("%cs = catchswitch within none [label %h1, label %h2] unwind to caller");
+        round_trip("%cs = catchswitch within %p [label %h] unwind label %u");
+        round_trip("catchret from %c to label %next");
+        round_trip("cleanupret from %c unwind label %u");
+        round_trip("cleanupret from %c unwind to caller");
+        round_trip("unreachable");
+    }
+
+    #[test]
+    fn test_terminator_detection() {
+        let (_, i) = instruction("br label %exit").unwrap();
+        assert!(i.is_terminator());
+        assert_eq!(i.successors(), vec!["exit"]);
+        let (_, i) = instruction("%1 = add i32 1, 2").unwrap();
+        assert!(!i.is_terminator());
+        assert_eq!(i.get_value(), Some("%1".to_string()));
+        assert!(instruction("%1 = add i32 1").is_err());
+        assert!(instruction("%1 = addx i32 1, 2").is_err());
+    }
+}
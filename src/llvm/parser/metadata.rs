@@ -0,0 +1,365 @@
+//! # Metadata parser
+//!
+//! Parse metadata nodes, named metadata and metadata attachments.
+//! Specialized nodes are supported for debug info, which is
+//! generated by codegen.
+//!
+//! https://llvm.org/docs/LangRef.html#metadata
+
+use nom::{
+    branch::alt,
+    bytes::complete::{
+        tag,
+        take_while,
+        take_while1,
+    },
+    character::complete::{
+        alpha1,
+        char,
+    },
+    combinator::{
+        map,
+        map_opt,
+        opt,
+        recognize,
+        value as constant,
+        verify,
+    },
+    multi::separated_list,
+    sequence::{
+        delimited,
+        pair,
+        preceded,
+        separated_pair,
+        terminated,
+        tuple,
+    },
+};
+use std::str::FromStr;
+
+use super::{
+    comma,
+    integer,
+    is_name_char,
+    keyword,
+    quoted_string,
+    types::ty,
+    value,
+    ws,
+    ParseResult,
+};
+use crate::llvm::debug_info::{
+    DIBasicType,
+    DICompileUnit,
+    DIDerivedType,
+    DIExpression,
+    DIFile,
+    DILocalVariable,
+    DILocation,
+    DISubprogram,
+    DISubroutineType,
+    DebugInfo,
+    DwarfEncoding,
+    EmissionKind,
+};
+use crate::llvm::metadata::{
+    Attachment,
+    Metadata,
+    MetadataKind,
+    MetadataNode,
+    MetadataRef,
+    NamedMetadata,
+};
+
+/// ## RULE:
+/// ```js
+/// metadata-ref = "!" integer
+/// ```
+pub fn metadata_ref(data: &str) -> ParseResult<'_, MetadataRef> {
+    map(preceded(char('!'), integer), |v| MetadataRef(v as usize))(data)
+}
+
+/// Name of named metadata or metadata kind
+/// ## RULE:
+/// ```js
+/// metadata-name = [-a-zA-Z$._] name-char*
+/// ```
+fn metadata_name(data: &str) -> ParseResult<'_, &str> {
+    recognize(pair(
+        verify(take_while1(is_name_char), |s: &str| {
+            !s.starts_with(|c: char| c.is_ascii_digit())
+        }),
+        take_while(is_name_char),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// metadata-tuple = "!{" [ metadata ( "," metadata )* ] "}"
+/// ```
+fn metadata_tuple(data: &str) -> ParseResult<'_, Vec<Metadata>> {
+    delimited(
+        tag("!{"),
+        separated_list(comma, ws(metadata)),
+        ws(char('}')),
+    )(data)
+}
+
+/// Fields of specialized node. Field values kept as text and
+/// converted according to kind of node.
+struct Fields<'a>(Vec<(&'a str, &'a str)>);
+
+impl<'a> Fields<'a> {
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.0.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+
+    fn string(&self, name: &str) -> Option<String> {
+        let v = self.get(name)?;
+        v.strip_prefix('"')?.strip_suffix('"').map(str::to_string)
+    }
+
+    fn node(&self, name: &str) -> Option<MetadataRef> {
+        match metadata_ref(self.get(name)?) {
+            Ok(("", v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn integer<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name)?.parse().ok()
+    }
+
+    /// Flags joined with `|`
+    fn has_flag(&self, name: &str, flag: &str) -> bool {
+        self.get(name)
+            .map(|v| v.split('|').any(|f| f.trim() == flag))
+            .unwrap_or(false)
+    }
+}
+
+/// ## RULE:
+/// ```js
+/// debug-info-field = name ":" ( quoted-string | [^,)]+ )
+/// ```
+fn debug_info_field(data: &str) -> ParseResult<'_, (&str, &str)> {
+    separated_pair(
+        alpha1,
+        char(':'),
+        map(
+            ws(alt((quoted_string, take_while1(|c| c != ',' && c != ')')))),
+            str::trim_end,
+        ),
+    )(data)
+}
+
+/// Specialized node by name and fields
+fn debug_info_node(name: &str, f: &Fields) -> Option<DebugInfo> {
+    let v = match name {
+        "DICompileUnit" => DebugInfo::CompileUnit(DICompileUnit {
+            language: f.get("language")?.to_string(),
+            file: f.node("file")?,
+            producer: f.string("producer").unwrap_or_default(),
+            is_optimized: f.get("isOptimized") == Some("true"),
+            runtime_version: f.integer("runtimeVersion").unwrap_or(0),
+            emission_kind: EmissionKind::new(f.get("emissionKind")?)?,
+        }),
+        "DIFile" => DebugInfo::File(DIFile {
+            filename: f.string("filename")?,
+            directory: f.string("directory")?,
+        }),
+        "DIBasicType" => DebugInfo::BasicType(DIBasicType {
+            name: f.string("name")?,
+            size: f.integer("size")?,
+            encoding: DwarfEncoding::new(f.get("encoding")?)?,
+        }),
+        "DIDerivedType" => DebugInfo::DerivedType(DIDerivedType {
+            tag: f.get("tag")?.to_string(),
+            base_type: f.node("baseType")?,
+            size: f.integer("size")?,
+        }),
+        "DISubroutineType" => DebugInfo::SubroutineType(DISubroutineType {
+            types: f.node("types")?,
+        }),
+        "DISubprogram" => DebugInfo::Subprogram(DISubprogram {
+            name: f.string("name")?,
+            scope: f.node("scope")?,
+            file: f.node("file")?,
+            line: f.integer("line")?,
+            ty: f.node("type"),
+            scope_line: f.integer("scopeLine").unwrap_or(0),
+            artificial: f.has_flag("flags", "DIFlagArtificial"),
+            local_to_unit: f.has_flag("spFlags", "DISPFlagLocalToUnit"),
+            definition: f.has_flag("spFlags", "DISPFlagDefinition"),
+            unit: f.node("unit"),
+            retained_nodes: f.node("retainedNodes"),
+        }),
+        "DILocalVariable" => DebugInfo::LocalVariable(DILocalVariable {
+            name: f.string("name")?,
+            arg: f.integer("arg"),
+            scope: f.node("scope")?,
+            file: f.node("file")?,
+            line: f.integer("line")?,
+            ty: f.node("type"),
+        }),
+        "DILocation" => DebugInfo::Location(DILocation {
+            line: f.integer("line")?,
+            column: f.integer("column").unwrap_or(0),
+            scope: f.node("scope")?,
+        }),
+        _ => return None,
+    };
+    Some(v)
+}
+
+/// Specialized metadata node. Expression operands are DWARF
+/// operations, other nodes have named fields.
+/// ## RULE:
+/// ```js
+/// debug-info = "!DIExpression(" [ name ( "," name )* ] ")" |
+///     "!" name "(" [ debug-info-field ( "," debug-info-field )* ] ")"
+/// ```
+pub fn debug_info(data: &str) -> ParseResult<'_, DebugInfo> {
+    alt((
+        map(
+            delimited(
+                tag("!DIExpression("),
+                separated_list(comma, ws(take_while1(is_name_char))),
+                ws(char(')')),
+            ),
+            |ops| DebugInfo::Expression(DIExpression(ops.iter().map(|v| v.to_string()).collect())),
+        ),
+        map_opt(
+            pair(
+                delimited(char('!'), alpha1, char('(')),
+                terminated(separated_list(comma, ws(debug_info_field)), ws(char(')'))),
+            ),
+            |(name, fields)| debug_info_node(name, &Fields(fields)),
+        ),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// metadata = "null" | "!" quoted-string | metadata-tuple | metadata-ref |
+///     debug-info | type value
+/// ```
+pub fn metadata(data: &str) -> ParseResult<'_, Metadata> {
+    alt((
+        constant(Metadata::Null, keyword("null")),
+        map(preceded(char('!'), quoted_string), |s| {
+            Metadata::String(s[1..s.len() - 1].to_string())
+        }),
+        map(metadata_tuple, Metadata::Tuple),
+        map(metadata_ref, Metadata::Node),
+        map(debug_info, |v| Metadata::DebugInfo(Box::new(v))),
+        map(pair(ty, ws(value)), |(t, v)| Metadata::Value(t, v)),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// metadata-node = metadata-ref "=" [ "distinct" ] metadata
+/// ```
+pub fn metadata_node(data: &str) -> ParseResult<'_, (MetadataRef, MetadataNode)> {
+    map(
+        tuple((
+            metadata_ref,
+            ws(char('=')),
+            opt(ws(keyword("distinct"))),
+            ws(metadata),
+        )),
+        |(id, _, distinct, value)| {
+            (
+                id,
+                MetadataNode {
+                    distinct: distinct.is_some(),
+                    value,
+                },
+            )
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// named-metadata = "!" metadata-name "=" "!{" [ metadata-ref ( "," metadata-ref )* ] "}"
+/// ```
+pub fn named_metadata(data: &str) -> ParseResult<'_, NamedMetadata> {
+    map(
+        tuple((
+            preceded(char('!'), metadata_name),
+            ws(char('=')),
+            ws(delimited(
+                tag("!{"),
+                separated_list(comma, ws(metadata_ref)),
+                ws(char('}')),
+            )),
+        )),
+        |(name, _, nodes)| NamedMetadata {
+            name: name.to_string(),
+            nodes,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// attachment = "!" metadata-name metadata-ref
+/// ```
+pub fn attachment(data: &str) -> ParseResult<'_, Attachment> {
+    map(
+        pair(preceded(char('!'), metadata_name), ws(metadata_ref)),
+        |(kind, node)| Attachment(MetadataKind::new(kind), node),
+    )(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata() {
+        let s = r#"!{i32 7, !"PIC Level", !1, null, !{}}"#;
+        assert_eq!(metadata(s).unwrap().1.to_string(), s);
+        let (i, (id, node)) = metadata_node("!3 = distinct !{!3}\n").unwrap();
+        assert_eq!(i, "\n");
+        assert_eq!(id, MetadataRef(3));
+        assert!(node.distinct);
+        assert_eq!(
+            node.value,
+            Metadata::Tuple(vec![Metadata::Node(MetadataRef(3))])
+        );
+        let s = "!llvm.module.flags = !{!0, !1}";
+        assert_eq!(named_metadata(s).unwrap().1.to_string(), s);
+        assert!(named_metadata("!0 = !{}").is_err());
+        assert_eq!(
+            attachment("!dbg !12,"),
+            Ok((",", Attachment(MetadataKind::Dbg, MetadataRef(12))))
+        );
+    }
+
+    #[test]
+    fn test_debug_info() {
+        for s in [
+            r#"!DICompileUnit(language: DW_LANG_C, file: !1, producer: "iLang, v0.1", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)"#,
+            r#"!DIFile(filename: "main.i", directory: "/src")"#,
+            r#"!DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)"#,
+            "!DIDerivedType(tag: DW_TAG_pointer_type, baseType: !7, size: 64)",
+            "!DISubroutineType(types: !6)",
+            r#"!DISubprogram(name: "f", scope: !1, file: !1, line: 3, type: !5, scopeLine: 3, flags: DIFlagArtificial, spFlags: DISPFlagLocalToUnit | DISPFlagDefinition, unit: !0, retainedNodes: !2)"#,
+            r#"!DILocalVariable(name: "x", arg: 1, scope: !4, file: !1, line: 3, type: !7)"#,
+            "!DILocation(line: 4, column: 13, scope: !4)",
+            "!DIExpression()",
+            "!DIExpression(DW_OP_deref, DW_OP_stack_value)",
+        ]
+        .iter()
+        {
+            let (i, v) = metadata(s).unwrap();
+            assert_eq!(i, "");
+            assert_eq!(v.to_string(), *s);
+        }
+        assert!(debug_info("!DILocation(column: 13)").is_err());
+        assert!(debug_info("!DIUnknown(line: 1)").is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! # IR parser
+//!
+//! Parse textual LLVM IR to in-memory IR module model. Parser covers
+//! subset of LLVM IR, which is modeled by `llvm::*`: types, global
+//! variables, functions, instructions and attribute groups. Printing
+//! of parsed module produces canonical text form, so parser can be
+//! used for normalization of IR text, for example in golden tests.
+//!
+//! Operands and constants are kept as text, as in instructions
+//! structs.
+//!
+//! https://llvm.org/docs/LangRef.html
+
+use nom::{
+    branch::alt,
+    bytes::complete::{
+        tag,
+        take_while,
+        take_while1,
+    },
+    character::complete::{
+        alpha1,
+        char,
+        digit1,
+        space0,
+        space1,
+    },
+    combinator::{
+        map,
+        map_res,
+        not,
+        opt,
+        peek,
+        recognize,
+    },
+    error::ErrorKind,
+    multi::many1,
+    sequence::{
+        pair,
+        preceded,
+        terminated,
+        tuple,
+    },
+    IResult,
+};
+
+use crate::llvm::ir::Module;
+
+pub mod attributes;
+pub mod instructions;
+pub mod metadata;
+pub mod module;
+pub mod types;
+
+pub type ParseResult<'a, T> = IResult<&'a str, T>;
+
+/// Error of IR text parsing
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ParseError {
+    /// Line number, starting from 1
+    pub line: usize,
+    /// Text of line, which can't be parsed
+    pub text: String,
+}
+
+impl ParseError {
+    fn new(source: &str, rest: &str) -> Self {
+        let offset = source.len() - rest.len();
+        let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: source[..offset].matches('\n').count() + 1,
+            text: source[start..]
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: can't parse: {}", self.line, self.text)
+    }
+}
+
+/// Parse IR text to IR module
+pub fn parse(source: &str) -> Result<Module, ParseError> {
+    match module::module(source) {
+        Ok(("", m)) => Ok(m),
+        Ok((rest, _)) => Err(ParseError::new(source, rest)),
+        Err(nom::Err::Error((rest, _))) | Err(nom::Err::Failure((rest, _))) => {
+            Err(ParseError::new(source, rest))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new(source, source)),
+    }
+}
+
+/// Normalize IR text: parse and print it in canonical form.
+/// Comments (except module ID), spaces and blank lines are not
+/// significant for normalized text.
+pub fn normalize(source: &str) -> Result<String, ParseError> {
+    parse(source).map(|m| m.to_string())
+}
+
+/// Apply parser func after optional spaces
+/// ## RULE:
+/// ```js
+/// [SPACE] parser-func
+/// ```
+pub fn ws<'a, O, F>(func: F) -> impl Fn(&'a str) -> ParseResult<'a, O>
+where
+    F: Fn(&'a str) -> ParseResult<'a, O>,
+{
+    preceded(space0, func)
+}
+
+/// ## RULE:
+/// ```js
+/// [SPACE] ","
+/// ```
+pub fn comma(data: &str) -> ParseResult<'_, char> {
+    preceded(space0, char(','))(data)
+}
+
+/// Keyword, which isn't part of longer name
+/// ## RULE:
+/// ```js
+/// keyword !name-char
+/// ```
+pub fn keyword<'a>(kw: &'static str) -> impl Fn(&'a str) -> ParseResult<'a, &'a str> {
+    terminated(tag(kw), not(take_while1(is_name_char)))
+}
+
+/// ## RULE:
+/// ```js
+/// name-char = [-a-zA-Z$._0-9]
+/// ```
+pub fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-$._".contains(c)
+}
+
+/// ## RULE:
+/// ```js
+/// quoted-string = '"' [^"]* '"'
+/// ```
+pub fn quoted_string(data: &str) -> ParseResult<'_, &str> {
+    recognize(tuple((char('"'), take_while(|c| c != '"'), char('"'))))(data)
+}
+
+/// Name of value, label or global. Quoted names kept with quotes.
+/// ## RULE:
+/// ```js
+/// name = name-char+ | quoted-string
+/// ```
+pub fn name(data: &str) -> ParseResult<'_, &str> {
+    alt((take_while1(is_name_char), quoted_string))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// local-value = "%" name
+/// ```
+pub fn local_value(data: &str) -> ParseResult<'_, String> {
+    map(recognize(pair(char('%'), name)), String::from)(data)
+}
+
+/// ## RULE:
+/// ```js
+/// global-value = "@" name
+/// ```
+pub fn global_value(data: &str) -> ParseResult<'_, String> {
+    map(recognize(pair(char('@'), name)), String::from)(data)
+}
+
+/// Label of basic block, without `%` sigil
+/// ## RULE:
+/// ```js
+/// label = "label" "%" name
+/// ```
+pub fn label(data: &str) -> ParseResult<'_, String> {
+    map(
+        preceded(tuple((keyword("label"), space1, char('%'))), name),
+        String::from,
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// integer = [0-9]+
+/// ```
+pub fn integer(data: &str) -> ParseResult<'_, u64> {
+    map_res(digit1, str::parse)(data)
+}
+
+/// ## RULE:
+/// ```js
+/// signed-integer = ["-"] [0-9]+
+/// ```
+pub fn signed_integer(data: &str) -> ParseResult<'_, i64> {
+    map_res(recognize(pair(opt(char('-')), digit1)), str::parse)(data)
+}
+
+/// Operand or constant, kept as text
+/// ## RULE:
+/// ```js
+/// value = "c" quoted-string | constant-expression | group | value-char+
+/// constant-expression = ( alpha+ SPACE )+ "(" ... ")"
+/// ```
+pub fn value(data: &str) -> ParseResult<'_, String> {
+    map(
+        alt((
+            recognize(pair(char('c'), quoted_string)),
+            recognize(pair(
+                many1(terminated(alpha1, space1)),
+                preceded(peek(char('(')), group),
+            )),
+            group,
+            take_while1(|c| is_name_char(c) || "%@+".contains(c)),
+        )),
+        String::from,
+    )(data)
+}
+
+/// Group in balanced brackets: `(..)`, `[..]`, `{..}` or `<..>`
+pub fn group(data: &str) -> ParseResult<'_, &str> {
+    let mut depth = 0;
+    let mut quoted = false;
+    for (i, c) in data.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            _ if quoted => {}
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((&data[i + 1..], &data[..=i]));
+                }
+            }
+            _ if depth == 0 => break,
+            _ => {}
+        }
+    }
+    Err(nom::Err::Error((data, ErrorKind::Verify)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values() {
+        assert_eq!(value("%1, %2"), Ok((", %2", "%1".to_string())));
+        assert_eq!(value("@.str)"), Ok((")", "@.str".to_string())));
+        assert_eq!(value("-1.5e+10 to"), Ok((" to", "-1.5e+10".to_string())));
+        assert_eq!(
+            value(r#"c"a\0A\00", align 1"#),
+            Ok((", align 1", r#"c"a\0A\00""#.to_string()))
+        );
+        assert_eq!(
+            value("[{ i32, ptr } { i32 1, ptr null }]\n"),
+            Ok(("\n", "[{ i32, ptr } { i32 1, ptr null }]".to_string()))
+        );
+        let gep = "getelementptr inbounds ([2 x i8], ptr @s, i64 0, i64 0)";
+        assert_eq!(value(gep), Ok(("", gep.to_string())));
+        assert_eq!(
+            value("none [label %a]"),
+            Ok((" [label %a]", "none".to_string()))
+        );
+        assert!(value("(i32").is_err());
+        assert_eq!(label("label %then,"), Ok((",", "then".to_string())));
+        assert_eq!(
+            local_value(r#"%"a b" ="#),
+            Ok((" =", r#"%"a b""#.to_string()))
+        );
+        assert_eq!(signed_integer("-4,"), Ok((",", -4)));
+        assert!(keyword("add")("addrspace").is_err());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse("; ModuleID = 'x'\n\ndefine void @f() {\n\tbogus\n}\n").unwrap_err();
+        assert_eq!(err.line, 4);
+        assert_eq!(err.text, "\tbogus");
+        assert_eq!(err.to_string(), "line 4: can't parse: \tbogus");
+    }
+}
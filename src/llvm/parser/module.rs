@@ -0,0 +1,804 @@
+//! # Module parser
+//!
+//! Parse module level entities: source file name, target triple,
+//! global variables, function definitions and declarations,
+//! attribute groups and metadata.
+//!
+//! https://llvm.org/docs/LangRef.html#module-structure
+
+use nom::{
+    branch::alt,
+    bytes::complete::{
+        tag,
+        take_while,
+        take_while1,
+    },
+    character::complete::{
+        char,
+        line_ending,
+        not_line_ending,
+        space0,
+        space1,
+    },
+    combinator::{
+        cut,
+        map,
+        not,
+        opt,
+        recognize,
+        value as constant,
+        verify,
+    },
+    error::ErrorKind,
+    multi::{
+        many0,
+        many1,
+        separated_list,
+    },
+    sequence::{
+        delimited,
+        preceded,
+        terminated,
+        tuple,
+    },
+};
+
+use super::{
+    attributes::{
+        addrspace,
+        alignment,
+        calling_convention,
+        dll_storage_class,
+        linkage,
+        parameter_attribute,
+        preemption_specifier,
+        thread_local,
+        unnamed_addr,
+        visibility,
+    },
+    comma,
+    global_value,
+    instructions::instruction,
+    integer,
+    keyword,
+    local_value,
+    metadata::{
+        attachment,
+        metadata_node,
+        named_metadata,
+    },
+    name,
+    quoted_string,
+    types::{
+        ty,
+        type_definition,
+    },
+    value,
+    ws,
+    ParseResult,
+};
+use crate::llvm::{
+    attribute_groups::Attributes,
+    data_layout::DataLayout,
+    functions::{
+        ArgumentList,
+        Function,
+        FunctionDefinitionType,
+    },
+    global_variables::{
+        GlobalVariable,
+        GlobalVariableKind,
+    },
+    ir,
+    metadata::{
+        AttachedInstruction,
+        Attachment,
+        MetadataNode,
+        MetadataRef,
+        NamedMetadata,
+    },
+    section::Section,
+    source_filename::SourceFileName,
+    target_triple::TargetTriple,
+    type_system::aggregate::TypeDefinition,
+    InstructionSet,
+};
+
+/// Module level entity
+enum Item {
+    ModuleId(String),
+    SourceFileName(SourceFileName),
+    DataLayout(DataLayout),
+    TargetTriple(TargetTriple),
+    TypeDefinition(TypeDefinition),
+    Global(GlobalVariable),
+    Function(ir::Function),
+    Declaration(Function),
+    AttributeGroup(Attributes),
+    MetadataNode((MetadataRef, MetadataNode)),
+    NamedMetadata(NamedMetadata),
+    Empty,
+}
+
+/// Line of function body
+enum Line {
+    Label(String),
+    Instruction(Box<dyn InstructionSet>),
+    Empty,
+}
+
+/// ## RULE:
+/// ```js
+/// comment = ";" [^\n]*
+/// ```
+fn comment(data: &str) -> ParseResult<'_, &str> {
+    preceded(char(';'), not_line_ending)(data)
+}
+
+/// End of line or end of input, with optional comment
+/// ## RULE:
+/// ```js
+/// end-of-line = [SPACE] [ comment ] ( NEWLINE | EOF )
+/// ```
+fn end_of_line(data: &str) -> ParseResult<'_, ()> {
+    let (i, _) = tuple((space0, opt(comment)))(data)?;
+    if i.is_empty() {
+        return Ok((i, ()));
+    }
+    constant((), line_ending)(i)
+}
+
+/// ## RULE:
+/// ```js
+/// empty-line = [SPACE] [ comment ] NEWLINE
+/// ```
+fn empty_line(data: &str) -> ParseResult<'_, ()> {
+    constant((), tuple((space0, opt(comment), line_ending)))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// module-id = "; ModuleID = '" [^']* "'"
+/// ```
+fn module_id(data: &str) -> ParseResult<'_, String> {
+    map(
+        delimited(tag("; ModuleID = '"), take_while(|c| c != '\''), char('\'')),
+        String::from,
+    )(data)
+}
+
+fn unquote(s: &str) -> String {
+    s.trim_matches('"').to_string()
+}
+
+/// ## RULE:
+/// ```js
+/// source-filename = "source_filename" "=" quoted-string
+/// ```
+pub fn source_filename(data: &str) -> ParseResult<'_, SourceFileName> {
+    map(
+        preceded(
+            tuple((keyword("source_filename"), ws(char('=')))),
+            ws(quoted_string),
+        ),
+        |s| SourceFileName(unquote(s)),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// data-layout = "target" "datalayout" "=" quoted-string
+/// ```
+pub fn data_layout(data: &str) -> ParseResult<'_, DataLayout> {
+    map(
+        preceded(
+            tuple((keyword("target"), ws(keyword("datalayout")), ws(char('=')))),
+            ws(quoted_string),
+        ),
+        |s| DataLayout(unquote(s)),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// target-triple = "target" "triple" "=" quoted-string
+/// ```
+pub fn target_triple(data: &str) -> ParseResult<'_, TargetTriple> {
+    map(
+        preceded(
+            tuple((keyword("target"), ws(keyword("triple")), ws(char('=')))),
+            ws(quoted_string),
+        ),
+        |s| TargetTriple(unquote(s)),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// section = "section" quoted-string
+/// ```
+fn section(data: &str) -> ParseResult<'_, String> {
+    map(
+        preceded(tuple((keyword("section"), space1)), quoted_string),
+        unquote,
+    )(data)
+}
+
+/// Global variable. Comdat is not supported.
+/// ## RULE:
+/// ```js
+/// global-variable = global-value "=" [ linkage ] [ preemption-specifier ]
+///     [ visibility ] [ dll-storage-class ] [ thread-local ] [ unnamed-addr ]
+///     [ addrspace ] ( "global" | "constant" ) type [ value ]
+///     [ "," section ] [ "," alignment ] ( "," attachment )*
+/// ```
+pub fn global_variable(data: &str) -> ParseResult<'_, GlobalVariable> {
+    map(
+        tuple((
+            global_value,
+            ws(char('=')),
+            opt(ws(linkage)),
+            opt(ws(preemption_specifier)),
+            opt(ws(visibility)),
+            opt(ws(dll_storage_class)),
+            opt(ws(thread_local)),
+            opt(ws(unnamed_addr)),
+            opt(ws(addrspace)),
+            ws(alt((
+                constant(GlobalVariableKind::Global, keyword("global")),
+                constant(GlobalVariableKind::Constant, keyword("constant")),
+            ))),
+            ws(ty),
+            opt(ws(value)),
+            opt(preceded(comma, ws(section))),
+            opt(preceded(comma, ws(alignment))),
+            many0(preceded(comma, ws(attachment))),
+        )),
+        |(
+            name,
+            _,
+            linkage,
+            preemption_specifier,
+            visibility,
+            dll_storage_classes,
+            thread_local,
+            unnamed_addr,
+            addrspace,
+            global_variable_kind,
+            value_type,
+            initializer_constant,
+            section,
+            alignment,
+            metadata,
+        )| GlobalVariable {
+            name: name[1..].to_string(),
+            linkage,
+            preemption_specifier,
+            visibility,
+            dll_storage_classes,
+            thread_local,
+            unnamed_addr,
+            addrspace,
+            global_variable_kind,
+            value_type,
+            initializer_constant,
+            section: section.map(Section),
+            comdat: None,
+            alignment,
+            metadata,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// argument = "..." | type [ parameter-attribute ] [ local-value ]
+/// ```
+fn argument(data: &str) -> ParseResult<'_, ArgumentList> {
+    alt((
+        constant(
+            ArgumentList {
+                parameter_type: None,
+                attributes: None,
+                name: None,
+                variable_argument: true,
+            },
+            ws(keyword("...")),
+        ),
+        map(
+            tuple((ws(ty), opt(ws(parameter_attribute)), opt(ws(local_value)))),
+            |(t, attributes, name)| ArgumentList {
+                parameter_type: Some(t),
+                attributes,
+                name,
+                variable_argument: false,
+            },
+        ),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// attribute-group-id = "#" integer
+/// ```
+fn attribute_group_id(data: &str) -> ParseResult<'_, u64> {
+    preceded(char('#'), integer)(data)
+}
+
+/// Function header of definition or declaration. Function
+/// attributes supported only as attribute groups.
+/// ## RULE:
+/// ```js
+/// function-header = ( "define" | "declare" ) [ linkage ] [ preemption-specifier ]
+///     [ visibility ] [ dll-storage-class ] [ calling-convention ]
+///     [ parameter-attribute ] type global-value "(" [ argument ( "," argument )* ] ")"
+///     [ unnamed-addr ] [ addrspace ] attribute-group-id* [ section ] [ alignment ]
+///     attachment*
+/// ```
+pub fn function_header(data: &str) -> ParseResult<'_, Function> {
+    map(
+        tuple((
+            alt((
+                constant(FunctionDefinitionType::Declare, keyword("declare")),
+                constant(FunctionDefinitionType::Define, keyword("define")),
+            )),
+            opt(ws(linkage)),
+            opt(ws(preemption_specifier)),
+            opt(ws(visibility)),
+            opt(ws(dll_storage_class)),
+            opt(ws(calling_convention)),
+            opt(ws(parameter_attribute)),
+            ws(ty),
+            ws(global_value),
+            delimited(char('('), separated_list(comma, argument), ws(char(')'))),
+            opt(ws(unnamed_addr)),
+            opt(ws(addrspace)),
+            many0(ws(attribute_group_id)),
+            opt(ws(section)),
+            opt(ws(alignment)),
+            many0(ws(attachment)),
+        )),
+        |(
+            definition_type,
+            linkage,
+            preemption_specifier,
+            visibility,
+            dll_storage_class,
+            cconv,
+            ret_attrs,
+            result_type,
+            name,
+            argument_list,
+            unnamed_addr,
+            addr_sapce,
+            attr_group,
+            section_name,
+            align,
+            metadata,
+        )| Function {
+            definition_type,
+            linkage,
+            preemption_specifier,
+            visibility,
+            dll_storage_class,
+            cconv,
+            ret_attrs,
+            result_type,
+            function_name: name[1..].to_string(),
+            argument_list,
+            unnamed_addr,
+            addr_sapce,
+            fn_attrs: vec![],
+            attr_group,
+            section_name,
+            comdat: None,
+            align,
+            gc: None,
+            prefix: None,
+            prologue: None,
+            personality: None,
+            metadata,
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// function-declaration = function-header
+/// ```
+pub fn function_declaration(data: &str) -> ParseResult<'_, Function> {
+    verify(function_header, |f| {
+        f.definition_type == FunctionDefinitionType::Declare
+    })(data)
+}
+
+/// ## RULE:
+/// ```js
+/// block-label = name ":"
+/// ```
+fn block_label(data: &str) -> ParseResult<'_, String> {
+    map(terminated(ws(name), char(':')), String::from)(data)
+}
+
+/// Instruction with optional metadata attachments
+/// ## RULE:
+/// ```js
+/// attached-instruction = instruction ( "," attachment )*
+/// ```
+fn attached_instruction(data: &str) -> ParseResult<'_, Box<dyn InstructionSet>> {
+    map(
+        tuple((instruction, many0(preceded(comma, ws(attachment))))),
+        |(instruction, attachments): (_, Vec<Attachment>)| {
+            if attachments.is_empty() {
+                instruction
+            } else {
+                Box::new(AttachedInstruction {
+                    instruction,
+                    attachments,
+                })
+            }
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// body-line = block-label end-of-line | attached-instruction end-of-line | empty-line
+/// ```
+fn body_line(data: &str) -> ParseResult<'_, Line> {
+    alt((
+        map(terminated(block_label, end_of_line), Line::Label),
+        map(
+            terminated(ws(attached_instruction), end_of_line),
+            Line::Instruction,
+        ),
+        map(empty_line, |_| Line::Empty),
+    ))(data)
+}
+
+/// Number of unnamed value or label
+fn numbered(value: &str) -> Option<usize> {
+    value.trim_start_matches('%').parse().ok()
+}
+
+/// Build basic blocks from lines of function body. Block without
+/// label gets next number of unnamed values.
+fn function(definition: Function, lines: Vec<Line>) -> ir::Function {
+    let mut next = definition
+        .argument_list
+        .iter()
+        .filter(|a| !a.variable_argument)
+        .filter(|a| match &a.name {
+            Some(n) => numbered(n).is_some(),
+            None => true,
+        })
+        .count();
+    let mut f = ir::Function::new(definition);
+    let mut current: Option<ir::BasicBlock> = None;
+    for line in lines {
+        match line {
+            Line::Label(label) => {
+                if let Some(bb) = current.take() {
+                    f.blocks.push(bb);
+                }
+                if let Some(n) = numbered(&label) {
+                    next = n + 1;
+                }
+                current = Some(ir::BasicBlock::new(&label));
+            }
+            Line::Instruction(instr) => {
+                if current.is_none() {
+                    current = Some(ir::BasicBlock::new(&next.to_string()));
+                    next += 1;
+                }
+                if let Some(n) = instr.get_value().as_deref().and_then(numbered) {
+                    next = n + 1;
+                }
+                let bb = current.as_mut().unwrap();
+                if instr.is_terminator() {
+                    bb.terminator = Some(instr);
+                    f.blocks.push(current.take().unwrap());
+                } else {
+                    bb.instructions.push(instr);
+                }
+            }
+            Line::Empty => {}
+        }
+    }
+    if let Some(bb) = current {
+        f.blocks.push(bb);
+    }
+    f
+}
+
+/// Function definition. Lines of function body, which can't be
+/// parsed, fail whole module parsing.
+/// ## RULE:
+/// ```js
+/// function-definition = function-header "{" end-of-line body-line* "}"
+/// ```
+pub fn function_definition(data: &str) -> ParseResult<'_, ir::Function> {
+    let header = verify(function_header, |f| {
+        f.definition_type == FunctionDefinitionType::Define
+    });
+    let closing = tuple((space0, char('}')));
+    map(
+        tuple((
+            header,
+            ws(char('{')),
+            end_of_line,
+            many0(preceded(not(closing), cut(body_line))),
+            ws(char('}')),
+        )),
+        |(definition, _, _, lines, _)| function(definition, lines),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// attribute = ( quoted-string | [^ \t\n{}"]+ )+
+/// ```
+fn attribute(data: &str) -> ParseResult<'_, String> {
+    map(
+        recognize(many1(alt((
+            quoted_string,
+            take_while1(|c: char| !c.is_whitespace() && !"{}\"".contains(c)),
+        )))),
+        String::from,
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// attribute-group = "attributes" attribute-group-id "=" "{" attribute* "}"
+/// ```
+pub fn attribute_group(data: &str) -> ParseResult<'_, Attributes> {
+    map(
+        tuple((
+            keyword("attributes"),
+            ws(attribute_group_id),
+            ws(char('=')),
+            ws(char('{')),
+            many0(ws(attribute)),
+            ws(char('}')),
+        )),
+        |(_, id, _, _, attributes, _)| Attributes(id as i32, attributes),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// item = module-id end-of-line | empty-line | ( source-filename |
+///     data-layout | target-triple | type-definition | global-variable | function-definition |
+///     function-declaration | attribute-group | metadata-node |
+///     named-metadata ) end-of-line
+/// ```
+fn item(data: &str) -> ParseResult<'_, Item> {
+    alt((
+        map(terminated(module_id, end_of_line), Item::ModuleId),
+        map(empty_line, |_| Item::Empty),
+        terminated(
+            alt((
+                map(source_filename, Item::SourceFileName),
+                map(data_layout, Item::DataLayout),
+                map(target_triple, Item::TargetTriple),
+                map(type_definition, Item::TypeDefinition),
+                map(global_variable, Item::Global),
+                map(function_definition, Item::Function),
+                map(function_declaration, Item::Declaration),
+                map(attribute_group, Item::AttributeGroup),
+                map(metadata_node, Item::MetadataNode),
+                map(named_metadata, Item::NamedMetadata),
+            )),
+            end_of_line,
+        ),
+    ))(data)
+}
+
+/// Parse module. Metadata nodes should be numbered in order of
+/// definitions, starting from `!0`, as they are printed.
+/// ## RULE:
+/// ```js
+/// module = item*
+/// ```
+pub fn module(data: &str) -> ParseResult<'_, ir::Module> {
+    let mut m = ir::Module::new("");
+    let mut i = data;
+    loop {
+        let (rest, item) = match item(i) {
+            Ok(v) => v,
+            Err(nom::Err::Error(_)) => return Ok((i, m)),
+            Err(err) => return Err(err),
+        };
+        match item {
+            Item::ModuleId(v) => m.module_id = v,
+            Item::SourceFileName(v) => m.source_filename = Some(v),
+            Item::DataLayout(v) => m.data_layout = Some(v),
+            Item::TargetTriple(v) => m.target_triple = Some(v),
+            Item::TypeDefinition(v) => m.types.push(v),
+            Item::Global(v) => m.globals.push(v),
+            Item::Function(v) => m.functions.push(v),
+            Item::Declaration(v) => m.declarations.push(v),
+            Item::AttributeGroup(v) => m.attribute_groups.push(v),
+            Item::MetadataNode((MetadataRef(id), _)) if id != m.metadata.len() => {
+                return Err(nom::Err::Failure((i, ErrorKind::Verify)));
+            }
+            Item::MetadataNode((_, v)) => m.metadata.push(v),
+            Item::NamedMetadata(v) => m.named_metadata.push(v),
+            Item::Empty => {}
+        }
+        i = rest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::parser::{
+        normalize,
+        parse,
+    };
+
+    fn round_trip<T: std::fmt::Display>(parser: fn(&str) -> ParseResult<T>, s: &str) {
+        let (i, v) = parser(s).unwrap();
+        assert_eq!(i, "");
+        assert_eq!(v.to_string(), s);
+    }
+
+    const MODULE: &str = r#"; ModuleID = 'name1.name2'
+source_filename = "name2.i"
+target triple = "x86_64-unknown-linux-gnu"
+
+@counter = internal global i32 zeroinitializer, !dbg !2
+@.str = private unnamed_addr constant [13 x i8] c"%d %d %d %d\0A\00"
+@llvm.global_ctors = appending global [1 x { i32, ptr, ptr }] [{ i32, ptr, ptr } { i32 65535, ptr @_GLOBAL_let_main, ptr null }]
+
+define internal void @__global_let_init.0() #0 section ".text.startup" {
+	store i32 10, ptr @counter
+	ret void
+}
+
+define internal i32 @main() #0 {
+	%1 = atomicrmw add ptr @counter, i32 1 seq_cst
+	%2 = add i32 %1, 1
+	%3 = cmpxchg ptr @counter, i32 11, i32 20 seq_cst seq_cst
+	%4 = extractvalue { i32, i1 } %3, 0
+	%5 = getelementptr inbounds [13 x i8], ptr @.str, i64 0, i64 0
+	%6 = load i32, ptr @counter
+	%7 = call i32 (ptr, ...) @printf(ptr %5, i32 %2, i32 %4, i32 %6)
+	ret i32 %7
+}
+
+define i32 @max(i32 %a, i32 %b) !dbg !1 {
+	%1 = icmp sgt i32 %a, %b
+	br i1 %1, label %then, label %else
+then:
+	br label %exit
+else:
+	br label %exit
+exit:
+	%2 = phi i32 [ %a, %then ], [ %b, %else ]
+	ret i32 %2, !dbg !2, !prof !3
+}
+
+declare i32 @printf(ptr, ...)
+
+attributes #0 = { noinline uwtable }
+
+!llvm.module.flags = !{!0}
+
+!0 = !{i32 1, !"wchar_size", i32 4}
+!1 = distinct !{}
+!2 = !{!1, null}
+!3 = !{!"branch_weights", i32 1}
+"#;
+
+    #[test]
+    fn test_global_variable() {
+        round_trip(global_variable, "@x = global i32 0");
+        round_trip(global_variable, "@y = external global ptr");
+        round_trip(
+            global_variable,
+            "@z = weak_odr dso_local hidden dllexport thread_local(localexec) local_unnamed_addr addrspace(1) constant [2 x i32] [i32 1, i32 2], section \".data\", align 8",
+        );
+        round_trip(
+            global_variable,
+            "@p = global ptr getelementptr inbounds ([2 x i8], ptr @s, i64 0, i64 0)",
+        );
+    }
+
+    #[test]
+    fn test_function_header() {
+        round_trip(function_header, "declare i32 @printf(ptr, ...)");
+        round_trip(
+            function_header,
+            "define internal fastcc noundef i32 @f(i32 signext %a, ptr %0) unnamed_addr #0 #1 section \".text.startup\" align 16",
+        );
+        round_trip(function_header, "declare void @g(...)");
+        round_trip(attribute_group, "attributes #0 = { noinline uwtable }");
+        round_trip(
+            attribute_group,
+            r#"attributes #1 = { nounwind "frame-pointer"="all" }"#,
+        );
+        round_trip(source_filename, "source_filename = \"app.i\"");
+        round_trip(
+            target_triple,
+            "target triple = \"x86_64-unknown-linux-gnu\"",
+        );
+        round_trip(
+            data_layout,
+            "target datalayout = \"e-m:e-i64:64-i128:128-n32:64-S128\"",
+        );
+    }
+
+    #[test]
+    fn test_module_round_trip() {
+        let m = parse(MODULE).unwrap();
+        assert_eq!(m.to_string(), MODULE);
+        let f = m.function("max").unwrap();
+        assert_eq!(f.blocks.len(), 4);
+        assert_eq!(f.entry().unwrap().label, "0");
+        assert_eq!(f.predecessors("exit"), vec!["then", "else"]);
+        assert_eq!(m.function("main").unwrap().entry().unwrap().label, "0");
+        assert_eq!(m.declarations.len(), 1);
+        assert_eq!(m.metadata.len(), 4);
+        assert_eq!(m.named_metadata[0].name, "llvm.module.flags");
+        assert_eq!(m.globals[0].metadata.len(), 1);
+    }
+
+    #[test]
+    fn test_module_type_definitions() {
+        let src = "; ModuleID = 'x'\n\n%Node = type { i32, ptr }\n%Pair = type <{ i8, %Node }>\n%Opaque = type opaque\n\n@p = global %Pair zeroinitializer\n\ndefine i32 @f(%Node %n) {\n\t%1 = extractvalue %Node %n, 0\n\tret i32 %1\n}\n";
+        let m = parse(src).unwrap();
+        assert_eq!(m.to_string(), src);
+        assert_eq!(m.types.len(), 3);
+        assert_eq!(m.type_definition("Opaque").unwrap().body, None);
+        assert!(
+            m.type_definition("Pair")
+                .unwrap()
+                .body
+                .as_ref()
+                .unwrap()
+                .packed
+        );
+    }
+
+    #[test]
+    fn test_module_metadata_numbering() {
+        let err = parse("!0 = !{}\n!2 = !{}\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(parse("!1 = !{}\n").is_err());
+    }
+
+    #[test]
+    fn test_module_unnamed_blocks() {
+        let src =
+            "; ModuleID = 'x'\n\ndefine i32 @f(i32 %0) {\n\tbr label %2\n2:\n\tret i32 %0\n}\n";
+        let m = parse(src).unwrap();
+        let f = m.function("f").unwrap();
+        assert_eq!(f.entry().unwrap().label, "1");
+        assert_eq!(f.successors("1"), vec!["2"]);
+        assert_eq!(m.to_string(), src);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let src = r#"; ModuleID = 'x'
+; comment
+source_filename   =   "x.i"
+
+define i32 @main() #0 {   ; entry
+  %1 = add   i32 1,2
+  br label %exit
+
+exit:     ; preds = %0
+  ret i32 %1
+}
+attributes #0 = {noinline}"#;
+        assert_eq!(
+            normalize(src).unwrap(),
+            "; ModuleID = 'x'\nsource_filename = \"x.i\"\n\ndefine i32 @main() #0 {\n\t%1 = add i32 1, 2\n\tbr label %exit\nexit:\n\tret i32 %1\n}\n\nattributes #0 = { noinline }\n"
+        );
+        assert_eq!(normalize(&normalize(src).unwrap()), normalize(src));
+    }
+}
@@ -0,0 +1,380 @@
+//! # Types parser
+//!
+//! Parse LLVM IR type syntax to `Type`.
+//!
+//! https://llvm.org/docs/LangRef.html#type-system
+
+use nom::{
+    branch::alt,
+    character::complete::{
+        char,
+        space0,
+    },
+    combinator::{
+        map,
+        opt,
+        value,
+    },
+    multi::separated_list,
+    sequence::{
+        delimited,
+        preceded,
+        terminated,
+        tuple,
+    },
+};
+
+use super::{
+    attributes::addrspace,
+    comma,
+    integer,
+    keyword,
+    name,
+    ws,
+    ParseResult,
+};
+use crate::llvm::{
+    addrspace::AddrSpace,
+    type_system::{
+        aggregate::{
+            ArrayType,
+            StructureType,
+            TypeDefinition,
+        },
+        single_value::{
+            FloatingPointType,
+            PointerType,
+            VectorType,
+        },
+        FunctionType,
+    },
+    types::Type,
+};
+
+/// Parse type
+/// ## RULE:
+/// ```js
+/// type = base-type ( [ addrspace ] "*" | "(" parameter-types ")" )*
+/// ```
+pub fn ty(data: &str) -> ParseResult<'_, Type> {
+    let (mut i, mut t) = base_type(data)?;
+    loop {
+        if let Ok((rest, addrspace)) = pointer_suffix(i) {
+            t = Type::Pointer(PointerType {
+                pointee: Some(Box::new(t)),
+                addrspace,
+            });
+            i = rest;
+        } else if let Ok((rest, (parameter_list, variable_argument))) = parameter_types(i) {
+            t = Type::Function(FunctionType {
+                return_type: Box::new(t),
+                parameter_list,
+                variable_argument,
+            });
+            i = rest;
+        } else {
+            return Ok((i, t));
+        }
+    }
+}
+
+/// Typed pointer suffix
+fn pointer_suffix(data: &str) -> ParseResult<'_, Option<AddrSpace>> {
+    terminated(opt(ws(addrspace)), preceded(space0, char('*')))(data)
+}
+
+/// Parameter types of function type
+/// ## RULE:
+/// ```js
+/// parameter-types = "(" [ type ( "," type )* [ "," "..." ] | "..." ] ")"
+/// ```
+pub fn parameter_types(data: &str) -> ParseResult<'_, (Vec<Type>, bool)> {
+    let variadic = |i| ws(keyword("..."))(i);
+    let params = alt((
+        map(variadic, |_| (vec![], true)),
+        map(
+            tuple((
+                separated_list(comma, ws(ty)),
+                opt(preceded(comma, variadic)),
+            )),
+            |(params, variadic)| (params, variadic.is_some()),
+        ),
+    ));
+    delimited(
+        preceded(space0, char('(')),
+        params,
+        preceded(space0, char(')')),
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// base-type = "void" | "token" | "metadata" | opaque-pointer-type | integer-type |
+///     floating-point-type | vector-type | array-type | structure-type |
+///     identified-type
+/// ```
+fn base_type(data: &str) -> ParseResult<'_, Type> {
+    alt((
+        value(Type::Void, keyword("void")),
+        opaque_pointer_type,
+        value(Type::Token, keyword("token")),
+        value(Type::Metadata, keyword("metadata")),
+        integer_type,
+        floating_point_type,
+        map(vector_type, Type::Vector),
+        array_type,
+        structure_type,
+        identified_type,
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// opaque-pointer-type = "ptr" [ addrspace ]
+/// ```
+fn opaque_pointer_type(data: &str) -> ParseResult<'_, Type> {
+    map(
+        preceded(keyword("ptr"), opt(ws(addrspace))),
+        Type::opaque_pointer,
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// integer-type = "i1" | "i8" | "i16" | "i32" | "i64" | "i128"
+/// ```
+fn integer_type(data: &str) -> ParseResult<'_, Type> {
+    alt((
+        value(Type::Integer1, keyword("i1")),
+        value(Type::Integer8, keyword("i8")),
+        value(Type::Integer16, keyword("i16")),
+        value(Type::Integer32, keyword("i32")),
+        value(Type::Integer64, keyword("i64")),
+        value(Type::Integer128, keyword("i128")),
+    ))(data)
+}
+
+/// ## RULE:
+/// ```js
+/// floating-point-type = "half" | "bfloat" | "float" | "double" |
+///     "fp128" | "x86_fp80" | "ppc_fp128"
+/// ```
+fn floating_point_type(data: &str) -> ParseResult<'_, Type> {
+    map(
+        alt((
+            value(FloatingPointType::Half, keyword("half")),
+            value(FloatingPointType::Bfloat, keyword("bfloat")),
+            value(FloatingPointType::Float, keyword("float")),
+            value(FloatingPointType::Double, keyword("double")),
+            value(FloatingPointType::Fp128, keyword("fp128")),
+            value(FloatingPointType::X86fp80, keyword("x86_fp80")),
+            value(FloatingPointType::PpcFp128, keyword("ppc_fp128")),
+        )),
+        Type::FloatingPoint,
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// vector-type = "<" [ "vscale" "x" ] number "x" type ">"
+/// ```
+pub fn vector_type(data: &str) -> ParseResult<'_, VectorType> {
+    map(
+        tuple((
+            char('<'),
+            opt(tuple((ws(keyword("vscale")), ws(char('x'))))),
+            ws(integer),
+            ws(char('x')),
+            ws(ty),
+            ws(char('>')),
+        )),
+        |(_, vscale, elemetns, _, element_type, _)| VectorType {
+            elemetns: elemetns as i64,
+            element_type: Box::new(element_type),
+            vscale: vscale.is_some(),
+        },
+    )(data)
+}
+
+/// ## RULE:
+/// ```js
+/// array-type = "[" number "x" type "]"
+/// ```
+fn array_type(data: &str) -> ParseResult<'_, Type> {
+    map(
+        tuple((char('['), ws(integer), ws(char('x')), ws(ty), ws(char(']')))),
+        |(_, n, _, t, _)| Type::Array(ArrayType(n as i32, Box::new(t))),
+    )(data)
+}
+
+/// Literal structure type
+/// ## RULE:
+/// ```js
+/// structure-type = "{" [ type ( "," type )* ] "}" |
+///     "<{" [ type ( "," type )* ] "}>"
+/// ```
+fn structure_type(data: &str) -> ParseResult<'_, Type> {
+    map(literal_structure, Type::Structure)(data)
+}
+
+fn literal_structure(data: &str) -> ParseResult<'_, StructureType> {
+    let body = |i| delimited(char('{'), separated_list(comma, ws(ty)), ws(char('}')))(i);
+    let packed = opt(tuple((char('<'), space0)));
+    let (i, (packed, type_list)) = tuple((packed, body))(data)?;
+    let i = if packed.is_some() {
+        preceded(space0, char('>'))(i)?.0
+    } else {
+        i
+    };
+    Ok((
+        i,
+        StructureType {
+            literal: true,
+            packed: packed.is_some(),
+            type_list,
+        },
+    ))
+}
+
+/// Reference to identified structure type
+/// ## RULE:
+/// ```js
+/// identified-type = "%" name
+/// ```
+fn identified_type(data: &str) -> ParseResult<'_, Type> {
+    map(preceded(char('%'), name), |v| {
+        Type::Identified(v.to_string())
+    })(data)
+}
+
+/// Identified structure type definition of module
+/// ## RULE:
+/// ```js
+/// type-definition = "%" name "=" "type" ( "opaque" | structure-type )
+/// ```
+pub fn type_definition(data: &str) -> ParseResult<'_, TypeDefinition> {
+    let body = alt((
+        map(keyword("opaque"), |_| None),
+        map(literal_structure, |v| {
+            Some(StructureType {
+                literal: false,
+                ..v
+            })
+        }),
+    ));
+    map(
+        tuple((
+            preceded(char('%'), name),
+            ws(char('=')),
+            ws(keyword("type")),
+            ws(body),
+        )),
+        |(name, _, _, body)| TypeDefinition {
+            name: name.to_string(),
+            body,
+        },
+    )(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::type_system::single_value::TypedPointers;
+
+    fn round_trip(s: &str) {
+        let (i, t) = ty(s).unwrap();
+        assert_eq!(i, "");
+        assert_eq!(t.to_string(), s);
+    }
+
+    #[test]
+    fn test_types() {
+        round_trip("void");
+        round_trip("token");
+        round_trip("metadata");
+        round_trip("i1");
+        round_trip("i128");
+        round_trip("double");
+        round_trip("ppc_fp128");
+        round_trip("ptr");
+        round_trip("ptr addrspace(1)");
+        round_trip("<4 x float>");
+        round_trip("<vscale x 4 x i32>");
+        round_trip("[13 x i8]");
+        round_trip("{ i32, i1 }");
+        round_trip("<{ i8, [2 x i32] }>");
+        round_trip("void ()");
+        round_trip("i32 (ptr, ...)");
+        round_trip("[1 x { i32, ptr, ptr }]");
+        assert_eq!(ty("i32 %x").unwrap(), (" %x", Type::Integer32));
+        assert!(ty("i7").is_err());
+        assert!(ty("i32x").is_err());
+        assert_eq!(ty("{i32,i8 *}").unwrap().1.to_string(), "{ i32, ptr }");
+        assert_ne!(ty("ptr").unwrap().1, Type::pointer1(Type::Integer8));
+        assert!(ty("ptr")
+            .unwrap()
+            .1
+            .is_compatible(&Type::pointer1(Type::Integer8)));
+        assert!(!ty("ptr addrspace(1)")
+            .unwrap()
+            .1
+            .is_compatible(&Type::pointer1(Type::Integer8)));
+        // Compatibility isn't transitive, equality is structural
+        let (i8_ptr, i32_ptr) = (ty("i8*").unwrap().1, ty("i32*").unwrap().1);
+        let ptr = ty("ptr").unwrap().1;
+        assert!(i8_ptr.is_compatible(&ptr) && ptr.is_compatible(&i32_ptr));
+        assert!(!i8_ptr.is_compatible(&i32_ptr));
+        assert_ne!(i8_ptr, ptr);
+        assert!(ty("{ i8*, <2 x i32> }")
+            .unwrap()
+            .1
+            .is_compatible(&ty("{ ptr, <2 x i32> }").unwrap().1));
+    }
+
+    #[test]
+    fn test_identified_types() {
+        round_trip("%Point");
+        round_trip("%\"struct.a b\"");
+        round_trip("{ i32, %Node }");
+        assert_eq!(ty("%T").unwrap().1, Type::Identified("T".to_string()));
+        let def = |s| {
+            let (i, v) = type_definition(s).unwrap();
+            assert_eq!(i, "");
+            assert_eq!(v.to_string(), s);
+            v
+        };
+        let v = def("%Point = type { i32, i32 }");
+        assert_eq!(v.name, "Point");
+        assert!(!v.body.unwrap().packed);
+        assert!(def("%P = type <{ i8, i32 }>").body.unwrap().packed);
+        assert_eq!(def("%Node = type opaque").body, None);
+        let v = def("%List = type { i32, ptr }");
+        let typed = type_definition("%List = type { i32, %List* }").unwrap().1;
+        assert_eq!(typed.to_string(), v.to_string());
+        assert!(typed
+            .body
+            .unwrap()
+            .type_list
+            .iter()
+            .zip(v.body.unwrap().type_list.iter())
+            .all(|(x, y)| x.is_compatible(y)));
+    }
+
+    #[test]
+    fn test_typed_pointers() {
+        let typed_round_trip = |s: &str| {
+            let (i, t) = ty(s).unwrap();
+            assert_eq!(i, "");
+            assert_eq!(TypedPointers(&t).to_string(), s);
+        };
+        typed_round_trip("i8**");
+        typed_round_trip("i8 addrspace(1)*");
+        typed_round_trip("void ()*");
+        typed_round_trip("i32 (i8*, ...)");
+        typed_round_trip("i32 (...)*");
+        typed_round_trip("[1 x { i32, void ()*, i8* }]");
+        // Opaque pointer has no pointee type to print
+        typed_round_trip("ptr");
+        assert_eq!(ty("i8**").unwrap().1.to_string(), "ptr");
+    }
+}
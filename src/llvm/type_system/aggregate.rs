@@ -35,7 +35,12 @@ pub struct ArrayType(pub i32, pub Box<Type>);
 /// module, which is required to match what the underlying code generator
 /// expects.
 ///
-/// Structures can either be “literal” or “identified”.
+/// Structures can either be “literal” or “identified”. A literal
+/// structure is defined inline with other types (e.g. `[2 x {i32, i32}]`)
+/// whereas identified types are always defined at the top level with
+/// a name. Non-literal structure is body of identified type in type
+/// definition, it's printed with `type` keyword.
+/// https://llvm.org/docs/LangRef.html#structure-type
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct StructureType {
     pub literal: bool,
@@ -43,6 +48,25 @@ pub struct StructureType {
     pub type_list: Vec<Type>,
 }
 
+/// Identified structure type definition in module type table.
+/// Identified type without body is opaque. Opaque structure types
+/// are used to represent named structure types that do not have a
+/// body specified, and for forward declarations of recursive types.
+/// Name is stored without `%` sigil.
+///
+/// Syntax:
+/// ```html
+/// %T1 = type { <type list> }     ; Identified normal struct type
+/// %T2 = type <{ <type list> }>   ; Identified packed struct type
+/// %X = type opaque
+/// ```
+/// https://llvm.org/docs/LangRef.html#opaque-structure-types
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TypeDefinition {
+    pub name: String,
+    pub body: Option<StructureType>,
+}
+
 impl std::fmt::Display for ArrayType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let s = format!("[{} x {}]", self.0, self.1);
@@ -61,7 +85,7 @@ impl std::fmt::Display for StructureType {
                 if i > 0 {
                     format!("{}, {}", s, ty)
                 } else {
-                    format!("{} {}", s, ty)
+                    format!("{}", ty)
                 }
             });
         let s = if self.literal {
@@ -71,10 +95,19 @@ impl std::fmt::Display for StructureType {
                 format!("{{ {} }}", s)
             }
         } else if self.packed {
-            format!("type {{ {} }}", s)
-        } else {
             format!("type <{{ {} }}>", s)
+        } else {
+            format!("type {{ {} }}", s)
         };
         write!(f, "{}", s)
     }
 }
+
+impl std::fmt::Display for TypeDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.body {
+            Some(body) => write!(f, "%{} = {}", self.name, body),
+            None => write!(f, "%{} = type opaque", self.name),
+        }
+    }
+}
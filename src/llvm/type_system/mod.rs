@@ -31,9 +31,24 @@ pub struct VoidType;
 /// https://llvm.org/docs/LangRef.html#function-type
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct FunctionType {
-    return_type: Box<Type>,
-    parameter_list: Vec<Type>,
-    variable_argument: bool,
+    pub return_type: Box<Type>,
+    pub parameter_list: Vec<Type>,
+    pub variable_argument: bool,
+}
+
+impl FunctionType {
+    /// Return and parameter types are compatible, see
+    /// `Type::is_compatible`
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.variable_argument == other.variable_argument
+            && self.return_type.is_compatible(&other.return_type)
+            && self.parameter_list.len() == other.parameter_list.len()
+            && self
+                .parameter_list
+                .iter()
+                .zip(other.parameter_list.iter())
+                .all(|(x, y)| x.is_compatible(y))
+    }
 }
 
 impl std::fmt::Display for VoidType {
@@ -54,7 +69,7 @@ impl std::fmt::Display for FunctionType {
                 if i > 0 {
                     format!("{}, {}", s, t)
                 } else {
-                    format!("{} {}", s, t)
+                    format!("{}", t)
                 }
             });
         let s = if self.variable_argument {
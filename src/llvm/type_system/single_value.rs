@@ -2,7 +2,11 @@
 //!
 //! These are the types that are valid in registers from CodeGen’s perspective.
 
-use crate::llvm::types::Type;
+use crate::llvm::{
+    addrspace::AddrSpace,
+    types::Type,
+};
+use std::cell::Cell;
 
 /// The integer type is a very simple type that simply specifies an
 /// arbitrary bit width for the integer type desired. Any bit width from 1
@@ -52,11 +56,52 @@ pub enum FloatingPointType {
 /// default address space is number zero. The semantics of non-zero address
 /// spaces are target-specific.
 ///
-/// Note that LLVM does not permit pointers to void (void*) nor does it
-/// permit pointers to labels (label*). Use i8* instead.
+/// Prior to LLVM 15, pointer types did specify a pointee type, such
+/// as `i8*`, `[4 x i32]*` or `i32 (i32*)*`. Opaque pointer `ptr` has
+/// no pointee type. Pointee type is still kept, when it is known, to
+/// check operands of instructions, and printed in typed pointers mode.
 /// https://llvm.org/docs/LangRef.html#pointer-type
 #[derive(Debug, Eq, PartialEq, Clone)]
-pub struct PointerType(pub Box<Type>);
+pub struct PointerType {
+    pub pointee: Option<Box<Type>>,
+    pub addrspace: Option<AddrSpace>,
+}
+
+thread_local! {
+    /// Pointer mode of printer, it's set only while item is printed
+    static TYPED_POINTERS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Restore previous pointer mode of printer
+struct PointerModeGuard(bool);
+
+impl Drop for PointerModeGuard {
+    fn drop(&mut self) {
+        TYPED_POINTERS.with(|v| v.set(self.0));
+    }
+}
+
+/// Print IR with pointer mode: pointers with pointee type, like
+/// `i8*`, for LLVM versions prior to 15, or opaque `ptr`. Mode is
+/// applied to nested items printed by `print`.
+pub(crate) fn print_with_pointers<T>(typed: bool, print: impl FnOnce() -> T) -> T {
+    let _guard = PointerModeGuard(TYPED_POINTERS.with(|v| v.replace(typed)));
+    print()
+}
+
+fn typed_pointers() -> bool {
+    TYPED_POINTERS.with(|v| v.get())
+}
+
+/// Printer of IR item with typed pointers. Module is printed with
+/// its own pointer mode.
+pub struct TypedPointers<'a, T>(pub &'a T);
+
+impl<T: std::fmt::Display> std::fmt::Display for TypedPointers<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        print_with_pointers(true, || self.0.fmt(f))
+    }
+}
 
 /// A vector type is a simple derived type that represents a vector of
 /// elements. Vector types are used when multiple primitive data are
@@ -124,15 +169,32 @@ impl std::fmt::Display for FloatingPointType {
             FloatingPointType::Double => "double",
             FloatingPointType::Fp128 => "fp128",
             FloatingPointType::X86fp80 => "x86_fp80",
-            FloatingPointType::PpcFp128 => "ppc_fp80",
+            FloatingPointType::PpcFp128 => "ppc_fp128",
         };
         write!(f, "{}", s)
     }
 }
 
+impl PointerType {
+    /// Pointers in the same address space are compatible, if one of
+    /// them is opaque or pointee types are compatible
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        let pointee = match (&self.pointee, &other.pointee) {
+            (Some(x), Some(y)) => x.is_compatible(y),
+            _ => true,
+        };
+        pointee && self.addrspace == other.addrspace
+    }
+}
+
 impl std::fmt::Display for PointerType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}*", self.0)
+        match (&self.pointee, &self.addrspace) {
+            (Some(ty), Some(v)) if typed_pointers() => write!(f, "{} {}*", ty, v),
+            (Some(ty), None) if typed_pointers() => write!(f, "{}*", ty),
+            (_, Some(v)) => write!(f, "ptr {}", v),
+            (_, None) => write!(f, "ptr"),
+        }
     }
 }
 
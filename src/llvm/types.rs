@@ -1,9 +1,12 @@
 //! # Basic LLVM types
 
-use super::type_system::{
-    aggregate::*,
-    single_value::*,
-    *,
+use super::{
+    addrspace::AddrSpace,
+    type_system::{
+        aggregate::*,
+        single_value::*,
+        *,
+    },
 };
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -21,6 +24,11 @@ pub enum Type {
     Vector(VectorType),
     Array(ArrayType),
     Structure(StructureType),
+    /// Identified structure type `%T`, without `%` sigil. Its body
+    /// is defined in module type table.
+    Identified(String),
+    Token,
+    Metadata,
 }
 
 impl std::fmt::Display for Type {
@@ -39,6 +47,9 @@ impl std::fmt::Display for Type {
             Type::Vector(x) => format!("{}", x),
             Type::Array(x) => format!("{}", x),
             Type::Structure(x) => format!("{}", x),
+            Type::Identified(x) => format!("%{}", x),
+            Type::Token => "token".to_string(),
+            Type::Metadata => "metadata".to_string(),
         };
         write!(f, "{}", s)
     }
@@ -46,21 +57,157 @@ impl std::fmt::Display for Type {
 
 impl Type {
     pub fn pointer1(ty: Type) -> Self {
-        Type::Pointer(PointerType(Box::new(ty)))
+        Type::Pointer(PointerType {
+            pointee: Some(Box::new(ty)),
+            addrspace: None,
+        })
     }
 
     pub fn pointer2(ty: Type) -> Self {
-        let ty1 = Type::Pointer(PointerType(Box::new(ty)));
-        Type::Pointer(PointerType(Box::new(ty1)))
+        Type::pointer1(Type::pointer1(ty))
     }
 
     pub fn pointer3(ty: Type) -> Self {
-        let ty1 = Type::Pointer(PointerType(Box::new(ty)));
-        let ty2 = Type::Pointer(PointerType(Box::new(ty1)));
-        Type::Pointer(PointerType(Box::new(ty2)))
+        Type::pointer1(Type::pointer2(ty))
     }
 
+    /// Opaque pointer `ptr` with optional address space
+    pub fn opaque_pointer(addrspace: Option<AddrSpace>) -> Self {
+        Type::Pointer(PointerType {
+            pointee: None,
+            addrspace,
+        })
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        matches!(self, Type::Pointer(_))
+    }
+
+    /// Integer types, except boolean `i1`
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Type::Integer8 | Type::Integer16 | Type::Integer32 | Type::Integer64 | Type::Integer128
+        )
+    }
+
+    pub fn is_floating_point(&self) -> bool {
+        matches!(self, Type::FloatingPoint(_))
+    }
+
+    /// Element type for vector types, type itself for scalars
+    pub fn scalar_type(&self) -> &Type {
+        match self {
+            Type::Vector(x) => &x.element_type,
+            _ => self,
+        }
+    }
+
+    /// Bit width of integer type, including boolean `i1`
+    pub fn integer_width(&self) -> Option<u32> {
+        match self {
+            Type::Integer1 => Some(1),
+            Type::Integer8 => Some(8),
+            Type::Integer16 => Some(16),
+            Type::Integer32 => Some(32),
+            Type::Integer64 => Some(64),
+            Type::Integer128 => Some(128),
+            _ => None,
+        }
+    }
+
+    /// Bit width of floating point type
+    pub fn floating_point_width(&self) -> Option<u32> {
+        match self {
+            Type::FloatingPoint(FloatingPointType::Half)
+            | Type::FloatingPoint(FloatingPointType::Bfloat) => Some(16),
+            Type::FloatingPoint(FloatingPointType::Float) => Some(32),
+            Type::FloatingPoint(FloatingPointType::Double) => Some(64),
+            Type::FloatingPoint(FloatingPointType::X86fp80) => Some(80),
+            Type::FloatingPoint(FloatingPointType::Fp128)
+            | Type::FloatingPoint(FloatingPointType::PpcFp128) => Some(128),
+            _ => None,
+        }
+    }
+
+    /// Types are equal, except pointee types of pointers: opaque
+    /// pointer is compatible with any pointer in the same address
+    /// space. It's not equivalence relation: `i8*` and `i32*` are
+    /// both compatible with `ptr`, but not with each other.
+    pub fn is_compatible(&self, other: &Type) -> bool {
+        let all = |x: &[Type], y: &[Type]| {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| x.is_compatible(y))
+        };
+        match (self, other) {
+            (Type::Pointer(x), Type::Pointer(y)) => x.is_compatible(y),
+            (Type::Vector(x), Type::Vector(y)) => {
+                x.elemetns == y.elemetns
+                    && x.vscale == y.vscale
+                    && x.element_type.is_compatible(&y.element_type)
+            }
+            (Type::Array(x), Type::Array(y)) => x.0 == y.0 && x.1.is_compatible(&y.1),
+            (Type::Structure(x), Type::Structure(y)) => {
+                x.literal == y.literal && x.packed == y.packed && all(&x.type_list, &y.type_list)
+            }
+            (Type::Function(x), Type::Function(y)) => x.is_compatible(y),
+            _ => self == other,
+        }
+    }
+
+    /// Type of value addressed by pointer
+    pub fn pointee_type(&self) -> Option<&Type> {
+        match self {
+            Type::Pointer(x) => x.pointee.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Type of element for aggregate or vector type by index
+    pub fn indexed_type(&self, idx: u64) -> Option<Type> {
+        match self {
+            Type::Array(x) => Some(*x.1.clone()),
+            Type::Vector(x) => Some(*x.element_type.clone()),
+            Type::Structure(x) => x.type_list.get(idx as usize).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Type of element by index. Identified structure types are
+    /// resolved with module type table.
+    pub fn indexed_type_in(&self, idx: u64, types: &[TypeDefinition]) -> Option<Type> {
+        match self {
+            Type::Identified(name) => {
+                let body = types.iter().find(|t| &t.name == name)?.body.as_ref()?;
+                body.type_list.get(idx as usize).cloned()
+            }
+            _ => self.indexed_type(idx),
+        }
+    }
+
+    /// Result type of comparison: `i1` or vector of `i1` for
+    /// vector operands
+    pub fn compare_result(&self) -> Self {
+        if let Type::Vector(x) = self {
+            Type::Vector(VectorType {
+                elemetns: x.elemetns,
+                element_type: Box::new(Type::Integer1),
+                vscale: x.vscale,
+            })
+        } else {
+            Type::Integer1
+        }
+    }
+
+    /// String constant. Quotes, backslashes and non-printable
+    /// characters are escaped as `\XX` hex codes.
     pub fn raw_string(s: &str) -> String {
+        let s = s.bytes().fold("".to_string(), |s, b| {
+            if b == b'"' || b == b'\\' || !(b' '..=b'~').contains(&b) {
+                format!("{}\\{:02X}", s, b)
+            } else {
+                format!("{}{}", s, b as char)
+            }
+        });
         format!(r#"c"{}\00""#, s)
     }
 }
@@ -0,0 +1,465 @@
+//! # IR verifier
+//!
+//! Structural checks of in-memory IR module before it is handed to
+//! LLVM. Verifier reports first found problem with function, basic
+//! block and instruction, where it is found:
+//!
+//! - function has basic blocks, each basic block has exactly one
+//!   terminator at the end, and branches go to existing blocks
+//! - `phi` nodes are grouped at the beginning of basic block
+//! - SSA values are defined once and before use, unnamed values and
+//!   labels are numbered sequentially
+//! - operand types match types of value definitions
+//! - called functions are defined or declared in module, and
+//!   arguments match function signature
+//!
+//! Values are checked to be defined before use in order of basic
+//! blocks in function. Dominance is not computed, so it's weaker
+//! than LLVM verifier, but enough for IR produced by builder.
+//!
+//! https://llvm.org/docs/LangRef.html#functions
+
+use crate::llvm::{
+    functions,
+    ir::{
+        BasicBlock,
+        Function,
+        Module,
+    },
+    type_system::FunctionType,
+    types::Type,
+    InstructionSet,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VerifyErrorKind {
+    EmptyFunction,
+    MissingTerminator,
+    /// Terminator is not the last instruction of basic block
+    MisplacedTerminator,
+    /// Phi node after non-phi instruction
+    MisplacedPhi,
+    UnknownLabel(String),
+    UndefinedValue(String),
+    Redefinition(String),
+    /// Expected number and actual unnamed value
+    UnexpectedNumber(usize, String),
+    /// Value, expected and actual types
+    TypeMismatch(String, Type, Type),
+    UndeclaredFunction(String),
+    /// Function name and count of arguments
+    ArgumentsMismatch(String, usize),
+}
+
+/// Verification error: function name, basic block label and text of
+/// instruction, where error is found.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VerifyError {
+    pub function: String,
+    pub block: Option<String>,
+    pub instruction: Option<String>,
+    pub kind: VerifyErrorKind,
+}
+
+pub type VerifyResult = std::result::Result<(), Box<VerifyError>>;
+
+impl std::fmt::Display for VerifyErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VerifyErrorKind::EmptyFunction => write!(f, "function has no basic blocks"),
+            VerifyErrorKind::MissingTerminator => write!(f, "basic block has no terminator"),
+            VerifyErrorKind::MisplacedTerminator => {
+                write!(f, "terminator in the middle of basic block")
+            }
+            VerifyErrorKind::MisplacedPhi => {
+                write!(f, "phi node is not at the beginning of basic block")
+            }
+            VerifyErrorKind::UnknownLabel(v) => write!(f, "branch to unknown label %{}", v),
+            VerifyErrorKind::UndefinedValue(v) => write!(f, "use of undefined value {}", v),
+            VerifyErrorKind::Redefinition(v) => write!(f, "redefinition of {}", v),
+            VerifyErrorKind::UnexpectedNumber(n, v) => {
+                write!(f, "unnamed value {} out of sequence, expected %{}", v, n)
+            }
+            VerifyErrorKind::TypeMismatch(v, expected, actual) => {
+                write!(f, "value {} of type {} used as {}", v, actual, expected)
+            }
+            VerifyErrorKind::UndeclaredFunction(v) => {
+                write!(f, "call of undeclared function {}", v)
+            }
+            VerifyErrorKind::ArgumentsMismatch(v, n) => {
+                write!(f, "function {} called with {} arguments", v, n)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = format!("@{}", self.function);
+        if let Some(v) = &self.block {
+            s = format!("{}, block %{}", s, v);
+        }
+        if let Some(v) = &self.instruction {
+            s = format!("{}, `{}`", s, v);
+        }
+        write!(f, "{}: {}", s, self.kind)
+    }
+}
+
+/// Verify all function definitions of module
+pub fn verify(module: &Module) -> VerifyResult {
+    module
+        .functions
+        .iter()
+        .try_for_each(|f| FunctionVerifier::new(module, f).verify())
+}
+
+/// Number of unnamed value or label
+fn numbered(value: &str) -> Option<usize> {
+    value.trim_start_matches('%').parse().ok()
+}
+
+/// Function type of function definition or declaration
+fn function_type(f: &functions::Function) -> FunctionType {
+    FunctionType {
+        return_type: Box::new(f.result_type.clone()),
+        parameter_list: f
+            .argument_list
+            .iter()
+            .filter_map(|a| a.parameter_type.clone())
+            .collect(),
+        variable_argument: f.argument_list.iter().any(|a| a.variable_argument),
+    }
+}
+
+struct FunctionVerifier<'a> {
+    module: &'a Module,
+    function: &'a Function,
+    /// All values defined in function, with their types
+    values: HashMap<String, Option<Type>>,
+    /// Values defined before current instruction
+    defined: HashMap<String, Option<Type>>,
+    /// Next number for unnamed value or label
+    next: usize,
+}
+
+impl<'a> FunctionVerifier<'a> {
+    fn new(module: &'a Module, function: &'a Function) -> Self {
+        let mut values = HashMap::new();
+        for bb in &function.blocks {
+            for instr in bb.instructions.iter().chain(bb.terminator.iter()) {
+                if let Some(v) = instr.get_value().filter(|_| instr.is_assignment()) {
+                    values.insert(v, instr.get_type());
+                }
+            }
+        }
+        Self {
+            module,
+            function,
+            values,
+            defined: HashMap::new(),
+            next: 0,
+        }
+    }
+
+    fn error(
+        &self,
+        bb: Option<&BasicBlock>,
+        instr: Option<&dyn InstructionSet>,
+        kind: VerifyErrorKind,
+    ) -> Box<VerifyError> {
+        Box::new(VerifyError {
+            function: self.function.name().to_string(),
+            block: bb.map(|bb| bb.label.clone()),
+            instruction: instr.map(|v| v.to_string()),
+            kind,
+        })
+    }
+
+    fn verify(mut self) -> VerifyResult {
+        for arg in self.function.definition.argument_list.clone() {
+            if arg.variable_argument {
+                continue;
+            }
+            match arg.name {
+                Some(name) => self
+                    .define(&name, arg.parameter_type)
+                    .map_err(|kind| self.error(None, None, kind))?,
+                None => self.next += 1,
+            }
+        }
+        if self.function.blocks.is_empty() {
+            return Err(self.error(None, None, VerifyErrorKind::EmptyFunction));
+        }
+        let function = self.function;
+        for bb in &function.blocks {
+            self.verify_block(bb)?;
+        }
+        Ok(())
+    }
+
+    /// Define value. Unnamed values should follow in sequence.
+    fn define(&mut self, name: &str, ty: Option<Type>) -> Result<(), VerifyErrorKind> {
+        if self.defined.contains_key(name) {
+            return Err(VerifyErrorKind::Redefinition(name.to_string()));
+        }
+        if let Some(n) = numbered(name) {
+            if n != self.next {
+                return Err(VerifyErrorKind::UnexpectedNumber(
+                    self.next,
+                    name.to_string(),
+                ));
+            }
+            self.next += 1;
+        }
+        self.defined.insert(name.to_string(), ty);
+        Ok(())
+    }
+
+    fn verify_block(&mut self, bb: &BasicBlock) -> VerifyResult {
+        let label = format!("%{}", bb.label);
+        self.define(&label, None)
+            .map_err(|kind| self.error(Some(bb), None, kind))?;
+        let mut phi_allowed = true;
+        for instr in &bb.instructions {
+            let instr = instr.as_ref();
+            if instr.is_terminator() {
+                let kind = VerifyErrorKind::MisplacedTerminator;
+                return Err(self.error(Some(bb), Some(instr), kind));
+            }
+            if instr.is_phi() && !phi_allowed {
+                let kind = VerifyErrorKind::MisplacedPhi;
+                return Err(self.error(Some(bb), Some(instr), kind));
+            }
+            phi_allowed &= instr.is_phi();
+            self.verify_instruction(instr)
+                .map_err(|kind| self.error(Some(bb), Some(instr), kind))?;
+        }
+        match &bb.terminator {
+            Some(t) if t.is_terminator() => {
+                let t = t.as_ref();
+                self.verify_instruction(t)
+                    .map_err(|kind| self.error(Some(bb), Some(t), kind))?;
+                if let Some(label) = t
+                    .successors()
+                    .into_iter()
+                    .find(|s| self.function.block(s).is_none())
+                {
+                    let kind = VerifyErrorKind::UnknownLabel(label);
+                    return Err(self.error(Some(bb), Some(t), kind));
+                }
+                Ok(())
+            }
+            _ => Err(self.error(Some(bb), None, VerifyErrorKind::MissingTerminator)),
+        }
+    }
+
+    fn verify_instruction(&mut self, instr: &dyn InstructionSet) -> Result<(), VerifyErrorKind> {
+        // Phi node refers values of predecessors, which can be
+        // placed after current block
+        for (ty, value) in instr.operands() {
+            self.verify_operand(&ty, &value, instr.is_phi())?;
+        }
+        if let Some(callee) = instr.callee() {
+            self.verify_call(&callee, instr.operands())?;
+        }
+        if let Some(v) = instr.get_value().filter(|_| instr.is_assignment()) {
+            self.define(&v, instr.get_type())?;
+        }
+        Ok(())
+    }
+
+    /// Type of local or global value. Constants have no value type.
+    fn value_type(&self, value: &str, phi: bool) -> Result<Option<Type>, VerifyErrorKind> {
+        let undefined = || VerifyErrorKind::UndefinedValue(value.to_string());
+        if value.starts_with('%') {
+            let values = if phi { &self.values } else { &self.defined };
+            values.get(value).cloned().ok_or_else(undefined)
+        } else if let Some(name) = value.strip_prefix('@') {
+            if let Some(g) = self.module.globals.iter().find(|g| g.name == name) {
+                return Ok(Some(Type::pointer1(g.value_type.clone())));
+            }
+            self.function_definition(name)
+                .map(|f| Some(Type::pointer1(Type::Function(function_type(f)))))
+                .ok_or_else(undefined)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn verify_operand(&self, ty: &Type, value: &str, phi: bool) -> Result<(), VerifyErrorKind> {
+        match self.value_type(value, phi)? {
+            Some(actual) if !actual.is_compatible(ty) => Err(VerifyErrorKind::TypeMismatch(
+                value.to_string(),
+                ty.clone(),
+                actual,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Function definition or declaration by name
+    fn function_definition(&self, name: &str) -> Option<&'a functions::Function> {
+        self.module
+            .function(name)
+            .map(|f| &f.definition)
+            .or_else(|| {
+                self.module
+                    .declarations
+                    .iter()
+                    .find(|f| f.function_name == name)
+            })
+    }
+
+    fn verify_call(&self, callee: &str, args: Vec<(Type, String)>) -> Result<(), VerifyErrorKind> {
+        let name = match callee.strip_prefix('@') {
+            Some(name) => name,
+            // Indirect call through local value
+            None => return self.value_type(callee, false).map(|_| ()),
+        };
+        let ty = self
+            .function_definition(name)
+            .map(function_type)
+            .ok_or_else(|| VerifyErrorKind::UndeclaredFunction(callee.to_string()))?;
+        let params = ty.parameter_list.len();
+        if args.len() < params || (args.len() > params && !ty.variable_argument) {
+            return Err(VerifyErrorKind::ArgumentsMismatch(
+                callee.to_string(),
+                args.len(),
+            ));
+        }
+        match ty
+            .parameter_list
+            .iter()
+            .zip(args)
+            .find(|(param, (arg_ty, _))| !param.is_compatible(arg_ty))
+        {
+            Some((param, (arg_ty, value))) => {
+                Err(VerifyErrorKind::TypeMismatch(value, param.clone(), arg_ty))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llvm::{
+        instructions::terminator::Ret,
+        parser::parse,
+    };
+
+    fn module(body: &str) -> Module {
+        let src = format!(
+            "@g = global i32 0\n\ndefine i32 @f(i32 %a, i1 %c) {{\n{}}}\n\ndeclare i32 @printf(ptr, ...)\n",
+            body
+        );
+        parse(&src).unwrap()
+    }
+
+    fn check(body: &str) -> VerifyErrorKind {
+        verify(&module(body)).unwrap_err().kind
+    }
+
+    #[test]
+    fn test_verify_valid() {
+        let m = module(
+            "\t%1 = load i32, ptr @g\n\tbr i1 %c, label %then, label %exit\nthen:\n\t%2 = add i32 %1, %a\n\tbr label %exit\nexit:\n\t%3 = phi i32 [ %1, %0 ], [ %2, %then ]\n\tret i32 %3\n",
+        );
+        assert_eq!(verify(&m), Ok(()));
+        let m = module("\t%1 = call i32 (ptr, ...) @printf(ptr null, i32 %a)\n\tret i32 %1\n");
+        assert_eq!(verify(&m), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_values() {
+        assert_eq!(
+            check("\t%1 = add i32 %a, %2\n\tret i32 %1\n"),
+            VerifyErrorKind::UndefinedValue("%2".to_string())
+        );
+        assert_eq!(
+            check("\t%2 = add i32 %a, 1\n\tret i32 %2\n"),
+            VerifyErrorKind::UnexpectedNumber(1, "%2".to_string())
+        );
+        assert_eq!(
+            check("\t%x = add i32 %a, 1\n\t%x = add i32 %a, 2\n\tret i32 %x\n"),
+            VerifyErrorKind::Redefinition("%x".to_string())
+        );
+        assert_eq!(
+            check("\t%1 = add i64 %a, 1\n\tret i32 0\n"),
+            VerifyErrorKind::TypeMismatch("%a".to_string(), Type::Integer64, Type::Integer32)
+        );
+        assert_eq!(
+            check("\t%1 = load i64, ptr @g\n\tret i32 0\n"),
+            VerifyErrorKind::TypeMismatch(
+                "@g".to_string(),
+                Type::pointer1(Type::Integer64),
+                Type::pointer1(Type::Integer32)
+            )
+        );
+        assert_eq!(
+            check("\tstore i32 1, ptr @h\n\tret i32 0\n"),
+            VerifyErrorKind::UndefinedValue("@h".to_string())
+        );
+    }
+
+    #[test]
+    fn test_verify_blocks() {
+        assert_eq!(
+            check("\tbr label %next\n"),
+            VerifyErrorKind::UnknownLabel("next".to_string())
+        );
+        assert_eq!(
+            check(
+                "\tbr label %1\n1:\n\t%2 = add i32 %a, 1\n\t%3 = phi i32 [ 0, %0 ]\n\tret i32 %3\n"
+            ),
+            VerifyErrorKind::MisplacedPhi
+        );
+        assert_eq!(
+            check("\tbr label %2\n2:\n\tret i32 0\n"),
+            VerifyErrorKind::UnexpectedNumber(1, "%2".to_string())
+        );
+
+        let mut m = module("\tret i32 0\n");
+        let bb = &mut m.functions[0].blocks[0];
+        bb.instructions.push(Box::new(Ret(None)));
+        assert_eq!(
+            verify(&m).unwrap_err().kind,
+            VerifyErrorKind::MisplacedTerminator
+        );
+        let bb = &mut m.functions[0].blocks[0];
+        bb.instructions.clear();
+        bb.terminator = None;
+        let err = verify(&m).unwrap_err();
+        assert_eq!(err.kind, VerifyErrorKind::MissingTerminator);
+        assert_eq!(
+            err.to_string(),
+            "@f, block %0: basic block has no terminator"
+        );
+        m.functions[0].blocks.clear();
+        assert_eq!(verify(&m).unwrap_err().kind, VerifyErrorKind::EmptyFunction);
+    }
+
+    #[test]
+    fn test_verify_calls() {
+        assert_eq!(
+            check("\t%1 = call i32 @g2(i32 %a)\n\tret i32 %1\n"),
+            VerifyErrorKind::UndeclaredFunction("@g2".to_string())
+        );
+        assert_eq!(
+            check("\t%1 = call i32 @f(i32 %a)\n\tret i32 %1\n"),
+            VerifyErrorKind::ArgumentsMismatch("@f".to_string(), 1)
+        );
+        assert_eq!(
+            check("\t%1 = call i32 @f(i32 %a, i32 %a)\n\tret i32 %1\n"),
+            VerifyErrorKind::TypeMismatch("%a".to_string(), Type::Integer1, Type::Integer32)
+        );
+        let err = verify(&module("\t%1 = call i32 @f(i32 %a)\n\tret i32 %1\n")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@f, block %0, `%1 = call i32 @f(i32 %a)`: function @f called with 1 arguments"
+        );
+    }
+}
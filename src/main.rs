@@ -2,46 +2,390 @@
 //!
 //! Currently it's Parser stage.
 //!
+//! LLVM is optional `llvm` feature. Without it, programs are run with
+//! interpreter only.
 #![warn(clippy::all)]
 #![allow(clippy::ptr_arg)]
-use crate::codegen::Codegen;
+#[cfg(feature = "llvm")]
+use crate::codegen::{
+    Codegen,
+    CodegenOptions,
+};
+#[cfg(feature = "llvm")]
+use crate::compiler::{
+    jit,
+    options::{
+        self,
+        CompileOptions,
+        OptLevel,
+    },
+    Emit,
+};
+use crate::interpreter::InterpreterOptions;
+#[cfg(feature = "llvm")]
+use crate::parser::{
+    ast::Span,
+    token,
+};
+#[cfg(feature = "llvm")]
+use crate::project::Project;
 use clap::{
     App,
+    AppSettings,
     Arg,
+    ArgMatches,
+    SubCommand,
+};
+#[cfg(feature = "llvm")]
+use inkwell::targets::TargetMachine;
+#[cfg(feature = "llvm")]
+use std::path::{
+    Path,
+    PathBuf,
 };
 
+#[cfg(feature = "llvm")]
 pub mod compiler;
 #[macro_use]
 pub mod llvm;
 pub mod codegen;
+pub mod interpreter;
 pub mod parser;
+pub mod project;
+#[cfg(feature = "llvm")]
+pub mod repl;
 mod tests;
 
-fn read_source(file: &str) -> String {
-    std::fs::read_to_string(file).unwrap_or_else(|_| panic!("input file {} not found", file))
+fn read_source(file: &str) -> Result<String, String> {
+    std::fs::read_to_string(file).map_err(|err| format!("Can't read input file {}: {}", file, err))
 }
 
-pub fn main() {
-    let matches = App::new("iLang")
-        .version("v0.1")
+/// Arguments of code generation and optimization, shared by build
+/// and run commands
+#[cfg(feature = "llvm")]
+fn codegen_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("v")
+            .short("v")
+            .multiple(true)
+            .help("Sets the level of verbosity"),
+        Arg::with_name("g")
+            .short("g")
+            .help("Generate source-level debug information and trap on integer overflow"),
+        Arg::with_name("opt-level")
+            .short("O")
+            .takes_value(true)
+            .possible_values(&OptLevel::NAMES)
+            .default_value("0")
+            .help("Optimization level: 0-3, s - for size, z - aggressively for size"),
+        Arg::with_name("passes")
+            .long("passes")
+            .takes_value(true)
+            .value_name("PIPELINE")
+            .help("Run custom LLVM pass pipeline instead of optimization level pipeline"),
+    ]
+}
+
+/// Compile options from command line. Values are checked by
+/// `possible_values`, options of other commands are default.
+#[cfg(feature = "llvm")]
+fn compile_options(matches: &ArgMatches) -> CompileOptions {
+    let mut compile_options = CompileOptions::default();
+    if let Some(v) = matches.value_of("opt-level") {
+        compile_options.opt_level = v.parse().unwrap();
+    }
+    if let Some(v) = matches.value_of("reloc") {
+        compile_options.reloc_mode = options::reloc_mode(v).unwrap();
+    }
+    if let Some(v) = matches.value_of("code-model") {
+        compile_options.code_model = options::code_model(v).unwrap();
+    }
+    compile_options.passes = matches.value_of("passes").map(String::from);
+    compile_options.target = matches.value_of("target").map(String::from);
+    compile_options.cpu = matches.value_of("cpu").map(String::from);
+    compile_options.features = matches.value_of("features").map(String::from);
+    compile_options
+}
+
+/// Codegen options from command line for target machine
+#[cfg(feature = "llvm")]
+fn codegen_options(
+    matches: &ArgMatches,
+    source_file: Option<&str>,
+    target_machine: &TargetMachine,
+) -> CodegenOptions {
+    let (target_triple, data_layout) = compiler::target_description(target_machine);
+    CodegenOptions {
+        debug_info: matches.is_present("g"),
+        overflow_checks: matches.is_present("g"),
+        typed_pointers: matches.is_present("typed-pointers"),
+        source_file: source_file.map(String::from),
+        target_triple: Some(target_triple),
+        data_layout: Some(data_layout),
+        ..CodegenOptions::default()
+    }
+}
+
+/// Generate LLVM IR for target machine
+#[cfg(feature = "llvm")]
+fn codegen(
+    matches: &ArgMatches,
+    source_file: &str,
+    src: &str,
+    target_machine: &TargetMachine,
+) -> Result<String, String> {
+    let codegen_options = codegen_options(matches, Some(source_file), target_machine);
+    Codegen::build_with_options(src, codegen_options).map_err(|err| err.to_string())
+}
+
+/// Directory of build outputs, `--out-dir` or default `build`
+#[cfg(feature = "llvm")]
+fn out_dir(matches: &ArgMatches) -> PathBuf {
+    PathBuf::from(matches.value_of("out-dir").unwrap_or(compiler::BUILD_DIR))
+}
+
+/// Build project of manifest to executable or static library of
+/// manifest `output` kind
+#[cfg(feature = "llvm")]
+fn build_project(matches: &ArgMatches, manifest: &Path) -> Result<(), String> {
+    let verbose = matches.occurrences_of("v") > 0;
+    if verbose {
+        println!("# Using manifest: {}", manifest.display());
+    }
+    if matches.occurrences_of("emit") > 0 {
+        return Err("`--emit` isn't supported for projects, output kind is set by manifest".into());
+    }
+    let project = Project::load(manifest)?;
+    let package = &project.manifest.package;
+    let out_dir = out_dir(matches);
+    let objects_dir = out_dir.join(&package.name);
+    let output = matches.value_of("output").map_or_else(
+        || out_dir.join(package.output.file_name(&package.name)),
+        PathBuf::from,
+    );
+    if verbose {
+        for m in project.modules.iter() {
+            println!("# Module {}: {}", m.name, m.path.display());
+        }
+        println!("# Output file: {}", output.display());
+    }
+    let compile_options = compile_options(matches);
+    let target_machine = compiler::get_target_machine(&compile_options)
+        .map_err(|err| format!("Failed init target: {}", err))?;
+    let codegen_options = codegen_options(matches, None, &target_machine);
+    let compiled = compiler::project::build(
+        &project,
+        &objects_dir,
+        &output,
+        &target_machine,
+        &compile_options,
+        &codegen_options,
+    )?;
+    if verbose {
+        println!("# Compiled modules: {}", compiled.join(", "));
+    }
+    Ok(())
+}
+
+/// Build source file to output of `--emit` kind. Project directory or
+/// manifest is built as project.
+#[cfg(feature = "llvm")]
+fn build(matches: &ArgMatches) -> Result<(), String> {
+    let source_file = matches.value_of("INPUT").unwrap();
+    if let Some(manifest) = Project::manifest_path(Path::new(source_file)) {
+        return build_project(matches, &manifest);
+    }
+    let verbose = matches.occurrences_of("v") > 0;
+    if verbose {
+        println!("# Using input file: {}", source_file);
+    }
+    let src = read_source(source_file)?;
+    let emit: Emit = matches.value_of("emit").unwrap().parse()?;
+    let ast = token::main(Span::new(&src))
+        .map_err(|err| format!("Parse error: {:?}", err))?
+        .1;
+    let module_id = Codegen::module_id(&ast);
+    let app_name = compiler::app_name(module_id.as_deref(), Path::new(source_file));
+    let output = matches.value_of("output").map_or_else(
+        || out_dir(matches).join(emit.file_name(&app_name)),
+        PathBuf::from,
+    );
+    if verbose {
+        println!("# Output file: {}", output.display());
+    }
+    if emit == Emit::Ast {
+        compiler::create_output_dir(&output)?;
+        return std::fs::write(&output, format!("{:#?}\n", ast))
+            .map_err(|err| format!("Failed write AST: {}", err));
+    }
+    let compile_options = compile_options(matches);
+    let target_machine = compiler::get_target_machine(&compile_options)
+        .map_err(|err| format!("Failed init target: {}", err))?;
+    let llvm_code = codegen(matches, source_file, &src, &target_machine)?;
+    compiler::compile(&llvm_code, emit, &output, &target_machine, &compile_options)
+        .map_err(|err| format!("Failed build: {}", err))
+}
+
+/// Build is not available without LLVM
+#[cfg(not(feature = "llvm"))]
+fn build(_matches: &ArgMatches) -> Result<(), String> {
+    Err("built without LLVM, only `run` command is available".to_string())
+}
+
+/// Run LLVM IR of source file with JIT
+#[cfg(feature = "llvm")]
+fn run_jit(
+    matches: &ArgMatches,
+    source_file: &str,
+    src: &str,
+    args: &[&str],
+) -> Result<i32, String> {
+    let compile_options = compile_options(matches);
+    let target_machine = compiler::get_target_machine(&compile_options)
+        .map_err(|err| format!("Failed init target: {}", err))?;
+    let llvm_code = codegen(matches, source_file, src, &target_machine)?;
+    jit::run_main(&llvm_code, args, &compile_options).map_err(|err| format!("Failed run: {}", err))
+}
+
+/// Run source file with JIT, or with interpreter for `--interpret`
+/// and without LLVM. Exit code of `main` is returned.
+fn run(matches: &ArgMatches) -> Result<i32, String> {
+    let source_file = matches.value_of("INPUT").unwrap();
+    if matches.occurrences_of("v") > 0 {
+        println!("# Running input file: {}", source_file);
+    }
+    let src = read_source(source_file)?;
+    // Program name is first argument of `main`
+    let args = std::iter::once(source_file)
+        .chain(matches.values_of("ARGS").into_iter().flatten())
+        .collect::<Vec<_>>();
+    #[cfg(feature = "llvm")]
+    if !matches.is_present("interpret") {
+        return run_jit(matches, source_file, &src, &args);
+    }
+    let options = InterpreterOptions {
+        overflow_checks: matches.is_present("g"),
+    };
+    interpreter::run(&src, &args, options, std::io::stdout())
+        .map_err(|err| format!("Failed run: {}", err))
+}
+
+/// Run interactive session
+#[cfg(feature = "llvm")]
+fn run_repl(matches: &ArgMatches) -> Result<(), String> {
+    let compile_options = compile_options(matches);
+    let target_machine = compiler::get_target_machine(&compile_options)
+        .map_err(|err| format!("Failed init target: {}", err))?;
+    let codegen_options = codegen_options(matches, None, &target_machine);
+    repl::run(codegen_options, compile_options).map_err(|err| format!("REPL: {}", err))
+}
+
+/// Arguments of build command
+#[cfg(feature = "llvm")]
+fn build_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    let mut args = codegen_args();
+    args.extend(vec![
+        Arg::with_name("typed-pointers")
+            .long("typed-pointers")
+            .help("Emit typed pointers for LLVM versions prior to 15"),
+        Arg::with_name("emit")
+            .long("emit")
+            .takes_value(true)
+            .possible_values(&Emit::NAMES)
+            .default_value("exe")
+            .help("Kind of output to emit"),
+        Arg::with_name("output")
+            .short("o")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("Write output to PATH, default is in output directory"),
+        Arg::with_name("out-dir")
+            .long("out-dir")
+            .takes_value(true)
+            .value_name("DIR")
+            .help("Directory of outputs and project object files, default is `build`"),
+        Arg::with_name("reloc")
+            .long("reloc")
+            .takes_value(true)
+            .possible_values(&options::RELOC_MODES)
+            .default_value("pic")
+            .help("Relocation model"),
+        Arg::with_name("code-model")
+            .long("code-model")
+            .takes_value(true)
+            .possible_values(&options::CODE_MODELS)
+            .default_value("default")
+            .help("Code model"),
+        Arg::with_name("target")
+            .long("target")
+            .takes_value(true)
+            .value_name("TRIPLE")
+            .help("Target triple for cross-compilation, default is host"),
+        Arg::with_name("cpu")
+            .long("cpu")
+            .takes_value(true)
+            .help("Target CPU, default is host CPU or target default for cross-compilation"),
+        Arg::with_name("features")
+            .long("features")
+            .takes_value(true)
+            .help("Target features, like `+avx2,-sse4.1`"),
+    ]);
+    args
+}
+
+/// Run command: `run` with JIT, or with interpreter
+fn run_command<'a, 'b>() -> App<'a, 'b> {
+    let command = SubCommand::with_name("run")
+        .about("Run source file with JIT or interpreter, without building executable")
+        .setting(AppSettings::TrailingVarArg)
         .arg(
             Arg::with_name("INPUT")
-                .help("Sets the input file to use")
+                .help("Sets the input file to run")
                 .required(true)
                 .index(1),
         )
         .arg(
-            Arg::with_name("v")
-                .short("v")
+            Arg::with_name("ARGS")
+                .help("Arguments passed to program")
                 .multiple(true)
-                .help("Sets the level of verbosity"),
+                .index(2),
         )
-        .get_matches();
-    let source_file = matches.value_of("INPUT").unwrap();
-    println!("# Using input file: {}", source_file);
-    let src = read_source(source_file);
-    println!("# Source code: {}", src);
-    let llvm_code = Codegen::build(&src).unwrap_or_else(|err| panic!("Error: {:?}", err));
-    compiler::builder("app".into(), llvm_code)
-        .unwrap_or_else(|err| panic!("Failed build: {}", err));
+        .arg(
+            Arg::with_name("interpret")
+                .long("interpret")
+                .help("Run with interpreter instead of JIT, it's default without LLVM"),
+        );
+    #[cfg(feature = "llvm")]
+    let command = command.args(&codegen_args());
+    command
+}
+
+pub fn main() {
+    let app = App::new("iLang")
+        .version("v0.1")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .arg(
+            Arg::with_name("INPUT")
+                .help("Sets the input file, project directory or `ilang.toml` to use")
+                .required(true)
+                .index(1),
+        )
+        .subcommand(run_command());
+    #[cfg(feature = "llvm")]
+    let app = app.args(&build_args()).subcommand(
+        SubCommand::with_name("repl")
+            .about("Interactive session: definitions and expressions evaluated with JIT")
+            .args(&codegen_args()),
+    );
+    let matches = app.get_matches();
+    let res = match matches.subcommand() {
+        ("run", Some(m)) => run(m).map(|code| std::process::exit(code)),
+        #[cfg(feature = "llvm")]
+        ("repl", Some(m)) => run_repl(m),
+        _ => build(&matches),
+    };
+    if let Err(err) = res {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
 }
@@ -16,7 +16,7 @@ pub type Ident<'a> = Span<'a>;
 
 /// Expression Operations
 /// Describe type of operations for expressions
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExpressionOperation {
     Plus,
     Minus,
@@ -177,6 +177,13 @@ pub enum MainStatement<'a> {
 /// Main - entry point for all definitions
 pub type Main<'a> = Vec<MainStatement<'a>>;
 
+/// Single statement, entry point for interactive input
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement<'a> {
+    Main(MainStatement<'a>),
+    Expression(Box<Expression<'a>>),
+}
+
 /// A string fragment contains a fragment of a string being parsed: either
 /// a non-empty Literal (a series of non-escaped characters), a single
 /// parsed escaped character, or a block of escaped whitespace.
@@ -195,7 +202,10 @@ pub struct StringIdent(pub String);
 #[derive(Debug, Clone, PartialEq)]
 pub enum BasicTypeExpression {
     String(String),
+    /// Integer literal
     Number(f64),
+    /// Literal with fraction or exponent
+    Float(f64),
     Bool(bool),
 }
 
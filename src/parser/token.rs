@@ -15,8 +15,10 @@ use nom::{
     },
     combinator::{
         map,
+        map_res,
         not,
         opt,
+        recognize,
         value,
     },
     error::{
@@ -449,30 +451,34 @@ pub fn  function_body(data: Span) -> ParseResult<ast::FunctionBody> {
         line: u32,
         column: usize,
     }
-    fn select_block(func_body: &ast::FunctionBodyStatement) -> Block {
-        match func_body {
-            ast::FunctionBodyStatement::Expression(ref e) => match e.function_statement {
-                ast::ExpressionFunctionValueCall::FunctionValue(ref x) => match x {
-                    ast::FunctionValue::ValueList(ref val_list) => match val_list[0] {
-                        ast::ValueExpression::ParameterValue(ref param_val) => {
-                            let line = param_val.location_line();
-                            let column = param_val.get_column();
-                            Block { line, column }
-                        }
-                        ast::ValueExpression::TypeExpression(ref e) => {
-                            let line = e.position.line;
-                            let column = e.position.column;
-                            Block { line, column }
-                        }
-                    },
-                    _ => unimplemented!(),
+    fn expression_block(e: &ast::Expression) -> Block {
+        match e.function_statement {
+            ast::ExpressionFunctionValueCall::FunctionValue(ref x) => match x {
+                ast::FunctionValue::ValueList(ref val_list) => match val_list[0] {
+                    ast::ValueExpression::ParameterValue(ref param_val) => {
+                        let line = param_val.location_line();
+                        let column = param_val.get_column();
+                        Block { line, column }
+                    }
+                    ast::ValueExpression::TypeExpression(ref e) => {
+                        let line = e.position.line;
+                        let column = e.position.column;
+                        Block { line, column }
+                    }
                 },
-                ast::ExpressionFunctionValueCall::FunctionCall(ref fn_call) => {
-                    let line = fn_call.function_call_name[0].location_line();
-                    let column = fn_call.function_call_name[0].get_column();
-                    Block { line, column }
-                }
+                // Expression in brackets - position of first operand
+                ast::FunctionValue::Expression(ref e) => expression_block(e),
             },
+            ast::ExpressionFunctionValueCall::FunctionCall(ref fn_call) => {
+                let line = fn_call.function_call_name[0].location_line();
+                let column = fn_call.function_call_name[0].get_column();
+                Block { line, column }
+            }
+        }
+    }
+    fn select_block(func_body: &ast::FunctionBodyStatement) -> Block {
+        match func_body {
+            ast::FunctionBodyStatement::Expression(ref e) => expression_block(e),
             ast::FunctionBodyStatement::LetBinding(ref let_bind) => match let_bind.value_list[0] {
                 ast::ParameterValueList::ParameterValue(ref param_val) => {
                     let line = param_val.location_line();
@@ -717,9 +723,52 @@ pub fn main(data: Span) -> ParseResult<ast::Main> {
     Ok((i, o))
 }
 
-/// Numbers parser
+/// Single statement parser, for interactive input. Main statements
+/// have precedence over expression. Function call is parsed as
+/// expression, so operations after call are not left.
+/// ## RULES:
+/// ```js
+/// statement = (
+///     namespace   |
+///     module      |
+///     function    |
+///     let-binding |
+///     expression
+/// )
+/// ```
+pub fn statement(data: Span) -> ParseResult<ast::Statement> {
+    alt((
+        map(delimited_space(namespace), |v| {
+            ast::Statement::Main(ast::MainStatement::Namespace(v))
+        }),
+        map(delimited_space(module), |v| {
+            ast::Statement::Main(ast::MainStatement::Module(v))
+        }),
+        map(delimited_space(function), |v| {
+            ast::Statement::Main(ast::MainStatement::Function(v))
+        }),
+        map(delimited_space(let_binding), |v| {
+            ast::Statement::Main(ast::MainStatement::LetBinding(v))
+        }),
+        map(delimited_space(expression), |v| {
+            ast::Statement::Expression(Box::new(v))
+        }),
+    ))(data)
+}
+
+/// Numbers parser. Literal with fraction or exponent is float, even
+/// if its value is integer, like `2.0`
 pub fn number(data: Span) -> ParseResult<ast::BasicTypeExpression> {
-    map(double, BasicTypeExpression::Number)(data)
+    map_res(recognize(double), |v: Span| {
+        let s = v.fragment();
+        s.parse().map(|n| {
+            if s.contains(&['.', 'e', 'E'][..]) {
+                BasicTypeExpression::Float(n)
+            } else {
+                BasicTypeExpression::Number(n)
+            }
+        })
+    })(data)
 }
 
 /// Boolean parser
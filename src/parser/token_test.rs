@@ -2543,12 +2543,17 @@ fn test_expression_value_type() {
     assert_eq!(x, 10_f64);
 
     let x = expression_value_type(Span::new("10.1")).unwrap();
-    let x = if let BasicTypeExpression::Number(v) = (x.1).expr {
+    let x = if let BasicTypeExpression::Float(v) = (x.1).expr {
         v
     } else {
         unimplemented!()
     };
     assert_eq!(x, 10.1_f64);
+
+    let x = expression_value_type(Span::new("2.0")).unwrap();
+    assert_eq!((x.1).expr, BasicTypeExpression::Float(2.0));
+    let x = expression_value_type(Span::new("1e3")).unwrap();
+    assert_eq!((x.1).expr, BasicTypeExpression::Float(1000.0));
 }
 
 #[test]
@@ -2598,3 +2603,27 @@ fn test_func_binding_body_blocks() {
     let x = function(Span::new("let fn1 x1 = fn2 x")).unwrap();
     assert_eq!(x.0.fragment(), &"");
 }
+
+#[test]
+fn test_statement() {
+    let x = statement(Span::new("let x = 1 + 2")).unwrap();
+    assert_eq!(x.0.fragment(), &"");
+    assert!(matches!(x.1, Statement::Main(MainStatement::LetBinding(_))));
+
+    let x = statement(Span::new("let f a =\n    let y = a\n    y\n")).unwrap();
+    assert_eq!(x.0.fragment(), &"");
+    assert!(matches!(x.1, Statement::Main(MainStatement::Function(_))));
+
+    let x = statement(Span::new("module repl")).unwrap();
+    assert!(matches!(x.1, Statement::Main(MainStatement::Module(_))));
+
+    let x = statement(Span::new("f 1 * 2")).unwrap();
+    assert_eq!(x.0.fragment(), &"");
+    assert!(matches!(x.1, Statement::Expression(_)));
+
+    let x = statement(Span::new("printf \"%d\" 1")).unwrap();
+    assert_eq!(x.0.fragment(), &"");
+    assert!(matches!(x.1, Statement::Expression(_)));
+
+    assert!(statement(Span::new("= 1")).is_err());
+}
@@ -0,0 +1,290 @@
+//! # Interface
+//!
+//! Interface file is written with object file of compiled module. It
+//! lists exported functions with signatures and symbols, so
+//! dependants are compiled against interface, and fingerprint of
+//! module build:
+//! ```toml
+//! module = "App.Utils"
+//! fingerprint = "1f0e3dad99908345"
+//!
+//! [[functions]]
+//! name = "answer"
+//! symbol = "App.Utils.answer"
+//! parameter_types = ["i32", "double"]
+//! result_type = "i32"
+//! ```
+
+use super::SourceModule;
+use crate::llvm::{
+    builder::FunctionRef,
+    ir::Module,
+    type_system::single_value::{
+        FloatingPointType,
+        VectorType,
+    },
+    types::Type,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{
+        Hash,
+        Hasher,
+    },
+    path::Path,
+};
+
+/// Extension of interface files
+pub const INTERFACE_EXTENSION: &str = "iface";
+
+/// Exported function of compiled module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportedFunction {
+    /// Function name in module
+    pub name: String,
+    pub symbol: String,
+    #[serde(default, with = "parameter_types")]
+    pub parameter_types: Vec<Type>,
+    #[serde(with = "result_type")]
+    pub result_type: Type,
+}
+
+/// Result type is serialized as LLVM IR type
+mod result_type {
+    use super::*;
+    use serde::{
+        de::Error,
+        Deserializer,
+        Serializer,
+    };
+
+    pub fn serialize<S: Serializer>(ty: &Type, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&ty.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Type, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_type(&s).ok_or_else(|| D::Error::custom(format!("unsupported type `{}`", s)))
+    }
+}
+
+/// Parameter types are serialized as list of LLVM IR types
+mod parameter_types {
+    use super::*;
+    use serde::{
+        de::Error,
+        Deserializer,
+        Serializer,
+    };
+
+    pub fn serialize<S: Serializer>(types: &[Type], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(types.iter().map(|ty| ty.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Type>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| {
+                parse_type(s).ok_or_else(|| D::Error::custom(format!("unsupported type `{}`", s)))
+            })
+            .collect()
+    }
+}
+
+/// Parse LLVM IR type of function result: void, integer, floating
+/// point, pointer and vector types
+pub fn parse_type(s: &str) -> Option<Type> {
+    let s = s.trim();
+    if let Some(pointee) = s.strip_suffix('*') {
+        return parse_type(pointee).map(Type::pointer1);
+    }
+    if let Some(v) = s.strip_prefix('<').and_then(|v| v.strip_suffix('>')) {
+        let (elements, element_type) = v.split_once(" x ")?;
+        return Some(Type::Vector(VectorType {
+            elemetns: elements.trim().parse().ok()?,
+            element_type: Box::new(parse_type(element_type)?),
+            vscale: false,
+        }));
+    }
+    let ty = match s {
+        "void" => Type::Void,
+        "i1" => Type::Integer1,
+        "i8" => Type::Integer8,
+        "i16" => Type::Integer16,
+        "i32" => Type::Integer32,
+        "i64" => Type::Integer64,
+        "i128" => Type::Integer128,
+        "half" => Type::FloatingPoint(FloatingPointType::Half),
+        "float" => Type::FloatingPoint(FloatingPointType::Float),
+        "double" => Type::FloatingPoint(FloatingPointType::Double),
+        "ptr" => Type::opaque_pointer(None),
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// Interface of compiled module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Interface {
+    /// Module id
+    pub module: String,
+    /// Fingerprint of module build, see `fingerprint`
+    pub fingerprint: String,
+    #[serde(default)]
+    pub functions: Vec<ExportedFunction>,
+}
+
+impl Interface {
+    /// Interface of LLVM IR module. Functions with external linkage
+    /// and symbol qualified by module id are exported, entry point
+    /// isn't qualified.
+    pub fn new(module_name: &str, fingerprint: String, module: &Module) -> Self {
+        let prefix = format!("{}.", module_name);
+        let functions = module
+            .functions
+            .iter()
+            .filter(|f| f.definition.linkage.is_none())
+            .filter_map(|f| {
+                let symbol = &f.definition.function_name;
+                symbol.strip_prefix(&prefix).map(|name| ExportedFunction {
+                    name: name.to_string(),
+                    symbol: symbol.clone(),
+                    parameter_types: FunctionRef::from(&f.definition).ty.parameter_list,
+                    result_type: f.definition.result_type.clone(),
+                })
+            })
+            .collect();
+        Self {
+            module: module_name.to_string(),
+            fingerprint,
+            functions,
+        }
+    }
+
+    /// Read interface file
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path)
+            .map_err(|err| format!("Can't read `{}`: {}", path.display(), err))?;
+        toml::from_str(&s).map_err(|err| format!("Invalid interface `{}`: {}", path.display(), err))
+    }
+
+    /// Write interface file
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let s = toml::to_string(self).map_err(|err| err.to_string())?;
+        std::fs::write(path, s).map_err(|err| format!("Can't write `{}`: {}", path.display(), err))
+    }
+}
+
+/// Fingerprint of module build: hash of module source, interfaces of
+/// dependencies and build options. Changes of dependency, which
+/// don't change its interface, don't change fingerprint.
+pub fn fingerprint(m: &SourceModule, dependencies: &[&Interface], options: &str) -> String {
+    // Hash isn't stable between compiler versions, mismatch only
+    // causes rebuild
+    let mut hasher = DefaultHasher::new();
+    m.name.hash(&mut hasher);
+    m.src.hash(&mut hasher);
+    options.hash(&mut hasher);
+    for dependency in dependencies.iter() {
+        dependency.module.hash(&mut hasher);
+        for f in dependency.functions.iter() {
+            f.name.hash(&mut hasher);
+            f.symbol.hash(&mut hasher);
+            for ty in f.parameter_types.iter() {
+                ty.to_string().hash(&mut hasher);
+            }
+            f.result_type.to_string().hash(&mut hasher);
+        }
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_type() {
+        let types = [
+            Type::Void,
+            Type::Integer1,
+            Type::Integer64,
+            Type::FloatingPoint(FloatingPointType::Double),
+            Type::opaque_pointer(None),
+            Type::Vector(VectorType {
+                elemetns: 4,
+                element_type: Box::new(Type::Integer32),
+                vscale: false,
+            }),
+        ];
+        for ty in types.iter() {
+            assert_eq!(parse_type(&ty.to_string()).as_ref(), Some(ty));
+        }
+        assert_eq!(parse_type("i8*"), Some(Type::pointer1(Type::Integer8)));
+        assert_eq!(parse_type("{ i32 }"), None);
+        assert_eq!(parse_type("<x i32>"), None);
+    }
+
+    #[test]
+    fn test_interface_serialize() {
+        let interface = Interface {
+            module: "App.Utils".to_string(),
+            fingerprint: "0123456789abcdef".to_string(),
+            functions: vec![ExportedFunction {
+                name: "answer".to_string(),
+                symbol: "App.Utils.answer".to_string(),
+                parameter_types: vec![Type::Integer32, Type::opaque_pointer(None)],
+                result_type: Type::Integer32,
+            }],
+        };
+        let s = toml::to_string(&interface).unwrap();
+        assert!(s.contains("parameter_types = [\"i32\", \"ptr\"]"));
+        assert!(s.contains("result_type = \"i32\""));
+        assert_eq!(toml::from_str::<Interface>(&s).unwrap(), interface);
+        let s = "module = \"A\"\nfingerprint = \"0\"\n";
+        assert!(toml::from_str::<Interface>(s).unwrap().functions.is_empty());
+        let s = "module = \"A\"\nfingerprint = \"0\"\n\
+            [[functions]]\nname = \"f\"\nsymbol = \"A.f\"\nresult_type = \"label\"\n";
+        assert!(toml::from_str::<Interface>(s).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let m = SourceModule::parse(
+            PathBuf::from("main.i"),
+            "module Main\nlet main () = Utils.answer()".to_string(),
+        )
+        .unwrap();
+        let mut utils = Interface {
+            module: "Utils".to_string(),
+            fingerprint: "1".to_string(),
+            functions: vec![],
+        };
+        let v = fingerprint(&m, &[&utils], "");
+        assert_eq!(v, fingerprint(&m, &[&utils], ""));
+        assert_ne!(v, fingerprint(&m, &[&utils], "-O2"));
+        // Fingerprint of dependency isn't part of interface
+        utils.fingerprint = "2".to_string();
+        assert_eq!(v, fingerprint(&m, &[&utils], ""));
+        utils.functions.push(ExportedFunction {
+            name: "answer".to_string(),
+            symbol: "Utils.answer".to_string(),
+            parameter_types: vec![],
+            result_type: Type::Integer32,
+        });
+        let v2 = fingerprint(&m, &[&utils], "");
+        assert_ne!(v, v2);
+        utils.functions[0].parameter_types.push(Type::Integer32);
+        assert_ne!(v2, fingerprint(&m, &[&utils], ""));
+        let mut changed = m.clone();
+        changed.src.push('\n');
+        assert_ne!(v, fingerprint(&changed, &[&utils], ""));
+    }
+}
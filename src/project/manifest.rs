@@ -0,0 +1,118 @@
+//! # Manifest
+//!
+//! Project manifest `ilang.toml`:
+//! ```toml
+//! [package]
+//! name = "app"
+//! # Source files or directories, relative to manifest
+//! sources = ["src"]
+//! # Module with `main` function
+//! entry = "App.Main"
+//! # `exe` or `staticlib`
+//! output = "exe"
+//! ```
+
+use serde::Deserialize;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+/// File name of manifest
+pub const MANIFEST: &str = "ilang.toml";
+
+/// Kind of project output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum OutputKind {
+    #[default]
+    #[serde(rename = "exe")]
+    Executable,
+    #[serde(rename = "staticlib")]
+    StaticLib,
+}
+
+impl OutputKind {
+    /// Default output file name for package
+    pub fn file_name(&self, name: &str) -> String {
+        match self {
+            OutputKind::Executable => name.to_string(),
+            OutputKind::StaticLib => format!("lib{}.a", name),
+        }
+    }
+}
+
+fn default_sources() -> Vec<PathBuf> {
+    vec![PathBuf::from("src")]
+}
+
+/// Package description
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Package {
+    pub name: String,
+    /// Source files and directories with `.i` files
+    #[serde(default = "default_sources")]
+    pub sources: Vec<PathBuf>,
+    /// Entry module, by default it's module with `main` function
+    pub entry: Option<String>,
+    #[serde(default)]
+    pub output: OutputKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub package: Package,
+}
+
+impl std::str::FromStr for Manifest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|err| format!("Invalid manifest: {}", err))
+    }
+}
+
+impl Manifest {
+    /// Read manifest file
+    pub fn read(path: &Path) -> Result<Self, String> {
+        std::fs::read_to_string(path)
+            .map_err(|err| format!("Can't read `{}`: {}", path.display(), err))?
+            .parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let manifest: Manifest = "[package]\nname = \"app\"".parse().unwrap();
+        assert_eq!(manifest.package.name, "app");
+        assert_eq!(manifest.package.sources, [PathBuf::from("src")]);
+        assert_eq!(manifest.package.entry, None);
+        assert_eq!(manifest.package.output, OutputKind::Executable);
+
+        let manifest: Manifest = r#"
+            [package]
+            name = "lib"
+            sources = ["a.i", "lib"]
+            entry = "App.Main"
+            output = "staticlib"
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(manifest.package.sources.len(), 2);
+        assert_eq!(manifest.package.entry.as_deref(), Some("App.Main"));
+        assert_eq!(manifest.package.output.file_name("lib"), "liblib.a");
+
+        assert!("[package]\nname = \"app\"\noutput = \"dll\""
+            .parse::<Manifest>()
+            .is_err());
+        assert!("[package]\nname = \"app\"\nsrc = []"
+            .parse::<Manifest>()
+            .is_err());
+        assert!("[package]".parse::<Manifest>().is_err());
+    }
+}
@@ -0,0 +1,377 @@
+//! # Project
+//!
+//! Multi-file project described by `ilang.toml` manifest. Each source
+//! file is module, qualified by namespace if it's declared. Module
+//! depends on other module, if it calls functions of that module by
+//! qualified name, like `Utils.add`. Modules of same namespace are
+//! referenced by relative names. Modules are ordered so, that
+//! dependencies precede dependants.
+
+pub mod interface;
+pub mod manifest;
+
+use self::manifest::{
+    Manifest,
+    OutputKind,
+    MANIFEST,
+};
+use crate::codegen::{
+    Codegen,
+    ENTRY_POINT,
+};
+use crate::parser::{
+    ast::*,
+    token,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+/// Extension of source files
+pub const SOURCE_EXTENSION: &str = "i";
+
+/// Source file of project
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceModule {
+    pub path: PathBuf,
+    pub src: String,
+    /// Module id, qualified by namespace
+    pub name: String,
+    /// Modules, which functions are called
+    pub dependencies: Vec<String>,
+    /// Module defines `main` function
+    pub has_entry_point: bool,
+    /// Qualified names of called functions, without function name
+    qualified_calls: Vec<String>,
+}
+
+/// Names of module visible from other module: full name, and name
+/// relative to namespace of caller
+pub fn module_references(caller: &str, module: &str) -> Vec<String> {
+    let mut names = vec![module.to_string()];
+    if let Some((namespace, _)) = caller.rsplit_once('.') {
+        if let Some(name) = module.strip_prefix(&format!("{}.", namespace)) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Qualified names of functions, called in expression
+fn expression_calls(e: &Expression, calls: &mut Vec<String>) {
+    match &e.function_statement {
+        ExpressionFunctionValueCall::FunctionValue(fv) => function_value_calls(fv, calls),
+        ExpressionFunctionValueCall::FunctionCall(fc) => function_call_calls(fc, calls),
+    }
+    if let Some(e) = &e.expression {
+        expression_calls(e, calls);
+    }
+}
+
+fn function_value_calls(fv: &FunctionValue, calls: &mut Vec<String>) {
+    if let FunctionValue::Expression(e) = fv {
+        expression_calls(e, calls);
+    }
+}
+
+fn function_call_calls(fc: &FunctionCall, calls: &mut Vec<String>) {
+    if let Some((_, module)) = fc.function_call_name.split_last() {
+        if !module.is_empty() {
+            let module = module.iter().map(|v| v.fragment().to_string());
+            calls.push(module.collect::<Vec<_>>().join("."));
+        }
+    }
+    for fv in fc.function_value.iter() {
+        function_value_calls(fv, calls);
+    }
+}
+
+fn function_body_calls(body: &FunctionBody, calls: &mut Vec<String>) {
+    for statement in body.iter() {
+        match statement {
+            FunctionBodyStatement::LetBinding(l) => function_body_calls(&l.function_body, calls),
+            FunctionBodyStatement::FunctionCall(fc) => function_call_calls(fc, calls),
+            FunctionBodyStatement::Expression(e) => expression_calls(e, calls),
+        }
+    }
+}
+
+impl SourceModule {
+    /// Parse source file, dependencies are not resolved
+    pub fn parse(path: PathBuf, src: String) -> Result<Self, String> {
+        let ast = token::main(Span::new(&src))
+            .map_err(|_| format!("{}: syntax error", path.display()))?
+            .1;
+        let name = Codegen::module_id(&ast)
+            .ok_or_else(|| format!("{}: module isn't declared", path.display()))?;
+        let mut qualified_calls = vec![];
+        let mut has_entry_point = false;
+        for v in ast.iter() {
+            match v {
+                MainStatement::Function(f) => {
+                    has_entry_point |= *f.function_name.fragment() == ENTRY_POINT;
+                    function_body_calls(&f.function_body, &mut qualified_calls);
+                }
+                MainStatement::LetBinding(l) => {
+                    function_body_calls(&l.function_body, &mut qualified_calls)
+                }
+                _ => (),
+            }
+        }
+        Ok(Self {
+            path,
+            src,
+            name,
+            dependencies: vec![],
+            has_entry_point,
+            qualified_calls,
+        })
+    }
+
+    /// Resolve dependencies by module names. Calls of unknown modules,
+    /// like build-in `Math`, are not dependencies.
+    fn resolve_dependencies(&mut self, names: &[String]) {
+        let mut dependencies = vec![];
+        for name in names.iter().filter(|name| **name != self.name) {
+            let references = module_references(&self.name, name);
+            if self.qualified_calls.iter().any(|v| references.contains(v)) {
+                dependencies.push(name.clone());
+            }
+        }
+        self.dependencies = dependencies;
+    }
+}
+
+/// Resolve dependencies of modules and order them: dependencies
+/// precede dependants, otherwise order of modules is kept.
+pub fn dependency_order(mut modules: Vec<SourceModule>) -> Result<Vec<SourceModule>, String> {
+    let names = modules.iter().map(|m| m.name.clone()).collect::<Vec<_>>();
+    for (i, name) in names.iter().enumerate() {
+        if let Some(m) = modules[..i].iter().find(|m| m.name == *name) {
+            return Err(format!(
+                "Module `{}` is defined in `{}` and `{}`",
+                name,
+                m.path.display(),
+                modules[i].path.display()
+            ));
+        }
+    }
+    for m in modules.iter_mut() {
+        m.resolve_dependencies(&names);
+    }
+    let mut ordered: Vec<SourceModule> = vec![];
+    while !modules.is_empty() {
+        let is_ordered = |name: &String| ordered.iter().any(|m| m.name == *name);
+        match modules
+            .iter()
+            .position(|m| m.dependencies.iter().all(is_ordered))
+        {
+            Some(i) => ordered.push(modules.remove(i)),
+            None => {
+                let cycle = modules.iter().map(|m| m.name.clone()).collect::<Vec<_>>();
+                return Err(format!(
+                    "Cyclic dependencies of modules: {}",
+                    cycle.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+/// Source files of path: file itself, or `.i` files of directory
+/// and subdirectories, sorted by path
+fn source_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    let entries = std::fs::read_dir(path)
+        .map_err(|err| format!("Can't read `{}`: {}", path.display(), err))?;
+    for entry in entries {
+        let path = entry.map_err(|err| err.to_string())?.path();
+        if path.is_dir() {
+            files.append(&mut source_files(&path)?);
+        } else if path.extension().is_some_and(|v| v == SOURCE_EXTENSION) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Project of manifest with modules in dependency order
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub manifest: Manifest,
+    /// Directory of manifest
+    pub root: PathBuf,
+    pub modules: Vec<SourceModule>,
+    /// Entry module, executable has it always
+    pub entry: Option<String>,
+}
+
+impl Project {
+    /// Manifest path for input path: manifest itself, or directory
+    /// with manifest. Source file isn't project.
+    pub fn manifest_path(input: &Path) -> Option<PathBuf> {
+        if input.is_dir() {
+            Some(input.join(MANIFEST))
+        } else if input.file_name().is_some_and(|v| v == MANIFEST) {
+            Some(input.to_path_buf())
+        } else {
+            None
+        }
+    }
+
+    /// Read manifest and sources of project
+    pub fn load(manifest_path: &Path) -> Result<Self, String> {
+        let manifest = Manifest::read(manifest_path)?;
+        let root = manifest_path
+            .parent()
+            .map_or_else(PathBuf::new, Path::to_path_buf);
+        let mut modules = vec![];
+        for source in manifest.package.sources.iter() {
+            for path in source_files(&root.join(source))? {
+                let src = std::fs::read_to_string(&path)
+                    .map_err(|err| format!("Can't read `{}`: {}", path.display(), err))?;
+                modules.push(SourceModule::parse(path, src)?);
+            }
+        }
+        if modules.is_empty() {
+            return Err(format!("No source files in `{}`", manifest_path.display()));
+        }
+        Self::new(manifest, root, modules)
+    }
+
+    /// Project of parsed modules
+    pub fn new(
+        manifest: Manifest,
+        root: PathBuf,
+        modules: Vec<SourceModule>,
+    ) -> Result<Self, String> {
+        let modules = dependency_order(modules)?;
+        let entry = match &manifest.package.entry {
+            Some(entry) => match modules.iter().find(|m| m.name == *entry) {
+                Some(m) if m.has_entry_point => Some(entry.clone()),
+                Some(_) => {
+                    return Err(format!(
+                        "Entry module `{}` has no `{}` function",
+                        entry, ENTRY_POINT
+                    ))
+                }
+                None => return Err(format!("Entry module `{}` not found", entry)),
+            },
+            None => {
+                let entries = modules
+                    .iter()
+                    .filter(|m| m.has_entry_point)
+                    .map(|m| m.name.clone())
+                    .collect::<Vec<_>>();
+                match (entries.len(), manifest.package.output) {
+                    (1, _) => entries.into_iter().next(),
+                    (_, OutputKind::StaticLib) => None,
+                    (0, _) => return Err(format!("`{}` function not found", ENTRY_POINT)),
+                    _ => {
+                        return Err(format!(
+                            "Entry module should be declared, `{}` function is defined in: {}",
+                            ENTRY_POINT,
+                            entries.join(", ")
+                        ))
+                    }
+                }
+            }
+        };
+        Ok(Self {
+            manifest,
+            root,
+            modules,
+            entry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(path: &str, src: &str) -> SourceModule {
+        SourceModule::parse(PathBuf::from(path), src.to_string()).unwrap()
+    }
+
+    fn names(modules: &[SourceModule]) -> Vec<&str> {
+        modules.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_project_module_references() {
+        assert_eq!(
+            module_references("App.Main", "App.Utils"),
+            ["App.Utils", "Utils"]
+        );
+        assert_eq!(module_references("Main", "App.Utils"), ["App.Utils"]);
+        assert_eq!(module_references("App.Main", "Lib.Utils"), ["Lib.Utils"]);
+    }
+
+    #[test]
+    fn test_project_dependency_order() {
+        let modules = vec![
+            module(
+                "main.i",
+                "namespace App\nmodule Main\nlet main () =\n    let x = (Utils.f())\n    Math.abs (Lib.Io.g())",
+            ),
+            module("io.i", "module Lib.Io\nlet g () = App.Utils.f()"),
+            module("utils.i", "namespace App\nmodule Utils\nlet f () = 1"),
+        ];
+        assert!(modules[0].has_entry_point);
+        let ordered = dependency_order(modules).unwrap();
+        assert_eq!(names(&ordered), ["App.Utils", "Lib.Io", "App.Main"]);
+        assert_eq!(ordered[2].dependencies, ["Lib.Io", "App.Utils"]);
+
+        let modules = vec![
+            module("a.i", "module A\nlet f () = B.f()"),
+            module("b.i", "module B\nlet f () = A.f()"),
+            module("c.i", "module C\nlet f () = 1"),
+        ];
+        assert_eq!(
+            dependency_order(modules).unwrap_err(),
+            "Cyclic dependencies of modules: A, B"
+        );
+        let modules = vec![module("a.i", "module A"), module("b.i", "module A")];
+        assert!(dependency_order(modules).is_err());
+    }
+
+    #[test]
+    fn test_project_entry() {
+        let manifest: Manifest = "[package]\nname = \"app\"".parse().unwrap();
+        let modules = vec![
+            module("a.i", "module A\nlet main () = B.f()"),
+            module("b.i", "module B\nlet f () = 1"),
+        ];
+        let project = Project::new(manifest.clone(), PathBuf::new(), modules.clone()).unwrap();
+        assert_eq!(project.entry.as_deref(), Some("A"));
+        assert_eq!(names(&project.modules), ["B", "A"]);
+
+        let mut entry = manifest.clone();
+        entry.package.entry = Some("B".to_string());
+        assert!(Project::new(entry, PathBuf::new(), modules.clone()).is_err());
+        let mut lib = manifest.clone();
+        lib.package.output = OutputKind::StaticLib;
+        assert!(Project::new(lib, PathBuf::new(), modules[1..].to_vec()).is_ok());
+        assert!(Project::new(manifest, PathBuf::new(), modules[1..].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_project_load_example() {
+        let path = Project::manifest_path(Path::new("./examples/project")).unwrap();
+        let project = Project::load(&path).unwrap();
+        assert_eq!(project.manifest.package.name, "answer");
+        assert_eq!(names(&project.modules), ["App.Utils", "App.Main"]);
+        assert_eq!(project.entry.as_deref(), Some("App.Main"));
+        assert_eq!(
+            Project::manifest_path(Path::new("./examples/hello.i")),
+            None
+        );
+    }
+}
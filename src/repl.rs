@@ -0,0 +1,464 @@
+//! # REPL
+//!
+//! Interactive session. Top-level `let` definitions and expressions
+//! are entered line by line. Each entry is compiled to its own
+//! module, with symbols qualified by module id, like `repl2.f`.
+//! Definitions of earlier entries are declared by module as external
+//! symbols, later definitions shadow earlier ones. Modules are added
+//! to one JIT session, which is started by first expression, and
+//! global let-values are initialized once, when their module is
+//! added. Expressions are compiled to function of module and
+//! evaluated with JIT.
+//!
+//! Multi-line entries follow indentation rules of function body:
+//! entry is continued by indented lines, and finished by empty or
+//! not indented line. Expression is finished as soon as it's parsed.
+
+use crate::codegen::{
+    Codegen,
+    CodegenOptions,
+    ExternalFunction,
+    ExternalGlobal,
+    GLOBAL_INIT_FUNCTION,
+};
+use crate::compiler::{
+    jit::JitSession,
+    options::CompileOptions,
+};
+use crate::llvm::{
+    builder::FunctionRef,
+    ir::Module,
+    types::Type,
+};
+use crate::parser::{
+    ast::{
+        Function,
+        FunctionBodyStatement,
+        Main,
+        MainStatement,
+        ParameterList,
+        ParameterValueList,
+        Span,
+        Statement,
+    },
+    token,
+};
+use inkwell::context::Context;
+use std::io::{
+    self,
+    BufRead,
+    Write,
+};
+
+/// Name of function generated for expression
+const EXPRESSION_FUNCTION: &str = "__repl_it";
+
+/// Module name of entries, it's numbered by entry
+const MODULE: &str = "repl";
+
+/// Lines of multi-line entry
+#[derive(Debug, Default)]
+pub struct Input {
+    lines: Vec<String>,
+}
+
+impl Input {
+    /// Is entry continued on next line
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Add line, completed entries are returned
+    pub fn push(&mut self, line: &str) -> Vec<String> {
+        let line = line.trim_end();
+        let mut entries = vec![];
+        if !self.lines.is_empty() && line.starts_with(char::is_whitespace) && !line.is_empty() {
+            self.lines.push(line.to_string());
+        } else {
+            entries.extend(self.finish());
+            let line = line.trim_start();
+            if !line.is_empty() {
+                self.lines.push(line.to_string());
+            }
+        }
+        // Definition can be continued by indented lines, even if
+        // it's parsed
+        let definition =
+            self.lines.first().and_then(|v| v.split_whitespace().next()) == Some("let");
+        if !definition && parse_statement(&self.lines.join("\n")).is_ok() {
+            entries.extend(self.finish());
+        }
+        entries
+    }
+
+    /// Finish pending entry
+    pub fn finish(&mut self) -> Option<String> {
+        if self.lines.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.lines).join("\n"))
+        }
+    }
+}
+
+/// Interactive session with accepted definitions
+pub struct Session<'ctx> {
+    context: &'ctx Context,
+    /// Functions of accepted definitions
+    functions: Vec<ExternalFunction>,
+    /// Global let-values of accepted definitions
+    globals: Vec<ExternalGlobal>,
+    /// Modules of definitions and their initializers, which are not
+    /// added to JIT yet
+    pending: Vec<(String, Option<String>)>,
+    /// Number of compiled entries
+    entries: usize,
+    jit: Option<JitSession<'ctx>>,
+    codegen_options: CodegenOptions,
+    options: CompileOptions,
+}
+
+/// Parse single statement, whole entry should be parsed
+fn parse_statement(src: &str) -> Result<Statement<'_>, String> {
+    let (rest, statement) =
+        token::statement(Span::new(src)).map_err(|_| "Syntax error".to_string())?;
+    match rest.fragment().trim() {
+        "" => Ok(statement),
+        rest => Err(format!("Syntax error: unexpected `{}`", rest)),
+    }
+}
+
+/// Make initializer of global let-values of module external, instead
+/// of static constructor, so it's called once by session. Symbol of
+/// initializer is returned.
+fn take_initializer(module: &mut Module) -> Option<String> {
+    let symbol = format!("{}.{}", module.module_id, GLOBAL_INIT_FUNCTION);
+    let f = module
+        .functions
+        .iter_mut()
+        .find(|f| f.definition.function_name == GLOBAL_INIT_FUNCTION)?;
+    f.definition.function_name = symbol.clone();
+    f.definition.linkage = None;
+    module.globals.retain(|g| g.name != "llvm.global_ctors");
+    Some(symbol)
+}
+
+impl<'ctx> Session<'ctx> {
+    pub fn new(
+        context: &'ctx Context,
+        codegen_options: CodegenOptions,
+        options: CompileOptions,
+    ) -> Self {
+        Self {
+            context,
+            functions: vec![],
+            globals: vec![],
+            pending: vec![],
+            entries: 0,
+            jit: None,
+            codegen_options,
+            options,
+        }
+    }
+
+    /// Generate module of new statement. Accepted definitions are
+    /// external for module.
+    fn module(&mut self, statement: MainStatement) -> Result<Module, String> {
+        self.entries += 1;
+        let module_name = format!("module {}{}", MODULE, self.entries);
+        let module = token::module(Span::new(&module_name))
+            .map_err(|_| "Syntax error".to_string())?
+            .1;
+        let ast: Main = vec![MainStatement::Module(module), statement];
+        let options = CodegenOptions {
+            qualified_symbols: true,
+            entry_module: false,
+            external_functions: self.functions.clone(),
+            external_globals: self.globals.clone(),
+            ..self.codegen_options.clone()
+        };
+        Codegen::fn_ir_module_with_options(&ast, options).map_err(|err| err.to_string())
+    }
+
+    /// Symbol of definition of module
+    fn symbol(module: &Module, name: &str) -> String {
+        format!("{}.{}", module.module_id, name)
+    }
+
+    /// JIT session with added modules of all accepted definitions
+    fn jit(&mut self) -> Result<&mut JitSession<'ctx>, String> {
+        if self.jit.is_none() {
+            self.jit = Some(JitSession::new(self.context, &self.options)?);
+        }
+        let jit = self.jit.as_mut().unwrap();
+        // Modules are removed one by one, so modules after failed
+        // one are kept
+        while !self.pending.is_empty() {
+            jit.add_module(&self.pending[0].0)?;
+            let (_, initializer) = self.pending.remove(0);
+            if let Some(initializer) = initializer {
+                jit.call_function(&initializer, &Type::Void)?;
+            }
+        }
+        Ok(jit)
+    }
+
+    /// Type-check and evaluate entry. Description of defined value
+    /// or value of expression is returned.
+    pub fn eval(&mut self, src: &str) -> Result<String, String> {
+        let (mut module, description) = match parse_statement(src)? {
+            Statement::Main(MainStatement::Function(f)) => {
+                let module = self.module(MainStatement::Function(f.clone()))?;
+                let name = f.function_name.to_string();
+                let symbol = Self::symbol(&module, &name);
+                let definition = &module
+                    .function(&symbol)
+                    .ok_or_else(|| format!("Function `{}` not found", name))?
+                    .definition;
+                // Signature is described by generated function
+                let parameter_types = FunctionRef::from(definition).ty.parameter_list;
+                let result_type = definition.result_type.clone();
+                let params = if parameter_types.is_empty() {
+                    "unit".to_string()
+                } else {
+                    parameter_types
+                        .iter()
+                        .map(Codegen::source_type_name)
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                };
+                let description = format!(
+                    "val {}: {} -> {}",
+                    name,
+                    params,
+                    Codegen::source_type_name(&result_type)
+                );
+                self.functions.retain(|f| f.name != name);
+                self.functions.push(ExternalFunction {
+                    name,
+                    symbol,
+                    parameter_types,
+                    result_type,
+                });
+                (module, description)
+            }
+            Statement::Main(MainStatement::LetBinding(l)) => {
+                let module = self.module(MainStatement::LetBinding(l.clone()))?;
+                let description = match l.value_list.first() {
+                    Some(ParameterValueList::ParameterValue(name)) => {
+                        let name = name.fragment().to_string();
+                        let symbol = Self::symbol(&module, &name);
+                        match module.globals.iter().find(|g| g.name == symbol) {
+                            Some(g) => {
+                                let description = format!(
+                                    "val {}: {}",
+                                    name,
+                                    Codegen::source_type_name(&g.value_type)
+                                );
+                                let value_type = g.value_type.clone();
+                                self.globals.retain(|g| g.name != name);
+                                self.globals.push(ExternalGlobal {
+                                    name,
+                                    symbol,
+                                    value_type,
+                                });
+                                description
+                            }
+                            None => format!("val {}", name),
+                        }
+                    }
+                    _ => "val".to_string(),
+                };
+                (module, description)
+            }
+            Statement::Main(_) => {
+                return Err("Modules and namespaces are not supported in REPL".to_string())
+            }
+            Statement::Expression(e) => {
+                let f = Function {
+                    modifier: None,
+                    function_name: Span::new(EXPRESSION_FUNCTION),
+                    parameter_list: ParameterList::ParameterValueList(vec![]),
+                    return_type: None,
+                    function_body: vec![FunctionBodyStatement::Expression(e)],
+                };
+                let module = self.module(MainStatement::Function(f))?;
+                let symbol = Self::symbol(&module, EXPRESSION_FUNCTION);
+                let result_type = module
+                    .function(&symbol)
+                    .ok_or_else(|| format!("Function `{}` not found", EXPRESSION_FUNCTION))?
+                    .definition
+                    .result_type
+                    .clone();
+                let jit = self.jit()?;
+                jit.add_module(&module.to_string())?;
+                let value = jit.call_function(&symbol, &result_type)?;
+                // Expression isn't kept in session
+                return Ok(format!(
+                    "val it: {} = {}",
+                    Codegen::source_type_name(&result_type),
+                    value
+                ));
+            }
+        };
+        let initializer = take_initializer(&mut module);
+        self.pending.push((module.to_string(), initializer));
+        Ok(description)
+    }
+}
+
+/// Print result of evaluated entry
+fn print_result(res: Result<String, String>) {
+    match res {
+        Ok(v) => println!("{}", v),
+        Err(err) => eprintln!("Error: {}", err),
+    }
+}
+
+/// Run interactive session with standard input until end of input
+pub fn run(codegen_options: CodegenOptions, options: CompileOptions) -> io::Result<()> {
+    let context = Context::create();
+    let mut session = Session::new(&context, codegen_options, options);
+    let mut input = Input::default();
+    let stdin = io::stdin();
+    loop {
+        print!("{}", if input.is_empty() { "> " } else { ". " });
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            if let Some(entry) = input.finish() {
+                print_result(session.eval(&entry));
+            }
+            println!();
+            return Ok(());
+        }
+        for entry in input.push(&line) {
+            print_result(session.eval(&entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repl_input() {
+        let mut input = Input::default();
+        assert_eq!(input.push("  1 + 2\n"), vec!["1 + 2"]);
+        assert!(input.push("\n").is_empty());
+        assert!(input.push("  let x = 1\n").is_empty());
+        assert_eq!(input.push("\n"), vec!["let x = 1"]);
+        assert!(input.push("let f a = g a\n").is_empty());
+        assert!(!input.is_empty());
+        assert!(input.push("    let y = a\n").is_empty());
+        assert!(input.push("    printf \"y=\"\n").is_empty());
+        assert_eq!(
+            input.push("f 2\n"),
+            vec!["let f a = g a\n    let y = a\n    printf \"y=\"", "f 2"]
+        );
+        assert!(input.is_empty());
+        assert!(input.push("let z =").is_empty());
+        assert!(input.push("    3").is_empty());
+        assert_eq!(input.push("let w = 4"), vec!["let z =\n    3"]);
+        assert_eq!(input.finish(), Some("let w = 4".to_string()));
+        // Incomplete expression is continued by indented lines
+        assert!(input.push("1 +").is_empty());
+        assert!(input.push("    2").is_empty());
+        assert_eq!(input.push(""), vec!["1 +\n    2"]);
+        assert!(input.push("let = 1").is_empty());
+        assert_eq!(input.push("x"), vec!["let = 1", "x"]);
+        assert_eq!(input.finish(), None);
+    }
+
+    #[test]
+    fn test_repl_definitions() {
+        let context = Context::create();
+        let mut session = Session::new(&context, CodegenOptions::default(), Default::default());
+        assert_eq!(session.eval("let x = 1 + 2"), Ok("val x: int".to_string()));
+        assert_eq!(
+            session.eval("let f () =\n    let y = 2.5 * (float x)\n    y"),
+            Ok("val f: unit -> float".to_string())
+        );
+        assert_eq!(
+            session.eval("let g (a: int, b: float) = printf \"%d %f\" a b"),
+            Ok("val g: int -> float -> int".to_string())
+        );
+        assert!(session.eval("let h () = z").is_err());
+        assert!(session.eval("let h a = a + 1.5").is_err());
+        assert!(session.eval("let = 1").is_err());
+        assert!(session.eval("module m").is_err());
+        // Failed definitions are not kept
+        assert_eq!(session.pending.len(), 3);
+        assert_eq!(session.functions.len(), 2);
+        assert_eq!(
+            session.globals,
+            [ExternalGlobal {
+                name: "x".to_string(),
+                symbol: "repl1.x".to_string(),
+                value_type: Type::Integer32,
+            }]
+        );
+        // Earlier definitions are external for later modules
+        let (src, initializer) = &session.pending[0];
+        assert_eq!(initializer.as_deref(), Some("repl1._GLOBAL_let_main"));
+        assert!(!src.contains("llvm.global_ctors"));
+        assert!(session.pending[1]
+            .0
+            .contains("@repl1.x = external global i32"));
+        assert!(session.pending[1].0.contains("define double @repl2.f()"));
+        assert!(session.pending[2]
+            .0
+            .contains("define i32 @repl3.g(i32 %a, double %b)"));
+        session.eval("let f () = 1").unwrap();
+        assert_eq!(session.functions.len(), 2);
+        assert_eq!(session.functions[1].symbol, "repl6.f");
+        // Functions of earlier entries are declared with their signatures
+        session.eval("let k () = g 1 2.5").unwrap();
+        assert!(session.pending[4]
+            .0
+            .contains("declare i32 @repl3.g(i32, double)"));
+        assert!(session.eval("let k () = g 1").is_err());
+    }
+
+    #[test]
+    fn test_jit_repl_expressions() {
+        let context = Context::create();
+        let mut session = Session::new(&context, CodegenOptions::default(), Default::default());
+        session.eval("let x = 20").unwrap();
+        session.eval("let f () = x * 2").unwrap();
+        assert_eq!(session.eval("f() + 2"), Ok("val it: int = 42".to_string()));
+        assert_eq!(
+            session.eval("float (f() + 2)"),
+            Ok("val it: float = 42.0".to_string())
+        );
+        assert!(session.pending.is_empty());
+        // Global let-values are initialized once
+        session.eval("let c = 0").unwrap();
+        session.eval("let g () = Atomic.increment c").unwrap();
+        assert_eq!(session.eval("g()"), Ok("val it: int = 1".to_string()));
+        assert_eq!(session.eval("g()"), Ok("val it: int = 2".to_string()));
+        // Later definition shadows earlier one
+        session.eval("let x = 1").unwrap();
+        assert_eq!(session.eval("x + f()"), Ok("val it: int = 41".to_string()));
+        session.eval("let add a b = a + b").unwrap();
+        assert_eq!(session.eval("add x 2"), Ok("val it: int = 3".to_string()));
+    }
+
+    #[test]
+    fn test_jit_repl_pending_error() {
+        let context = Context::create();
+        let mut session = Session::new(&context, CodegenOptions::default(), Default::default());
+        session.eval("let x = 20").unwrap();
+        session.eval("let y = x + 1").unwrap();
+        session
+            .pending
+            .insert(1, ("malformed module".to_string(), None));
+        assert!(session.eval("y").is_err());
+        // Modules after failed one are kept
+        assert_eq!(session.pending.len(), 2);
+        session.pending.remove(0);
+        assert_eq!(session.eval("y"), Ok("val it: int = 21".to_string()));
+        assert!(session.pending.is_empty());
+    }
+}
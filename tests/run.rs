@@ -0,0 +1,27 @@
+use std::process::Command;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_i-lang"))
+        .arg("run")
+        .args(args)
+        .output()
+        .unwrap();
+    // Exit code is `main` result, here `printf` result
+    assert!(
+        output.stderr.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_run_interpret_stdout() {
+    assert_eq!(run(&["--interpret", "examples/hello.i"]), "Hello world\n");
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_jit_run_stdout() {
+    assert_eq!(run(&["examples/hello.i"]), "Hello world\n");
+}